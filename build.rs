@@ -0,0 +1,6 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/ezkl.proto").expect("failed to compile protos");
+    #[cfg(feature = "node-bindings")]
+    napi_build::setup();
+}
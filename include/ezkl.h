@@ -0,0 +1,42 @@
+/* C interface to ezkl, built with `cargo build --release --features ffi`. */
+#ifndef EZKL_H
+#define EZKL_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define EZKL_OK 0
+#define EZKL_INVALID_ARGUMENT -1
+#define EZKL_BUFFER_TOO_SMALL -2
+#define EZKL_NOT_VERIFIED -3
+/* ezkl panicked, don't reuse the handle that was passed in */
+#define EZKL_PANIC -4
+/* positive statuses are the codes of the failure kind: 1 graph, 2 quantization, 3 proving, 4 io, 99 other */
+
+typedef struct EzklProver EzklProver;
+
+/* srs_path may be NULL to use the default srs for the circuit's logrows */
+int32_t ezkl_prover_load(const char *compiled_circuit, const char *pk_path, const char *vk_path,
+                         const char *srs_path, EzklProver **out);
+
+void ezkl_prover_free(EzklProver *prover);
+
+/* writes the json proof to out; on EZKL_BUFFER_TOO_SMALL *out_len holds the size needed and the
+   proof is kept on the handle, a retry with the same witness returns it without proving again */
+int32_t ezkl_prove(const EzklProver *prover, const uint8_t *witness, size_t witness_len,
+                   uint8_t *out, size_t out_cap, size_t *out_len);
+
+int32_t ezkl_verify(const EzklProver *prover, const uint8_t *proof, size_t proof_len);
+
+/* returns the length of the last error message on this thread */
+size_t ezkl_last_error(char *out, size_t out_cap);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* EZKL_H */
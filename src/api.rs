@@ -0,0 +1,204 @@
+//! Entry points for embedding ezkl in other programs, e.g. a proving service. They run the same
+//! pipeline as the CLI over in-memory values instead of files and flags: every type they take
+//! ([RunArgs], [GraphSettings], [GraphData], [Proof], ...) is a plain struct that can be built
+//! directly or deserialized, and `RunArgs::default()` matches the CLI's defaults.
+//!
+//! ```no_run
+//! use ezkl::api;
+//! use ezkl::graph::input::GraphData;
+//! use std::path::Path;
+//!
+//! # async fn run() -> Result<(), ezkl::error::EzklError> {
+//! let model = Path::new("network.onnx");
+//! let settings = api::gen_settings(model, &ezkl::RunArgs::default())?;
+//! let params = api::load_srs(&settings, None)?;
+//! let pk = api::setup(&api::compile(model, &settings)?, &params)?;
+//!
+//! let inputs = GraphData::from_path("input.json".into())?;
+//! let proof = api::prove(model, &inputs, &settings, &params, &pk).await?;
+//! assert!(api::verify(&proof, pk.get_vk(), &settings, &params)?);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::circuit::CheckMode;
+use crate::error::EzklError;
+use crate::graph::input::GraphData;
+use crate::graph::{GraphCircuit, GraphSettings, GraphWitness};
+use crate::pfsys::{
+    create_keys, verify_proof_circuit_kzg, verify_proof_circuit_kzg_gwc, Commitments, MultiOpen,
+    ProofType, Snark,
+};
+use crate::RunArgs;
+use halo2_proofs::plonk::{ProvingKey, VerifyingKey};
+use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
+use halo2_proofs::poly::kzg::strategy::SingleStrategy;
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use std::path::{Path, PathBuf};
+
+/// A proof of a model's execution, with its public inputs
+pub type Proof = Snark<Fr, G1Affine>;
+
+/// Errors for settings this api can't prove with
+fn check_commitment(settings: &GraphSettings) -> Result<(), EzklError> {
+    if settings.run_args.commitment != Commitments::KZG {
+        return Err(EzklError::Other(
+            "the library api only supports kzg commitments".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Generates the circuit settings for a model, as `ezkl gen-settings` does
+pub fn gen_settings(model_path: &Path, run_args: &RunArgs) -> Result<GraphSettings, EzklError> {
+    Ok(GraphCircuit::from_run_args(run_args, model_path)?
+        .settings()
+        .clone())
+}
+
+/// Compiles a model into the circuit `settings` describe, as `ezkl compile-circuit` does. The
+/// circuit can be reused across [setup], [gen_witness] and [prove_witness] calls.
+pub fn compile(model_path: &Path, settings: &GraphSettings) -> Result<GraphCircuit, EzklError> {
+    Ok(GraphCircuit::from_settings(
+        settings,
+        model_path,
+        CheckMode::UNSAFE,
+    )?)
+}
+
+/// Loads the kzg srs sized for `settings`, from `srs_path` or the default location
+/// ($EZKL_REPO_PATH/srs/kzg{logrows}.srs)
+pub fn load_srs(
+    settings: &GraphSettings,
+    srs_path: Option<PathBuf>,
+) -> Result<ParamsKZG<Bn256>, EzklError> {
+    Ok(crate::execute::load_params_cmd(
+        srs_path,
+        settings.run_args.logrows,
+    )?)
+}
+
+/// Generates the proving key of a circuit, its verifying key is [ProvingKey::get_vk]
+pub fn setup(
+    circuit: &GraphCircuit,
+    params: &ParamsKZG<Bn256>,
+) -> Result<ProvingKey<G1Affine>, EzklError> {
+    check_commitment(circuit.settings())?;
+    Ok(create_keys::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(
+        circuit, params,
+    )?)
+}
+
+/// Runs the quantized model on `inputs`. The verifying key and srs are only needed when inputs,
+/// outputs or params are committed to with kzg (`polycommit` visibility). Async as inputs can be
+/// read from a chain.
+pub async fn gen_witness(
+    circuit: &mut GraphCircuit,
+    inputs: &GraphData,
+    vk: Option<&VerifyingKey<G1Affine>>,
+    params: Option<&ParamsKZG<Bn256>>,
+) -> Result<GraphWitness, EzklError> {
+    let mut inputs = circuit.load_graph_input(inputs).await?;
+    Ok(circuit.forward(&mut inputs, vk, params)?)
+}
+
+/// Proves a witness of `circuit`
+pub fn prove_witness(
+    circuit: GraphCircuit,
+    witness: GraphWitness,
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+) -> Result<Proof, EzklError> {
+    check_commitment(circuit.settings())?;
+    Ok(crate::execute::prove_witness(
+        witness,
+        circuit,
+        params,
+        pk,
+        ProofType::Single,
+        CheckMode::UNSAFE,
+    )?)
+}
+
+/// Proves a model's execution on `inputs`. This compiles the model on every call, to prove
+/// repeatedly [compile] once and use [gen_witness] and [prove_witness].
+pub async fn prove(
+    model_path: &Path,
+    inputs: &GraphData,
+    settings: &GraphSettings,
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+) -> Result<Proof, EzklError> {
+    let mut circuit = compile(model_path, settings)?;
+    let witness = gen_witness(&mut circuit, inputs, Some(pk.get_vk()), Some(params)).await?;
+    prove_witness(circuit, witness, params, pk)
+}
+
+/// Verifies a proof. Returns false if the proof is rejected, and errors if it can't be checked
+/// at all (e.g. the settings use a commitment this api doesn't support).
+pub fn verify(
+    proof: &Proof,
+    vk: &VerifyingKey<G1Affine>,
+    settings: &GraphSettings,
+    params: &ParamsKZG<Bn256>,
+) -> Result<bool, EzklError> {
+    check_commitment(settings)?;
+    let params = params.verifier_params();
+    let verified = match settings.run_args.multiopen {
+        MultiOpen::Shplonk => {
+            verify_proof_circuit_kzg(params, proof.clone(), vk, SingleStrategy::new(params)).is_ok()
+        }
+        MultiOpen::Gwc => verify_proof_circuit_kzg_gwc(params, proof.clone(), vk).is_ok(),
+    };
+    Ok(verified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Visibility;
+    use crate::pfsys::srs::gen_srs;
+
+    #[tokio::test]
+    async fn test_prove_and_verify() {
+        let model = Path::new("./examples/onnx/1l_relu/network.onnx");
+        let run_args = RunArgs {
+            logrows: 12,
+            ..Default::default()
+        };
+        let settings = gen_settings(model, &run_args).unwrap();
+        let params = gen_srs::<KZGCommitmentScheme<Bn256>>(settings.run_args.logrows);
+        let pk = setup(&compile(model, &settings).unwrap(), &params).unwrap();
+
+        let inputs = GraphData::from_path("./examples/onnx/1l_relu/input.json".into()).unwrap();
+        let mut proof = prove(model, &inputs, &settings, &params, &pk)
+            .await
+            .unwrap();
+        assert!(verify(&proof, pk.get_vk(), &settings, &params).unwrap());
+
+        // a proof of other outputs doesn't verify
+        proof.instances[0][0] += Fr::one();
+        assert!(!verify(&proof, pk.get_vk(), &settings, &params).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_gen_witness_encrypted_input() {
+        let model = Path::new("./examples/onnx/1l_relu/network.onnx");
+        let run_args = RunArgs {
+            logrows: 17,
+            input_visibility: Visibility::Encrypted,
+            ..Default::default()
+        };
+        let settings = gen_settings(model, &run_args).unwrap();
+        let mut circuit = compile(model, &settings).unwrap();
+
+        // no key is passed, so the inputs are encrypted under fresh variables
+        let inputs = GraphData::from_path("./examples/onnx/1l_relu/input.json".into()).unwrap();
+        let witness = gen_witness(&mut circuit, &inputs, None, None)
+            .await
+            .unwrap();
+        let elgamal = witness.processed_inputs.and_then(|p| p.elgamal).unwrap();
+        assert!(!elgamal.ciphertexts.is_empty());
+    }
+}
@@ -5,34 +5,48 @@ use clap::Parser;
 #[cfg(not(target_arch = "wasm32"))]
 use colored_json::ToColoredJson;
 #[cfg(not(target_arch = "wasm32"))]
-use ezkl::commands::Cli;
+use ezkl::commands::{Cli, OutputFormat};
 #[cfg(not(target_arch = "wasm32"))]
-use ezkl::execute::run;
+use ezkl::execute::{pin_devices, run, run_with_output};
 #[cfg(not(target_arch = "wasm32"))]
-use ezkl::logger::init_logger;
+use ezkl::logger::{init_chrome_trace, init_logger, init_logger_with_target};
 #[cfg(not(target_arch = "wasm32"))]
 use log::{error, info};
 #[cfg(not(target_arch = "wasm32"))]
 use rand::prelude::SliceRandom;
 #[cfg(not(target_arch = "wasm32"))]
-#[cfg(feature = "icicle")]
-use std::env;
-#[cfg(not(target_arch = "wasm32"))]
 use std::error::Error;
 
 #[tokio::main(flavor = "current_thread")]
 #[cfg(not(target_arch = "wasm32"))]
 pub async fn main() -> Result<(), Box<dyn Error>> {
     let args = Cli::parse();
-    init_logger();
-    banner();
-    #[cfg(feature = "icicle")]
-    if env::var("ENABLE_ICICLE_GPU").is_ok() {
-        info!("Running with ICICLE GPU");
+    // an artifact may be piped through stdout when a path is `-`
+    let piped = std::env::args().any(|a| a == ezkl::stdio::STDIO_PATH);
+    if args.format == OutputFormat::Json || piped {
+        // keep stdout clean for the structured result or piped artifact
+        init_logger_with_target(env_logger::Target::Stderr);
     } else {
-        info!("Running with CPU");
+        init_logger();
+        banner();
     }
+    let trace_guard = args.trace_path.as_deref().map(init_chrome_trace);
+    pin_devices(&args.command);
+    #[cfg(feature = "icicle")]
+    ezkl::gpu::init_gpu();
     info!("command: \n {}", &args.as_json()?.to_colored_json_auto()?);
+    if args.format == OutputFormat::Json {
+        let output = run_with_output(args.command).await;
+        println!("{}", serde_json::to_string(&output)?);
+        if let Some(e) = output.error {
+            error!("failed: {}", e);
+            // exiting skips destructors, write the trace first
+            drop(trace_guard);
+            std::process::exit(1);
+        }
+        info!("succeeded");
+        return Ok(());
+    }
     let res = run(args.command).await;
     match &res {
         Ok(_) => info!("succeeded"),
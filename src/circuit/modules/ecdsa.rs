@@ -0,0 +1,471 @@
+/*
+In-circuit verification of secp256k1 ECDSA signatures over the Poseidon digests of a circuit's
+inputs, so that a proof can attest "this inference ran on data signed by key X".
+*/
+
+use ark_std::rand::{CryptoRng, RngCore};
+use halo2_proofs::circuit::{Layouter, Value};
+use halo2_proofs::plonk::{Column, ConstraintSystem, Error, Instance};
+use halo2_wrong_ecc::integer::rns::{Integer, Rns};
+use halo2_wrong_ecc::integer::{
+    IntegerChip, IntegerConfig, IntegerInstructions, Range, UnassignedInteger,
+};
+use halo2_wrong_ecc::maingate::{
+    big_to_fe, fe_to_big, AssignedValue, MainGate, MainGateConfig, RangeChip, RangeConfig,
+    RangeInstructions, RegionCtx,
+};
+use halo2_wrong_ecc::{EccConfig, GeneralEccChip};
+use halo2_wrong_ecdsa::ecdsa::{AssignedEcdsaSig, AssignedPublicKey, EcdsaChip};
+use halo2curves::bn256::Fr;
+use halo2curves::ff::Field;
+use halo2curves::group::{Curve, Group};
+use halo2curves::secp256k1::{Fp, Fq, Secp256k1, Secp256k1Affine};
+use halo2curves::CurveAffine;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+const NUMBER_OF_LIMBS: usize = 4;
+const BIT_LEN_LIMB: usize = 68;
+/// The window size used in the ECC chip's multi scalar multiplication.
+const WINDOW_SIZE: usize = 4;
+/// The number of instances exposed by the signature chip, the public key's x and y.
+pub const NUM_SIGNATURE_INSTANCES: usize = 2;
+
+/// The native field represented with the same limbs as secp256k1 scalars, used to check that
+/// a signed message is the canonical integer of a digest.
+type NativeRns = Rns<Fr, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>;
+
+/// The secp256k1 scalar that the Poseidon digest of an input is signed as. Digests are
+/// smaller than the secp256k1 order so the conversion is exact.
+pub fn digest_to_scalar(digest: Fr) -> Fq {
+    big_to_fe(fe_to_big(digest))
+}
+
+/// Reduces a base field element (e.g. the x coordinate of `k.G`) modulo the curve order.
+fn mod_n(x: Fp) -> Fq {
+    big_to_fe(fe_to_big(x))
+}
+
+/// The instances that identify a public key, the native reductions of its coordinates.
+pub fn public_key_instances(public_key: &Secp256k1Affine) -> [Fr; NUM_SIGNATURE_INSTANCES] {
+    let coordinates = public_key.coordinates().unwrap();
+    [
+        big_to_fe(fe_to_big(*coordinates.x())),
+        big_to_fe(fe_to_big(*coordinates.y())),
+    ]
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+/// An ECDSA signature over secp256k1.
+pub struct Signature {
+    /// The x coordinate of the nonce point, reduced modulo the curve order.
+    pub r: Fq,
+    /// The signature proof.
+    pub s: Fq,
+}
+
+impl Signature {
+    /// Signs the Poseidon digest of an input with the secret key `sk`.
+    pub fn sign<R: CryptoRng + RngCore>(sk: Fq, digest: Fr, mut rng: &mut R) -> Self {
+        let msg = digest_to_scalar(digest);
+        loop {
+            let k = Fq::random(&mut rng);
+            let nonce = (Secp256k1::generator() * k).to_affine();
+            let r = mod_n(*nonce.coordinates().unwrap().x());
+            let s = k.invert().unwrap() * (msg + r * sk);
+            if !bool::from(r.is_zero()) && !bool::from(s.is_zero()) {
+                return Self { r, s };
+            }
+        }
+    }
+
+    /// Checks the signature of a digest against a public key, outside of the circuit.
+    pub fn verify(&self, public_key: &Secp256k1Affine, digest: Fr) -> bool {
+        let s_inv = self.s.invert();
+        if bool::from(s_inv.is_none()) {
+            return false;
+        }
+        let s_inv = s_inv.unwrap();
+        let u1 = digest_to_scalar(digest) * s_inv;
+        let u2 = self.r * s_inv;
+        let nonce = (Secp256k1::generator() * u1 + *public_key * u2).to_affine();
+        match Option::from(nonce.coordinates()) {
+            Some(coordinates) => mod_n(*coordinates.x()) == self.r,
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// The signatures of an input file: one per input, over the input's Poseidon digest, all
+/// made with the same key.
+pub struct SignatureVariables {
+    /// The signer's public key.
+    pub public_key: Secp256k1Affine,
+    /// The signature of each input's digest.
+    pub signatures: Vec<Signature>,
+    /// The auxiliary generator used in the ECC chip.
+    pub aux_generator: Secp256k1Affine,
+}
+
+impl Default for SignatureVariables {
+    fn default() -> Self {
+        let generator = Secp256k1::generator();
+        Self {
+            public_key: generator.to_affine(),
+            signatures: vec![],
+            aux_generator: generator.double().to_affine(),
+        }
+    }
+}
+
+impl SignatureVariables {
+    /// Signs the digests of every input with the secret key `sk`.
+    pub fn sign<R: CryptoRng + RngCore>(sk: Fq, digests: &[Fr], mut rng: &mut R) -> Self {
+        Self {
+            public_key: (Secp256k1::generator() * sk).to_affine(),
+            signatures: digests
+                .iter()
+                .map(|d| Signature::sign(sk, *d, &mut rng))
+                .collect(),
+            aux_generator: Secp256k1::random(&mut rng).to_affine(),
+        }
+    }
+
+    /// Checks that there is a valid signature for every digest.
+    pub fn verify(&self, digests: &[Fr]) -> Result<(), String> {
+        if self.signatures.len() != digests.len() {
+            return Err(format!(
+                "expected {} signatures (one per input), found {}",
+                digests.len(),
+                self.signatures.len()
+            ));
+        }
+        for (i, (signature, digest)) in self.signatures.iter().zip(digests).enumerate() {
+            if !signature.verify(&self.public_key, *digest) {
+                return Err(format!(
+                    "signature {} doesn't match the digest of input {}",
+                    i, i
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The signature of the `i`th input. Missing signatures (e.g. during key generation) are
+    /// laid out as a dummy signature that won't verify.
+    fn signature(&self, i: usize) -> Signature {
+        self.signatures.get(i).copied().unwrap_or(Signature {
+            r: Fq::ONE,
+            s: Fq::ONE,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Configuration for the signature chip.
+pub struct SignatureConfig {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+    /// The column used for the instance.
+    pub instance: Column<Instance>,
+    /// The config has been initialized.
+    pub initialized: bool,
+}
+
+impl SignatureConfig {
+    /// Configure the chip, exposing its instances in an existing instance column.
+    pub fn configure(meta: &mut ConstraintSystem<Fr>, instance: Column<Instance>) -> Self {
+        let (rns_base, rns_scalar) =
+            GeneralEccChip::<Secp256k1Affine, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
+        let main_gate_config = MainGate::<Fr>::configure(meta);
+
+        let mut overflow_bit_lens: Vec<usize> = vec![];
+        overflow_bit_lens.extend(rns_base.overflow_lengths());
+        overflow_bit_lens.extend(rns_scalar.overflow_lengths());
+        overflow_bit_lens.extend(NativeRns::construct().overflow_lengths());
+        let composition_bit_lens = vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS];
+
+        let range_config = RangeChip::<Fr>::configure(
+            meta,
+            &main_gate_config,
+            composition_bit_lens,
+            overflow_bit_lens,
+        );
+
+        SignatureConfig {
+            main_gate_config,
+            range_config,
+            instance,
+            initialized: false,
+        }
+    }
+
+    fn config_range(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), Error> {
+        let range_chip = RangeChip::<Fr>::new(self.range_config.clone());
+        range_chip.load_table(layouter)?;
+        Ok(())
+    }
+
+    fn ecc_chip_config(&self) -> EccConfig {
+        EccConfig::new(self.range_config.clone(), self.main_gate_config.clone())
+    }
+}
+
+#[derive(Debug)]
+/// A chip verifying ECDSA signatures over digests exposed in the instance column.
+pub struct SignatureChip {
+    /// The configuration for this chip.
+    pub config: SignatureConfig,
+}
+
+impl SignatureChip {
+    /// Create a new `SignatureChip`.
+    pub fn new(config: SignatureConfig) -> Self {
+        Self { config }
+    }
+
+    /// Verifies a signature for each of the `digests`, which must already be exposed at
+    /// consecutive instance rows from `digest_row`, and exposes the signer's public key at
+    /// `public_key_row` and `public_key_row + 1`.
+    pub fn layout(
+        &self,
+        layouter: &mut impl Layouter<Fr>,
+        variables: &SignatureVariables,
+        digests: &[Value<Fr>],
+        digest_row: usize,
+        public_key_row: usize,
+    ) -> Result<(), Error> {
+        let start_time = instant::Instant::now();
+
+        if !self.config.initialized {
+            self.config.config_range(layouter)?;
+        }
+
+        let mut ecc_chip =
+            GeneralEccChip::<Secp256k1Affine, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(
+                self.config.ecc_chip_config(),
+            );
+        layouter.assign_region(
+            || "signature aux values",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                ecc_chip.assign_aux_generator(ctx, Value::known(variables.aux_generator))?;
+                ecc_chip.assign_aux(ctx, WINDOW_SIZE, 2)?;
+                Ok(())
+            },
+        )?;
+
+        let ecdsa_chip = EcdsaChip::new(ecc_chip.clone());
+        let scalar_chip = ecc_chip.scalar_field_chip();
+        let native_rns = Rc::new(NativeRns::construct());
+        let native_chip = IntegerChip::new(
+            IntegerConfig::new(
+                self.config.range_config.clone(),
+                self.config.main_gate_config.clone(),
+            ),
+            native_rns.clone(),
+        );
+
+        let mut public_key_cells: Option<(AssignedValue<Fr>, AssignedValue<Fr>)> = None;
+        for (i, digest) in digests.iter().enumerate() {
+            let signature = variables.signature(i);
+            let (msg_cell, pk_x, pk_y) = layouter.assign_region(
+                || format!("verify signature {}", i),
+                |region| {
+                    let ctx = &mut RegionCtx::new(region, 0);
+                    let r = ecc_chip.new_unassigned_scalar(Value::known(signature.r));
+                    let s = ecc_chip.new_unassigned_scalar(Value::known(signature.s));
+                    let msg = ecc_chip.new_unassigned_scalar(digest.map(digest_to_scalar));
+
+                    let sig = AssignedEcdsaSig {
+                        r: scalar_chip.assign_integer(ctx, r, Range::Remainder)?,
+                        s: scalar_chip.assign_integer(ctx, s, Range::Remainder)?,
+                    };
+                    let pk = AssignedPublicKey {
+                        point: ecc_chip.assign_point(ctx, Value::known(variables.public_key))?,
+                    };
+                    let msg = scalar_chip.assign_integer(ctx, msg, Range::Remainder)?;
+
+                    ecdsa_chip.verify(ctx, &sig, &pk, &msg)?;
+
+                    // the native value of msg only fixes it modulo the native field order, so
+                    // any msg = digest + k.p would also pass. msg shares its limbs with an
+                    // integer that is range checked below the native field order, which makes it
+                    // the canonical integer of the digest.
+                    let digest_int = UnassignedInteger::from(
+                        digest.map(|d| Integer::from_fe(d, native_rns.clone())),
+                    );
+                    let digest_int =
+                        native_chip.assign_integer(ctx, digest_int, Range::Remainder)?;
+                    native_chip.assert_in_field(ctx, &digest_int)?;
+                    for j in 0..NUMBER_OF_LIMBS {
+                        ctx.constrain_equal(msg.limb(j).cell(), digest_int.limb(j).cell())?;
+                    }
+
+                    Ok((
+                        digest_int.native().clone(),
+                        pk.point.x().native().clone(),
+                        pk.point.y().native().clone(),
+                    ))
+                },
+            )?;
+
+            // the signed message is the digest the poseidon module exposed for this input
+            layouter.constrain_instance(msg_cell.cell(), self.config.instance, digest_row + i)?;
+
+            // every input is signed by the same key
+            match &public_key_cells {
+                Some((x, y)) => {
+                    layouter.assign_region(
+                        || format!("same signer {}", i),
+                        |mut region| {
+                            region.constrain_equal(x.cell(), pk_x.cell())?;
+                            region.constrain_equal(y.cell(), pk_y.cell())
+                        },
+                    )?;
+                }
+                None => public_key_cells = Some((pk_x, pk_y)),
+            }
+        }
+
+        if let Some((x, y)) = public_key_cells {
+            layouter.constrain_instance(x.cell(), self.config.instance, public_key_row)?;
+            layouter.constrain_instance(y.cell(), self.config.instance, public_key_row + 1)?;
+        }
+
+        log::trace!(
+            "layout (N={:?}) took: {:?}",
+            digests.len(),
+            start_time.elapsed()
+        );
+
+        Ok(())
+    }
+
+    /// A conservative estimate of the rows used to verify `num_signatures` signatures with a
+    /// window size of 4, including the one time cost of the range table and aux values.
+    pub fn num_rows(num_signatures: usize) -> usize {
+        const NUM_ROWS_PER_SIGNATURE: usize = 220_000;
+        const NUM_ROWS_FIXED: usize = 1 << 17;
+        num_signatures * NUM_ROWS_PER_SIGNATURE + NUM_ROWS_FIXED
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::test_rng;
+    use halo2_proofs::circuit::SimpleFloorPlanner;
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::plonk::Circuit;
+
+    const K: u32 = 19;
+
+    struct SignatureCircuit {
+        variables: SignatureVariables,
+        digests: Vec<Value<Fr>>,
+    }
+
+    impl Circuit<Fr> for SignatureCircuit {
+        type Config = SignatureConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = ();
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                variables: self.variables.clone(),
+                digests: vec![Value::unknown(); self.digests.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> SignatureConfig {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            SignatureConfig::configure(meta, instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: SignatureConfig,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let chip = SignatureChip::new(config);
+            chip.layout(
+                &mut layouter,
+                &self.variables,
+                &self.digests,
+                0,
+                self.digests.len(),
+            )
+        }
+    }
+
+    /// Runs the chip on signed `digests`, exposing `instances` as the digests.
+    fn mock_prove(variables: &SignatureVariables, digests: &[Fr], instances: &[Fr]) -> bool {
+        let circuit = SignatureCircuit {
+            variables: variables.clone(),
+            digests: digests.iter().map(|d| Value::known(*d)).collect(),
+        };
+        let mut public_inputs = instances.to_vec();
+        public_inputs.extend(public_key_instances(&variables.public_key));
+        let prover = MockProver::run(K, &circuit, vec![public_inputs]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    #[test]
+    fn test_circuit_valid_signature() {
+        let mut rng = test_rng();
+        let digests = vec![Fr::random(&mut rng)];
+        let variables = SignatureVariables::sign(Fq::random(&mut rng), &digests, &mut rng);
+
+        assert!(mock_prove(&variables, &digests, &digests));
+    }
+
+    #[test]
+    fn test_circuit_tampered_signature() {
+        let mut rng = test_rng();
+        let digests = vec![Fr::random(&mut rng)];
+        let mut variables = SignatureVariables::sign(Fq::random(&mut rng), &digests, &mut rng);
+        variables.signatures[0].s += Fq::ONE;
+
+        assert!(!mock_prove(&variables, &digests, &digests));
+    }
+
+    #[test]
+    fn test_circuit_tampered_message() {
+        let mut rng = test_rng();
+        let digests = vec![Fr::random(&mut rng)];
+        let variables = SignatureVariables::sign(Fq::random(&mut rng), &digests, &mut rng);
+
+        // the signature doesn't cover another message
+        let tampered = vec![digests[0] + Fr::one()];
+        assert!(!mock_prove(&variables, &tampered, &tampered));
+        // and the signed message has to be the exposed digest
+        assert!(!mock_prove(&variables, &digests, &tampered));
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut rng = test_rng();
+        let sk = Fq::random(&mut rng);
+        let digests = vec![Fr::random(&mut rng), Fr::random(&mut rng)];
+
+        let variables = SignatureVariables::sign(sk, &digests, &mut rng);
+        assert!(variables.verify(&digests).is_ok());
+
+        let tampered = vec![digests[0], digests[0]];
+        assert!(variables.verify(&tampered).is_err());
+        assert!(variables.verify(&digests[..1]).is_err());
+    }
+
+    #[test]
+    fn test_variables_serialization_round_trip() {
+        let mut rng = test_rng();
+        let variables = SignatureVariables::sign(Fq::random(&mut rng), &[Fr::one()], &mut rng);
+
+        let json = serde_json::to_string(&variables).unwrap();
+        let variables2: SignatureVariables = serde_json::from_str(&json).unwrap();
+        assert_eq!(variables, variables2);
+    }
+}
@@ -202,6 +202,16 @@ impl ElGamalVariables {
         }
     }
 
+    /// The variables with the secret key and randomness zeroed, safe to publish alongside a
+    /// ciphertext.
+    pub fn without_secrets(&self) -> Self {
+        Self {
+            r: Fr::zero(),
+            sk: Fr::zero(),
+            ..self.clone()
+        }
+    }
+
     /// Generate random variables.
     pub fn gen_random<R: CryptoRng + RngCore>(mut rng: &mut R) -> Self {
         // get a random element from the scalar field
@@ -239,6 +249,9 @@ pub struct ElGamalGadget {
     pub config: ElGamalConfig,
     /// The variables used in this gadget.
     variables: Option<ElGamalVariables>,
+    /// Whether the message is encrypted to the public key of a recipient, who alone can decrypt
+    /// it, rather than under the prover's own secret key.
+    to_recipient: bool,
 }
 
 impl ElGamalGadget {
@@ -247,6 +260,12 @@ impl ElGamalGadget {
         self.variables = Some(variables);
     }
 
+    /// Encrypt to the public key of the variables, proving knowledge of the randomness instead
+    /// of the secret key. The prover doesn't need the recipient's secret key.
+    pub fn to_recipient(&mut self) {
+        self.to_recipient = true;
+    }
+
     fn rns() -> Rc<Rns<Fq, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>> {
         let rns = Rns::<Fq, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::construct();
         Rc::new(rns)
@@ -288,6 +307,28 @@ impl ElGamalGadget {
             .unwrap()[0][0]
     }
 
+    /// Hash the recipient's public key to be used as a public input.
+    pub fn hash_pk(pk: G1Affine) -> Fr {
+        let coords = pk.coordinates().unwrap();
+        let x = Integer::from_fe(*coords.x(), Self::rns());
+        let y = Integer::from_fe(*coords.y(), Self::rns());
+        PoseidonChip::<PoseidonSpec, POSEIDON_WIDTH, POSEIDON_RATE, POSEIDON_LEN>::run(vec![
+            x.native(),
+            y.native(),
+        ])
+        .unwrap()[0][0]
+    }
+
+    /// The public input identifying the key that can decrypt the message: the hash of the
+    /// recipient's public key, or of the prover's secret key.
+    pub fn key_hash(variables: &ElGamalVariables, to_recipient: bool) -> Fr {
+        if to_recipient {
+            Self::hash_pk(variables.pk)
+        } else {
+            Self::hash_sk(variables.sk)
+        }
+    }
+
     /// Decrypt a ciphertext using the secret key.
     pub fn decrypt(cipher: &ElGamalCipher, sk: Fr) -> Vec<Fr> {
         let c1 = cipher.c1;
@@ -444,6 +485,82 @@ impl ElGamalGadget {
         )
     }
 
+    /// Constrain c1 = r*G and s = r*pk for the recipient's public key pk, so that the recipient
+    /// recovers s as sk*c1. Returns s, c1 and pk.
+    pub(crate) fn verify_recipient_secret(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        config: &ElGamalConfig,
+        r: &AssignedCell<Fr, Fr>,
+    ) -> Result<[AssignedPoint<Fq, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>; 3], plonk::Error> {
+        let mut chip = ElGamalChip::new(config.clone());
+
+        let variables = match self.variables {
+            Some(ref variables) => variables,
+            None => {
+                log::error!("variables not loaded");
+                return Err(plonk::Error::Synthesis);
+            }
+        };
+
+        layouter.assign_region(
+            || "obtain_s_from_recipient",
+            |region| {
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+
+                chip.ecc
+                    .assign_aux_generator(ctx, Value::known(variables.aux_generator))?;
+                chip.ecc.assign_aux(ctx, variables.window_size, 1)?;
+
+                let g = chip.ecc.assign_constant(ctx, G1Affine::generator())?;
+                let pk = chip.ecc.assign_point(ctx, Value::known(variables.pk))?;
+
+                let c1 = chip.ecc.mul(ctx, &g, r, variables.window_size)?;
+                let s = chip.ecc.mul(ctx, &pk, r, variables.window_size)?;
+
+                Ok([s, c1, pk])
+            },
+        )
+    }
+
+    /// Hash the recipient's public key to be used as a public input.
+    pub(crate) fn verify_pk_hash(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        config: &ElGamalConfig,
+        pk: &AssignedPoint<Fq, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) -> Result<AssignedCell<Fr, Fr>, plonk::Error> {
+        let chip = ElGamalChip::new(config.clone());
+
+        let pk_hash = {
+            let poseidon_message = Tensor::from(
+                [
+                    ValType::from(pk.x().native().clone()),
+                    ValType::from(pk.y().native().clone()),
+                ]
+                .into_iter(),
+            );
+
+            chip.poseidon.layout(
+                &mut layouter.namespace(|| "Poseidon hash (pk)"),
+                &[poseidon_message.into()],
+                0,
+            )?
+        };
+
+        match &pk_hash
+            .get_inner_tensor()
+            .map_err(|_| plonk::Error::Synthesis)?[0]
+        {
+            ValType::PrevAssigned(v) => Ok(v.clone()),
+            _ => {
+                log::error!("poseidon hash should be an assigned value");
+                Err(plonk::Error::Synthesis)
+            }
+        }
+    }
+
     pub(crate) fn verify_encryption(
         &self,
         mut layouter: impl Layouter<Fr>,
@@ -499,6 +616,7 @@ impl Module<Fr> for ElGamalGadget {
         Self {
             config,
             variables: None,
+            to_recipient: false,
         }
     }
 
@@ -633,20 +751,33 @@ impl Module<Fr> for ElGamalGadget {
             self.config.config_range(layouter).unwrap();
         }
 
-        let (msg_var, sk_var) = self.layout_inputs(layouter, inputs)?;
-
-        let [s, c1] = self.verify_secret(
-            layouter.namespace(|| "verify_secret"),
-            &self.config,
-            &sk_var,
-        )?;
-
-        // Force the public input to be the hash of the secret key so that we can ascertain decryption can happen
-        let sk_hash = self.verify_sk_hash(
-            layouter.namespace(|| "verify_sk_hash"),
-            &self.config,
-            &sk_var,
-        )?;
+        // the secret is the secret key, or the randomness when encrypting to a recipient
+        let (msg_var, secret_var) = self.layout_inputs(layouter, inputs)?;
+
+        let (s, c1, key_hash) = if self.to_recipient {
+            let [s, c1, pk] = self.verify_recipient_secret(
+                layouter.namespace(|| "verify_recipient_secret"),
+                &self.config,
+                &secret_var,
+            )?;
+            // Force the public input to be the hash of the recipient's public key so that we can ascertain who can decrypt
+            let pk_hash =
+                self.verify_pk_hash(layouter.namespace(|| "verify_pk_hash"), &self.config, &pk)?;
+            (s, c1, pk_hash)
+        } else {
+            let [s, c1] = self.verify_secret(
+                layouter.namespace(|| "verify_secret"),
+                &self.config,
+                &secret_var,
+            )?;
+            // Force the public input to be the hash of the secret key so that we can ascertain decryption can happen
+            let sk_hash = self.verify_sk_hash(
+                layouter.namespace(|| "verify_sk_hash"),
+                &self.config,
+                &secret_var,
+            )?;
+            (s, c1, sk_hash)
+        };
 
         layouter
             .constrain_instance(
@@ -660,7 +791,7 @@ impl Module<Fr> for ElGamalGadget {
                 C1_Y + row_offset,
             ))
             .and(layouter.constrain_instance(
-                sk_hash.cell(),
+                key_hash.cell(),
                 self.config.instance,
                 SK_H + row_offset,
             ))?;
@@ -737,6 +868,14 @@ impl Module<Fr> for ElGamalGadget {
     }
 }
 
+impl ElGamalGadget {
+    /// The number of rows used when encrypting to a recipient. The fixed cost is dominated by
+    /// the scalar multiplication, so the second one (c1 = r*G) at most doubles it.
+    pub fn num_rows_to_recipient(input_len: usize) -> usize {
+        Self::num_rows(input_len) + Self::num_rows(0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::circuit::modules::ModulePlanner;
@@ -748,6 +887,7 @@ mod tests {
     struct EncryptionCircuit {
         message: ValTensor<Fr>,
         variables: ElGamalVariables,
+        to_recipient: bool,
     }
 
     impl Circuit<Fr> for EncryptionCircuit {
@@ -764,6 +904,7 @@ mod tests {
             Self {
                 message: message.into(),
                 variables,
+                to_recipient: self.to_recipient,
             }
         }
 
@@ -778,9 +919,15 @@ mod tests {
         ) -> Result<(), Error> {
             let mut chip = ElGamalGadget::new(config);
             chip.load_variables(self.variables.clone());
-            let sk: Tensor<ValType<Fr>> =
-                Tensor::new(Some(&[Value::known(self.variables.sk).into()]), &[1]).unwrap();
-            chip.layout(&mut layouter, &[self.message.clone(), sk.into()], 0)?;
+            let secret = if self.to_recipient {
+                chip.to_recipient();
+                self.variables.r
+            } else {
+                self.variables.sk
+            };
+            let secret: Tensor<ValType<Fr>> =
+                Tensor::new(Some(&[Value::known(secret).into()]), &[1]).unwrap();
+            chip.layout(&mut layouter, &[self.message.clone(), secret.into()], 0)?;
             Ok(())
         }
     }
@@ -800,6 +947,19 @@ mod tests {
         assert_eq!(var, var2);
     }
 
+    #[test]
+    fn test_without_secrets() {
+        let mut rng = test_rng();
+
+        let var = ElGamalVariables::gen_random(&mut rng);
+        let public = var.without_secrets();
+
+        assert_eq!(public.sk, Fr::zero());
+        assert_eq!(public.r, Fr::zero());
+        assert_eq!(public.pk, var.pk);
+        assert_eq!(public.aux_generator, var.aux_generator);
+    }
+
     #[test]
     pub fn test_encrypt_decrypt() {
         let mut rng = test_rng();
@@ -839,12 +999,41 @@ mod tests {
         let circuit = EncryptionCircuit {
             message: message.into(),
             variables: var,
+            to_recipient: false,
         };
 
         let res = MockProver::run(17, &circuit, public_inputs).unwrap();
         res.assert_satisfied_par();
     }
 
+    #[test]
+    pub fn test_circuit_to_recipient() {
+        let mut rng = test_rng();
+
+        let mut var = ElGamalVariables::gen_random(&mut rng);
+        let sk = var.sk;
+        // the prover only knows the recipient's public key
+        var.sk = Fr::zero();
+
+        let msg = vec![Fr::random(&mut rng), Fr::random(&mut rng)];
+
+        let cipher = ElGamalGadget::encrypt(var.pk, msg.clone(), var.r);
+        assert_eq!(ElGamalGadget::decrypt(&cipher, sk), msg);
+        let public_inputs =
+            ElGamalGadget::get_instances(&cipher, ElGamalGadget::key_hash(&var, true));
+
+        let message: Tensor<ValType<Fr>> = msg.into_iter().map(|m| Value::known(m).into()).into();
+
+        let circuit = EncryptionCircuit {
+            message: message.into(),
+            variables: var,
+            to_recipient: true,
+        };
+
+        let res = MockProver::run(18, &circuit, public_inputs).unwrap();
+        res.assert_satisfied_par();
+    }
+
     #[test]
     #[ignore]
     pub fn test_circuit_range_of_input_sizes() {
@@ -872,6 +1061,7 @@ mod tests {
             let circuit = EncryptionCircuit {
                 message: message.into(),
                 variables: var,
+                to_recipient: false,
             };
 
             let res = MockProver::run(19, &circuit, public_inputs).unwrap();
@@ -4,6 +4,9 @@ pub mod poseidon;
 ///
 pub mod elgamal;
 
+///
+pub mod ecdsa;
+
 ///
 pub mod kzg;
 
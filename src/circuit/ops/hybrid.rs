@@ -19,6 +19,11 @@ pub enum HybridOp {
     ReduceArgMax {
         dim: usize,
     },
+    ReduceMean {
+        axes: Vec<usize>,
+        /// the number of elements averaged into each output
+        len: usize,
+    },
     SumPool {
         padding: [(usize, usize); 2],
         stride: (usize, usize),
@@ -54,6 +59,10 @@ pub enum HybridOp {
         dim: usize,
         k: usize,
     },
+    TopKIndices {
+        dim: usize,
+        k: usize,
+    },
     OneHot {
         dim: usize,
         num_classes: usize,
@@ -72,7 +81,11 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
     ///
     fn requires_homogenous_input_scales(&self) -> Vec<usize> {
         match self {
-            HybridOp::Greater | HybridOp::Less | HybridOp::Equals => vec![0, 1],
+            HybridOp::Greater
+            | HybridOp::GreaterEqual
+            | HybridOp::Less
+            | HybridOp::LessEqual
+            | HybridOp::Equals => vec![0, 1],
             HybridOp::ScatterElements { .. } => vec![0, 2],
             _ => vec![],
         }
@@ -175,6 +188,16 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
 
                 (res.clone(), inter_equals)
             }
+            HybridOp::TopKIndices { dim, k } => {
+                let res = tensor::ops::topk_indices_axes(&x, *k, *dim)?.map(|i| i as i128);
+                let indices = Tensor::from(0..x.dims()[*dim] as i128);
+                let mut inter_equals: Vec<Tensor<i128>> = vec![indices.clone(), -indices];
+                let inter =
+                    Op::f(&HybridOp::TopK { dim: *dim, k: *k }, inputs)?.intermediate_lookups;
+                inter_equals.extend(inter);
+
+                (res.clone(), inter_equals)
+            }
             HybridOp::GatherElements { dim, constant_idx } => {
                 if let Some(idx) = constant_idx {
                     log::debug!("idx: {}", idx.show());
@@ -222,6 +245,11 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
                     vec![inter_1, inter_2],
                 )
             }
+            HybridOp::ReduceMean { axes, len } => {
+                let sum = tensor::ops::sum_axes(&x, axes)?;
+                let res = tensor::ops::nonlinearities::const_div(&sum, *len as f64);
+                (res, vec![sum])
+            }
             HybridOp::SumPool {
                 padding,
                 stride,
@@ -282,6 +310,9 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
             ),
             HybridOp::ReduceMax { axes } => format!("REDUCEMAX (axes={:?})", axes),
             HybridOp::ReduceArgMax { dim } => format!("REDUCEARGMAX (dim={})", dim),
+            HybridOp::ReduceMean { axes, len } => {
+                format!("REDUCEMEAN (axes={:?}, len={})", axes, len)
+            }
             HybridOp::MaxPool2d {
                 padding,
                 stride,
@@ -303,6 +334,7 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
             HybridOp::Equals => "EQUALS".into(),
             HybridOp::Gather { dim, .. } => format!("GATHER (dim={})", dim),
             HybridOp::TopK { k, dim } => format!("TOPK (k={}, dim={})", k, dim),
+            HybridOp::TopKIndices { k, dim } => format!("TOPKINDICES (k={}, dim={})", k, dim),
             HybridOp::GatherElements { dim, .. } => format!("GATHERELEMENTS (dim={})", dim),
             HybridOp::ScatterElements { dim, .. } => format!("SCATTERELEMENTS (dim={})", dim),
             HybridOp::OneHot { dim, num_classes } => {
@@ -377,6 +409,9 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
             HybridOp::ReduceArgMax { dim } => {
                 layouts::argmax_axes(config, region, values[..].try_into()?, *dim)?
             }
+            HybridOp::ReduceMean { axes, len } => {
+                layouts::mean_axes(config, region, values[..].try_into()?, axes, *len)?
+            }
             HybridOp::ReduceMin { axes } => {
                 layouts::min_axes(config, region, values[..].try_into()?, axes)?
             }
@@ -403,6 +438,9 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
             HybridOp::TopK { dim, k } => {
                 layouts::topk_axes(config, region, values[..].try_into()?, *k, *dim)?
             }
+            HybridOp::TopKIndices { dim, k } => {
+                layouts::topk_indices_axes(config, region, values[..].try_into()?, *k, *dim)?
+            }
             HybridOp::OneHot { dim, num_classes } => {
                 layouts::one_hot_axis(config, region, values[..].try_into()?, *num_classes, *dim)?
             }
@@ -416,9 +454,14 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
             | HybridOp::Less { .. }
             | HybridOp::LessEqual { .. }
             | HybridOp::ReduceArgMax { .. }
+            | HybridOp::TopKIndices { .. }
             | HybridOp::OneHot { .. }
             | HybridOp::ReduceArgMin { .. } => 0,
-            HybridOp::Softmax { .. } => 2 * in_scales[0],
+            // the exponentials are taken at `scale`, which is usually the input's but absorbs any
+            // power of two the scores were divided by
+            HybridOp::Softmax { scale, .. } => {
+                2 * crate::graph::multiplier_to_scale(scale.0 as f64)
+            }
             _ => in_scales[0],
         };
         Ok(scale)
@@ -462,7 +505,7 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
                     a: circuit::utils::F32(0.),
                 }]
             }
-            HybridOp::TopK { .. } => {
+            HybridOp::TopK { .. } | HybridOp::TopKIndices { .. } => {
                 vec![
                     LookupOp::GreaterThan {
                         a: circuit::utils::F32(0.),
@@ -486,6 +529,11 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for HybridOp {
             HybridOp::ReduceArgMax { .. } | HybridOp::ReduceArgMin { .. } => {
                 vec![LookupOp::ReLU, LookupOp::KroneckerDelta]
             }
+            HybridOp::ReduceMean { len, .. } => {
+                vec![LookupOp::Div {
+                    denom: utils::F32(*len as f32),
+                }]
+            }
             HybridOp::SumPool {
                 kernel_shape,
                 normalized: true,
@@ -1,8 +1,4 @@
-use std::{
-    collections::{HashMap, HashSet},
-    error::Error,
-    ops::Range,
-};
+use std::{collections::HashSet, error::Error, ops::Range};
 
 use halo2_proofs::circuit::Value;
 use halo2curves::ff::PrimeField;
@@ -178,20 +174,30 @@ pub fn einsum<F: PrimeField + TensorType + PartialOrd>(
         return Err(Box::new(TensorError::DimMismatch("einsum".to_string())));
     }
 
-    let mut indices_to_size = HashMap::new();
-    for (i, input) in inputs.iter().enumerate() {
-        for j in 0..inputs_eq[i].len() {
-            let c = inputs_eq[i]
-                .chars()
-                .nth(j)
-                .ok_or(CircuitError::InvalidEinsum)?;
-            if let std::collections::hash_map::Entry::Vacant(e) = indices_to_size.entry(c) {
-                e.insert(input.dims()[j]);
-            } else if indices_to_size[&c] != input.dims()[j] {
-                return Err(Box::new(TensorError::DimMismatch("einsum".to_string())));
+    let mut indices_to_size = tensor::ops::einsum_index_sizes(
+        &inputs_eq,
+        &inputs.iter().map(|i| i.dims()).collect::<Vec<_>>(),
+    )?;
+
+    // broadcast the inputs along their indices of size 1
+    let inputs = inputs
+        .iter()
+        .zip(&inputs_eq)
+        .map(|(input, eq)| {
+            let dims = eq.chars().map(|c| indices_to_size[&c]).collect::<Vec<_>>();
+            if input.dims() == dims.as_slice() {
+                return Ok(input.clone());
             }
-        }
-    }
+            // instances have to be copied to the advice before they can be reindexed
+            let mut input = if matches!(input, ValTensor::Instance { .. }) {
+                identity(config, region, &[input.clone()])?
+            } else {
+                input.clone()
+            };
+            input.expand(&dims)?;
+            Ok(input)
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
 
     // maps unrepresented indices in the output to a trivial 1
     for c in output_eq.chars() {
@@ -414,8 +420,18 @@ fn _sort_descending<F: PrimeField + TensorType + PartialOrd>(
         )?
     };
 
+    constrain_sort_descending(config, region, &input, sorted)
+}
+
+/// Assigns the witness `sorted` and constrains it to be `input` sorted in descending order
+pub(crate) fn constrain_sort_descending<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    input: &ValTensor<F>,
+    sorted: Tensor<Value<F>>,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
     let assigned_sort = region.assign(&config.inputs[0], &sorted.into())?;
-    let input = region.assign(&config.inputs[1], &input)?;
+    let input = region.assign(&config.inputs[1], input)?;
 
     let mut unit = Tensor::from(vec![F::from(1)].into_iter());
     unit.set_visibility(&crate::graph::Visibility::Fixed);
@@ -423,7 +439,7 @@ fn _sort_descending<F: PrimeField + TensorType + PartialOrd>(
 
     region.increment(assigned_sort.len());
 
-    for i in 0..assigned_sort.len() - 1 {
+    for i in 0..assigned_sort.len().saturating_sub(1) {
         // assert that each thing in turn is larger than the next
         let window_a = assigned_sort.get_slice(&[i..i + 1])?;
         let window_b = assigned_sort.get_slice(&[i + 1..i + 2])?;
@@ -444,23 +460,63 @@ fn _sort_descending<F: PrimeField + TensorType + PartialOrd>(
         )?;
 
         enforce_equality(config, region, &[unit.clone(), greater_than.clone()])?;
+    }
 
-        // now assert that the elem is in the original vector
-        let is_present = equals(config, region, &[window_a, input.clone()])?;
-        let sum_equals = sum(config, region, &[is_present])?;
-        let greater_than = nonlinearity(
-            config,
-            region,
-            &[sum_equals],
-            &LookupOp::GreaterThan { a: 0.0.into() },
-        )?;
+    for i in 0..assigned_sort.len() {
+        // assert that each elem occurs as many times in the original vector as in the sorted one,
+        // so that the sorted vector is a permutation of the original (and not, for instance, the
+        // max repeated k times)
+        let window = assigned_sort.get_slice(&[i..i + 1])?;
+        let in_input = equals(config, region, &[window.clone(), input.clone()])?;
+        let in_input = sum(config, region, &[in_input])?;
+        let in_sorted = equals(config, region, &[window, assigned_sort.clone()])?;
+        let in_sorted = sum(config, region, &[in_sorted])?;
 
-        enforce_equality(config, region, &[unit.clone(), greater_than.clone()])?;
+        enforce_equality(config, region, &[in_input, in_sorted])?;
     }
 
     Ok(assigned_sort)
 }
 
+/// The indices of the elements of `input` in `sorted`, its descending sort, with equal elements
+/// taken in the order they appear in `input`
+fn _sort_descending_indices<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    input: &ValTensor<F>,
+    sorted: &ValTensor<F>,
+    dim_indices: &ValTensor<F>,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let n = input.len();
+    if n == 0 {
+        return Ok(input.clone());
+    }
+
+    let constant = |x: u64| -> Result<ValTensor<F>, Box<dyn Error>> {
+        let mut c = Tensor::from(vec![F::from(x)].into_iter());
+        c.set_visibility(&crate::graph::Visibility::Fixed);
+        Ok(c.try_into()?)
+    };
+    let (len, last) = (constant(n as u64)?, constant(n as u64 - 1)?);
+
+    // the keys `input[j] * n + (n - 1 - j)` sort by value and then by position, so the i-th key of
+    // their descending sort belongs to `sorted[i]` and encodes the position it came from
+    let scaled = pairwise(config, region, &[input.clone(), len.clone()], BaseOp::Mult)?;
+    let reversed = pairwise(
+        config,
+        region,
+        &[last.clone(), dim_indices.clone()],
+        BaseOp::Sub,
+    )?;
+    let keys = pairwise(config, region, &[scaled, reversed], BaseOp::Add)?;
+    let sorted_keys = _sort_descending(config, region, &[keys])?;
+
+    // the position is n - 1 - (key - value * n)
+    let sorted_scaled = pairwise(config, region, &[sorted.clone(), len], BaseOp::Mult)?;
+    let reversed = pairwise(config, region, &[sorted_keys, sorted_scaled], BaseOp::Sub)?;
+    pairwise(config, region, &[last, reversed], BaseOp::Sub)
+}
+
 fn _sort_ascending<F: PrimeField + TensorType + PartialOrd>(
     config: &BaseConfig<F>,
     region: &mut RegionCtx<F>,
@@ -478,8 +534,8 @@ fn _sort_ascending<F: PrimeField + TensorType + PartialOrd>(
             .get_int_evals()?
             .iter()
             .sorted_by(|a, b| a.cmp(b))
-            .map(|x| Ok(Value::known(input.get_felt_evals()?.get(&[*x as usize]))))
-            .collect::<Result<Tensor<Value<F>>, Box<dyn Error>>>()?
+            .map(|x| Value::known(i128_to_felt(*x)))
+            .collect::<Tensor<_>>()
     } else {
         Tensor::new(
             Some(&vec![Value::<F>::unknown(); input.len()]),
@@ -495,7 +551,7 @@ fn _sort_ascending<F: PrimeField + TensorType + PartialOrd>(
 
     region.increment(assigned_sort.len());
 
-    for i in 0..assigned_sort.len() - 1 {
+    for i in 0..assigned_sort.len().saturating_sub(1) {
         // assert that each thing in turn is larger than the next
         let window_a = assigned_sort.get_slice(&[i..i + 1])?;
         let window_b = assigned_sort.get_slice(&[i + 1..i + 2])?;
@@ -549,6 +605,19 @@ fn _select_topk<F: PrimeField + TensorType + PartialOrd>(
     Ok(sorted)
 }
 
+/// The indices of the top k elements
+fn _select_topk_indices<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    k: usize,
+    dim_indices: &ValTensor<F>,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let sorted = _sort_descending(config, region, values)?;
+    let indices = _sort_descending_indices(config, region, &values[0], &sorted, dim_indices)?;
+    Ok(indices.get_slice(&[0..k])?)
+}
+
 /// Select top k elements
 pub fn topk_axes<F: PrimeField + TensorType + PartialOrd>(
     config: &BaseConfig<F>,
@@ -569,6 +638,32 @@ pub fn topk_axes<F: PrimeField + TensorType + PartialOrd>(
     Ok(output)
 }
 
+/// Select the indices of the top k elements
+pub fn topk_indices_axes<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    k: usize,
+    dim: usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    // these will be assigned as constants
+    let mut indices = Tensor::from((0..values[0].dims()[dim] as u64).map(|x| F::from(x)));
+    indices.set_visibility(&crate::graph::Visibility::Fixed);
+    let indices = region.assign(&config.inputs[1], &indices.try_into()?)?;
+    region.increment(indices.len());
+
+    let topk_at_k = move |config: &BaseConfig<F>,
+                          region: &mut RegionCtx<F>,
+                          values: &[ValTensor<F>; 1]|
+          -> Result<ValTensor<F>, Box<dyn Error>> {
+        _select_topk_indices(config, region, values, k, &indices)
+    };
+
+    let output: ValTensor<F> = multi_dim_axes_op(config, region, values, &[dim], topk_at_k)?;
+
+    Ok(output)
+}
+
 fn select<F: PrimeField + TensorType + PartialOrd>(
     config: &BaseConfig<F>,
     region: &mut RegionCtx<F>,
@@ -797,10 +892,8 @@ pub fn gather<F: PrimeField + TensorType + PartialOrd>(
 
     region.apply_in_loop(&mut output, inner_loop_function)?;
 
-    // Reshape the output tensor
-    if index_clone.is_singleton() {
-        output_size.remove(dim);
-    }
+    // the gathered axis is replaced by the dims of the index
+    let output_size = [&input_dims[..dim], values[1].dims(), &input_dims[dim + 1..]].concat();
     output.reshape(&output_size)?;
 
     Ok(output.into())
@@ -1219,6 +1312,58 @@ pub fn sum_axes<F: PrimeField + TensorType + PartialOrd>(
     axes_wise_op(config, region, values, axes, sum)
 }
 
+/// Cumulative sum layout, a chain of additions along `axis`: each slice of the output is the
+/// previous slice of the output plus the matching slice of the input
+pub fn cumsum<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    axis: usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let mut input = values[0].clone();
+    // instances can only be sliced once copied to the advice
+    if matches!(input, ValTensor::Instance { .. }) {
+        input = identity(config, region, &[input])?;
+    }
+    let dims = input.dims().to_vec();
+    let slice = |i: usize| {
+        let ranges = dims
+            .iter()
+            .enumerate()
+            .map(|(j, d)| if j == axis { i..i + 1 } else { 0..*d })
+            .collect::<Vec<_>>();
+        input.get_slice(&ranges)
+    };
+
+    let mut running = slice(0)?;
+    let mut slices = vec![running.clone()];
+    for i in 1..dims[axis] {
+        running = pairwise(config, region, &[running, slice(i)?], BaseOp::Add)?;
+        slices.push(running.clone());
+    }
+    concat(&slices, &axis)
+}
+
+/// Mean accumulated layout, sums along `axes` then divides by the `len` elements summed into
+/// each output
+pub fn mean_axes<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    axes: &[usize],
+    len: usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let sum = sum_axes(config, region, values, axes)?;
+    nonlinearity(
+        config,
+        region,
+        &[sum],
+        &LookupOp::Div {
+            denom: utils::F32(len as f32),
+        },
+    )
+}
+
 /// argmax layout
 pub fn argmax_axes<F: PrimeField + TensorType + PartialOrd>(
     config: &BaseConfig<F>,
@@ -1769,7 +1914,7 @@ pub fn max_pool2d<F: PrimeField + TensorType + PartialOrd>(
     let horz_slides = (image_width + padding[0].1 + padding[1].1 - pool_dims.1) / stride.1 + 1;
 
     let mut output: Tensor<ValType<F>> =
-        Tensor::new(None, &[batch, input_channels, horz_slides, vert_slides])?;
+        Tensor::new(None, &[batch, input_channels, vert_slides, horz_slides])?;
 
     let cartesian_coord = [
         (0..batch),
@@ -2169,11 +2314,17 @@ pub fn reshape<F: PrimeField + TensorType + PartialOrd>(
 
 /// Dummy (no contraints) move_axis layout
 pub fn move_axis<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
     values: &[ValTensor<F>; 1],
     source: usize,
     destination: usize,
 ) -> Result<ValTensor<F>, Box<dyn Error>> {
     let mut t = values[0].clone();
+    // instances (e.g public NHWC inputs) can only be reindexed once copied to the advice
+    if matches!(t, ValTensor::Instance { .. }) {
+        t = identity(config, region, &[t])?;
+    }
     t.move_axis(source, destination)?;
     Ok(t)
 }
@@ -2192,6 +2343,30 @@ pub fn resize<F: PrimeField + TensorType + PartialOrd>(
     Ok(output)
 }
 
+/// bilinear resize layout, interpolates each resized axis in turn with a constant matrix of
+/// weights
+pub fn resize_bilinear<F: PrimeField + TensorType + PartialOrd>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    scales: &[usize],
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let mut output = values[0].clone();
+    let rank = output.dims().len();
+    for (axis, scale) in scales.iter().enumerate().take(rank) {
+        if *scale == 1 {
+            continue;
+        }
+        let mut weights = tensor::ops::linear_interpolation_weights(output.dims()[axis], *scale)?
+            .map(|w| F::from(w as u64));
+        weights.set_visibility(&crate::graph::Visibility::Fixed);
+        let equation = tensor::ops::interpolation_equation(rank, axis);
+        output = einsum(config, region, &[output, weights.try_into()?], &equation)?;
+    }
+
+    Ok(output)
+}
+
 /// Slice layout
 pub fn slice<F: PrimeField + TensorType + PartialOrd>(
     config: &BaseConfig<F>,
@@ -17,41 +17,130 @@ use halo2curves::ff::PrimeField;
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
 pub enum LookupOp {
     Abs,
-    Div { denom: utils::F32 },
+    Div {
+        denom: utils::F32,
+    },
     ReLU,
-    Max { scale: utils::F32, a: utils::F32 },
-    Min { scale: utils::F32, a: utils::F32 },
-    Ceil { scale: utils::F32 },
-    Floor { scale: utils::F32 },
-    Round { scale: utils::F32 },
-    RoundHalfToEven { scale: utils::F32 },
-    Sqrt { scale: utils::F32 },
-    Rsqrt { scale: utils::F32 },
-    Recip { scale: utils::F32 },
-    LeakyReLU { slope: utils::F32 },
-    Sigmoid { scale: utils::F32 },
-    Ln { scale: utils::F32 },
-    Exp { scale: utils::F32 },
-    Cos { scale: utils::F32 },
-    ACos { scale: utils::F32 },
-    Cosh { scale: utils::F32 },
-    ACosh { scale: utils::F32 },
-    Sin { scale: utils::F32 },
-    ASin { scale: utils::F32 },
-    Sinh { scale: utils::F32 },
-    ASinh { scale: utils::F32 },
-    Tan { scale: utils::F32 },
-    ATan { scale: utils::F32 },
-    Tanh { scale: utils::F32 },
-    ATanh { scale: utils::F32 },
-    Erf { scale: utils::F32 },
-    GreaterThan { a: utils::F32 },
-    LessThan { a: utils::F32 },
-    GreaterThanEqual { a: utils::F32 },
-    LessThanEqual { a: utils::F32 },
+    Max {
+        scale: utils::F32,
+        a: utils::F32,
+    },
+    Min {
+        scale: utils::F32,
+        a: utils::F32,
+    },
+    Clip {
+        scale: utils::F32,
+        min: utils::F32,
+        max: utils::F32,
+    },
+    Ceil {
+        scale: utils::F32,
+    },
+    Floor {
+        scale: utils::F32,
+    },
+    Trunc {
+        scale: utils::F32,
+    },
+    Round {
+        scale: utils::F32,
+    },
+    RoundHalfToEven {
+        scale: utils::F32,
+    },
+    Sqrt {
+        scale: utils::F32,
+    },
+    Rsqrt {
+        scale: utils::F32,
+    },
+    Recip {
+        scale: utils::F32,
+    },
+    LeakyReLU {
+        slope: utils::F32,
+    },
+    Sigmoid {
+        scale: utils::F32,
+    },
+    HardSigmoid {
+        scale: utils::F32,
+        alpha: utils::F32,
+        beta: utils::F32,
+    },
+    HardSwish {
+        scale: utils::F32,
+    },
+    Softplus {
+        scale: utils::F32,
+    },
+    Mish {
+        scale: utils::F32,
+    },
+    Ln {
+        scale: utils::F32,
+    },
+    Exp {
+        scale: utils::F32,
+    },
+    Cos {
+        scale: utils::F32,
+    },
+    ACos {
+        scale: utils::F32,
+    },
+    Cosh {
+        scale: utils::F32,
+    },
+    ACosh {
+        scale: utils::F32,
+    },
+    Sin {
+        scale: utils::F32,
+    },
+    ASin {
+        scale: utils::F32,
+    },
+    Sinh {
+        scale: utils::F32,
+    },
+    ASinh {
+        scale: utils::F32,
+    },
+    Tan {
+        scale: utils::F32,
+    },
+    ATan {
+        scale: utils::F32,
+    },
+    Tanh {
+        scale: utils::F32,
+    },
+    ATanh {
+        scale: utils::F32,
+    },
+    Erf {
+        scale: utils::F32,
+    },
+    GreaterThan {
+        a: utils::F32,
+    },
+    LessThan {
+        a: utils::F32,
+    },
+    GreaterThanEqual {
+        a: utils::F32,
+    },
+    LessThanEqual {
+        a: utils::F32,
+    },
     Sign,
     KroneckerDelta,
-    Pow { scale: utils::F32, a: utils::F32 },
+    Pow {
+        scale: utils::F32,
+        a: utils::F32,
+    },
 }
 
 impl LookupOp {
@@ -75,6 +164,7 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
             LookupOp::Abs => Ok(tensor::ops::abs(&x)?),
             LookupOp::Ceil { scale } => Ok(tensor::ops::nonlinearities::ceil(&x, scale.into())),
             LookupOp::Floor { scale } => Ok(tensor::ops::nonlinearities::floor(&x, scale.into())),
+            LookupOp::Trunc { scale } => Ok(tensor::ops::nonlinearities::trunc(&x, scale.into())),
             LookupOp::Round { scale } => Ok(tensor::ops::nonlinearities::round(&x, scale.into())),
             LookupOp::RoundHalfToEven { scale } => Ok(
                 tensor::ops::nonlinearities::round_half_to_even(&x, scale.into()),
@@ -95,6 +185,12 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
                 scale.0.into(),
                 a.0.into(),
             )),
+            LookupOp::Clip { scale, min, max } => Ok(tensor::ops::nonlinearities::clip(
+                &x,
+                scale.0.into(),
+                min.0.into(),
+                max.0.into(),
+            )),
             LookupOp::Sign => Ok(tensor::ops::nonlinearities::sign(&x)),
             LookupOp::LessThan { a } => Ok(tensor::ops::nonlinearities::less_than(
                 &x,
@@ -124,6 +220,21 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
             LookupOp::Sigmoid { scale } => {
                 Ok(tensor::ops::nonlinearities::sigmoid(&x, scale.into()))
             }
+            LookupOp::HardSigmoid { scale, alpha, beta } => {
+                Ok(tensor::ops::nonlinearities::hard_sigmoid(
+                    &x,
+                    scale.into(),
+                    alpha.0.into(),
+                    beta.0.into(),
+                ))
+            }
+            LookupOp::HardSwish { scale } => {
+                Ok(tensor::ops::nonlinearities::hard_swish(&x, scale.into()))
+            }
+            LookupOp::Softplus { scale } => {
+                Ok(tensor::ops::nonlinearities::softplus(&x, scale.into()))
+            }
+            LookupOp::Mish { scale } => Ok(tensor::ops::nonlinearities::mish(&x, scale.into())),
             LookupOp::Sqrt { scale } => Ok(tensor::ops::nonlinearities::sqrt(&x, scale.into())),
             LookupOp::Rsqrt { scale } => Ok(tensor::ops::nonlinearities::rsqrt(&x, scale.into())),
             LookupOp::Erf { scale } => Ok(tensor::ops::nonlinearities::erffunc(&x, scale.into())),
@@ -157,12 +268,16 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
             LookupOp::Abs => "ABS".into(),
             LookupOp::Ceil { scale } => format!("CEIL(scale={})", scale),
             LookupOp::Floor { scale } => format!("FLOOR(scale={})", scale),
+            LookupOp::Trunc { scale } => format!("TRUNC(scale={})", scale),
             LookupOp::Round { scale } => format!("ROUND(scale={})", scale),
             LookupOp::RoundHalfToEven { scale } => format!("ROUND_HALF_TO_EVEN(scale={})", scale),
             LookupOp::Pow { a, scale } => format!("POW(scale={}, exponent={})", scale, a),
             LookupOp::KroneckerDelta => "K_DELTA".into(),
             LookupOp::Max { scale, a } => format!("MAX(scale={}, a={})", scale, a),
             LookupOp::Min { scale, a } => format!("MIN(scale={}, a={})", scale, a),
+            LookupOp::Clip { scale, min, max } => {
+                format!("CLIP(scale={}, min={}, max={})", scale, min, max)
+            }
             LookupOp::Sign => "SIGN".into(),
             LookupOp::GreaterThan { .. } => "GREATER_THAN".into(),
             LookupOp::GreaterThanEqual { .. } => "GREATER_THAN_EQUAL".into(),
@@ -174,6 +289,13 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
             LookupOp::ReLU => "RELU".to_string(),
             LookupOp::LeakyReLU { slope: a } => format!("L_RELU(slope={})", a),
             LookupOp::Sigmoid { scale } => format!("SIGMOID(scale={})", scale),
+            LookupOp::HardSigmoid { scale, alpha, beta } => format!(
+                "HARD_SIGMOID(scale={}, alpha={}, beta={})",
+                scale, alpha, beta
+            ),
+            LookupOp::HardSwish { scale } => format!("HARD_SWISH(scale={})", scale),
+            LookupOp::Softplus { scale } => format!("SOFTPLUS(scale={})", scale),
+            LookupOp::Mish { scale } => format!("MISH(scale={})", scale),
             LookupOp::Sqrt { scale } => format!("SQRT(scale={})", scale),
             LookupOp::Erf { scale } => format!("ERF(scale={})", scale),
             LookupOp::Rsqrt { scale } => format!("RSQRT(scale={})", scale),
@@ -232,7 +354,8 @@ impl<F: PrimeField + TensorType + PartialOrd> Op<F> for LookupOp {
             | LookupOp::Round { .. }
             | LookupOp::RoundHalfToEven { .. }
             | LookupOp::Ceil { .. }
-            | LookupOp::Floor { .. } => 0,
+            | LookupOp::Floor { .. }
+            | LookupOp::Trunc { .. } => 0,
             _ => inputs_scale[0],
         };
         Ok(scale)
@@ -3,7 +3,7 @@ use std::{any::Any, error::Error};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    graph::quantize_tensor,
+    graph::{quantize_tensor, RoundingMode},
     tensor::{self, Tensor, TensorError, TensorType, ValTensor},
 };
 use halo2curves::ff::PrimeField;
@@ -110,6 +110,30 @@ impl<F: PrimeField + TensorType + PartialOrd> Clone for Box<dyn Op<F>> {
     }
 }
 
+/// Rounds `x` to the nearest half precision float (ties to even), as the input of an f16 model
+/// would be.
+fn round_to_f16(x: f32) -> f32 {
+    // the largest finite f16, anything that rounds above it overflows
+    const F16_MAX: f32 = 65504.0;
+    // below the smallest normal f16 the spacing between f16s is fixed at 2^-24
+    const F16_MIN_NORMAL: f32 = 6.103_515_6e-5;
+    if !x.is_finite() {
+        return x;
+    }
+    if x.abs() < F16_MIN_NORMAL {
+        let step = 2f32.powi(-24);
+        return (x / step).round() * step;
+    }
+    // drop the 13 mantissa bits an f16 doesn't have, rounding to nearest even
+    let bits = x.to_bits();
+    let rounded = f32::from_bits(bits.wrapping_add(0x0fff + ((bits >> 13) & 1)) & !0x1fff);
+    if rounded.abs() > F16_MAX {
+        f32::INFINITY.copysign(x)
+    } else {
+        rounded
+    }
+}
+
 ///
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum InputType {
@@ -142,9 +166,8 @@ impl InputType {
                 *input = T::from_i64(boolean_input).unwrap();
             }
             InputType::F16 => {
-                // TODO: implement f16
                 let f32_input = input.clone().to_f32().unwrap();
-                *input = T::from_f32(f32_input).unwrap();
+                *input = T::from_f32(round_to_f16(f32_input)).unwrap();
             }
             InputType::F32 => {
                 let f32_input = input.clone().to_f32().unwrap();
@@ -270,6 +293,9 @@ pub struct Constant<F: PrimeField + TensorType + PartialOrd> {
     ///
     #[serde(skip)]
     pub pre_assigned_val: Option<ValTensor<F>>,
+    /// how the raw values are rounded when they are (re)quantized
+    #[serde(default)]
+    pub rounding: RoundingMode,
 }
 
 impl<F: PrimeField + TensorType + PartialOrd> Constant<F> {
@@ -279,12 +305,18 @@ impl<F: PrimeField + TensorType + PartialOrd> Constant<F> {
             quantized_values,
             raw_values,
             pre_assigned_val: None,
+            rounding: RoundingMode::default(),
         }
     }
     /// Rebase the scale of the constant
     pub fn rebase_scale(&mut self, new_scale: crate::Scale) -> Result<(), Box<dyn Error>> {
         let visibility = self.quantized_values.visibility().unwrap();
-        self.quantized_values = quantize_tensor(self.raw_values.clone(), new_scale, &visibility)?;
+        self.quantized_values = quantize_tensor(
+            self.raw_values.clone(),
+            new_scale,
+            &visibility,
+            self.rounding,
+        )?;
         Ok(())
     }
 
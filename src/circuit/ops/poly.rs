@@ -48,6 +48,9 @@ pub enum PolyOp<F: PrimeField + TensorType + PartialOrd> {
     Sum {
         axes: Vec<usize>,
     },
+    CumSum {
+        axis: usize,
+    },
     Prod {
         axes: Vec<usize>,
         len_prod: usize,
@@ -67,6 +70,9 @@ pub enum PolyOp<F: PrimeField + TensorType + PartialOrd> {
     Resize {
         scale_factor: Vec<usize>,
     },
+    ResizeBilinear {
+        scale_factor: Vec<usize>,
+    },
     Not,
     And,
     Or,
@@ -89,6 +95,7 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
             PolyOp::MoveAxis { .. } => "MOVEAXIS".into(),
             PolyOp::Downsample { .. } => "DOWNSAMPLE".into(),
             PolyOp::Resize { .. } => "RESIZE".into(),
+            PolyOp::ResizeBilinear { .. } => "RESIZEBILINEAR".into(),
             PolyOp::Iff => "IFF".into(),
             PolyOp::Einsum { equation, .. } => format!("EINSUM {}", equation),
             PolyOp::Identity => "IDENTITY".into(),
@@ -99,6 +106,7 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
             PolyOp::Mult => "MULT".into(),
             PolyOp::Sub => "SUB".into(),
             PolyOp::Sum { .. } => "SUM".into(),
+            PolyOp::CumSum { .. } => "CUMSUM".into(),
             PolyOp::Prod { .. } => "PROD".into(),
             PolyOp::Pow(_) => "POW".into(),
             PolyOp::Pack(_, _) => "PACK".into(),
@@ -139,6 +147,9 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
                 modulo,
             } => tensor::ops::downsample(&inputs[0], *axis, *stride, *modulo),
             PolyOp::Resize { scale_factor } => tensor::ops::resize(&inputs[0], scale_factor),
+            PolyOp::ResizeBilinear { scale_factor } => {
+                tensor::ops::resize_bilinear(&inputs[0], scale_factor)
+            }
             PolyOp::Iff => tensor::ops::iff(&inputs[0], &inputs[1], &inputs[2]),
             PolyOp::Einsum { equation } => tensor::ops::einsum(equation, &inputs),
             PolyOp::Identity => Ok(inputs[0].clone()),
@@ -210,6 +221,12 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
                 }
                 tensor::ops::sum_axes(&inputs[0], axes)
             }
+            PolyOp::CumSum { axis } => {
+                if 1 != inputs.len() {
+                    return Err(TensorError::DimMismatch("cumsum inputs".to_string()));
+                }
+                tensor::ops::cumsum(&inputs[0], *axis)
+            }
             PolyOp::Prod { axes, .. } => {
                 if 1 != inputs.len() {
                     return Err(TensorError::DimMismatch("prod inputs".to_string()));
@@ -253,7 +270,13 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
             PolyOp::MoveAxis {
                 source,
                 destination,
-            } => layouts::move_axis(values[..].try_into()?, *source, *destination)?,
+            } => layouts::move_axis(
+                config,
+                region,
+                values[..].try_into()?,
+                *source,
+                *destination,
+            )?,
             PolyOp::Downsample {
                 axis,
                 stride,
@@ -262,12 +285,18 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
             PolyOp::Resize { scale_factor } => {
                 layouts::resize(config, region, values[..].try_into()?, scale_factor)?
             }
+            PolyOp::ResizeBilinear { scale_factor } => {
+                layouts::resize_bilinear(config, region, values[..].try_into()?, scale_factor)?
+            }
             PolyOp::Neg => layouts::neg(config, region, values[..].try_into()?)?,
             PolyOp::Iff => layouts::iff(config, region, values[..].try_into()?)?,
             PolyOp::Einsum { equation } => layouts::einsum(config, region, &values, equation)?,
             PolyOp::Sum { axes } => {
                 layouts::sum_axes(config, region, values[..].try_into()?, axes)?
             }
+            PolyOp::CumSum { axis } => {
+                layouts::cumsum(config, region, values[..].try_into()?, *axis)?
+            }
             PolyOp::Prod { axes, .. } => {
                 layouts::prod_axes(config, region, values[..].try_into()?, axes)?
             }
@@ -338,6 +367,15 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
             PolyOp::MoveAxis { .. } => in_scales[0],
             PolyOp::Downsample { .. } => in_scales[0],
             PolyOp::Resize { .. } => in_scales[0],
+            // each resized axis scales the output up by 2 * factor (a power of two)
+            PolyOp::ResizeBilinear { scale_factor } => {
+                in_scales[0]
+                    + scale_factor
+                        .iter()
+                        .filter(|s| **s > 1)
+                        .map(|s| (2 * s).ilog2() as crate::Scale)
+                        .sum::<crate::Scale>()
+            }
             PolyOp::Iff => in_scales[1],
             PolyOp::Einsum { .. } => {
                 let mut scale = in_scales[0];
@@ -347,7 +385,7 @@ impl<F: PrimeField + TensorType + PartialOrd + Serialize + for<'de> Deserialize<
                 scale
             }
             PolyOp::Prod { len_prod, .. } => in_scales[0] * (*len_prod as crate::Scale),
-            PolyOp::Sum { .. } => in_scales[0],
+            PolyOp::Sum { .. } | PolyOp::CumSum { .. } => in_scales[0],
             PolyOp::Conv { kernel, bias, .. } => {
                 let kernel_scale = match kernel.scale() {
                     Some(s) => s,
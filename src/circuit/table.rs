@@ -23,6 +23,61 @@ use super::Op;
 pub const RANGE_MULTIPLIER: i128 = 2;
 /// The safety factor offset for the number of rows in the lookup table.
 pub const RESERVED_BLINDING_ROWS_PAD: usize = 3;
+/// The most bytes of evaluated tables kept in the table cache, the least recently written tables
+/// are evicted past it.
+#[cfg(not(target_arch = "wasm32"))]
+pub const MAX_TABLE_CACHE_BYTES: u64 = 1 << 30;
+/// The number of evaluations of a cached table that are recomputed and checked before the table
+/// is used.
+#[cfg(not(target_arch = "wasm32"))]
+const TABLE_CACHE_PROBES: usize = 1 << 10;
+
+/// The directory evaluated tables are cached in
+#[cfg(not(target_arch = "wasm32"))]
+fn table_cache_dir() -> std::path::PathBuf {
+    std::path::PathBuf::from(format!("{}/tables", *crate::execute::EZKL_REPO_PATH))
+}
+
+/// Evicts the least recently written tables in `dir` until they take at most `max_bytes`.
+#[cfg(not(target_arch = "wasm32"))]
+fn prune_table_cache(dir: &std::path::Path, max_bytes: u64) -> std::io::Result<()> {
+    let tables = std::fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "bin" {
+                return None;
+            }
+            let metadata = path.metadata().ok()?;
+            Some((metadata.modified().ok()?, metadata.len(), path))
+        })
+        .collect::<Vec<_>>();
+    for path in tables_to_evict(tables, max_bytes) {
+        std::fs::remove_file(&path)?;
+        log::debug!("evicted {} from the table cache", path.display());
+    }
+    Ok(())
+}
+
+/// The oldest of the `(written at, size, path)` tables to evict so the rest take at most
+/// `max_bytes`
+#[cfg(not(target_arch = "wasm32"))]
+fn tables_to_evict(
+    mut tables: Vec<(std::time::SystemTime, u64, std::path::PathBuf)>,
+    max_bytes: u64,
+) -> Vec<std::path::PathBuf> {
+    let mut total = tables.iter().map(|(_, len, _)| len).sum::<u64>();
+    // newest first, so that the oldest are popped first
+    tables.sort_by(|a, b| b.0.cmp(&a.0));
+    let mut evicted = vec![];
+    while total > max_bytes {
+        let Some((_, len, path)) = tables.pop() else {
+            break;
+        };
+        evicted.push(path);
+        total -= len;
+    }
+    evicted
+}
 
 #[derive(Debug, Clone)]
 ///
@@ -184,6 +239,124 @@ impl<F: PrimeField + TensorType + PartialOrd> Table<F> {
         }
     }
 
+    /// Evaluates the non-linearity over the table's inputs, reusing the evaluations of previous
+    /// runs (keygen, mock, prove, ...) from the table cache when they exist.
+    fn evals(&self, inputs: &Tensor<F>) -> Result<Vec<F>, Box<dyn Error>> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let cache_dir = table_cache_dir();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(evals) = self.load_cached_evals(&cache_dir, inputs) {
+            log::debug!("loaded {:?} table from the cache", self.nonlinearity);
+            return Ok(evals);
+        }
+
+        let evals = Op::<F>::f(&self.nonlinearity, &[inputs.clone()])?
+            .output
+            .to_vec();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(e) = self.cache_evals(&cache_dir, &evals) {
+            warn!("failed to cache {:?} table: {}", self.nonlinearity, e);
+        }
+        Ok(evals)
+    }
+
+    /// Tables are keyed by a hash of the op's definition (which holds its scale and other
+    /// parameters), the evaluated range and the ezkl version whose implementation of the op
+    /// evaluated it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn cache_path(&self, dir: &std::path::Path) -> std::path::PathBuf {
+        let key = format!(
+            "{:?}_{}_{}_{}",
+            self.nonlinearity,
+            self.range.0,
+            self.range.1,
+            env!("CARGO_PKG_VERSION")
+        );
+        dir.join(format!(
+            "{}.bin",
+            hex::encode(ethers::utils::keccak256(key))
+        ))
+    }
+
+    /// Loads the cached evaluations of the table over `inputs`. Every evaluation is checked
+    /// against the hash of the full table stored with it. As the key can't capture a change to an
+    /// op's implementation between releases, evenly spaced evaluations (and both ends) are also
+    /// recomputed and a table that disagrees with them is discarded.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_cached_evals(&self, dir: &std::path::Path, inputs: &Tensor<F>) -> Option<Vec<F>> {
+        let path = self.cache_path(dir);
+        let file = std::fs::read(&path).ok()?;
+        let repr_len = F::Repr::default().as_ref().len();
+        if file.len() != inputs.len() * repr_len + 32 {
+            warn!("ignoring malformed cached {:?} table", self.nonlinearity);
+            return None;
+        }
+        let (bytes, hash) = file.split_at(file.len() - 32);
+        if ethers::utils::keccak256(bytes) != hash {
+            warn!(
+                "discarding corrupted cached {:?} table at {}",
+                self.nonlinearity,
+                path.display()
+            );
+            let _ = std::fs::remove_file(&path);
+            return None;
+        }
+        let evals = bytes
+            .chunks(repr_len)
+            .map(|chunk| {
+                let mut repr = F::Repr::default();
+                repr.as_mut().copy_from_slice(chunk);
+                Option::from(F::from_repr(repr))
+            })
+            .collect::<Option<Vec<F>>>()?;
+
+        let stride = (evals.len() / TABLE_CACHE_PROBES).max(1);
+        let probes = (0..evals.len())
+            .step_by(stride)
+            .chain(std::iter::once(evals.len() - 1))
+            .collect::<Vec<_>>();
+        let expected = Op::<F>::f(
+            &self.nonlinearity,
+            &[Tensor::from(probes.iter().map(|i| inputs[*i]))],
+        )
+        .ok()?
+        .output;
+        if probes
+            .iter()
+            .zip(expected.iter())
+            .any(|(i, e)| evals[*i] != *e)
+        {
+            warn!(
+                "discarding stale cached {:?} table at {}",
+                self.nonlinearity,
+                path.display()
+            );
+            let _ = std::fs::remove_file(&path);
+            return None;
+        }
+        Some(evals)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn cache_evals(&self, dir: &std::path::Path, evals: &[F]) -> Result<(), Box<dyn Error>> {
+        let path = self.cache_path(dir);
+        std::fs::create_dir_all(dir)?;
+        let mut bytes = evals
+            .iter()
+            .flat_map(|e| e.to_repr().as_ref().to_vec())
+            .collect::<Vec<_>>();
+        // the table is followed by its hash
+        let hash = ethers::utils::keccak256(&bytes);
+        bytes.extend(hash);
+        // write then rename so that concurrent runs never read a partially written table
+        let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(tmp, path)?;
+        prune_table_cache(dir, MAX_TABLE_CACHE_BYTES)?;
+        Ok(())
+    }
+
     /// Take a linear coordinate and output the (column, row) position in the storage block.
     pub fn cartesian_coord(&self, linear_coord: usize) -> (usize, usize) {
         let x = linear_coord / self.col_size;
@@ -205,7 +378,7 @@ impl<F: PrimeField + TensorType + PartialOrd> Table<F> {
         let largest = self.range.1;
 
         let inputs = Tensor::from(smallest..=largest).map(|x| i128_to_felt(x));
-        let evals = Op::<F>::f(&self.nonlinearity, &[inputs.clone()])?;
+        let evals = self.evals(&inputs)?;
         let chunked_inputs = inputs.chunks(self.col_size);
 
         self.is_assigned = true;
@@ -237,7 +410,7 @@ impl<F: PrimeField + TensorType + PartialOrd> Table<F> {
                                     )?;
                                 }
 
-                                let output = evals.output[row_offset];
+                                let output = evals[row_offset];
 
                                 table.assign_cell(
                                     || format!("nl_o_col row {}", row_offset),
@@ -257,3 +430,95 @@ impl<F: PrimeField + TensorType + PartialOrd> Table<F> {
         Ok(())
     }
 }
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use halo2curves::bn256::Fr as Fp;
+
+    fn relu_table() -> (Table<Fp>, Tensor<Fp>, Vec<Fp>) {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let table = Table::<Fp>::configure(&mut cs, (-64, 64), 8, &LookupOp::ReLU, None);
+        let inputs = Tensor::from(-64..=64).map(|x| i128_to_felt(x));
+        let evals = Op::<Fp>::f(&LookupOp::ReLU, &[inputs.clone()])
+            .unwrap()
+            .output
+            .to_vec();
+        (table, inputs, evals)
+    }
+
+    #[test]
+    fn test_table_cache_hit() {
+        let dir = tempfile::tempdir().unwrap();
+        let (table, inputs, evals) = relu_table();
+
+        assert_eq!(table.load_cached_evals(dir.path(), &inputs), None);
+        table.cache_evals(dir.path(), &evals).unwrap();
+        assert_eq!(table.load_cached_evals(dir.path(), &inputs), Some(evals));
+    }
+
+    #[test]
+    fn test_table_cache_invalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let (table, inputs, mut evals) = relu_table();
+
+        // a table cached by an older implementation of the op
+        evals[0] += Fp::one();
+        table.cache_evals(dir.path(), &evals).unwrap();
+        assert_eq!(table.load_cached_evals(dir.path(), &inputs), None);
+        assert!(!table.cache_path(dir.path()).exists());
+
+        // another op or range is keyed separately
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let other = Table::<Fp>::configure(&mut cs, (-32, 32), 8, &LookupOp::ReLU, None);
+        assert_ne!(table.cache_path(dir.path()), other.cache_path(dir.path()));
+    }
+
+    #[test]
+    fn test_table_cache_corruption() {
+        let dir = tempfile::tempdir().unwrap();
+        let (table, inputs, evals) = relu_table();
+
+        // the evaluations are all correct, but they don't match the stored hash
+        table.cache_evals(dir.path(), &evals).unwrap();
+        let path = table.cache_path(dir.path());
+        let mut bytes = std::fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        std::fs::write(&path, bytes).unwrap();
+        assert_eq!(table.load_cached_evals(dir.path(), &inputs), None);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_table_cache_is_bounded() {
+        let start = std::time::SystemTime::UNIX_EPOCH;
+        // written out of order, one second apart
+        let tables = [2, 0, 3, 1]
+            .into_iter()
+            .map(|i| {
+                (
+                    start + std::time::Duration::from_secs(i),
+                    100,
+                    std::path::PathBuf::from(format!("{}.bin", i)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // the two oldest tables are evicted
+        assert_eq!(
+            tables_to_evict(tables.clone(), 250),
+            vec![
+                std::path::PathBuf::from("0.bin"),
+                std::path::PathBuf::from("1.bin")
+            ]
+        );
+        assert!(tables_to_evict(tables, 400).is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..4 {
+            std::fs::write(dir.path().join(format!("{}.bin", i)), [0u8; 100]).unwrap();
+        }
+        prune_table_cache(dir.path(), 250).unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+}
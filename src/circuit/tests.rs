@@ -2330,6 +2330,106 @@ mod relu {
     }
 }
 
+#[cfg(test)]
+mod shared_lookup_table {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    const K: usize = 4;
+
+    fn nonlinearities() -> [LookupOp; 2] {
+        [LookupOp::ReLU, LookupOp::Sigmoid { scale: 1.0.into() }]
+    }
+
+    #[derive(Clone)]
+    struct MultiNlCircuit<F: PrimeField + TensorType + PartialOrd> {
+        pub input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for MultiNlCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, 16))
+                .collect::<Vec<_>>();
+
+            let mut config = BaseConfig::default();
+            for nl in nonlinearities() {
+                config
+                    .configure_lookup(cs, &advices[0], &advices[1], &advices[2], (-6, 6), K, &nl)
+                    .unwrap();
+            }
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1);
+                        for nl in nonlinearities() {
+                            config
+                                .layout(&mut region, &[self.input.clone()], Box::new(nl))
+                                .map_err(|_| Error::Synthesis)?;
+                        }
+                        Ok(())
+                    },
+                )
+                .unwrap();
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tables_share_input_columns() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let config = MultiNlCircuit::<F>::configure(&mut cs);
+
+        let tables = config.tables.values().collect::<Vec<_>>();
+        assert_eq!(tables.len(), 2);
+        // one set of input columns for every nonlinearity, each with its own output columns
+        assert_eq!(tables[0].table_inputs, tables[1].table_inputs);
+        assert_ne!(tables[0].table_outputs, tables[1].table_outputs);
+    }
+
+    #[test]
+    fn multinlcircuit() {
+        let input: Tensor<Value<F>> = Tensor::new(
+            Some(
+                &[-2, -1, 0, 1, 2, 3]
+                    .map(|x| Value::known(crate::fieldutils::i128_to_felt::<F>(x))),
+            ),
+            &[6],
+        )
+        .unwrap();
+
+        let circuit = MultiNlCircuit::<F> {
+            input: ValTensor::from(input),
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+}
+
 #[cfg(test)]
 #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 mod lookup_ultra_overflow {
@@ -2558,3 +2658,120 @@ mod softmax {
         prover.assert_satisfied_par();
     }
 }
+
+#[cfg(test)]
+mod topk {
+
+    use super::*;
+    use crate::circuit::ops::layouts;
+
+    const K: usize = 10;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct TopKCircuit<F: PrimeField + TensorType + PartialOrd> {
+        pub input: ValTensor<F>,
+        /// a witness for the descending sort of the input, or None to lay out the indices of the
+        /// top 2 elements
+        pub sorted: Option<Tensor<Value<F>>>,
+    }
+
+    impl Circuit<F> for TopKCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+            let mut config = Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE);
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+
+            for nl in [
+                LookupOp::GreaterThan { a: 0.0.into() },
+                LookupOp::KroneckerDelta,
+            ] {
+                config
+                    .configure_lookup(cs, &advices[0], &advices[1], &advices[2], (-32, 32), K, &nl)
+                    .unwrap();
+            }
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    match &self.sorted {
+                        Some(sorted) => layouts::constrain_sort_descending(
+                            &config,
+                            &mut region,
+                            &self.input,
+                            sorted.clone(),
+                        )
+                        .map(|_| ()),
+                        None => config
+                            .layout(
+                                &mut region,
+                                &[self.input.clone()],
+                                Box::new(HybridOp::TopKIndices { dim: 0, k: 2 }),
+                            )
+                            .map(|_| ()),
+                    }
+                    .map_err(|_| Error::Synthesis)
+                },
+            )?;
+
+            Ok(())
+        }
+    }
+
+    fn witness(values: &[u64]) -> Tensor<Value<F>> {
+        Tensor::from(values.iter().map(|v| Value::known(F::from(*v))))
+    }
+
+    #[test]
+    fn sort_circuit() {
+        let circuit = TopKCircuit::<F> {
+            input: ValTensor::from(witness(&[3, 5, 1, 0])),
+            sorted: Some(witness(&[5, 3, 1, 0])),
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+
+    #[test]
+    fn sort_circuit_rejects_repeated_max() {
+        // in descending order, but not a permutation of the input
+        let circuit = TopKCircuit::<F> {
+            input: ValTensor::from(witness(&[3, 5, 1, 0])),
+            sorted: Some(witness(&[5, 5, 3, 1])),
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn topk_indices_circuit() {
+        // ties go to the lowest index
+        let circuit = TopKCircuit::<F> {
+            input: ValTensor::from(witness(&[3, 5, 5, 1])),
+            sorted: None,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied_par();
+    }
+}
@@ -17,7 +17,7 @@ use crate::{pfsys::ProofType, RunArgs};
 use crate::circuit::CheckMode;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::graph::TestDataSource;
-use crate::pfsys::TranscriptType;
+use crate::pfsys::{Commitments, ProofEncoding, TranscriptType};
 
 /// The default path to the .json data file
 pub const DEFAULT_DATA: &str = "input.json";
@@ -39,10 +39,16 @@ pub const DEFAULT_PK_AGGREGATED: &str = "pk_aggr.key";
 pub const DEFAULT_VK_AGGREGATED: &str = "vk_aggr.key";
 /// The default path to the proof file
 pub const DEFAULT_PROOF: &str = "proof.proof";
+/// Default path for a snarkjs style proof
+pub const DEFAULT_SNARKJS_PROOF: &str = "proof.json";
+/// Default path for snarkjs style public signals
+pub const DEFAULT_SNARKJS_PUBLIC: &str = "public.json";
 /// The default path to the proof file for aggregated proofs
 pub const DEFAULT_PROOF_AGGREGATED: &str = "proof_aggr.proof";
 /// Default for whether to split proofs
 pub const DEFAULT_SPLIT: &str = "false";
+/// Default directory for the pieces of a split model
+pub const DEFAULT_SPLIT_DIR: &str = "split";
 /// Default verifier abi
 pub const DEFAULT_VERIFIER_ABI: &str = "verifier_abi.json";
 /// Default verifier abi for aggregated proofs
@@ -59,6 +65,16 @@ pub const DEFAULT_SOL_CODE_DA: &str = "evm_deploy_da.sol";
 pub const DEFAULT_CONTRACT_ADDRESS: &str = "contract.address";
 /// Default contract address for data attestation
 pub const DEFAULT_CONTRACT_ADDRESS_DA: &str = "contract_da.address";
+/// Default solidity code for a verifying key rendered separately from its verifier
+pub const DEFAULT_VK_SOL_CODE: &str = "evm_deploy_vk.sol";
+/// Default contract address for a verifying key rendered separately from its verifier
+pub const DEFAULT_CONTRACT_ADDRESS_VK: &str = "contract_vk.address";
+/// Default path of the evm gas report
+pub const DEFAULT_GAS_REPORT: &str = "gas_report.json";
+/// Default path of the benchmark report
+pub const DEFAULT_BENCH_REPORT: &str = "bench.json";
+/// Default path of the circuit cost estimate
+pub const DEFAULT_ESTIMATE_REPORT: &str = "estimate.json";
 /// Default check mode
 pub const DEFAULT_CHECKMODE: &str = "safe";
 /// Default calibration target
@@ -69,9 +85,121 @@ pub const DEFAULT_AGGREGATED_LOGROWS: &str = "23";
 pub const DEFAULT_OPTIMIZER_RUNS: &str = "1";
 /// Default fuzz runs
 pub const DEFAULT_FUZZ_RUNS: &str = "10";
+/// Default seed for random fuzz inputs
+pub const DEFAULT_FUZZ_SEED: &str = "0";
+/// Default largest absolute value of random fuzz inputs
+pub const DEFAULT_FUZZ_MAX_ABS: &str = "1.0";
 /// Default calibration file
 pub const DEFAULT_CALIBRATION_FILE: &str = "calibration.json";
+/// Default test vector file
+pub const DEFAULT_TEST_VECTOR: &str = "test_vector.json";
+/// Default address for the http server
+pub const DEFAULT_SERVE_ADDR: &str = "127.0.0.1:8080";
+/// Default address for the gRPC server
+pub const DEFAULT_GRPC_ADDR: &str = "127.0.0.1:50051";
+/// Default number of unfinished jobs the gRPC server accepts
+pub const DEFAULT_MAX_QUEUED_JOBS: &str = "16";
+/// Default address for the json-rpc server
+pub const DEFAULT_RPC_ADDR: &str = "127.0.0.1:9545";
+/// Default ElGamal key path
+pub const DEFAULT_ELGAMAL_KEY: &str = "elgamal_key.json";
+/// Default encrypted input path
+pub const DEFAULT_ENCRYPTED_INPUT: &str = "encrypted_input.json";
+/// Default path for the ElGamal key and randomness of an encryption
+pub const DEFAULT_ENCRYPTION_SECRET: &str = "encryption_secret.json";
+/// Default committed params path
+pub const DEFAULT_PARAMS_COMMITMENT: &str = "params_commitment.json";
+/// Default proof encoding
+pub const DEFAULT_PROOF_ENCODING: &str = "json";
+/// Default number of samples `prove-batch` proves at once
+pub const DEFAULT_MAX_PARALLEL: &str = "2";
+/// Default output format
+pub const DEFAULT_OUTPUT_FORMAT: &str = "human";
+/// Default table format
+pub const DEFAULT_TABLE_FORMAT: &str = "pretty";
+/// Default width of a rendered circuit in pixels
+pub const DEFAULT_RENDER_WIDTH: &str = "1024";
+/// Default height of a rendered circuit in pixels
+pub const DEFAULT_RENDER_HEIGHT: &str = "1024";
+/// Default watch mode
+pub const DEFAULT_WATCH: &str = "false";
+/// Default bound on the absolute value of model inputs for `audit`
+pub const DEFAULT_INPUT_BOUND: &str = "1.0";
+/// Default batched verification
+pub const DEFAULT_BATCH_VERIFY: &str = "false";
+/// Default for reusing keys already generated for a circuit
+pub const DEFAULT_REUSE_KEYS: &str = "false";
+/// Default for using the key cache
+pub const DEFAULT_KEY_CACHE: &str = "false";
+/// Default for rendering the evm verifier as a bare Yul object
+pub const DEFAULT_YUL: &str = "false";
 
+#[derive(
+    ValueEnum, Default, Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize, PartialOrd,
+)]
+/// How the model table is printed
+pub enum TableFormat {
+    /// a human readable table
+    #[default]
+    Pretty,
+    /// a json array of node summaries
+    Json,
+    /// csv with one row per node
+    Csv,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize, PartialOrd)]
+/// Vetted run args for a class of model sizes
+pub enum SettingsProfile {
+    /// small models: low scales, narrow lookups and few rows
+    Tiny,
+    /// the default run args, a reasonable starting point for most models
+    Balanced,
+    /// high scales and wide lookups, at the cost of a much larger circuit
+    Accuracy,
+}
+
+impl SettingsProfile {
+    /// Overrides the scales, lookup range, logrows and inner columns of `run_args` with the
+    /// values of this profile. Visibility, tolerance and variables are left untouched.
+    pub fn apply(&self, run_args: &mut RunArgs) {
+        let (scale, lookup_range, logrows, num_inner_cols) = match self {
+            SettingsProfile::Tiny => (4, (-4096, 4096), 14, 1),
+            SettingsProfile::Balanced => {
+                let d = RunArgs::default();
+                (d.input_scale, d.lookup_range, d.logrows, d.num_inner_cols)
+            }
+            SettingsProfile::Accuracy => (12, (-262144, 262144), 20, 2),
+        };
+        run_args.input_scale = scale;
+        run_args.param_scale = scale;
+        run_args.scale_rebase_multiplier = 1;
+        run_args.lookup_range = lookup_range;
+        run_args.logrows = logrows;
+        run_args.num_inner_cols = num_inner_cols;
+    }
+}
+
+impl std::fmt::Display for SettingsProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_possible_value()
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
+
+#[derive(
+    ValueEnum, Default, Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize, PartialOrd,
+)]
+/// How the cli reports the result of a command
+pub enum OutputFormat {
+    /// log lines meant for a terminal
+    #[default]
+    Human,
+    /// a single structured json object printed to stdout (logs go to stderr)
+    Json,
+}
 
 impl std::fmt::Display for TranscriptType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -88,6 +216,7 @@ impl IntoPy<PyObject> for TranscriptType {
         match self {
             TranscriptType::Poseidon => "poseidon".to_object(py),
             TranscriptType::EVM => "evm".to_object(py),
+            TranscriptType::Blake2b => "blake2b".to_object(py),
         }
     }
 }
@@ -100,6 +229,7 @@ impl<'source> FromPyObject<'source> for TranscriptType {
         match strval.to_lowercase().as_str() {
             "poseidon" => Ok(TranscriptType::Poseidon),
             "evm" => Ok(TranscriptType::EVM),
+            "blake2b" => Ok(TranscriptType::Blake2b),
             _ => Err(PyValueError::new_err("Invalid value for TranscriptType")),
         }
     }
@@ -209,6 +339,14 @@ pub struct Cli {
     #[command(subcommand)]
     #[allow(missing_docs)]
     pub command: Commands,
+    /// The output format (human or json)
+    #[arg(long, global = true, default_value = DEFAULT_OUTPUT_FORMAT)]
+    #[serde(default)]
+    pub format: OutputFormat,
+    /// Write a Chrome trace of where time is spent (load, configure, layout of each node, keygen, prove) to this path
+    #[arg(long, global = true)]
+    #[serde(default)]
+    pub trace_path: Option<PathBuf>,
 }
 
 impl Cli {
@@ -237,6 +375,15 @@ pub enum Commands {
         /// The path to the .onnx model file
         #[arg(short = 'M', long, default_value = DEFAULT_MODEL)]
         model: PathBuf,
+        /// The format of the table (pretty, json or csv)
+        #[arg(long, default_value = DEFAULT_TABLE_FORMAT)]
+        table_format: TableFormat,
+        /// Path to write the table to (optional - printed to stdout otherwise)
+        #[arg(short = 'O', long)]
+        output: Option<PathBuf>,
+        /// Re-run whenever the model file changes
+        #[arg(long, default_value = DEFAULT_WATCH)]
+        watch: bool,
         /// proving arguments
         #[clap(flatten)]
         args: RunArgs,
@@ -249,9 +396,15 @@ pub enum Commands {
         /// The path to the .onnx model file
         #[arg(short = 'M', long)]
         model: PathBuf,
-        /// Path to save the .png circuit render
+        /// Path to save the circuit render (.png or .svg)
         #[arg(short = 'O', long)]
         output: PathBuf,
+        /// Width of the render in pixels
+        #[arg(long, default_value = DEFAULT_RENDER_WIDTH)]
+        render_width: u32,
+        /// Height of the render in pixels
+        #[arg(long, default_value = DEFAULT_RENDER_HEIGHT)]
+        render_height: u32,
         /// proving arguments
         #[clap(flatten)]
         args: RunArgs,
@@ -274,6 +427,53 @@ pub enum Commands {
         /// Path to the srs file (optional - solely used to generate kzg commits)
         #[arg(short = 'P', long)]
         srs_path: Option<PathBuf>,
+        /// Path to the ElGamal key .json file written by encrypt-input (optional - solely used for encrypted inputs, so the proof's ciphertext is the one published by encrypt-input)
+        #[arg(short = 'K', long)]
+        key_path: Option<PathBuf>,
+        /// Path to the processed input .json file written by encrypt-input (solely used for signed inputs, holds the data provider's signatures)
+        #[arg(long)]
+        signature_path: Option<PathBuf>,
+    },
+
+    /// Encrypts (or commits to) an input file as expected by the circuit's input visibility, so data providers can prepare inputs without running the prover
+    EncryptInput {
+        /// The path to the .json data file
+        #[arg(short = 'D', long, default_value = DEFAULT_DATA)]
+        data: PathBuf,
+        /// The path to the compiled model file
+        #[arg(short = 'M', long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+        /// Path to the ElGamal key .json file for encrypted visibility, or the secp256k1 signing key .json file for signed visibility (generated if it doesn't exist). For encrypted/recipient visibility it must hold the recipient's public key
+        #[arg(short = 'K', long, default_value = DEFAULT_ELGAMAL_KEY)]
+        key_path: PathBuf,
+        /// Path to the verification key file (optional - solely used to generate kzg commits)
+        #[arg(short = 'V', long)]
+        vk_path: Option<PathBuf>,
+        /// Path to the srs file (optional - solely used to generate kzg commits)
+        #[arg(short = 'P', long)]
+        srs_path: Option<PathBuf>,
+        /// Path to the processed input .json file. It holds no secrets and can be handed to a prover or published
+        #[arg(short = 'O', long, default_value = DEFAULT_ENCRYPTED_INPUT)]
+        output: PathBuf,
+        /// Path to write the ElGamal key and this encryption's randomness to, pass it to `gen-witness --key-path` to reproduce the ciphertext. It holds the secret key: never publish it
+        #[arg(long, default_value = DEFAULT_ENCRYPTION_SECRET)]
+        secret_path: PathBuf,
+    },
+
+    /// Commits to the model's parameters with KZG (for `kzgcommit` param visibility), so verifiers can pin the model a proof was generated with without seeing its weights
+    CommitParams {
+        /// The path to the compiled model file
+        #[arg(short = 'M', long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+        /// Path to the verification key file
+        #[arg(short = 'V', long, default_value = DEFAULT_VK)]
+        vk_path: PathBuf,
+        /// Path to the srs file
+        #[arg(short = 'P', long)]
+        srs_path: Option<PathBuf>,
+        /// Path to write the params commitment .json file to
+        #[arg(short = 'O', long, default_value = DEFAULT_PARAMS_COMMITMENT)]
+        output: PathBuf,
     },
 
     /// Produces the proving hyperparameters, from run-args
@@ -284,15 +484,52 @@ pub enum Commands {
         /// Path to circuit_settings file to output
         #[arg(short = 'O', long, default_value = DEFAULT_SETTINGS)]
         settings_path: PathBuf,
+        /// A named profile (tiny, balanced or accuracy) that overrides the scales, lookup range, logrows and inner columns of the run args
+        #[arg(long)]
+        profile: Option<SettingsProfile>,
+        /// The largest absolute value (before quantization) any model input can take. If set, the lookup range and logrows are sized to fit the model's values for such inputs, overriding those of the run args
+        #[arg(long)]
+        input_bound: Option<f64>,
+        /// Fit the circuit into 2^target_logrows rows, choosing the number of advice columns per block (num_inner_cols) that folds the widest layers into that many rows. Overrides the logrows and num_inner_cols of the run args
+        #[arg(long)]
+        target_logrows: Option<u32>,
+        /// proving arguments
+        #[clap(flatten)]
+        args: RunArgs,
+    },
+
+    /// Loads a model and lowers every node without proving, then reports unsupported ops, shape issues and the projected circuit size
+    #[cfg(not(target_arch = "wasm32"))]
+    Check {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long, default_value = DEFAULT_MODEL)]
+        model: PathBuf,
+        /// Optional path to write the json report to
+        #[arg(short = 'O', long)]
+        output: Option<PathBuf>,
         /// proving arguments
         #[clap(flatten)]
         args: RunArgs,
     },
 
+    /// Bounds the values of every node from a bound on the model's inputs and its weights, and fails if a lookup can fall outside of the lookup range or a value can overflow the field
+    #[cfg(not(target_arch = "wasm32"))]
+    Audit {
+        /// The path to the compiled model file
+        #[arg(short = 'M', long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+        /// The largest absolute value (before quantization) any model input can take
+        #[arg(long, default_value = DEFAULT_INPUT_BOUND)]
+        input_bound: f64,
+        /// Optional path to write the json report to
+        #[arg(short = 'O', long)]
+        output: Option<PathBuf>,
+    },
+
     /// Calibrates the proving scale, lookup bits and logrows from a circuit settings file.
     #[cfg(not(target_arch = "wasm32"))]
     CalibrateSettings {
-        /// The path to the .json calibration data file.
+        /// The path to the .json calibration data file (or an .arrow / .ipc Arrow IPC file with the `arrow` feature, one row per sample), or a directory of such files whose samples are all used.
         #[arg(short = 'D', long, default_value = DEFAULT_CALIBRATION_FILE)]
         data: PathBuf,
         /// The path to the .onnx model file
@@ -312,6 +549,19 @@ pub enum Commands {
         max_logrows: Option<u32>,
     },
 
+    /// Interactively recommends a settings file: loads the model, estimates its size, asks about the target chain, accuracy tolerance and hardware, and calibrates on a sample input
+    Init {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long, default_value = DEFAULT_MODEL)]
+        model: PathBuf,
+        /// The path to a sample .json input file used for calibration
+        #[arg(short = 'D', long, default_value = DEFAULT_DATA)]
+        data: PathBuf,
+        /// Path to write the recommended settings to
+        #[arg(short = 'O', long, default_value = DEFAULT_SETTINGS)]
+        settings_path: PathBuf,
+    },
+
     /// Generates a dummy SRS
     #[command(name = "gen-srs", arg_required_else_help = true)]
     GenSrs {
@@ -321,6 +571,9 @@ pub enum Commands {
         /// number of logrows to use for srs
         #[arg(long)]
         logrows: usize,
+        /// The commitment scheme the srs is for
+        #[arg(long, default_value = "kzg")]
+        commitment: Commitments,
     },
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -348,11 +601,76 @@ pub enum Commands {
         /// The path to the .onnx model file
         #[arg(short = 'M', long, default_value = DEFAULT_MODEL)]
         model: PathBuf,
+        /// Re-run the mock prover whenever the model or witness file changes
+        #[arg(long, default_value = DEFAULT_WATCH)]
+        watch: bool,
+    },
+
+    /// Prints rows, column counts, gates, lookups and minimal logrows of a compiled circuit
+    Stats {
+        /// The path to the compiled model file
+        #[arg(short = 'M', long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+    },
+    /// Generates a golden test vector (input, quantized outputs, instances and settings hash) for regression testing
+    GenTestVector {
+        /// The path to the .json data file
+        #[arg(short = 'D', long, default_value = DEFAULT_DATA)]
+        data: PathBuf,
+        /// The path to the compiled model file
+        #[arg(short = 'M', long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+        /// Path to the test vector .json file
+        #[arg(short = 'O', long, default_value = DEFAULT_TEST_VECTOR)]
+        output: PathBuf,
+    },
+    /// Runs the quantized forward pass and the circuit layout on the same input and diffs their outputs cell-by-cell
+    Compare {
+        /// The path to the .json data file
+        #[arg(short = 'D', long, default_value = DEFAULT_DATA)]
+        data: PathBuf,
+        /// The path to the compiled model file
+        #[arg(short = 'M', long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+    },
+
+    /// Runs the float model through tract and the quantized forward pass on every sample of the data, and reports the mean squared error, largest deviation and top-1 agreement of each output
+    #[cfg(not(target_arch = "wasm32"))]
+    Accuracy {
+        /// The path to the .json data file (or an .arrow / .ipc Arrow IPC file with the `arrow` feature, one row per sample), or a directory of such files whose samples are all used.
+        #[arg(short = 'D', long, default_value = DEFAULT_DATA)]
+        data: PathBuf,
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long, default_value = DEFAULT_MODEL)]
+        model: PathBuf,
+        /// The path to the compiled model file
+        #[arg(long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+        /// Optional path to write the json accuracy report to
+        #[arg(short = 'O', long)]
+        output: Option<PathBuf>,
+    },
+
+    #[cfg(feature = "ort-compare")]
+    /// Runs the float model through onnxruntime and the quantized forward pass on the same input, and reports the error of each output
+    CompareOnnxruntime {
+        /// The path to the .json data file
+        #[arg(short = 'D', long, default_value = DEFAULT_DATA)]
+        data: PathBuf,
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long, default_value = DEFAULT_MODEL)]
+        model: PathBuf,
+        /// The path to the compiled model file
+        #[arg(long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+        /// Optional path to write the json accuracy report to
+        #[arg(short = 'O', long)]
+        output: Option<PathBuf>,
     },
 
     /// Mock aggregate proofs
     MockAggregate {
-        /// The path to the snarks to aggregate over
+        /// The path to the snarks to aggregate over, these can be proofs of different circuits
         #[arg(long, default_value = DEFAULT_PROOF, value_delimiter = ',', allow_hyphen_values = true)]
         aggregation_snarks: Vec<PathBuf>,
         /// logrows used for aggregation circuit
@@ -365,7 +683,7 @@ pub enum Commands {
 
     /// setup aggregation circuit :)
     SetupAggregate {
-        /// The path to samples of snarks that will be aggregated over
+        /// The path to samples of snarks that will be aggregated over, these can be proofs of different circuits but must be in the same order as when aggregating
         #[arg(long, default_value = DEFAULT_PROOF, value_delimiter = ',', allow_hyphen_values = true)]
         sample_snarks: Vec<PathBuf>,
         /// The path to save the desired verification key file
@@ -386,7 +704,7 @@ pub enum Commands {
     },
     /// Aggregates proofs :)
     Aggregate {
-        /// The path to the snarks to aggregate over
+        /// The path to the snarks to aggregate over, in the same order as the sample snarks used at setup
         #[arg(long, default_value = DEFAULT_PROOF, value_delimiter = ',', allow_hyphen_values = true)]
         aggregation_snarks: Vec<PathBuf>,
         /// The path to load the desired proving key file
@@ -445,6 +763,12 @@ pub enum Commands {
         /// The graph witness (optional - used to override fixed values in the circuit)
         #[arg(short = 'W', long)]
         witness: Option<PathBuf>,
+        /// Skip keygen if the keys at `pk_path` and `vk_path` were already generated for this model and settings
+        #[arg(long, default_value = DEFAULT_REUSE_KEYS)]
+        reuse_keys: bool,
+        /// Reuse and store keys in the key cache ($EZKL_REPO_PATH/keys). The cache is never pruned, every circuit and srs adds a proving key to it. Proving only falls back to a cached proving key when EZKL_USE_CACHED_PK is set
+        #[arg(long, default_value = DEFAULT_KEY_CACHE)]
+        key_cache: bool,
     },
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -469,6 +793,63 @@ pub enum Commands {
         num_runs: usize,
     },
     #[cfg(not(target_arch = "wasm32"))]
+    /// Runs the forward pass and mock prover over many seeded random inputs, reporting panics, constraint failures and forward/circuit divergences
+    FuzzInputs {
+        /// The path to the compiled model file
+        #[arg(short = 'M', long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+        /// number of random inputs to try
+        #[arg(long, default_value = DEFAULT_FUZZ_RUNS)]
+        num_runs: usize,
+        /// seed for the random inputs, run `i` uses `seed + i`
+        #[arg(long, default_value = DEFAULT_FUZZ_SEED)]
+        seed: u64,
+        /// largest absolute value of the (unquantized) random float inputs
+        #[arg(long, default_value = DEFAULT_FUZZ_MAX_ABS)]
+        max_abs: f64,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Runs the whole pipeline (load, quantize, keygen, witness, prove, verify) on a model and reports the wall clock time and peak memory of each phase
+    Bench {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long, default_value = DEFAULT_MODEL)]
+        model: PathBuf,
+        /// The path to the .json data file
+        #[arg(short = 'D', long, default_value = DEFAULT_DATA)]
+        data: PathBuf,
+        /// Optional circuit settings to benchmark, otherwise settings are generated from the proving arguments
+        #[arg(long)]
+        settings_path: Option<PathBuf>,
+        /// The path to SRS, if None will use $EZKL_REPO_PATH/srs/kzg{logrows}.srs
+        #[arg(long)]
+        srs_path: Option<PathBuf>,
+        /// The path to write the json report to
+        #[arg(short = 'O', long, default_value = DEFAULT_BENCH_REPORT)]
+        output: PathBuf,
+        /// proving arguments
+        #[clap(flatten)]
+        args: RunArgs,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Configures the circuit for a model without proving and reports its rows, columns and lookup tables, along with a projection of the time and memory proving would take
+    Estimate {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long, default_value = DEFAULT_MODEL)]
+        model: PathBuf,
+        /// Optional circuit settings to estimate, otherwise settings are generated from the proving arguments
+        #[arg(long)]
+        settings_path: Option<PathBuf>,
+        /// Optional report of `ezkl bench` on a reference model, measured on this machine, to scale the projection from
+        #[arg(long)]
+        bench_report: Option<PathBuf>,
+        /// The path to write the json report to
+        #[arg(short = 'O', long, default_value = DEFAULT_ESTIMATE_REPORT)]
+        output: PathBuf,
+        /// proving arguments
+        #[clap(flatten)]
+        args: RunArgs,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
     #[command(arg_required_else_help = true)]
     SetupTestEVMData {
         /// The path to the .json data file, which should include both the network input (possibly private) and the network output (public input to the proof)
@@ -519,7 +900,8 @@ pub enum Commands {
     #[cfg(not(target_arch = "wasm32"))]
     /// Loads model, data, and creates proof
     Prove {
-        /// The path to the .json witness file, which should include both the network input (possibly private) and the network output (public input to the proof)
+        /// The path to the .json witness file, which should include both the network input (possibly private) and the network output (public input to the proof).
+        /// A glob pattern (eg. `inputs/*.json`) proves every match, writing each proof beside its witness
         #[arg(short = 'W', long, default_value = DEFAULT_WITNESS)]
         witness: PathBuf,
         /// The path to the compiled model file
@@ -545,6 +927,61 @@ pub enum Commands {
         /// run sanity checks during calculations (safe or unsafe)
         #[arg(long, default_value = DEFAULT_CHECKMODE)]
         check_mode: CheckMode,
+        /// how to encode the proof file (bin, hex, base64 or json)
+        #[arg(long, default_value = DEFAULT_PROOF_ENCODING)]
+        proof_encoding: ProofEncoding,
+        /// The gpus to prove on (eg. `0,1`) in builds with gpu support. A single proof runs on the first device,
+        /// a glob of witnesses is split across one process per device, each proof still runs on a single device
+        #[arg(long, value_delimiter = ',')]
+        devices: Vec<usize>,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Generates a witness for and proves every input file in a directory, proving the samples in parallel and optionally aggregating them into a single proof
+    ProveBatch {
+        /// The directory of .json input files, one sample per file
+        #[arg(short = 'D', long)]
+        data_dir: PathBuf,
+        /// The path to the compiled model file
+        #[arg(short = 'M', long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+        /// The path to load the desired proving key file
+        #[arg(long, default_value = DEFAULT_PK)]
+        pk_path: PathBuf,
+        /// The parameter path
+        #[arg(long)]
+        srs_path: Option<PathBuf>,
+        /// The directory to write the witness and proof of each sample to
+        #[arg(short = 'O', long)]
+        output_dir: PathBuf,
+        #[arg(
+            long,
+            require_equals = true,
+            num_args = 0..=1,
+            default_value_t = ProofType::Single,
+            value_enum
+        )]
+        proof_type: ProofType,
+        /// run sanity checks during calculations (safe or unsafe)
+        #[arg(long, default_value = DEFAULT_CHECKMODE)]
+        check_mode: CheckMode,
+        /// how to encode the proof files (bin, hex, base64 or json)
+        #[arg(long, default_value = DEFAULT_PROOF_ENCODING)]
+        proof_encoding: ProofEncoding,
+        /// The most samples proven at once. Each proof in flight holds its own witness polynomials in memory, the proofs share the cpu threads
+        #[arg(long, default_value = DEFAULT_MAX_PARALLEL)]
+        max_parallel: usize,
+        /// Aggregates the batch into a single proof with this aggregation proving key (see `setup-aggregate`), the samples are then proven for aggregation and the aggregated proof has the transcript of `proof_type`
+        #[arg(long)]
+        aggregated_pk_path: Option<PathBuf>,
+        /// The path to SRS of the aggregation circuit
+        #[arg(long)]
+        aggregated_srs_path: Option<PathBuf>,
+        /// logrows used for aggregation circuit
+        #[arg(long, default_value = DEFAULT_AGGREGATED_LOGROWS)]
+        aggregated_logrows: u32,
+        /// The path to write the aggregated proof to
+        #[arg(long, default_value = DEFAULT_PROOF_AGGREGATED)]
+        aggregated_proof_path: PathBuf,
     },
     #[cfg(not(target_arch = "wasm32"))]
     /// Creates an EVM verifier for a single proof
@@ -565,6 +1002,17 @@ pub enum Commands {
         /// The path to output the Solidity verifier ABI
         #[arg(long, default_value = DEFAULT_VERIFIER_ABI)]
         abi_path: PathBuf,
+        /// If set, the verifying key is rendered to its own contract at this path and the verifier
+        /// at `sol_code_path` reads it from the vk address passed to `verifyProof`. The verifier can
+        /// then be deployed once and reused by every model sharing its circuit layout, each of which
+        /// only deploys its (much smaller) verifying key contract with `deploy-evm-vk`.
+        #[arg(long)]
+        vk_sol_code_path: Option<PathBuf>,
+        /// Render the verifier as a bare Yul object with the verifying key inlined, rather than a Solidity contract.
+        /// The code is written next to `sol_code_path` with a `.yul` extension and takes the instances followed by
+        /// the proof as raw calldata, so it has no ABI. Compare the two with `estimate-evm-gas`.
+        #[arg(long, default_value = DEFAULT_YUL)]
+        yul: bool,
     },
     #[cfg(not(target_arch = "wasm32"))]
     /// Creates an EVM verifier that attests to on-chain inputs for a single proof
@@ -618,6 +1066,76 @@ pub enum Commands {
         #[arg(long, default_value = DEFAULT_AGGREGATED_LOGROWS)]
         logrows: u32,
     },
+    #[cfg(feature = "server")]
+    /// Serves POST /prove, POST /verify and GET /settings over http, keeping the circuit, keys and srs loaded between requests
+    Serve {
+        /// The path to the compiled model file
+        #[arg(short = 'M', long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+        /// The path to load the desired proving key file
+        #[arg(long, default_value = DEFAULT_PK)]
+        pk_path: PathBuf,
+        /// The path to load the desired verification key file
+        #[arg(long, default_value = DEFAULT_VK)]
+        vk_path: PathBuf,
+        /// The parameter path
+        #[arg(long)]
+        srs_path: Option<PathBuf>,
+        /// The address to listen on
+        #[arg(long, default_value = DEFAULT_SERVE_ADDR)]
+        addr: std::net::SocketAddr,
+        #[arg(
+            long,
+            require_equals = true,
+            num_args = 0..=1,
+            default_value_t = ProofType::Single,
+            value_enum
+        )]
+        proof_type: ProofType,
+        /// run sanity checks during calculations (safe or unsafe)
+        #[arg(long, default_value = DEFAULT_CHECKMODE)]
+        check_mode: CheckMode,
+    },
+    #[cfg(feature = "grpc")]
+    /// Serves the gRPC Prover service (SubmitWitness, GetProof, Verify) defined in proto/ezkl.proto, keeping the circuit, keys and srs loaded between requests
+    ServeGrpc {
+        /// The path to the compiled model file
+        #[arg(short = 'M', long, default_value = DEFAULT_COMPILED_CIRCUIT)]
+        compiled_circuit: PathBuf,
+        /// The path to load the desired proving key file
+        #[arg(long, default_value = DEFAULT_PK)]
+        pk_path: PathBuf,
+        /// The path to load the desired verification key file
+        #[arg(long, default_value = DEFAULT_VK)]
+        vk_path: PathBuf,
+        /// The parameter path
+        #[arg(long)]
+        srs_path: Option<PathBuf>,
+        /// The address to listen on
+        #[arg(long, default_value = DEFAULT_GRPC_ADDR)]
+        addr: std::net::SocketAddr,
+        #[arg(
+            long,
+            require_equals = true,
+            num_args = 0..=1,
+            default_value_t = ProofType::Single,
+            value_enum
+        )]
+        proof_type: ProofType,
+        /// run sanity checks during calculations (safe or unsafe)
+        #[arg(long, default_value = DEFAULT_CHECKMODE)]
+        check_mode: CheckMode,
+        /// The most jobs queued or proving at once, further witnesses are rejected with RESOURCE_EXHAUSTED until one finishes
+        #[arg(long, default_value = DEFAULT_MAX_QUEUED_JOBS)]
+        max_queued_jobs: usize,
+    },
+    #[cfg(feature = "server")]
+    /// Serves the mock, gen-witness, prove, verify and aggregate commands as JSON-RPC 2.0 methods over http, taking the command's arguments as named params
+    ServeRpc {
+        /// The address to listen on
+        #[arg(long, default_value = DEFAULT_RPC_ADDR)]
+        addr: std::net::SocketAddr,
+    },
     /// Verifies a proof, returning accept or reject
     Verify {
         /// The path to load circuit params from
@@ -632,6 +1150,36 @@ pub enum Commands {
         /// The kzg srs path
         #[arg(long)]
         srs_path: Option<PathBuf>,
+        /// how the proof file is encoded (bin, hex, base64 or json)
+        #[arg(long, default_value = DEFAULT_PROOF_ENCODING)]
+        proof_encoding: ProofEncoding,
+        /// Path to a committed input .json (as written by `encrypt-input` for hashed/public inputs) the proof's input hash must match
+        #[arg(long)]
+        input_commitment: Option<PathBuf>,
+        /// Path to a params commitment .json (as written by `commit-params`) pinning the model the proof must have been generated with
+        #[arg(long)]
+        params_commitment: Option<PathBuf>,
+    },
+    /// Verifies every `.proof` file in a directory against a single verification key
+    VerifyMany {
+        /// The path to load circuit params from
+        #[arg(short = 'S', long, default_value = DEFAULT_SETTINGS)]
+        settings_path: PathBuf,
+        /// The directory containing the proof files
+        #[arg(long)]
+        proof_dir: PathBuf,
+        /// The path to the verification key file
+        #[arg(long, default_value = DEFAULT_VK)]
+        vk_path: PathBuf,
+        /// The kzg srs path
+        #[arg(long)]
+        srs_path: Option<PathBuf>,
+        /// how the proof files are encoded (bin, hex, base64 or json)
+        #[arg(long, default_value = DEFAULT_PROOF_ENCODING)]
+        proof_encoding: ProofEncoding,
+        /// accumulate all proofs into a single final pairing check, falling back to verifying each proof on its own if the batch is rejected
+        #[arg(long, default_value = DEFAULT_BATCH_VERIFY)]
+        batch: bool,
     },
     /// Verifies an aggregate proof, returning accept or reject
     VerifyAggr {
@@ -667,6 +1215,26 @@ pub enum Commands {
         private_key: Option<String>,
     },
     #[cfg(not(target_arch = "wasm32"))]
+    /// Deploys a verifying key contract rendered with `create-evm-verifier --vk-sol-code-path`
+    #[command(name = "deploy-evm-vk")]
+    DeployEvmVK {
+        /// The path to the Solidity code of the verifying key
+        #[arg(long, default_value = DEFAULT_VK_SOL_CODE)]
+        sol_code_path: PathBuf,
+        /// RPC URL for an Ethereum node, if None will use Anvil but WON'T persist state
+        #[arg(short = 'U', long)]
+        rpc_url: Option<String>,
+        #[arg(long, default_value = DEFAULT_CONTRACT_ADDRESS_VK)]
+        /// The path to output the contract address
+        addr_path: PathBuf,
+        /// The optimizer runs to set on the contract. (Lower values optimize for deployment, while higher values optimize for execution)
+        #[arg(long, default_value = DEFAULT_OPTIMIZER_RUNS)]
+        optimizer_runs: usize,
+        /// Private secp256K1 key in hex format, 64 chars, no 0x prefix, of the account signing transactions. If None the private key will be generated by Anvil
+        #[arg(short = 'P', long)]
+        private_key: Option<String>,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
     #[command(name = "deploy-evm-da")]
     DeployEvmDataAttestation {
         /// The path to the .json data file, which should include both the network input (possibly private) and the network output (public input to the proof)
@@ -692,6 +1260,50 @@ pub enum Commands {
         private_key: Option<String>,
     },
     #[cfg(not(target_arch = "wasm32"))]
+    /// Verifies a proof on-chain by sending it to a deployed verifier in a transaction, rather than the read-only call `verify-evm` makes
+    #[command(name = "verify-evm-onchain")]
+    VerifyEvmOnchain {
+        /// The path to the proof file
+        #[arg(long, default_value = DEFAULT_PROOF)]
+        proof_path: PathBuf,
+        /// The path to verfier contract's address
+        #[arg(long, default_value = DEFAULT_CONTRACT_ADDRESS)]
+        addr_verifier: H160,
+        /// RPC URL for the Ethereum node the verifier is deployed to
+        #[arg(short = 'U', long)]
+        rpc_url: String,
+        /// Private secp256K1 key in hex format, 64 chars, no 0x prefix, of the account paying for the transaction
+        #[arg(short = 'P', long)]
+        private_key: String,
+        /// The address of the verifying key contract, for verifiers rendered separately from their vk
+        #[arg(long)]
+        addr_vk: Option<H160>,
+        /// Whether the verifier was rendered with `create-evm-verifier --yul`
+        #[arg(long, default_value = DEFAULT_YUL)]
+        yul: bool,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
+    /// Deploys a verifier to an in-process EVM and reports the gas it takes to deploy it and verify a proof with it,
+    /// so settings can be compared by on-chain cost before anything is deployed for real
+    #[command(name = "estimate-evm-gas")]
+    EstimateEvmGas {
+        /// The path to the proof file
+        #[arg(long, default_value = DEFAULT_PROOF)]
+        proof_path: PathBuf,
+        /// The path to the Solidity code of the verifier
+        #[arg(long, default_value = DEFAULT_SOL_CODE)]
+        sol_code_path: PathBuf,
+        /// The path to the Solidity code of the verifying key, if it was rendered separately from the verifier
+        #[arg(long)]
+        vk_sol_code_path: Option<PathBuf>,
+        /// The optimizer runs to set on the verifier. (Lower values optimize for deployment, while higher values optimize for execution)
+        #[arg(long, default_value = DEFAULT_OPTIMIZER_RUNS)]
+        optimizer_runs: usize,
+        /// The path to output the gas report
+        #[arg(short = 'O', long, default_value = DEFAULT_GAS_REPORT)]
+        output: PathBuf,
+    },
+    #[cfg(not(target_arch = "wasm32"))]
     /// Verifies a proof using a local EVM executor, returning accept or reject
     #[command(name = "verify-evm")]
     VerifyEVM {
@@ -707,8 +1319,64 @@ pub enum Commands {
         /// does the verifier use data attestation ?
         #[arg(long)]
         addr_da: Option<H160>,
+        /// The address of the verifying key contract, for verifiers rendered separately from their vk
+        #[arg(long)]
+        addr_vk: Option<H160>,
+        /// Whether the verifier was rendered with `create-evm-verifier --yul`
+        #[arg(long, default_value = DEFAULT_YUL)]
+        yul: bool,
     },
 
+    /// Exports a proof as a snarkjs style proof.json and public.json for verifier frontends built around snarkjs
+    #[command(name = "export-snarkjs")]
+    ExportSnarkjs {
+        /// The path to the proof file
+        #[arg(long, default_value = DEFAULT_PROOF)]
+        proof_path: PathBuf,
+        /// Where to write the proof.json
+        #[arg(long, default_value = DEFAULT_SNARKJS_PROOF)]
+        proof_json: PathBuf,
+        /// Where to write the public.json
+        #[arg(long, default_value = DEFAULT_SNARKJS_PUBLIC)]
+        public_json: PathBuf,
+    },
+    /// Verifies a chain of proofs and checks that it composes, i.e. that the hashed outputs of each proof are the hashed inputs
+    /// of the next. A pipeline of models (e.g. feature extractor -> classifier) can then be proven one model at a time, proving the
+    /// first with `--output-visibility hashed`, the next with `--input-visibility hashed` and so on
+    #[command(name = "link-proofs")]
+    LinkProofs {
+        /// The paths to the proofs, in pipeline order
+        #[arg(long, value_delimiter = ',', allow_hyphen_values = true)]
+        proof_paths: Vec<PathBuf>,
+        /// The paths to the settings each proof was generated with, in the same order
+        #[arg(long, value_delimiter = ',', allow_hyphen_values = true)]
+        settings_paths: Vec<PathBuf>,
+        /// The paths to the verification key of each proof, in the same order
+        #[arg(long, value_delimiter = ',', allow_hyphen_values = true)]
+        vk_paths: Vec<PathBuf>,
+        /// The kzg srs path, large enough for every proof
+        #[arg(long)]
+        srs_path: Option<PathBuf>,
+    },
+    /// Splits an onnx model into a chain of smaller models that each fit in a circuit. The tensors crossing each cut become
+    /// the outputs of one piece and the inputs of the next, so the pieces can be proven with polycommit visibilities and
+    /// aggregated with `--split-proofs` (or with hashed visibilities and checked with `link-proofs`)
+    #[cfg(not(target_arch = "wasm32"))]
+    #[command(name = "split-model")]
+    SplitModel {
+        /// The path to the .onnx model file
+        #[arg(short = 'M', long, default_value = DEFAULT_MODEL)]
+        model: PathBuf,
+        /// The nodes (or node outputs) to cut the model after
+        #[arg(long, value_delimiter = ',', conflicts_with = "num_pieces")]
+        cut_after: Vec<String>,
+        /// Split the model into this many pieces of similar sizes, cutting where the fewest tensors cross
+        #[arg(long)]
+        num_pieces: Option<usize>,
+        /// The directory to write the pieces and their split.json manifest to
+        #[arg(long, default_value = DEFAULT_SPLIT_DIR)]
+        output_dir: PathBuf,
+    },
     /// Print the proof in hexadecimal
     #[command(name = "print-proof-hex")]
     PrintProofHex {
@@ -0,0 +1,178 @@
+#[cfg(feature = "onnx")]
+use crate::graph::GraphError;
+use crate::tensor::TensorError;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::error::Error;
+use thiserror::Error;
+
+/// A typed error for programmatic consumers, which serializes to json as
+/// `{"code": .., "kind": .., "message": ..}` so callers can branch on the failure reason.
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum EzklError {
+    /// The model could not be loaded or the graph is malformed or unsupported
+    #[error("graph error: {0}")]
+    Graph(String),
+    /// A value could not be represented at the requested scale
+    #[error("quantization error: {0}")]
+    Quantization(String),
+    /// Key generation, proving or verification failed
+    #[error("proving error: {0}")]
+    Proving(String),
+    /// A file could not be read or written
+    #[error("io error: {0}")]
+    Io(String),
+    /// Any other failure
+    #[error("{0}")]
+    Other(String),
+}
+
+impl EzklError {
+    /// A stable numeric code for the error kind
+    pub fn code(&self) -> u32 {
+        match self {
+            EzklError::Graph(_) => 1,
+            EzklError::Quantization(_) => 2,
+            EzklError::Proving(_) => 3,
+            EzklError::Io(_) => 4,
+            EzklError::Other(_) => 99,
+        }
+    }
+
+    /// The snake case name of the error kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EzklError::Graph(_) => "graph",
+            EzklError::Quantization(_) => "quantization",
+            EzklError::Proving(_) => "proving",
+            EzklError::Io(_) => "io",
+            EzklError::Other(_) => "other",
+        }
+    }
+
+    /// The error message, without the kind prefix
+    pub fn message(&self) -> &str {
+        match self {
+            EzklError::Graph(m)
+            | EzklError::Quantization(m)
+            | EzklError::Proving(m)
+            | EzklError::Io(m)
+            | EzklError::Other(m) => m,
+        }
+    }
+
+    /// The error as a json value
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "code": self.code(),
+            "kind": self.kind(),
+            "message": self.message(),
+        })
+    }
+}
+
+impl Serialize for EzklError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("EzklError", 3)?;
+        state.serialize_field("code", &self.code())?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", self.message())?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for EzklError {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Repr {
+            kind: String,
+            message: String,
+        }
+        let Repr { kind, message } = Repr::deserialize(deserializer)?;
+        Ok(match kind.as_str() {
+            "graph" => EzklError::Graph(message),
+            "quantization" => EzklError::Quantization(message),
+            "proving" => EzklError::Proving(message),
+            "io" => EzklError::Io(message),
+            _ => EzklError::Other(message),
+        })
+    }
+}
+
+#[cfg(feature = "onnx")]
+impl From<GraphError> for EzklError {
+    fn from(e: GraphError) -> Self {
+        match e {
+            GraphError::RescalingError(_) | GraphError::PackingExponent => {
+                EzklError::Quantization(e.to_string())
+            }
+            _ => EzklError::Graph(e.to_string()),
+        }
+    }
+}
+
+impl From<TensorError> for EzklError {
+    fn from(e: TensorError) -> Self {
+        match e {
+            TensorError::SigBitTruncationError | TensorError::FeltError => {
+                EzklError::Quantization(e.to_string())
+            }
+            _ => EzklError::Graph(e.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for EzklError {
+    fn from(e: std::io::Error) -> Self {
+        EzklError::Io(e.to_string())
+    }
+}
+
+impl From<halo2_proofs::plonk::Error> for EzklError {
+    fn from(e: halo2_proofs::plonk::Error) -> Self {
+        EzklError::Proving(e.to_string())
+    }
+}
+
+impl From<Box<dyn Error>> for EzklError {
+    /// Recovers the kind of a boxed error where it is one of the errors ezkl knows about
+    fn from(e: Box<dyn Error>) -> Self {
+        let e = match e.downcast::<EzklError>() {
+            Ok(e) => return *e,
+            Err(e) => e,
+        };
+        #[cfg(feature = "onnx")]
+        let e = match e.downcast::<GraphError>() {
+            Ok(e) => return (*e).into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<TensorError>() {
+            Ok(e) => return (*e).into(),
+            Err(e) => e,
+        };
+        let e = match e.downcast::<std::io::Error>() {
+            Ok(e) => return (*e).into(),
+            Err(e) => e,
+        };
+        match e.downcast::<halo2_proofs::plonk::Error>() {
+            Ok(e) => (*e).into(),
+            Err(e) => EzklError::Other(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_json() {
+        let boxed: Box<dyn Error> = Box::new(TensorError::SigBitTruncationError);
+        let e = EzklError::from(boxed);
+        assert_eq!(e.code(), 2);
+        let json = serde_json::to_value(&e).unwrap();
+        assert_eq!(json, e.to_json());
+        assert_eq!(json["kind"], "quantization");
+        assert_eq!(serde_json::from_value::<EzklError>(json).unwrap(), e);
+    }
+}
@@ -3,7 +3,7 @@ use crate::graph::modules::{ELGAMAL_INSTANCES, POSEIDON_INSTANCES};
 use crate::graph::DataSource;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::graph::GraphSettings;
-use crate::pfsys::evm::EvmVerificationError;
+use crate::pfsys::evm::{EvmVerificationError, YUL_VERIFIER_NAME};
 use crate::pfsys::Snark;
 use ethers::abi::Contract;
 use ethers::contract::abigen;
@@ -18,6 +18,7 @@ use ethers::providers::{Http, Provider};
 use ethers::signers::Signer;
 use ethers::solc::{CompilerInput, Solc};
 use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::TransactionReceipt;
 use ethers::types::TransactionRequest;
 use ethers::types::H160;
 use ethers::types::U256;
@@ -31,8 +32,11 @@ use halo2_solidity_verifier::encode_calldata;
 use halo2curves::bn256::{Fr, G1Affine};
 use halo2curves::group::ff::PrimeField;
 use log::{debug, info, warn};
+use revm::primitives::{ExecutionResult, Output, TransactTo, TxEnv};
+use revm::{InMemoryDB, EVM};
+use serde::{Deserialize, Serialize};
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Duration;
 use std::{convert::TryFrom, sync::Arc};
@@ -106,12 +110,53 @@ pub async fn deploy_verifier_via_solidity(
     rpc_url: Option<&str>,
     runs: usize,
     private_key: Option<&str>,
+) -> Result<ethers::types::Address, Box<dyn Error>> {
+    let contract_name = verifier_contract_name(&sol_code_path);
+    deploy_contract_via_solidity(sol_code_path, contract_name, rpc_url, runs, private_key).await
+}
+
+/// Whether the verifier at `sol_code_path` is a Yul object rather than a Solidity contract
+pub fn is_yul(sol_code_path: &Path) -> bool {
+    sol_code_path.extension().map_or(false, |ext| ext == "yul")
+}
+
+fn verifier_contract_name(sol_code_path: &Path) -> &'static str {
+    if is_yul(sol_code_path) {
+        YUL_VERIFIER_NAME
+    } else {
+        "Halo2Verifier"
+    }
+}
+
+/// Deploys the verifying key contract rendered alongside a reusable verifier
+pub async fn deploy_vk_via_solidity(
+    sol_code_path: PathBuf,
+    rpc_url: Option<&str>,
+    runs: usize,
+    private_key: Option<&str>,
+) -> Result<ethers::types::Address, Box<dyn Error>> {
+    deploy_contract_via_solidity(
+        sol_code_path,
+        "Halo2VerifyingKey",
+        rpc_url,
+        runs,
+        private_key,
+    )
+    .await
+}
+
+async fn deploy_contract_via_solidity(
+    sol_code_path: PathBuf,
+    contract_name: &str,
+    rpc_url: Option<&str>,
+    runs: usize,
+    private_key: Option<&str>,
 ) -> Result<ethers::types::Address, Box<dyn Error>> {
     // anvil instance must be alive at least until the factory completes the deploy
     let (anvil, client) = setup_eth_backend(rpc_url, private_key).await?;
 
     let (abi, bytecode, runtime_bytecode) =
-        get_contract_artifacts(sol_code_path, "Halo2Verifier", runs)?;
+        get_contract_artifacts(sol_code_path, contract_name, runs)?;
 
     let factory = get_sol_contract_factory(abi, bytecode, runtime_bytecode, client.clone())?;
     let contract = factory.deploy(())?.send().await?;
@@ -254,6 +299,39 @@ pub async fn deploy_da_verifier_via_solidity(
 
 type ParsedCallsToAccount = (Vec<H160>, Vec<Vec<Bytes>>, Vec<Vec<U256>>);
 
+/// ABI encodes the call to a view function that an on-chain input is read from. The call is either
+/// hex encoded calldata or, like `cast calldata`, the function signature followed by its arguments,
+/// e.g. `balanceOf(address) 0x70997970C51812dc3A010C7d01b50e0d17dc79C8`.
+pub fn encode_call(call: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    use ethers::abi::token::{LenientTokenizer, Tokenizer};
+
+    let call = call.trim();
+    if !call.contains('(') {
+        return Ok(hex::decode(call.trim_start_matches("0x"))?);
+    }
+
+    let mut parts = call.split_whitespace();
+    let signature = parts.next().ok_or("empty call")?;
+    let function = ethers::abi::AbiParser::default().parse_function(signature)?;
+    let args = parts.collect::<Vec<_>>();
+    if args.len() != function.inputs.len() {
+        return Err(format!(
+            "{} takes {} arguments but {} were given",
+            signature,
+            function.inputs.len(),
+            args.len()
+        )
+        .into());
+    }
+    let tokens = function
+        .inputs
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| LenientTokenizer::tokenize(&param.kind, arg))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(function.encode_input(&tokens)?)
+}
+
 fn parse_calls_to_accounts(
     calls_to_accounts: Vec<CallsToAccount>,
 ) -> Result<ParsedCallsToAccount, Box<dyn Error>> {
@@ -267,7 +345,7 @@ fn parse_calls_to_accounts(
         call_data.push(vec![]);
         decimals.push(vec![]);
         for (call, decimal) in &val.call_data {
-            let call_data_bytes = hex::decode(call)?;
+            let call_data_bytes = encode_call(call)?;
             call_data[i].push(ethers::types::Bytes::from(call_data_bytes));
             decimals[i].push(ethers::types::U256::from_dec_str(&decimal.to_string())?);
         }
@@ -345,14 +423,79 @@ pub async fn update_account_calls(
 pub async fn verify_proof_via_solidity(
     proof: Snark<Fr, G1Affine>,
     addr: ethers::types::Address,
+    addr_vk: Option<H160>,
+    yul: bool,
     rpc_url: Option<&str>,
 ) -> Result<bool, Box<dyn Error>> {
-    let flattened_instances = proof.instances.into_iter().flatten();
+    let (anvil, client) = setup_eth_backend(rpc_url, None).await?;
+    verify_proof_with_client(proof, addr, addr_vk, yul, &client).await?;
+    drop(anvil);
+    Ok(true)
+}
+
+/// Submits the proof to a deployed verifier as a transaction, so the verification is recorded on-chain.
+/// The proof is checked with a call first so an invalid proof doesn't spend gas. The transaction is
+/// signed with `private_key`, there's no dev account to fall back to on a remote node.
+pub async fn submit_proof_via_solidity(
+    proof: Snark<Fr, G1Affine>,
+    addr: ethers::types::Address,
+    addr_vk: Option<H160>,
+    yul: bool,
+    rpc_url: &str,
+    private_key: &str,
+) -> Result<TransactionReceipt, Box<dyn Error>> {
+    let (anvil, client) = setup_eth_backend(Some(rpc_url), Some(private_key)).await?;
+    verify_proof_with_client(proof.clone(), addr, addr_vk, yul, &client).await?;
+
+    let encoded = verifier_calldata(&proof, addr_vk, yul);
+    let tx: TypedTransaction = TransactionRequest::default()
+        .to(addr)
+        .from(client.address())
+        .data(encoded)
+        .into();
+
+    let receipt = client
+        .send_transaction(tx, None)
+        .await?
+        .await?
+        .ok_or("verify transaction was dropped from the mempool")?;
+    if receipt.status != Some(1.into()) {
+        return Err(format!(
+            "verify transaction {:#?} reverted",
+            receipt.transaction_hash
+        )
+        .into());
+    }
+
+    drop(anvil);
+    Ok(receipt)
+}
 
-    let encoded = encode_calldata(None, &proof.proof, &flattened_instances.collect::<Vec<_>>());
+/// Calldata for a `verifyProof` call, or the bare instances followed by the proof for a Yul verifier
+fn verifier_calldata(proof: &Snark<Fr, G1Affine>, addr_vk: Option<H160>, yul: bool) -> Vec<u8> {
+    if yul {
+        return snark_verifier::loader::evm::encode_calldata(&proof.instances, &proof.proof);
+    }
+    let flattened_instances = proof
+        .instances
+        .iter()
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>();
+    encode_calldata(addr_vk.map(|vk| vk.0), &proof.proof, &flattened_instances)
+}
+
+/// Calls the verifier with the proof and returns the gas used by the call
+async fn verify_proof_with_client(
+    proof: Snark<Fr, G1Affine>,
+    addr: ethers::types::Address,
+    addr_vk: Option<H160>,
+    yul: bool,
+    client: &EthersClient,
+) -> Result<U256, Box<dyn Error>> {
+    let encoded = verifier_calldata(&proof, addr_vk, yul);
 
     info!("encoded: {:#?}", hex::encode(&encoded));
-    let (anvil, client) = setup_eth_backend(rpc_url, None).await?;
     let tx: TypedTransaction = TransactionRequest::default()
         .to(addr)
         .from(client.address())
@@ -367,10 +510,13 @@ pub async fn verify_proof_via_solidity(
     }
     let result = result?;
     info!("result: {:#?}", result.to_vec());
-    // decode return bytes value into uint8
-    let result = result.to_vec().last().ok_or("no contract ouput")? == &1u8;
-    if !result {
-        return Err(Box::new(EvmVerificationError::InvalidProof));
+    // yul verifiers return nothing and revert on an invalid proof, solidity ones return a bool
+    if !yul {
+        // decode return bytes value into uint8
+        let result = result.to_vec().last().ok_or("no contract ouput")? == &1u8;
+        if !result {
+            return Err(Box::new(EvmVerificationError::InvalidProof));
+        }
     }
 
     let gas = client.estimate_gas(&tx, None).await?;
@@ -388,8 +534,107 @@ pub async fn verify_proof_via_solidity(
         );
     }
 
-    drop(anvil);
-    Ok(true)
+    Ok(gas)
+}
+
+/// The on-chain cost of a verifier, measured by deploying it to a fresh local chain and verifying a proof against it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvmGasReport {
+    /// size of the verifier's runtime bytecode in bytes
+    pub verifier_size: usize,
+    /// gas used to deploy the verifier
+    pub verifier_deploy_gas: u64,
+    /// size of the verifying key contract's runtime bytecode, if the vk was rendered separately
+    pub vk_size: Option<usize>,
+    /// gas used to deploy the verifying key contract, if the vk was rendered separately
+    pub vk_deploy_gas: Option<u64>,
+    /// gas used by a `verifyProof` call
+    pub verify_gas: u64,
+}
+
+/// The contract size limit of the in-process evm, the same one anvil is launched with
+const EVM_CODE_SIZE_LIMIT: usize = 41943040;
+
+/// Runs a transaction from the zero address in an in-process evm, returning the gas it used and its output
+fn transact(
+    evm: &mut EVM<InMemoryDB>,
+    transact_to: TransactTo,
+    data: Vec<u8>,
+) -> Result<(u64, Output), Box<dyn Error>> {
+    evm.env.tx = TxEnv {
+        transact_to,
+        data: data.into(),
+        ..Default::default()
+    };
+    match evm
+        .transact_commit()
+        .map_err(|e| format!("evm error: {:?}", e))?
+    {
+        ExecutionResult::Success {
+            gas_used, output, ..
+        } => Ok((gas_used, output)),
+        ExecutionResult::Revert { gas_used, .. } => {
+            Err(format!("transaction reverted after {} gas", gas_used).into())
+        }
+        ExecutionResult::Halt { reason, .. } => {
+            Err(format!("transaction halted: {:?}", reason).into())
+        }
+    }
+}
+
+/// Deploys a verifier (and its verifying key contract, if rendered separately) to an in-process evm
+/// and measures what deploying it and verifying the proof against it cost
+pub fn estimate_verify_gas(
+    proof: Snark<Fr, G1Affine>,
+    sol_code_path: PathBuf,
+    vk_sol_code_path: Option<PathBuf>,
+    runs: usize,
+) -> Result<EvmGasReport, Box<dyn Error>> {
+    let mut evm = EVM::new();
+    evm.database(InMemoryDB::default());
+    evm.env.cfg.limit_contract_code_size = Some(EVM_CODE_SIZE_LIMIT);
+
+    let mut deploy = |sol_code_path: PathBuf,
+                      contract_name: &'static str|
+     -> Result<(H160, usize, u64), Box<dyn Error>> {
+        let (_, bytecode, runtime_bytecode) =
+            get_contract_artifacts(sol_code_path, contract_name, runs)?;
+        match transact(&mut evm, TransactTo::create(), bytecode.to_vec())? {
+            (gas, Output::Create(_, Some(addr))) => Ok((H160(addr.0), runtime_bytecode.len(), gas)),
+            _ => Err(format!("{} was not deployed", contract_name).into()),
+        }
+    };
+
+    let yul = is_yul(&sol_code_path);
+    let contract_name = verifier_contract_name(&sol_code_path);
+    let (addr, verifier_size, verifier_deploy_gas) = deploy(sol_code_path, contract_name)?;
+
+    let (addr_vk, vk_size, vk_deploy_gas) = match vk_sol_code_path {
+        Some(vk_sol_code_path) => {
+            let (addr_vk, size, gas) = deploy(vk_sol_code_path, "Halo2VerifyingKey")?;
+            (Some(addr_vk), Some(size), Some(gas))
+        }
+        None => (None, None, None),
+    };
+
+    let encoded = verifier_calldata(&proof, addr_vk, yul);
+    let (verify_gas, output) = transact(&mut evm, TransactTo::Call(addr.0.into()), encoded)
+        .map_err(|e| {
+            debug!("{}", e);
+            EvmVerificationError::SolidityExecution
+        })?;
+    // yul verifiers return nothing and revert on an invalid proof, solidity ones return a bool
+    if !yul && !matches!(&output, Output::Call(res) if res.last() == Some(&1u8)) {
+        return Err(Box::new(EvmVerificationError::InvalidProof));
+    }
+
+    Ok(EvmGasReport {
+        verifier_size,
+        verifier_deploy_gas,
+        vk_size,
+        vk_deploy_gas,
+        verify_gas,
+    })
 }
 
 fn count_decimal_places(num: f32) -> usize {
@@ -580,7 +825,7 @@ pub async fn read_on_chain_inputs<M: 'static + Middleware>(
         let contract_address_bytes = hex::decode(on_chain_data.address.clone())?;
         let contract_address = H160::from_slice(&contract_address_bytes);
         for (call_data, decimal) in &on_chain_data.call_data {
-            let call_data_bytes = hex::decode(call_data.clone())?;
+            let call_data_bytes = encode_call(call_data)?;
             let tx: TypedTransaction = TransactionRequest::default()
                 .to(contract_address)
                 .from(address)
@@ -2,30 +2,43 @@ use crate::circuit::CheckMode;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::commands::CalibrationTarget;
 use crate::commands::Commands;
+use crate::commands::{TableFormat, VERSION};
+use crate::error::EzklError;
 #[cfg(not(target_arch = "wasm32"))]
-use crate::eth::{deploy_da_verifier_via_solidity, deploy_verifier_via_solidity};
+use crate::eth::{
+    deploy_da_verifier_via_solidity, deploy_verifier_via_solidity, deploy_vk_via_solidity,
+};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::eth::{fix_da_sol, get_contract_artifacts, verify_proof_via_solidity};
+use crate::fieldutils::felt_to_i128;
 use crate::graph::input::GraphData;
-use crate::graph::{GraphCircuit, GraphSettings, GraphWitness, Model};
+use crate::graph::{
+    CircuitStats, GraphCircuit, GraphSettings, GraphWitness, Model, NodeIssue, NodeSummary,
+    TestVector,
+};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::graph::{TestDataSource, TestSources};
 use crate::pfsys::evm::aggregation::AggregationCircuit;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::pfsys::evm::{single::gen_evm_verifier, YulCode};
 use crate::pfsys::{
-    create_keys, load_pk, load_vk, save_params, save_pk, swap_proof_commitments_kzg, Snark,
-    StrategyType, TranscriptType,
+    create_keys, load_pk, load_vk, save_params, save_pk, swap_proof_commitments_kzg, ProofEncoding,
+    Snark, StrategyType, TranscriptType,
 };
+use crate::pfsys::{create_proof_circuit_ipa, verify_proof_circuit_ipa, Commitments};
 use crate::pfsys::{create_proof_circuit_kzg, verify_proof_circuit_kzg};
+use crate::pfsys::{create_proof_circuit_kzg_gwc, verify_proof_circuit_kzg_gwc, MultiOpen};
 use crate::pfsys::{save_vk, srs::*};
+use crate::tensor::Tensor;
 use crate::RunArgs;
 #[cfg(not(target_arch = "wasm32"))]
 use ethers::types::H160;
 use gag::Gag;
 use halo2_proofs::dev::VerifyFailure;
+use halo2_proofs::plonk::{ProvingKey, VerifyingKey};
 use halo2_proofs::poly::commitment::Params;
 use halo2_proofs::poly::commitment::ParamsProver;
+use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
 use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
 use halo2_proofs::poly::kzg::strategy::AccumulatorStrategy;
 use halo2_proofs::poly::kzg::{
@@ -43,13 +56,14 @@ use instant::Instant;
 use itertools::Itertools;
 #[cfg(not(target_arch = "wasm32"))]
 use log::debug;
-use log::{info, trace, warn};
+use log::{error, info, trace, warn};
 #[cfg(feature = "render")]
 use plotters::prelude::*;
 #[cfg(not(target_arch = "wasm32"))]
 use rand::Rng;
 #[cfg(not(target_arch = "wasm32"))]
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fs::File;
 #[cfg(not(target_arch = "wasm32"))]
@@ -106,6 +120,9 @@ lazy_static! {
     /// The path to the ezkl related data (SRS)
     pub static ref EZKL_SRS_REPO_PATH: String = format!("{}/srs", *EZKL_REPO_PATH);
 
+    /// The path to the cache of proving and verifying keys
+    pub static ref EZKL_KEY_CACHE_PATH: String = format!("{}/keys", *EZKL_REPO_PATH);
+
 }
 
 /// A wrapper for tensor related errors.
@@ -114,6 +131,9 @@ pub enum ExecutionError {
     /// Shape mismatch in a operation
     #[error("verification failed")]
     VerifyError(Vec<VerifyFailure>),
+    /// The forward pass and the circuit layout produced different outputs
+    #[error("forward pass and circuit layout disagree on {0} output cell(s)")]
+    OutputMismatch(usize),
 }
 
 lazy_static::lazy_static! {
@@ -128,6 +148,8 @@ lazy_static::lazy_static! {
 pub async fn run(command: Commands) -> Result<(), Box<dyn Error>> {
     // set working dir
     std::env::set_current_dir(WORKING_DIR.as_path())?;
+    // fetch any hf:// models or inputs before the command runs
+    let command = crate::hf::resolve_command(command).await?;
 
     match command {
         Commands::Empty => Ok(()),
@@ -138,8 +160,36 @@ pub async fn run(command: Commands) -> Result<(), Box<dyn Error>> {
             transcript,
             num_runs,
         } => fuzz(compiled_circuit, witness, transcript, num_runs),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::FuzzInputs {
+            compiled_circuit,
+            num_runs,
+            seed,
+            max_abs,
+        } => fuzz_inputs(compiled_circuit, num_runs, seed, max_abs).await,
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::Bench {
+            model,
+            data,
+            settings_path,
+            srs_path,
+            output,
+            args,
+        } => bench(model, data, settings_path, srs_path, output, args).await,
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::Estimate {
+            model,
+            settings_path,
+            bench_report,
+            output,
+            args,
+        } => estimate(model, settings_path, bench_report, output, args),
 
-        Commands::GenSrs { srs_path, logrows } => gen_srs_cmd(srs_path, logrows as u32),
+        Commands::GenSrs {
+            srs_path,
+            logrows,
+            commitment,
+        } => gen_srs_cmd(srs_path, logrows as u32, commitment),
         #[cfg(not(target_arch = "wasm32"))]
         Commands::GetSrs {
             srs_path,
@@ -147,18 +197,43 @@ pub async fn run(command: Commands) -> Result<(), Box<dyn Error>> {
             logrows,
             check,
         } => get_srs_cmd(srs_path, settings_path, logrows, check).await,
-        Commands::Table { model, args } => table(model, args),
+        Commands::Table {
+            model,
+            table_format,
+            output,
+            watch,
+            args,
+        } => {
+            if watch {
+                watch_files(&[model.clone()], |_| {
+                    table(model.clone(), table_format, output.clone(), args.clone())
+                })
+            } else {
+                table(model, table_format, output, args)
+            }
+        }
         #[cfg(feature = "render")]
         Commands::RenderCircuit {
             model,
             output,
+            render_width,
+            render_height,
             args,
-        } => render(model, output, args),
+        } => render(model, output, render_width, render_height, args),
         Commands::GenSettings {
             model,
             settings_path,
-            args,
-        } => gen_circuit_settings(model, settings_path, args),
+            profile,
+            input_bound,
+            target_logrows,
+            mut args,
+        } => {
+            if let Some(profile) = profile {
+                info!("using the {} settings profile", profile);
+                profile.apply(&mut args);
+            }
+            gen_circuit_settings(model, settings_path, args, input_bound, target_logrows)
+        }
         #[cfg(not(target_arch = "wasm32"))]
         Commands::CalibrateSettings {
             model,
@@ -168,16 +243,103 @@ pub async fn run(command: Commands) -> Result<(), Box<dyn Error>> {
             scales,
             max_logrows,
         } => calibrate(model, data, settings_path, target, scales, max_logrows),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::Check {
+            model,
+            output,
+            args,
+        } => check(model, output, args),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::Audit {
+            compiled_circuit,
+            input_bound,
+            output,
+        } => audit(compiled_circuit, input_bound, output),
+        Commands::Init {
+            model,
+            data,
+            settings_path,
+        } => init(model, data, settings_path),
         Commands::GenWitness {
             data,
             compiled_circuit,
             output,
             vk_path,
             srs_path,
-        } => gen_witness(compiled_circuit, data, Some(output), vk_path, srs_path)
+            key_path,
+            signature_path,
+        } => gen_witness(
+            compiled_circuit,
+            data,
+            Some(output),
+            vk_path,
+            srs_path,
+            key_path,
+            signature_path,
+        )
+        .await
+        .map(|_| ()),
+        Commands::EncryptInput {
+            data,
+            compiled_circuit,
+            key_path,
+            vk_path,
+            srs_path,
+            output,
+            secret_path,
+        } => {
+            encrypt_input(
+                compiled_circuit,
+                data,
+                key_path,
+                vk_path,
+                srs_path,
+                output,
+                secret_path,
+            )
             .await
-            .map(|_| ()),
-        Commands::Mock { model, witness } => mock(model, witness),
+        }
+        Commands::CommitParams {
+            compiled_circuit,
+            vk_path,
+            srs_path,
+            output,
+        } => commit_params(compiled_circuit, vk_path, srs_path, output),
+        Commands::Mock {
+            model,
+            witness,
+            watch,
+        } => {
+            if watch {
+                mock_watch(model, witness)
+            } else {
+                mock(model, witness)
+            }
+        }
+        Commands::Stats { compiled_circuit } => stats(compiled_circuit),
+        Commands::GenTestVector {
+            data,
+            compiled_circuit,
+            output,
+        } => gen_test_vector(compiled_circuit, data, output).await,
+        Commands::Compare {
+            data,
+            compiled_circuit,
+        } => compare(compiled_circuit, data).await,
+        #[cfg(feature = "ort-compare")]
+        Commands::CompareOnnxruntime {
+            data,
+            model,
+            compiled_circuit,
+            output,
+        } => compare_onnxruntime(model, compiled_circuit, data, output).await,
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::Accuracy {
+            data,
+            model,
+            compiled_circuit,
+            output,
+        } => accuracy(model, compiled_circuit, data, output).await,
         #[cfg(not(target_arch = "wasm32"))]
         Commands::CreateEVMVerifier {
             vk_path,
@@ -185,7 +347,17 @@ pub async fn run(command: Commands) -> Result<(), Box<dyn Error>> {
             settings_path,
             sol_code_path,
             abi_path,
-        } => create_evm_verifier(vk_path, srs_path, settings_path, sol_code_path, abi_path),
+            vk_sol_code_path,
+            yul,
+        } => create_evm_verifier(
+            vk_path,
+            srs_path,
+            settings_path,
+            sol_code_path,
+            abi_path,
+            vk_sol_code_path,
+            yul,
+        ),
         #[cfg(not(target_arch = "wasm32"))]
         Commands::CreateEVMDataAttestation {
             vk_path,
@@ -229,7 +401,17 @@ pub async fn run(command: Commands) -> Result<(), Box<dyn Error>> {
             vk_path,
             pk_path,
             witness,
-        } => setup(compiled_circuit, srs_path, vk_path, pk_path, witness),
+            reuse_keys,
+            key_cache,
+        } => setup(
+            compiled_circuit,
+            srs_path,
+            vk_path,
+            pk_path,
+            witness,
+            reuse_keys,
+            key_cache,
+        ),
         #[cfg(not(target_arch = "wasm32"))]
         Commands::SetupTestEVMData {
             data,
@@ -269,16 +451,103 @@ pub async fn run(command: Commands) -> Result<(), Box<dyn Error>> {
             srs_path,
             proof_type,
             check_mode,
-        } => prove(
-            witness,
+            proof_encoding,
+            devices,
+        } => {
+            if devices.len() > 1 && is_glob(&witness) {
+                prove_on_devices(
+                    &devices,
+                    witness,
+                    compiled_circuit,
+                    pk_path,
+                    srs_path,
+                    proof_type,
+                    check_mode,
+                    proof_encoding,
+                )
+            } else if is_glob(&witness) {
+                prove_many(
+                    witness,
+                    compiled_circuit,
+                    pk_path,
+                    srs_path,
+                    proof_type,
+                    check_mode,
+                    proof_encoding,
+                )
+                .map(|_| ())
+            } else {
+                if devices.len() > 1 {
+                    warn!(
+                        "a single proof runs on one device, proving on device {}",
+                        devices[0]
+                    );
+                }
+                prove(
+                    witness,
+                    compiled_circuit,
+                    pk_path,
+                    Some(proof_path),
+                    srs_path,
+                    proof_type,
+                    check_mode,
+                    proof_encoding,
+                )
+                .map(|_| ())
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::ProveBatch {
+            data_dir,
             compiled_circuit,
             pk_path,
-            Some(proof_path),
             srs_path,
+            output_dir,
             proof_type,
             check_mode,
-        )
-        .map(|_| ()),
+            proof_encoding,
+            max_parallel,
+            aggregated_pk_path,
+            aggregated_srs_path,
+            aggregated_logrows,
+            aggregated_proof_path,
+        } => {
+            // the requested proof type applies to the aggregated proof when aggregating
+            let aggregated_transcript: TranscriptType = proof_type.into();
+            let proof_type = if aggregated_pk_path.is_some() {
+                if proof_encoding != ProofEncoding::Json {
+                    return Err("proofs are aggregated from json, use --proof-encoding json".into());
+                }
+                ProofType::ForAggr
+            } else {
+                proof_type
+            };
+            let records = prove_batch(
+                data_dir,
+                compiled_circuit,
+                pk_path,
+                srs_path.clone(),
+                output_dir,
+                proof_type,
+                check_mode,
+                proof_encoding,
+                max_parallel,
+            )
+            .await?;
+            match aggregated_pk_path {
+                Some(aggregated_pk_path) => aggregate(
+                    aggregated_proof_path,
+                    records.into_iter().map(|r| r.proof).collect(),
+                    aggregated_pk_path,
+                    aggregated_srs_path,
+                    aggregated_transcript,
+                    aggregated_logrows,
+                    check_mode,
+                    false,
+                ),
+                None => Ok(()),
+            }
+        }
         Commands::MockAggregate {
             aggregation_snarks,
             logrows,
@@ -323,7 +592,80 @@ pub async fn run(command: Commands) -> Result<(), Box<dyn Error>> {
             settings_path,
             vk_path,
             srs_path,
-        } => verify(proof_path, settings_path, vk_path, srs_path),
+            proof_encoding,
+            input_commitment,
+            params_commitment,
+        } => verify(
+            proof_path,
+            settings_path,
+            vk_path,
+            srs_path,
+            proof_encoding,
+            input_commitment,
+            params_commitment,
+        ),
+        #[cfg(feature = "server")]
+        Commands::Serve {
+            compiled_circuit,
+            pk_path,
+            vk_path,
+            srs_path,
+            addr,
+            proof_type,
+            check_mode,
+        } => {
+            let state = crate::server::ProverState::load(
+                compiled_circuit,
+                pk_path,
+                vk_path,
+                srs_path,
+                proof_type,
+                check_mode,
+            )?;
+            crate::server::serve(addr, state).await
+        }
+        #[cfg(feature = "grpc")]
+        Commands::ServeGrpc {
+            compiled_circuit,
+            pk_path,
+            vk_path,
+            srs_path,
+            addr,
+            proof_type,
+            check_mode,
+            max_queued_jobs,
+        } => {
+            if max_queued_jobs == 0 {
+                return Err("max_queued_jobs must be >= 1".into());
+            }
+            let state = crate::server::ProverState::load(
+                compiled_circuit,
+                pk_path,
+                vk_path,
+                srs_path,
+                proof_type,
+                check_mode,
+            )?;
+            crate::grpc::serve_grpc(addr, state, max_queued_jobs).await
+        }
+        #[cfg(feature = "server")]
+        Commands::ServeRpc { addr } => crate::jsonrpc::serve_rpc(addr).await,
+        Commands::VerifyMany {
+            settings_path,
+            proof_dir,
+            vk_path,
+            srs_path,
+            proof_encoding,
+            batch,
+        } => verify_many(
+            proof_dir,
+            settings_path,
+            vk_path,
+            srs_path,
+            proof_encoding,
+            batch,
+        )
+        .map(|_| ()),
         Commands::VerifyAggr {
             proof_path,
             vk_path,
@@ -348,6 +690,23 @@ pub async fn run(command: Commands) -> Result<(), Box<dyn Error>> {
             .await
         }
         #[cfg(not(target_arch = "wasm32"))]
+        Commands::DeployEvmVK {
+            sol_code_path,
+            rpc_url,
+            addr_path,
+            optimizer_runs,
+            private_key,
+        } => {
+            deploy_evm_vk(
+                sol_code_path,
+                rpc_url,
+                addr_path,
+                optimizer_runs,
+                private_key,
+            )
+            .await
+        }
+        #[cfg(not(target_arch = "wasm32"))]
         Commands::DeployEvmDataAttestation {
             data,
             settings_path,
@@ -369,16 +728,207 @@ pub async fn run(command: Commands) -> Result<(), Box<dyn Error>> {
             .await
         }
         #[cfg(not(target_arch = "wasm32"))]
+        Commands::VerifyEvmOnchain {
+            proof_path,
+            addr_verifier,
+            rpc_url,
+            private_key,
+            addr_vk,
+            yul,
+        } => {
+            verify_evm_onchain(
+                proof_path,
+                addr_verifier,
+                rpc_url,
+                private_key,
+                addr_vk,
+                yul,
+            )
+            .await
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::EstimateEvmGas {
+            proof_path,
+            sol_code_path,
+            vk_sol_code_path,
+            optimizer_runs,
+            output,
+        } => {
+            estimate_evm_gas(
+                proof_path,
+                sol_code_path,
+                vk_sol_code_path,
+                optimizer_runs,
+                output,
+            )
+            .await
+        }
+        #[cfg(not(target_arch = "wasm32"))]
         Commands::VerifyEVM {
             proof_path,
             addr_verifier,
             rpc_url,
             addr_da,
-        } => verify_evm(proof_path, addr_verifier, rpc_url, addr_da).await,
+            addr_vk,
+            yul,
+        } => verify_evm(proof_path, addr_verifier, rpc_url, addr_da, addr_vk, yul).await,
+        Commands::ExportSnarkjs {
+            proof_path,
+            proof_json,
+            public_json,
+        } => export_snarkjs(proof_path, proof_json, public_json),
+        Commands::LinkProofs {
+            proof_paths,
+            settings_paths,
+            vk_paths,
+            srs_path,
+        } => link_proofs(proof_paths, settings_paths, vk_paths, srs_path),
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::SplitModel {
+            model,
+            cut_after,
+            num_pieces,
+            output_dir,
+        } => split_model(model, cut_after, num_pieces, output_dir),
         Commands::PrintProofHex { proof_path } => print_proof_hex(proof_path),
     }
 }
 
+/// A structured summary of a command run, printed when `--format json` is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandOutput {
+    /// the name of the command that was run
+    pub command: String,
+    /// whether the command succeeded
+    pub succeeded: bool,
+    /// wall clock time of the command in milliseconds
+    pub elapsed_ms: u128,
+    /// the arguments of the command along with any command specific results
+    pub result: serde_json::Value,
+    /// the error (with its code and kind) if the command failed
+    pub error: Option<EzklError>,
+}
+
+/// Run an ezkl command and summarize the outcome as a [CommandOutput]
+pub async fn run_with_output(command: Commands) -> CommandOutput {
+    let (name, mut result) = match serde_json::to_value(&command) {
+        Ok(serde_json::Value::Object(map)) => match map.into_iter().next() {
+            Some((name, args)) => (name, args),
+            None => (String::new(), serde_json::Value::Null),
+        },
+        Ok(serde_json::Value::String(name)) => (name, serde_json::Value::Null),
+        _ => (String::new(), serde_json::Value::Null),
+    };
+
+    let now = Instant::now();
+    let res = run(command.clone()).await;
+    let elapsed_ms = now.elapsed().as_millis();
+
+    let mut extra = verdict(&command, &res).into_iter().collect::<Vec<_>>();
+    let error = match res.and_then(|_| command_results(&command)) {
+        Ok(results) => {
+            extra.extend(results);
+            None
+        }
+        Err(e) => Some(EzklError::from(e)),
+    };
+    if let serde_json::Value::Object(map) = &mut result {
+        map.extend(extra);
+    }
+
+    CommandOutput {
+        command: name,
+        succeeded: error.is_none(),
+        elapsed_ms,
+        result,
+        error,
+    }
+}
+
+/// Whether a verifying or linking command accepted its proofs, taken from the command's outcome
+/// so that a rejected proof is reported as such
+fn verdict(
+    command: &Commands,
+    res: &Result<(), Box<dyn Error>>,
+) -> Option<(String, serde_json::Value)> {
+    let key = match command {
+        Commands::Verify { .. } | Commands::VerifyMany { .. } | Commands::VerifyAggr { .. } => {
+            "verified"
+        }
+        Commands::LinkProofs { .. } => "linked",
+        _ => return None,
+    };
+    Some((key.to_string(), serde_json::Value::Bool(res.is_ok())))
+}
+
+/// Results of a successful command that are not already part of its arguments
+fn command_results(command: &Commands) -> Result<Vec<(String, serde_json::Value)>, Box<dyn Error>> {
+    let results = match command {
+        Commands::Prove { witness, .. } if is_glob(witness) => {
+            let proofs = expand_glob(witness)?
+                .iter()
+                .map(|w| proof_path_for(w))
+                .collect::<Vec<_>>();
+            vec![("proofs".to_string(), serde_json::to_value(proofs)?)]
+        }
+        Commands::Prove {
+            proof_path,
+            proof_encoding,
+            ..
+        } => {
+            let snark = Snark::<Fr, G1Affine>::load_with_encoding::<KZGCommitmentScheme<Bn256>>(
+                proof_path,
+                *proof_encoding,
+            )?;
+            vec![
+                (
+                    "instances".to_string(),
+                    serde_json::to_value(&snark.instances)?,
+                ),
+                (
+                    "proof".to_string(),
+                    serde_json::Value::String(hex::encode(&snark.proof)),
+                ),
+            ]
+        }
+        Commands::Stats { compiled_circuit } => {
+            let stats = GraphCircuit::load(compiled_circuit.clone())?.stats()?;
+            vec![("stats".to_string(), serde_json::to_value(stats)?)]
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::SplitModel { output_dir, .. } => {
+            let pieces: serde_json::Value = serde_json::from_reader(std::fs::File::open(
+                output_dir.join(crate::graph::split::SPLIT_MANIFEST),
+            )?)?;
+            vec![("pieces".to_string(), pieces)]
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::Bench { output, .. } => {
+            let report: serde_json::Value = serde_json::from_reader(std::fs::File::open(output)?)?;
+            vec![("bench".to_string(), report)]
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::Estimate { output, .. } => {
+            let report: serde_json::Value = serde_json::from_reader(std::fs::File::open(output)?)?;
+            vec![("estimate".to_string(), report)]
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Commands::EstimateEvmGas { output, .. } => {
+            let report: serde_json::Value = serde_json::from_reader(std::fs::File::open(output)?)?;
+            vec![("gas".to_string(), report)]
+        }
+        Commands::GenWitness { output, .. } => {
+            let witness = GraphWitness::from_path(output.clone())?;
+            vec![(
+                "outputs".to_string(),
+                serde_json::to_value(&witness.outputs)?,
+            )]
+        }
+        _ => vec![],
+    };
+    Ok(results)
+}
+
 /// Get the srs path
 pub fn get_srs_path(logrows: u32, srs_path: Option<PathBuf>) -> PathBuf {
     if let Some(srs_path) = srs_path {
@@ -395,9 +945,21 @@ fn srs_exists_check(logrows: u32, srs_path: Option<PathBuf>) -> bool {
     Path::new(&get_srs_path(logrows, srs_path)).exists()
 }
 
-pub(crate) fn gen_srs_cmd(srs_path: PathBuf, logrows: u32) -> Result<(), Box<dyn Error>> {
-    let params = gen_srs::<KZGCommitmentScheme<Bn256>>(logrows);
-    save_params::<KZGCommitmentScheme<Bn256>>(&srs_path, &params)?;
+pub(crate) fn gen_srs_cmd(
+    srs_path: PathBuf,
+    logrows: u32,
+    commitment: Commitments,
+) -> Result<(), Box<dyn Error>> {
+    match commitment {
+        Commitments::KZG => {
+            let params = gen_srs::<KZGCommitmentScheme<Bn256>>(logrows);
+            save_params::<KZGCommitmentScheme<Bn256>>(&srs_path, &params)?;
+        }
+        Commitments::IPA => {
+            let params = gen_srs::<IPACommitmentScheme<G1Affine>>(logrows);
+            save_params::<IPACommitmentScheme<G1Affine>>(&srs_path, &params)?;
+        }
+    }
     Ok(())
 }
 
@@ -432,7 +994,7 @@ pub(crate) async fn get_srs_cmd(
     let k = if let Some(k) = logrows {
         k
     } else if let Some(settings_p) = settings_path {
-        if settings_p.exists() {
+        if settings_p.exists() || crate::stdio::is_stdio(&settings_p) {
             let settings = GraphSettings::load(&settings_p)?;
             settings.run_args.logrows
         } else {
@@ -473,9 +1035,36 @@ pub(crate) async fn get_srs_cmd(
     Ok(())
 }
 
-pub(crate) fn table(model: PathBuf, run_args: RunArgs) -> Result<(), Box<dyn Error>> {
+pub(crate) fn table(
+    model: PathBuf,
+    table_format: TableFormat,
+    output: Option<PathBuf>,
+    run_args: RunArgs,
+) -> Result<(), Box<dyn Error>> {
     let model = Model::from_run_args(&run_args, &model)?;
-    info!("\n {}", model.table_nodes());
+    let table = match table_format {
+        TableFormat::Pretty => {
+            if output.is_none() {
+                info!("\n {}", model.table_nodes());
+                return Ok(());
+            }
+            model.table_nodes()
+        }
+        TableFormat::Json => serde_json::to_string_pretty(&model.node_summaries(&run_args)?)?,
+        TableFormat::Csv => std::iter::once(NodeSummary::csv_header())
+            .chain(
+                model
+                    .node_summaries(&run_args)?
+                    .iter()
+                    .map(NodeSummary::csv_record),
+            )
+            .join("\n"),
+    };
+
+    match output {
+        Some(output) => std::fs::write(output, table)?,
+        None => println!("{}", table),
+    }
     Ok(())
 }
 
@@ -485,6 +1074,8 @@ pub(crate) async fn gen_witness(
     output: Option<PathBuf>,
     vk_path: Option<PathBuf>,
     srs_path: Option<PathBuf>,
+    key_path: Option<PathBuf>,
+    signature_path: Option<PathBuf>,
 ) -> Result<GraphWitness, Box<dyn Error>> {
     // these aren't real values so the sanity checks are mostly meaningless
 
@@ -521,7 +1112,20 @@ pub(crate) async fn gen_witness(
 
     let start_time = Instant::now();
 
-    let witness = circuit.forward(&mut input, vk.as_ref(), srs.as_ref())?;
+    let mut keys = crate::graph::modules::ProviderKeys::default();
+    if let Some(key_path) = key_path {
+        keys.elgamal = Some(serde_json::from_str(&std::fs::read_to_string(key_path)?)?);
+    }
+    if let Some(signature_path) = signature_path {
+        let processed: crate::graph::modules::ModuleForwardResult =
+            serde_json::from_str(&std::fs::read_to_string(signature_path)?)?;
+        keys.signature = processed.signature;
+    }
+    if settings.run_args.input_visibility.is_signed() && keys.signature.is_none() {
+        return Err("signed inputs need the data provider's signatures (--signature-path)".into());
+    }
+
+    let witness = circuit.forward_with_keys(&mut input, vk.as_ref(), srs.as_ref(), &keys)?;
 
     // print each variable tuple (symbol, value) as symbol=value
     trace!(
@@ -537,28 +1141,425 @@ pub(crate) async fn gen_witness(
     );
 
     if let Some(output_path) = output {
-        serde_json::to_writer(&File::create(output_path)?, &witness)?;
+        witness.save(output_path)?;
     }
     Ok(witness)
 }
 
-/// Generate a circuit settings file
-pub(crate) fn gen_circuit_settings(
-    model_path: PathBuf,
-    params_output: PathBuf,
-    run_args: RunArgs,
+/// Encrypts, hashes (and signs) or commits to an input as dictated by the circuit's input visibility.
+/// The result has the shape of a witness' `processed_inputs`, without any secrets: the ElGamal
+/// secret key and randomness of an encryption go to `secret_path` instead.
+pub(crate) async fn encrypt_input(
+    compiled_circuit_path: PathBuf,
+    data: PathBuf,
+    key_path: PathBuf,
+    vk_path: Option<PathBuf>,
+    srs_path: Option<PathBuf>,
+    output: PathBuf,
+    secret_path: PathBuf,
 ) -> Result<(), Box<dyn Error>> {
-    let circuit = GraphCircuit::from_run_args(&run_args, &model_path)?;
-    let params = circuit.settings();
-    params.save(&params_output).map_err(Box::<dyn Error>::from)
-}
+    use crate::circuit::modules::ecdsa::SignatureVariables;
+    use crate::circuit::modules::elgamal::ElGamalVariables;
+    use crate::graph::modules::{GraphModules, ModuleForwardResult};
+
+    let mut circuit = GraphCircuit::load(compiled_circuit_path)?;
+    let data = GraphData::from_path(data)?;
+    let settings = circuit.settings().clone();
+    let visibility = settings.run_args.input_visibility.clone();
+
+    if !visibility.requires_processing() {
+        return Err(format!(
+            "input visibility is {}, there is nothing to encrypt or commit to",
+            visibility
+        )
+        .into());
+    }
+
+    let mut inputs = circuit.load_graph_input(&data).await?;
+    let module_outlets = visibility.overwrites_inputs();
+    if !module_outlets.is_empty() {
+        inputs = module_outlets.iter().map(|o| inputs[*o].clone()).collect();
+    }
+
+    let processed = if visibility.is_encrypted() {
+        if visibility.is_encrypted_to_recipient() && !key_path.exists() {
+            return Err(format!(
+                "inputs are encrypted to a recipient, {} should hold the recipient's ElGamal public key",
+                key_path.display()
+            )
+            .into());
+        }
+        let mut variables: ElGamalVariables = if key_path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&key_path)?)?
+        } else {
+            info!("generating ElGamal key at {}", key_path.display());
+            let variables = ElGamalVariables::gen_random(&mut rand::thread_rng());
+            serde_json::to_writer(File::create(&key_path)?, &variables)?;
+            variables
+        };
+        // fresh randomness for every encryption, recorded next to the key (which is left
+        // untouched) so that `gen-witness --key-path` reproduces this exact ciphertext
+        variables.r = Fr::random(&mut rand::thread_rng());
+        serde_json::to_writer(File::create(&secret_path)?, &variables)?;
+        info!(
+            "wrote the encryption's secret key and randomness to {}, keep it private",
+            secret_path.display()
+        );
+        let mut encrypted =
+            GraphModules::encrypt(&inputs, variables, visibility.is_encrypted_to_recipient());
+        encrypted.variables = encrypted.variables.without_secrets();
+        ModuleForwardResult {
+            elgamal: Some(encrypted),
+            ..Default::default()
+        }
+    } else if visibility.is_signed() {
+        let sk: halo2curves::secp256k1::Fq = if key_path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&key_path)?)?
+        } else {
+            info!("generating secp256k1 signing key at {}", key_path.display());
+            let sk = halo2curves::secp256k1::Fq::random(&mut rand::thread_rng());
+            serde_json::to_writer(File::create(&key_path)?, &sk)?;
+            sk
+        };
+        let mut processed = GraphModules::forward(
+            &inputs,
+            crate::graph::Visibility::Hashed {
+                hash_is_public: true,
+                outlets: vec![],
+            },
+            None,
+            None,
+        )?;
+        let digests = processed.poseidon_hash.clone().unwrap_or_default();
+        processed.signature = Some(SignatureVariables::sign(
+            sk,
+            &digests,
+            &mut rand::thread_rng(),
+        ));
+        processed
+    } else {
+        let vk = if let Some(vk) = vk_path {
+            Some(load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(
+                vk,
+                settings.clone(),
+            )?)
+        } else {
+            None
+        };
+        let srs = if settings.module_requires_kzg() {
+            Some(load_params_cmd(srs_path, settings.run_args.logrows)?)
+        } else {
+            None
+        };
+        GraphModules::forward(&inputs, visibility, vk.as_ref(), srs.as_ref())?
+    };
+
+    let mut writer = crate::stdio::writer(&output)?;
+    serde_json::to_writer(&mut writer, &processed)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Commits to the model's parameters as `kzgcommit` param visibility does when proving, the
+/// commitment can be published to pin the model proofs must be generated with.
+pub(crate) fn commit_params(
+    compiled_circuit_path: PathBuf,
+    vk_path: PathBuf,
+    srs_path: Option<PathBuf>,
+    output: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    let circuit = GraphCircuit::load(compiled_circuit_path)?;
+    let settings = circuit.settings().clone();
+    let visibility = settings.run_args.param_visibility.clone();
+    if !visibility.is_kzgcommit() {
+        return Err(format!(
+            "param visibility is {}, only kzgcommit params can be committed to",
+            visibility
+        )
+        .into());
+    }
+
+    let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, settings.clone())?;
+    let srs = load_params_cmd(srs_path, settings.run_args.logrows)?;
+    let processed = circuit
+        .process_params(visibility, Some(&vk), Some(&srs))?
+        .ok_or("the model has no parameters to commit to")?;
+
+    let mut writer = crate::stdio::writer(&output)?;
+    serde_json::to_writer(&mut writer, &processed)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Generate a circuit settings file
+pub(crate) fn gen_circuit_settings(
+    model_path: PathBuf,
+    params_output: PathBuf,
+    run_args: RunArgs,
+    input_bound: Option<f64>,
+    target_logrows: Option<u32>,
+) -> Result<(), Box<dyn Error>> {
+    let mut circuit = GraphCircuit::from_run_args(&run_args, &model_path)?;
+    if let Some(input_bound) = input_bound {
+        circuit.size_to_input_bound(input_bound, None)?;
+    }
+    if let Some(target_logrows) = target_logrows {
+        circuit.fit_to_logrows(target_logrows)?;
+    }
+    let params = circuit.settings();
+    params.save(&params_output).map_err(Box::<dyn Error>::from)
+}
+
+/// The outcome of `ezkl check`
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CheckReport {
+    /// the checked model
+    pub model: PathBuf,
+    /// true if every node was lowered and the model laid out
+    pub supported: bool,
+    /// the error hit while loading or laying out the model, if any
+    pub error: Option<EzklError>,
+    /// nodes whose op has no circuit lowering
+    pub unsupported_ops: Vec<NodeIssue>,
+    /// nodes whose shapes can't be laid out
+    pub shape_issues: Vec<NodeIssue>,
+    /// the projected size of the circuit, if it could be laid out
+    pub resources: Option<CircuitStats>,
+}
+
+/// Loads the model and dry-runs its layout. Nothing is proven and no keys or srs are needed.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn check(
+    model_path: PathBuf,
+    output: Option<PathBuf>,
+    run_args: RunArgs,
+) -> Result<(), Box<dyn Error>> {
+    let mut report = CheckReport {
+        model: model_path.clone(),
+        ..Default::default()
+    };
+
+    match Model::from_run_args(&run_args, &model_path) {
+        Ok(model) => {
+            (report.unsupported_ops, report.shape_issues) = model.node_issues();
+            // an unknown op fails the layout, so only lay out once every node has been lowered
+            if report.unsupported_ops.is_empty() {
+                match GraphCircuit::new(model, &run_args).and_then(|circuit| circuit.stats()) {
+                    Ok(stats) => report.resources = Some(stats),
+                    Err(e) => report.error = Some(e.into()),
+                }
+            }
+        }
+        Err(e) => report.error = Some(e),
+    }
+    report.supported = report.error.is_none()
+        && report.unsupported_ops.is_empty()
+        && report.shape_issues.is_empty();
+
+    let json = serde_json::to_string_pretty(&report)?;
+    info!("check report: \n {}", json.to_colored_json_auto()?);
+    if let Some(output) = output {
+        crate::stdio::write(&output, json)?;
+    }
+
+    if report.supported {
+        Ok(())
+    } else {
+        Err(format!(
+            "model is not supported: {} unsupported ops, {} shape issues{}",
+            report.unsupported_ops.len(),
+            report.shape_issues.len(),
+            report.error.map(|e| format!(", {}", e)).unwrap_or_default()
+        )
+        .into())
+    }
+}
+
+/// The outcome of `ezkl audit`
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AuditReport {
+    /// the bound assumed on the absolute value of the model's inputs
+    pub input_bound: f64,
+    /// the range of the lookup tables
+    pub lookup_range: (i128, i128),
+    /// the quantized range of every node's values
+    pub ranges: Vec<crate::graph::audit::NodeRange>,
+    /// nodes whose lookups can fall outside of the tables or whose values can overflow
+    pub issues: Vec<NodeIssue>,
+}
+
+/// Bounds the values of every node of a compiled circuit before any keys are generated, so
+/// that overflows show up as a report rather than as failed (or, worse, satisfied) constraints.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn audit(
+    compiled_circuit: PathBuf,
+    input_bound: f64,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let circuit = GraphCircuit::load(compiled_circuit)?;
+    let lookup_range = circuit.settings().run_args.lookup_range;
+    let (ranges, issues) = circuit.model().audit_ranges(lookup_range, input_bound);
+    let report = AuditReport {
+        input_bound,
+        lookup_range,
+        ranges,
+        issues,
+    };
+
+    let json = serde_json::to_string_pretty(&report)?;
+    if let Some(output) = output {
+        crate::stdio::write(&output, &json)?;
+    }
+    for issue in &report.issues {
+        error!("node {} ({}): {}", issue.idx, issue.op, issue.reason);
+    }
+
+    if report.issues.is_empty() {
+        info!(
+            "no node can overflow for inputs within [-{}, {}]",
+            input_bound, input_bound
+        );
+        Ok(())
+    } else {
+        Err(format!(
+            "{} nodes can overflow for inputs within [-{}, {}]",
+            report.issues.len(),
+            input_bound,
+            input_bound
+        )
+        .into())
+    }
+}
+
+/// Asks a question on stdin and returns the (lowercased) answer. The default is returned for an
+/// empty answer, or without asking if stdin isn't an interactive terminal.
+#[cfg(not(target_arch = "wasm32"))]
+fn prompt(question: &str, default: &str) -> Result<String, Box<dyn Error>> {
+    use std::io::IsTerminal;
+    if !std::io::stdin().is_terminal() {
+        info!("{} [{}]: using default", question, default);
+        return Ok(default.to_string());
+    }
+    print!("{} [{}]: ", question, default);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim().to_lowercase();
+    if answer.is_empty() {
+        Ok(default.to_string())
+    } else {
+        Ok(answer)
+    }
+}
+
+/// Settings wizard: estimates the circuit size with default run args, asks a few questions, then
+/// calibrates on the sample input within the limits implied by the answers.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn init(
+    model_path: PathBuf,
+    data: PathBuf,
+    settings_path: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    info!("loading model at {}", model_path.display());
+    gen_circuit_settings(
+        model_path.clone(),
+        settings_path.clone(),
+        RunArgs::default(),
+        None,
+        None,
+    )?;
+    let mut settings = GraphSettings::load(&settings_path)?;
+    info!(
+        "with default settings the model uses {} rows ({} assignments, {} constants), logrows {}",
+        settings.num_rows,
+        settings.total_assignments,
+        settings.total_const_size,
+        settings.run_args.logrows
+    );
+
+    let chain = loop {
+        match prompt("target chain (evm, none)", "evm")?.as_str() {
+            c @ ("evm" | "none") => break c.to_string(),
+            c => warn!("unknown target chain {}", c),
+        }
+    };
+    let tolerance = loop {
+        match prompt("accuracy tolerance on outputs, as a percentage", "0")?.parse::<f32>() {
+            Ok(t) if t >= 0.0 => break t,
+            _ => warn!("tolerance should be a non-negative number"),
+        }
+    };
+    let hardware = loop {
+        match prompt("proving hardware (browser, laptop, server)", "laptop")?.as_str() {
+            h @ ("browser" | "laptop" | "server") => break h.to_string(),
+            h => warn!("unknown hardware {}", h),
+        }
+    };
+
+    // bigger machines can afford more rows and the higher scales that accuracy calibration picks
+    let max_logrows = match hardware.as_str() {
+        "browser" => 20,
+        "laptop" => 24,
+        _ => crate::graph::MAX_PUBLIC_SRS,
+    };
+    let target = if hardware == "server" {
+        CalibrationTarget::Accuracy
+    } else {
+        // an on-chain verifier has to fit in a contract so columns can't overflow
+        CalibrationTarget::Resources {
+            col_overflow: chain == "none",
+        }
+    };
+
+    settings.run_args.tolerance = tolerance.into();
+    settings.save(&settings_path)?;
+
+    info!(
+        "calibrating for {} with max logrows {}",
+        target.to_string(),
+        max_logrows
+    );
+    calibrate(
+        model_path,
+        data,
+        settings_path.clone(),
+        target,
+        None,
+        Some(max_logrows),
+    )?;
+
+    let settings = GraphSettings::load(&settings_path)?;
+    info!(
+        "wrote recommended settings to {}: input scale {}, param scale {}, logrows {}, lookup range {:?}",
+        settings_path.display(),
+        settings.run_args.input_scale,
+        settings.run_args.param_scale,
+        settings.run_args.logrows,
+        settings.run_args.lookup_range
+    );
+    Ok(())
+}
+
+// not for wasm targets
+#[cfg(not(target_arch = "wasm32"))]
+/// only draw progress when stdout is an interactive terminal, so piped output and logs stay clean
+fn set_progress_draw_target(pb: &ProgressBar) {
+    use std::io::IsTerminal;
+    if std::io::stdout().is_terminal() {
+        pb.set_draw_target(indicatif::ProgressDrawTarget::stdout());
+        pb.enable_steady_tick(Duration::from_millis(200));
+    } else {
+        pb.set_draw_target(indicatif::ProgressDrawTarget::hidden());
+    }
+}
 
 // not for wasm targets
 #[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn init_spinner() -> ProgressBar {
     let pb = indicatif::ProgressBar::new_spinner();
-    pb.set_draw_target(indicatif::ProgressDrawTarget::stdout());
-    pb.enable_steady_tick(Duration::from_millis(200));
+    set_progress_draw_target(&pb);
     pb.set_style(
         ProgressStyle::with_template("[{elapsed_precise}] {spinner:.blue} {msg}")
             .unwrap()
@@ -579,8 +1580,7 @@ pub(crate) fn init_spinner() -> ProgressBar {
 #[cfg(not(target_arch = "wasm32"))]
 pub(crate) fn init_bar(len: u64) -> ProgressBar {
     let pb = ProgressBar::new(len);
-    pb.set_draw_target(indicatif::ProgressDrawTarget::stdout());
-    pb.enable_steady_tick(Duration::from_millis(200));
+    set_progress_draw_target(&pb);
     let sty = ProgressStyle::with_template(
         "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}",
     )
@@ -909,109 +1909,815 @@ pub(crate) fn mock(
 
     let data = GraphWitness::from_path(data_path)?;
 
-    circuit.load_graph_witness(&data)?;
+    mock_circuit(&mut circuit, &data)
+}
 
-    let public_inputs = circuit.prepare_public_inputs(&data)?;
+/// Mock proves a loaded circuit over a witness.
+fn mock_circuit(circuit: &mut GraphCircuit, data: &GraphWitness) -> Result<(), Box<dyn Error>> {
+    circuit.load_graph_witness(data)?;
+
+    let public_inputs = circuit.prepare_public_inputs(data)?;
 
     info!("Mock proof");
 
     let prover = halo2_proofs::dev::MockProver::run(
         circuit.settings().run_args.logrows,
-        &circuit,
+        circuit,
         vec![public_inputs],
     )
     .map_err(Box::<dyn Error>::from)?;
-    prover
-        .verify_par()
-        .map_err(|e| Box::<dyn Error>::from(ExecutionError::VerifyError(e)))?;
+    if let Err(failures) = prover.verify_par() {
+        for description in describe_mock_failures(circuit, &failures)? {
+            error!("{}", description);
+        }
+        return Err(Box::<dyn Error>::from(ExecutionError::VerifyError(
+            failures,
+        )));
+    }
     Ok(())
 }
 
-pub(crate) fn print_proof_hex(proof_path: PathBuf) -> Result<(), Box<dyn Error>> {
-    let proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
-    for instance in proof.instances {
-        println!("{:?}", instance);
+/// Mock proves whenever the compiled circuit or the witness changes. The loaded circuit is kept
+/// across runs and only reloaded when the compiled circuit itself changes.
+fn mock_watch(compiled_circuit_path: PathBuf, data_path: PathBuf) -> Result<(), Box<dyn Error>> {
+    let mut circuit: Option<GraphCircuit> = None;
+    watch_files(
+        &[compiled_circuit_path.clone(), data_path.clone()],
+        |changed| {
+            let mut loaded = match circuit.take() {
+                Some(c) if !changed[0] => c,
+                _ => GraphCircuit::load(compiled_circuit_path.clone())?,
+            };
+            let data = GraphWitness::from_path(data_path.clone())?;
+            let res = mock_circuit(&mut loaded, &data);
+            circuit = Some(loaded);
+            res
+        },
+    )
+}
+
+/// How often watched files are polled for changes
+const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Runs `f` once, then again every time one of `paths` is modified, until the process is
+/// interrupted. `f` is passed a flag per path indicating whether that path changed since the last
+/// run. Errors are logged rather than returned so a broken intermediate state doesn't end the loop.
+fn watch_files(
+    paths: &[PathBuf],
+    mut f: impl FnMut(&[bool]) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let modified = |p: &PathBuf| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    let mut last_seen: Vec<_> = paths.iter().map(modified).collect();
+    let mut changed = vec![true; paths.len()];
+    loop {
+        match f(&changed) {
+            Ok(()) => info!("succeeded"),
+            Err(e) => error!("failed: {}", e),
+        }
+        info!(
+            "watching {} for changes",
+            paths.iter().map(|p| p.display()).join(", ")
+        );
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let seen: Vec<_> = paths.iter().map(modified).collect();
+            changed = seen.iter().zip(&last_seen).map(|(a, b)| a != b).collect();
+            if changed.iter().any(|c| *c) {
+                last_seen = seen;
+                break;
+            }
+        }
     }
-    info!("{}", hex::encode(proof.proof));
-    Ok(())
 }
 
-#[cfg(feature = "render")]
-pub(crate) fn render(model: PathBuf, output: PathBuf, args: RunArgs) -> Result<(), Box<dyn Error>> {
-    let circuit = GraphCircuit::from_run_args(&args, &model)?;
-    info!("Rendering circuit");
+/// Maps [VerifyFailure]s located in the model region back to the nodes laid out at the failing rows.
+/// The model region is folded over blocks of advice columns, so a single offset can match a node in each block.
+fn describe_mock_failures(
+    circuit: &GraphCircuit,
+    failures: &[VerifyFailure],
+) -> Result<Vec<String>, Box<dyn Error>> {
+    use halo2_proofs::dev::FailureLocation;
+
+    let settings = circuit.settings();
+    let spans = circuit
+        .model()
+        .node_spans(&settings.run_args, &circuit.model().graph.input_shapes()?)?;
+    let col_size = settings.available_col_size();
+
+    // the model is laid out in a single region, so the failing nodes are found from the offset
+    // and named in place of the region
+    let describe = |failure: &VerifyFailure, offset: usize| {
+        let nodes = spans
+            .iter()
+            .filter(|span| span.covers_offset(offset, col_size))
+            .map(|span| format!("node {} ({}: {})", span.idx, span.name, span.op))
+            .collect_vec();
+        if nodes.is_empty() {
+            format!("{}", failure)
+        } else {
+            let nodes = nodes.join(" or ");
+            let failure = failure
+                .to_string()
+                .replace("('model')", &format!("('{}')", nodes));
+            format!("{} failed: {}", nodes, failure)
+        }
+    };
 
-    // Create the area we want to draw on.
-    // We could use SVGBackend if we want to render to .svg instead.
-    // for an overview of how to interpret these plots, see https://zcash.github.io/halo2/user/dev-tools.html
-    let root = BitMapBackend::new(&output, (512, 512)).into_drawing_area();
-    root.fill(&TRANSPARENT)?;
-    let root = root.titled("Layout", ("sans-serif", 20))?;
+    Ok(failures
+        .iter()
+        .map(|failure| match failure {
+            VerifyFailure::ConstraintNotSatisfied {
+                location: FailureLocation::InRegion { region, offset },
+                ..
+            }
+            | VerifyFailure::Lookup {
+                location: FailureLocation::InRegion { region, offset },
+                ..
+            }
+            | VerifyFailure::Permutation {
+                location: FailureLocation::InRegion { region, offset },
+                ..
+            } if region.to_string().contains("'model'") => describe(failure, *offset),
+            _ => format!("{}", failure),
+        })
+        .collect())
+}
 
-    halo2_proofs::dev::CircuitLayout::default()
-        // We hide labels, else most circuits become impossible to decipher because of overlaid text
-        .show_labels(false)
-        .render(circuit.settings().run_args.logrows, &circuit, &root)?;
+pub(crate) fn stats(compiled_circuit_path: PathBuf) -> Result<(), Box<dyn Error>> {
+    let circuit = GraphCircuit::load(compiled_circuit_path)?;
+    let stats = circuit.stats()?;
+    info!(
+        "circuit stats: \n {}",
+        stats.as_json()?.to_colored_json_auto()?
+    );
     Ok(())
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-pub(crate) fn create_evm_verifier(
-    vk_path: PathBuf,
-    srs_path: Option<PathBuf>,
-    settings_path: PathBuf,
-    sol_code_path: PathBuf,
-    abi_path: PathBuf,
+pub(crate) async fn gen_test_vector(
+    compiled_circuit_path: PathBuf,
+    data_path: PathBuf,
+    output: PathBuf,
 ) -> Result<(), Box<dyn Error>> {
-    check_solc_requirement();
-    let circuit_settings = GraphSettings::load(&settings_path)?;
-    let params = load_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
+    let mut circuit = GraphCircuit::load(compiled_circuit_path)?;
+    let data = GraphData::from_path(data_path)?;
 
-    let num_instance = circuit_settings.total_instances();
-    let num_instance: usize = num_instance.iter().sum::<usize>();
+    let mut input = circuit.load_graph_input(&data).await?;
+    let witness = circuit.forward_with_ephemeral_keys(&mut input, None, None)?;
+    let instances = circuit.prepare_public_inputs(&witness)?;
 
-    let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, circuit_settings)?;
-    trace!("params computed");
+    let settings_hash = circuit.settings().hash()?;
 
-    let generator = halo2_solidity_verifier::SolidityGenerator::new(
-        &params,
-        &vk,
-        halo2_solidity_verifier::BatchOpenScheme::Bdfg21,
-        num_instance,
-    );
-    let verifier_solidity = generator.render()?;
+    let test_vector = TestVector {
+        version: VERSION.to_string(),
+        settings_hash,
+        input: data,
+        outputs: witness.outputs,
+        instances,
+    };
+    test_vector.save(output)?;
+    Ok(())
+}
 
-    File::create(sol_code_path.clone())?.write_all(verifier_solidity.as_bytes())?;
+/// A cell where the quantized forward pass and the circuit layout disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OutputMismatch {
+    output: usize,
+    index: usize,
+    forward: i128,
+    circuit: i128,
+}
 
-    // fetch abi of the contract
-    let (abi, _, _) = get_contract_artifacts(sol_code_path, "Halo2Verifier", 0)?;
-    // save abi to file
-    serde_json::to_writer(std::fs::File::create(abi_path)?, &abi)?;
+impl std::fmt::Display for OutputMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "output {} at flat index {}: forward={} circuit={}",
+            self.output, self.index, self.forward, self.circuit
+        )
+    }
+}
 
-    Ok(())
+/// Diffs the outputs of [GraphCircuit::forward] against those computed by [Model::layout_values]
+pub(crate) fn diff_outputs(forward: &[Vec<Fr>], circuit: &[Tensor<Fr>]) -> Vec<OutputMismatch> {
+    let mut mismatches = vec![];
+    for (output, (f, c)) in forward.iter().zip(circuit).enumerate() {
+        if f.len() != c.len() {
+            warn!(
+                "output {} has {} elements in the forward pass but {} in the circuit",
+                output,
+                f.len(),
+                c.len()
+            );
+        }
+        for (index, (f, c)) in f.iter().zip(c.iter()).enumerate() {
+            if f != c {
+                mismatches.push(OutputMismatch {
+                    output,
+                    index,
+                    forward: felt_to_i128(*f),
+                    circuit: felt_to_i128(*c),
+                });
+            }
+        }
+    }
+    mismatches
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-pub(crate) fn create_evm_data_attestation(
-    vk_path: PathBuf,
-    srs_path: Option<PathBuf>,
-    settings_path: PathBuf,
-    sol_code_path: PathBuf,
-    abi_path: PathBuf,
-    input: PathBuf,
+pub(crate) async fn compare(
+    compiled_circuit_path: PathBuf,
+    data: PathBuf,
 ) -> Result<(), Box<dyn Error>> {
-    use crate::graph::{DataSource, VarVisibility};
-    check_solc_requirement();
+    let mut circuit = GraphCircuit::load(compiled_circuit_path)?;
+    let data = GraphData::from_path(data)?;
 
-    let settings = GraphSettings::load(&settings_path)?;
-    let params = load_params_cmd(srs_path, settings.run_args.logrows)?;
+    let mut input = circuit.load_graph_input(&data).await?;
+    let original_input = input.clone();
 
-    let visibility = VarVisibility::from_args(&settings.run_args)?;
+    let witness = circuit.forward_with_ephemeral_keys(&mut input, None, None)?;
+    let layout_outputs = circuit
+        .model()
+        .layout_values(&circuit.settings().run_args, &original_input)?;
 
-    let num_instance = settings.total_instances();
-    let num_instance: usize = num_instance.iter().sum::<usize>();
+    let mismatches = diff_outputs(&witness.outputs, &layout_outputs);
+    for mismatch in &mismatches {
+        error!("{}", mismatch);
+    }
 
-    let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, settings.clone())?;
+    circuit.load_graph_witness(&witness)?;
+    let public_inputs = circuit.prepare_public_inputs(&witness)?;
+    let prover = halo2_proofs::dev::MockProver::run(
+        circuit.settings().run_args.logrows,
+        &circuit,
+        vec![public_inputs],
+    )
+    .map_err(Box::<dyn Error>::from)?;
+    let mock_result = prover.verify_par();
+    info!("mock prover verified: {}", mock_result.is_ok());
+
+    if !mismatches.is_empty() {
+        return Err(Box::new(ExecutionError::OutputMismatch(mismatches.len())));
+    }
+    if let Err(failures) = mock_result {
+        for description in describe_mock_failures(&circuit, &failures)? {
+            error!("{}", description);
+        }
+        return Err(Box::new(ExecutionError::VerifyError(failures)));
+    }
+
+    info!("forward pass and circuit outputs match");
+    Ok(())
+}
+
+/// The error of one quantized output against the float output of onnxruntime
+#[cfg(feature = "ort-compare")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, tabled::Tabled)]
+pub struct OutputAccuracy {
+    /// the index of the output
+    pub output: usize,
+    /// the number of elements in the output
+    pub len: usize,
+    /// the mean absolute error
+    pub mean_abs_error: f64,
+    /// the largest absolute error
+    pub max_abs_error: f64,
+    /// the mean error relative to the onnxruntime output, skipping zero elements
+    pub mean_rel_error: f64,
+}
+
+/// Runs the model through onnxruntime on float inputs
+#[cfg(feature = "ort-compare")]
+fn onnxruntime_forward(
+    model_path: &Path,
+    inputs: &[Vec<f32>],
+    input_shapes: &[Vec<usize>],
+) -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
+    use ndarray::{CowArray, IxDyn};
+    use ort::{Environment, SessionBuilder, Value};
+
+    let environment = Environment::builder().with_name("ezkl").build()?.into_arc();
+    let session = SessionBuilder::new(&environment)?.with_model_from_file(model_path)?;
+
+    let arrays = inputs
+        .iter()
+        .zip(input_shapes)
+        .map(|(input, shape)| {
+            Ok(CowArray::from(ndarray::Array::from_shape_vec(
+                IxDyn(shape),
+                input.clone(),
+            )?))
+        })
+        .collect::<Result<Vec<CowArray<f32, IxDyn>>, Box<dyn Error>>>()?;
+    let values = arrays
+        .iter()
+        .map(|a| Value::from_array(session.allocator(), a))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    session
+        .run(values)?
+        .iter()
+        .map(|output| {
+            let output = output.try_extract::<f32>()?;
+            let output = output.view().iter().map(|v| *v as f64).collect();
+            Ok(output)
+        })
+        .collect()
+}
+
+/// Compares the dequantized outputs of the forward pass against onnxruntime's float outputs, to
+/// catch lowering bugs that comparing against tract alone would miss.
+#[cfg(feature = "ort-compare")]
+pub(crate) async fn compare_onnxruntime(
+    model_path: PathBuf,
+    compiled_circuit_path: PathBuf,
+    data: PathBuf,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let mut circuit = GraphCircuit::load(compiled_circuit_path)?;
+    let data = GraphData::from_path(data)?;
+
+    let float_inputs = match &data.input_data {
+        crate::graph::input::DataSource::File(inputs) => inputs
+            .iter()
+            .map(|input| input.iter().map(|v| v.to_float() as f32).collect())
+            .collect::<Vec<Vec<f32>>>(),
+        _ => return Err("onnxruntime comparison needs file sourced input data".into()),
+    };
+    let input_shapes = circuit.model().graph.input_shapes()?;
+    let ort_outputs = onnxruntime_forward(&model_path, &float_inputs, &input_shapes)?;
+
+    let mut input = circuit.load_graph_input(&data).await?;
+    let witness = circuit.forward_with_ephemeral_keys(&mut input, None, None)?;
+    let output_scales = circuit.settings().model_output_scales.clone();
+
+    if ort_outputs.len() != witness.outputs.len() {
+        return Err(format!(
+            "onnxruntime returned {} outputs but the circuit has {}",
+            ort_outputs.len(),
+            witness.outputs.len()
+        )
+        .into());
+    }
+
+    let report = witness
+        .outputs
+        .iter()
+        .zip(output_scales)
+        .zip(ort_outputs)
+        .enumerate()
+        .map(|(output, ((quantized, scale), expected))| {
+            let mult = crate::graph::scale_to_multiplier(scale);
+            let errors = quantized
+                .iter()
+                .zip(&expected)
+                .map(|(q, e)| (felt_to_i128(*q) as f64 / mult - e, *e))
+                .collect::<Vec<_>>();
+            let len = errors.len();
+            let abs_errors = errors.iter().map(|(d, _)| d.abs()).collect::<Vec<_>>();
+            let rel_errors = errors
+                .iter()
+                .filter(|(_, e)| *e != 0.0)
+                .map(|(d, e)| (d / e).abs())
+                .collect::<Vec<_>>();
+            OutputAccuracy {
+                output,
+                len,
+                mean_abs_error: abs_errors.iter().sum::<f64>() / len.max(1) as f64,
+                max_abs_error: abs_errors.iter().cloned().fold(0.0, f64::max),
+                mean_rel_error: rel_errors.iter().sum::<f64>() / rel_errors.len().max(1) as f64,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut table = tabled::Table::new(&report);
+    table.with(tabled::settings::Style::modern());
+    info!("quantized outputs vs onnxruntime: \n {}", table);
+    if let Some(output) = output {
+        crate::stdio::write(&output, serde_json::to_string_pretty(&report)?)?;
+    }
+    Ok(())
+}
+
+/// How closely one quantized output tracks the float model over a set of samples
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, tabled::Tabled)]
+pub struct QuantizationAccuracy {
+    /// the index of the output
+    pub output: usize,
+    /// the number of samples compared
+    pub samples: usize,
+    /// the mean squared error over every element of every sample
+    pub mse: f64,
+    /// the largest absolute error
+    pub max_abs_error: f64,
+    /// the fraction of rows (along the last axis) whose argmax matches the float model's
+    pub top1_agreement: f64,
+}
+
+/// Compares the dequantized outputs of the forward pass against the float model run through
+/// tract, sample by sample, so settings can be validated before any keys are generated.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn accuracy(
+    model_path: PathBuf,
+    compiled_circuit_path: PathBuf,
+    data: PathBuf,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    let mut circuit = GraphCircuit::load(compiled_circuit_path)?;
+    let input_shapes = circuit.model().graph.input_shapes()?;
+    let output_shapes = circuit.model().graph.output_shapes()?;
+    let output_scales = circuit.settings().model_output_scales.clone();
+    let samples = GraphData::from_path(data)?.split_into_batches(input_shapes.clone())?;
+
+    let float_inputs = samples
+        .iter()
+        .map(|sample| match &sample.input_data {
+            crate::graph::input::DataSource::File(inputs) => Ok(inputs
+                .iter()
+                .map(|input| input.iter().map(|v| v.to_float() as f32).collect())
+                .collect::<Vec<Vec<f32>>>()),
+            _ => Err("accuracy needs file sourced input data".into()),
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    let float_outputs = Model::run_float_onnx(&model_path, &input_shapes, &float_inputs)?;
+
+    // per output: the sum of squared errors, the largest error, the number of elements, the
+    // number of rows whose argmax agree and the number of rows
+    let mut stats = vec![(0.0, 0.0, 0usize, 0usize, 0usize); output_shapes.len()];
+    for (sample, expected) in samples.iter().zip(float_outputs) {
+        let mut input = circuit.load_graph_input(sample).await?;
+        let witness = circuit.forward_with_ephemeral_keys(&mut input, None, None)?;
+        if expected.len() != witness.outputs.len() {
+            return Err(format!(
+                "the float model returned {} outputs but the circuit has {}",
+                expected.len(),
+                witness.outputs.len()
+            )
+            .into());
+        }
+
+        for (i, (quantized, expected)) in witness.outputs.iter().zip(expected).enumerate() {
+            let mult = crate::graph::scale_to_multiplier(output_scales[i]);
+            let actual = quantized
+                .iter()
+                .map(|q| felt_to_i128(*q) as f64 / mult)
+                .collect::<Vec<_>>();
+            let (sq_err, max_err, len, agree, rows) = &mut stats[i];
+            for (a, e) in actual.iter().zip(&expected) {
+                *sq_err += (a - e) * (a - e);
+                *max_err = f64::max(*max_err, (a - e).abs());
+                *len += 1;
+            }
+            let row = output_shapes[i].last().copied().unwrap_or(1).max(1);
+            for (a, e) in actual.chunks(row).zip(expected.chunks(row)) {
+                let argmax = |v: &[f64]| v.iter().position_max_by(|x, y| x.total_cmp(y));
+                if argmax(a) == argmax(e) {
+                    *agree += 1;
+                }
+                *rows += 1;
+            }
+        }
+    }
+
+    let report = stats
+        .into_iter()
+        .enumerate()
+        .map(
+            |(output, (sq_err, max_abs_error, len, agree, rows))| QuantizationAccuracy {
+                output,
+                samples: samples.len(),
+                mse: sq_err / len.max(1) as f64,
+                max_abs_error,
+                top1_agreement: agree as f64 / rows.max(1) as f64,
+            },
+        )
+        .collect::<Vec<_>>();
+
+    let mut table = tabled::Table::new(&report);
+    table.with(tabled::settings::Style::modern());
+    info!("quantized outputs vs the float model: \n {}", table);
+    if let Some(output) = output {
+        crate::stdio::write(&output, serde_json::to_string_pretty(&report)?)?;
+    }
+    Ok(())
+}
+
+/// Writes a proof in the layout snarkjs based frontends read:
+///
+/// * `public.json` is, as in snarkjs, the public signals as decimal strings: the instances of
+///   every instance column, flattened in column order.
+/// * `proof.json` keeps the snarkjs `protocol` and `curve` fields (`"halo2"` and `"bn128"`), with
+///   the raw proof bytes as `proof`, the abi encoded call to the generated evm verifier as
+///   `calldata` and the transcript the proof was made with as `transcript`. The halo2 proof has
+///   no `pi_a`/`pi_b`/`pi_c` points, so a frontend needs to send `calldata` to the verifier instead.
+pub(crate) fn export_snarkjs(
+    proof_path: PathBuf,
+    proof_json: PathBuf,
+    public_json: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    use halo2curves::ff::PrimeField;
+
+    let snark = Snark::<Fr, G1Affine>::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
+    let instances = snark
+        .instances
+        .iter()
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let public = instances
+        .iter()
+        .map(|i| ethers::types::U256::from_little_endian(i.to_repr().as_ref()).to_string())
+        .collect::<Vec<_>>();
+    let calldata = halo2_solidity_verifier::encode_calldata(None, &snark.proof, &instances);
+    let proof = serde_json::json!({
+        "protocol": "halo2",
+        "curve": "bn128",
+        "proof": format!("0x{}", hex::encode(&snark.proof)),
+        "calldata": format!("0x{}", hex::encode(calldata)),
+        "transcript": snark.transcript_type,
+    });
+
+    crate::stdio::write(&proof_json, serde_json::to_string_pretty(&proof)?)?;
+    crate::stdio::write(&public_json, serde_json::to_string_pretty(&public)?)?;
+    info!(
+        "wrote {} and {} ({} public signals)",
+        proof_json.display(),
+        public_json.display(),
+        public.len()
+    );
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn split_model(
+    model_path: PathBuf,
+    cut_after: Vec<String>,
+    num_pieces: Option<usize>,
+    output_dir: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    let pieces =
+        crate::graph::split::split_model(&model_path, &cut_after, num_pieces, &output_dir)?;
+    log_summary_table(
+        ["piece", "nodes", "inputs", "outputs"],
+        pieces.iter().map(|p| {
+            [
+                p.model.display().to_string(),
+                p.num_nodes.to_string(),
+                p.inputs.join(", "),
+                p.outputs.join(", "),
+            ]
+        }),
+    );
+    Ok(())
+}
+
+pub(crate) fn link_proofs(
+    proof_paths: Vec<PathBuf>,
+    settings_paths: Vec<PathBuf>,
+    vk_paths: Vec<PathBuf>,
+    srs_path: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    if proof_paths.len() < 2 {
+        return Err("at least two proofs are needed to link".into());
+    }
+    if proof_paths.len() != settings_paths.len() || proof_paths.len() != vk_paths.len() {
+        return Err(format!(
+            "got {} proofs, {} settings and {} vks, each proof needs the settings and vk it was generated with",
+            proof_paths.len(),
+            settings_paths.len(),
+            vk_paths.len()
+        )
+        .into());
+    }
+
+    let mut links = vec![];
+    for ((proof_path, settings_path), vk_path) in
+        proof_paths.iter().zip(&settings_paths).zip(&vk_paths)
+    {
+        // the instances of a proof only mean something once it verifies
+        verify(
+            proof_path.clone(),
+            settings_path.clone(),
+            vk_path.clone(),
+            srs_path.clone(),
+            ProofEncoding::Json,
+            None,
+            None,
+        )
+        .map_err(|e| format!("{} doesn't verify: {}", proof_path.display(), e))?;
+
+        let proof = Snark::load::<KZGCommitmentScheme<Bn256>>(proof_path)?;
+        let settings = GraphSettings::load(settings_path)?;
+        let instances = proof.instances.concat();
+        links.push((
+            settings.hashed_inputs(&instances).map(|h| h.to_vec()),
+            settings.hashed_outputs(&instances).map(|h| h.to_vec()),
+        ));
+    }
+
+    for (i, ((_, outputs), (inputs, _))) in links.iter().tuple_windows().enumerate() {
+        let outputs = outputs.as_ref().ok_or_else(|| {
+            format!(
+                "{} doesn't expose a hash of its outputs, prove it with a public hashed output visibility",
+                proof_paths[i].display()
+            )
+        })?;
+        let inputs = inputs.as_ref().ok_or_else(|| {
+            format!(
+                "{} doesn't expose a hash of its inputs, prove it with a public hashed input visibility",
+                proof_paths[i + 1].display()
+            )
+        })?;
+        if outputs != inputs {
+            return Err(format!(
+                "the outputs of {} aren't the inputs of {}",
+                proof_paths[i].display(),
+                proof_paths[i + 1].display()
+            )
+            .into());
+        }
+    }
+
+    info!("all {} proofs link up", proof_paths.len());
+    Ok(())
+}
+
+pub(crate) fn print_proof_hex(proof_path: PathBuf) -> Result<(), Box<dyn Error>> {
+    let proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
+    for instance in proof.instances {
+        println!("{:?}", instance);
+    }
+    info!("{}", hex::encode(proof.proof));
+    Ok(())
+}
+
+#[cfg(feature = "render")]
+pub(crate) fn render(
+    model: PathBuf,
+    output: PathBuf,
+    width: u32,
+    height: u32,
+    args: RunArgs,
+) -> Result<(), Box<dyn Error>> {
+    let circuit = GraphCircuit::from_run_args(&args, &model)?;
+    info!("Rendering circuit");
+
+    let spans = circuit.model().node_spans(
+        &circuit.settings().run_args,
+        &circuit.model().graph.input_shapes()?,
+    )?;
+
+    // for an overview of how to interpret these plots, see https://zcash.github.io/halo2/user/dev-tools.html
+    match output.extension().and_then(|e| e.to_str()) {
+        Some("svg") => {
+            let root = SVGBackend::new(&output, (width, height)).into_drawing_area();
+            render_annotated(&circuit, &spans, root)
+        }
+        _ => {
+            let root = BitMapBackend::new(&output, (width, height)).into_drawing_area();
+            render_annotated(&circuit, &spans, root)
+        }
+    }
+}
+
+/// Renders the circuit layout next to a strip labelling the rows each node is laid out in.
+#[cfg(feature = "render")]
+fn render_annotated<DB: DrawingBackend>(
+    circuit: &GraphCircuit,
+    spans: &[crate::graph::NodeSpan],
+    root: DrawingArea<DB, plotters::coord::Shift>,
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let root = root.titled("Layout", ("sans-serif", 20))?;
+    let (width, _) = root.dim_in_pixel();
+    let (labels, layout) = root.split_horizontally(width / 4);
+
+    let logrows = circuit.settings().run_args.logrows;
+    halo2_proofs::dev::CircuitLayout::default()
+        // We hide the halo2 labels, else most circuits become impossible to decipher because of overlaid text
+        .show_labels(false)
+        .render(logrows, circuit, &layout)?;
+
+    // the model region is folded over blocks of advice columns, each `col_size` rows tall
+    let col_size = circuit.settings().available_col_size();
+    let total_rows = 1usize << logrows;
+    let (_, label_height) = labels.dim_in_pixel();
+    let font = ("sans-serif", 10).into_font();
+    let mut last_y: Option<i32> = None;
+    for span in spans.iter().filter(|s| s.end_row > s.start_row) {
+        let block = span.start_row / col_size;
+        let row = span.start_row % col_size;
+        let y = (row * label_height as usize / total_rows) as i32;
+        // skip labels that would be drawn over the previous one
+        if matches!(last_y, Some(last) if y - last < 10) {
+            continue;
+        }
+        labels.draw(&Text::new(
+            format!("{} {} {} (block {})", span.idx, span.name, span.op, block),
+            (2, y),
+            font.clone(),
+        ))?;
+        last_y = Some(y);
+    }
+
+    root.present()?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn create_evm_verifier(
+    vk_path: PathBuf,
+    srs_path: Option<PathBuf>,
+    settings_path: PathBuf,
+    sol_code_path: PathBuf,
+    abi_path: PathBuf,
+    vk_sol_code_path: Option<PathBuf>,
+    yul: bool,
+) -> Result<(), Box<dyn Error>> {
+    check_solc_requirement();
+    if yul && vk_sol_code_path.is_some() {
+        return Err(
+            "yul verifiers inline their verifying key, it can't be rendered separately".into(),
+        );
+    }
+    let circuit_settings = GraphSettings::load(&settings_path)?;
+    if circuit_settings.run_args.commitment != Commitments::KZG {
+        return Err("evm verifiers can only be generated for kzg commitments".into());
+    }
+    let params = load_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
+
+    let num_instance = circuit_settings.total_instances();
+    let num_instance: usize = num_instance.iter().sum::<usize>();
+
+    let batch_open_scheme = match circuit_settings.run_args.multiopen {
+        MultiOpen::Shplonk => halo2_solidity_verifier::BatchOpenScheme::Bdfg21,
+        MultiOpen::Gwc => halo2_solidity_verifier::BatchOpenScheme::Gwc19,
+    };
+    if yul && circuit_settings.run_args.multiopen != MultiOpen::Shplonk {
+        return Err("yul verifiers only support the shplonk multiopen scheme".into());
+    }
+
+    let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, circuit_settings)?;
+    trace!("params computed");
+
+    if yul {
+        let yul_code: YulCode = gen_evm_verifier(&params, &vk, num_instance)?;
+        let yul_code_path = sol_code_path.with_extension("yul");
+        File::create(&yul_code_path)?.write_all(yul_code.as_bytes())?;
+        info!("yul verifier written to {}", yul_code_path.display());
+        return Ok(());
+    }
+
+    let generator = halo2_solidity_verifier::SolidityGenerator::new(
+        &params,
+        &vk,
+        batch_open_scheme,
+        num_instance,
+    );
+    let verifier_solidity = match vk_sol_code_path {
+        Some(vk_sol_code_path) => {
+            let (verifier_solidity, vk_solidity) = generator.render_separately()?;
+            File::create(vk_sol_code_path)?.write_all(vk_solidity.as_bytes())?;
+            verifier_solidity
+        }
+        None => generator.render()?,
+    };
+
+    File::create(sol_code_path.clone())?.write_all(verifier_solidity.as_bytes())?;
+
+    // fetch abi of the contract
+    let (abi, _, _) = get_contract_artifacts(sol_code_path, "Halo2Verifier", 0)?;
+    // save abi to file
+    serde_json::to_writer(std::fs::File::create(abi_path)?, &abi)?;
+
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn create_evm_data_attestation(
+    vk_path: PathBuf,
+    srs_path: Option<PathBuf>,
+    settings_path: PathBuf,
+    sol_code_path: PathBuf,
+    abi_path: PathBuf,
+    input: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    use crate::graph::{DataSource, VarVisibility};
+    check_solc_requirement();
+
+    let settings = GraphSettings::load(&settings_path)?;
+    let params = load_params_cmd(srs_path, settings.run_args.logrows)?;
+
+    let visibility = VarVisibility::from_args(&settings.run_args)?;
+
+    let num_instance = settings.total_instances();
+    let num_instance: usize = num_instance.iter().sum::<usize>();
+
+    let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, settings.clone())?;
     trace!("params computed");
 
     let yul_code: YulCode = gen_evm_verifier(&params, &vk, num_instance)?;
@@ -1115,17 +2821,130 @@ pub(crate) async fn deploy_evm(
     Ok(())
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn deploy_evm_vk(
+    sol_code_path: PathBuf,
+    rpc_url: Option<String>,
+    addr_path: PathBuf,
+    runs: usize,
+    private_key: Option<String>,
+) -> Result<(), Box<dyn Error>> {
+    check_solc_requirement();
+    let contract_address = deploy_vk_via_solidity(
+        sol_code_path,
+        rpc_url.as_deref(),
+        runs,
+        private_key.as_deref(),
+    )
+    .await?;
+
+    info!(
+        "Verifying key contract deployed at: {:#?}",
+        contract_address
+    );
+
+    let mut f = File::create(addr_path)?;
+    write!(f, "{:#?}", contract_address)?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn verify_evm_onchain(
+    proof_path: PathBuf,
+    addr_verifier: H160,
+    rpc_url: String,
+    private_key: String,
+    addr_vk: Option<H160>,
+    yul: bool,
+) -> Result<(), Box<dyn Error>> {
+    let proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
+    if proof.transcript_type != TranscriptType::EVM {
+        return Err(format!(
+            "the evm verifier needs a proof with the evm transcript, this one has the {} transcript",
+            proof.transcript_type
+        )
+        .into());
+    }
+
+    let receipt = crate::eth::submit_proof_via_solidity(
+        proof,
+        addr_verifier,
+        addr_vk,
+        yul,
+        &rpc_url,
+        &private_key,
+    )
+    .await?;
+
+    info!(
+        "Proof verified on-chain in transaction {:#?} (block {:?}, {:?} gas)",
+        receipt.transaction_hash,
+        receipt.block_number.unwrap_or_default(),
+        receipt.gas_used.unwrap_or_default()
+    );
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn estimate_evm_gas(
+    proof_path: PathBuf,
+    sol_code_path: PathBuf,
+    vk_sol_code_path: Option<PathBuf>,
+    runs: usize,
+    output: PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    check_solc_requirement();
+
+    let proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
+    if proof.transcript_type != TranscriptType::EVM {
+        return Err(format!(
+            "the evm verifier needs a proof with the evm transcript, this one has the {} transcript",
+            proof.transcript_type
+        )
+        .into());
+    }
+
+    let report = crate::eth::estimate_verify_gas(proof, sol_code_path, vk_sol_code_path, runs)?;
+
+    info!(
+        "verifier: {} bytes, {} gas to deploy",
+        report.verifier_size, report.verifier_deploy_gas
+    );
+    if let (Some(vk_size), Some(vk_deploy_gas)) = (report.vk_size, report.vk_deploy_gas) {
+        info!(
+            "verifying key: {} bytes, {} gas to deploy",
+            vk_size, vk_deploy_gas
+        );
+    }
+    info!("verifyProof: {} gas", report.verify_gas);
+
+    serde_json::to_writer_pretty(std::fs::File::create(output)?, &report)?;
+    Ok(())
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub(crate) async fn verify_evm(
     proof_path: PathBuf,
     addr_verifier: H160,
     rpc_url: Option<String>,
     addr_da: Option<H160>,
+    addr_vk: Option<H160>,
+    yul: bool,
 ) -> Result<(), Box<dyn Error>> {
     use crate::eth::verify_proof_with_data_attestation;
     check_solc_requirement();
+    if addr_da.is_some() && (addr_vk.is_some() || yul) {
+        return Err("data attestation verifiers are solidity contracts that embed their verifying key, drop addr_vk and yul".into());
+    }
 
     let proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
+    if proof.transcript_type != TranscriptType::EVM {
+        return Err(format!(
+            "the evm verifier needs a proof with the evm transcript, this one has the {} transcript",
+            proof.transcript_type
+        )
+        .into());
+    }
 
     let result = if let Some(addr_da) = addr_da {
         verify_proof_with_data_attestation(
@@ -1136,7 +2955,14 @@ pub(crate) async fn verify_evm(
         )
         .await?
     } else {
-        verify_proof_via_solidity(proof.clone(), addr_verifier, rpc_url.as_deref()).await?
+        verify_proof_via_solidity(
+            proof.clone(),
+            addr_verifier,
+            addr_vk,
+            yul,
+            rpc_url.as_deref(),
+        )
+        .await?
     };
 
     info!("Solidity verification result: {}", result);
@@ -1212,32 +3038,177 @@ pub(crate) fn compile_circuit(
     settings_path: PathBuf,
 ) -> Result<(), Box<dyn Error>> {
     let settings = GraphSettings::load(&settings_path)?;
-    let circuit = GraphCircuit::from_settings(&settings, &model_path, CheckMode::UNSAFE)?;
+    let mut circuit = GraphCircuit::from_settings(&settings, &model_path, CheckMode::UNSAFE)?;
+    circuit.store_key_hash()?;
     circuit.save(compiled_circuit)?;
     Ok(())
 }
 
+/// The file recording the [GraphCircuit::key_hash] of the circuit the key at `key_path` was
+/// generated for.
+fn key_hash_path(key_path: &Path) -> PathBuf {
+    let mut path = key_path.as_os_str().to_owned();
+    path.push(".hash");
+    path.into()
+}
+
+fn key_hash_matches(key_path: &Path, key_hash: &str) -> bool {
+    std::fs::read_to_string(key_hash_path(key_path)).map_or(false, |h| h.trim() == key_hash)
+}
+
+/// Errors if the key at `key_path` was generated for a different model or settings. Keys written
+/// before their hash was recorded are assumed to match.
+fn check_key_hash(key_path: &Path, circuit: &GraphCircuit) -> Result<(), Box<dyn Error>> {
+    if key_hash_path(key_path).exists() && !key_hash_matches(key_path, &circuit.key_hash()?) {
+        return Err(format!(
+            "the key at {} was generated for a different model, settings or circuit layout, re-run setup",
+            key_path.display()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Set to prove with the key cache's proving key for the circuit when there is no proving key at
+/// the given path
+pub const EZKL_USE_CACHED_PK: &str = "EZKL_USE_CACHED_PK";
+
+/// The directory of the key cache holding the keys generated for the circuit with `key_hash`
+/// ([GraphCircuit::key_hash]) against the srs identified by `srs_id`.
+fn key_cache_dir(key_hash: &str, srs_id: &str) -> PathBuf {
+    let id = hex::encode(ethers::utils::keccak256(format!("{}{}", key_hash, srs_id)));
+    PathBuf::from(format!("{}/{}", *EZKL_KEY_CACHE_PATH, id))
+}
+
+/// Identifies a kzg srs by its toxic waste commitment, so keys generated against a different
+/// srs for the same circuit aren't reused. IPA srs are determined by their size alone, which the
+/// key hash covers.
+fn kzg_srs_id(params: &ParamsKZG<Bn256>) -> String {
+    format!("{:?}", params.s_g2())
+}
+
+/// Copies the keys out of `cache_dir` to `pk_path` and `vk_path`. Returns false if there is no
+/// cache or the keys aren't in it.
+fn restore_cached_keys(
+    cache_dir: Option<&Path>,
+    pk_path: &Path,
+    vk_path: &Path,
+) -> Result<bool, Box<dyn Error>> {
+    let Some(cache_dir) = cache_dir else {
+        return Ok(false);
+    };
+    let (cached_pk, cached_vk) = (cache_dir.join("pk.key"), cache_dir.join("vk.key"));
+    if !cached_pk.exists() || !cached_vk.exists() {
+        return Ok(false);
+    }
+    info!("reusing the cached keys in {}", cache_dir.display());
+    // copies rather than links, as rewriting the keys in place would corrupt the cache
+    std::fs::copy(cached_pk, pk_path)?;
+    std::fs::copy(cached_vk, vk_path)?;
+    Ok(true)
+}
+
+/// Copies freshly generated keys into `cache_dir`, if there is one
+fn store_cached_keys(
+    cache_dir: Option<&Path>,
+    pk_path: &Path,
+    vk_path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(cache_dir) = cache_dir {
+        std::fs::create_dir_all(cache_dir)?;
+        std::fs::copy(pk_path, cache_dir.join("pk.key"))?;
+        std::fs::copy(vk_path, cache_dir.join("vk.key"))?;
+        debug!("cached the keys in {}", cache_dir.display());
+    }
+    Ok(())
+}
+
+/// The cached proving key for `circuit` if there's none at `pk_path` and [EZKL_USE_CACHED_PK] is
+/// set, otherwise `pk_path`
+fn pk_path_or_cached(pk_path: PathBuf, circuit: &GraphCircuit, srs_id: &str) -> PathBuf {
+    if pk_path.exists() || std::env::var(EZKL_USE_CACHED_PK).is_err() {
+        return pk_path;
+    }
+    match circuit.key_hash() {
+        Ok(key_hash) => {
+            let cached = key_cache_dir(&key_hash, srs_id).join("pk.key");
+            if cached.exists() {
+                warn!(
+                    "there is no proving key at {}, proving with the cached key at {} instead as {} is set",
+                    pk_path.display(),
+                    cached.display(),
+                    EZKL_USE_CACHED_PK
+                );
+                return cached;
+            }
+            pk_path
+        }
+        Err(_) => pk_path,
+    }
+}
+
 pub(crate) fn setup(
     compiled_circuit: PathBuf,
     srs_path: Option<PathBuf>,
     vk_path: PathBuf,
     pk_path: PathBuf,
     witness: Option<PathBuf>,
+    reuse_keys: bool,
+    key_cache: bool,
 ) -> Result<(), Box<dyn Error>> {
     // these aren't real values so the sanity checks are mostly meaningless
     let mut circuit = GraphCircuit::load(compiled_circuit)?;
+    let key_hash = circuit.key_hash()?;
+    // a witness overrides fixed values so the keys can't be matched on the model alone
+    if reuse_keys
+        && witness.is_none()
+        && key_hash_matches(&pk_path, &key_hash)
+        && key_hash_matches(&vk_path, &key_hash)
+    {
+        info!(
+            "reusing the keys at {} and {}",
+            pk_path.display(),
+            vk_path.display()
+        );
+        return Ok(());
+    }
+    // keys generated against a witness's fixed values can't be identified by the model alone
+    let use_cache = key_cache && witness.is_none();
     if let Some(witness) = witness {
         let data = GraphWitness::from_path(witness)?;
         circuit.load_graph_witness(&data)?;
     }
 
-    let params = load_params_cmd(srs_path, circuit.settings().run_args.logrows)?;
-
-    let pk = create_keys::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(&circuit, &params)
-        .map_err(Box::<dyn Error>::from)?;
-
-    save_vk::<KZGCommitmentScheme<Bn256>>(&vk_path, pk.get_vk())?;
-    save_pk::<KZGCommitmentScheme<Bn256>>(&pk_path, &pk)?;
+    let logrows = circuit.settings().run_args.logrows;
+    match circuit.settings().run_args.commitment {
+        Commitments::KZG => {
+            let params = load_params_cmd(srs_path, logrows)?;
+            let cache_dir = use_cache.then(|| key_cache_dir(&key_hash, &kzg_srs_id(&params)));
+            if !restore_cached_keys(cache_dir.as_deref(), &pk_path, &vk_path)? {
+                let pk =
+                    create_keys::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(&circuit, &params)
+                        .map_err(Box::<dyn Error>::from)?;
+                save_vk::<KZGCommitmentScheme<Bn256>>(&vk_path, pk.get_vk())?;
+                save_pk::<KZGCommitmentScheme<Bn256>>(&pk_path, &pk)?;
+                store_cached_keys(cache_dir.as_deref(), &pk_path, &vk_path)?;
+            }
+        }
+        Commitments::IPA => {
+            let params = load_ipa_params_cmd(srs_path, logrows)?;
+            let cache_dir = use_cache.then(|| key_cache_dir(&key_hash, ""));
+            if !restore_cached_keys(cache_dir.as_deref(), &pk_path, &vk_path)? {
+                let pk = create_keys::<IPACommitmentScheme<G1Affine>, Fr, GraphCircuit>(
+                    &circuit, &params,
+                )
+                .map_err(Box::<dyn Error>::from)?;
+                save_vk::<IPACommitmentScheme<G1Affine>>(&vk_path, pk.get_vk())?;
+                save_pk::<IPACommitmentScheme<G1Affine>>(&pk_path, &pk)?;
+                store_cached_keys(cache_dir.as_deref(), &pk_path, &vk_path)?;
+            }
+        }
+    }
+    std::fs::write(key_hash_path(&vk_path), &key_hash)?;
+    std::fs::write(key_hash_path(&pk_path), &key_hash)?;
     Ok(())
 }
 
@@ -1304,64 +3275,618 @@ pub(crate) fn prove(
     srs_path: Option<PathBuf>,
     proof_type: ProofType,
     check_mode: CheckMode,
+    proof_encoding: ProofEncoding,
 ) -> Result<Snark<Fr, G1Affine>, Box<dyn Error>> {
-    use crate::pfsys::ProofSplitCommit;
-
     let data = GraphWitness::from_path(data_path)?;
-    let mut circuit = GraphCircuit::load(compiled_circuit_path)?;
+    let circuit = GraphCircuit::load(compiled_circuit_path)?;
+    check_key_hash(&pk_path, &circuit)?;
+
+    let circuit_settings = circuit.settings().clone();
+    let logrows = circuit_settings.run_args.logrows;
+
+    let snark = match circuit_settings.run_args.commitment {
+        Commitments::KZG => {
+            let params = load_params_cmd(srs_path, logrows)?;
+            let pk_path = pk_path_or_cached(pk_path, &circuit, &kzg_srs_id(&params));
+            let pk =
+                load_pk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(pk_path, circuit_settings)
+                    .map_err(Box::<dyn Error>::from)?;
+            trace!("params computed");
+            prove_witness(data, circuit, &params, &pk, proof_type, check_mode)?
+        }
+        Commitments::IPA => {
+            if proof_type == ProofType::ForAggr {
+                return Err("ipa proofs can't be aggregated, use kzg commitments".into());
+            }
+            let params = load_ipa_params_cmd(srs_path, logrows)?;
+            let pk_path = pk_path_or_cached(pk_path, &circuit, "");
+            let pk = load_pk::<IPACommitmentScheme<G1Affine>, Fr, GraphCircuit>(
+                pk_path,
+                circuit_settings,
+            )
+            .map_err(Box::<dyn Error>::from)?;
+            trace!("params computed");
+            prove_witness_ipa(data, circuit, &params, &pk, proof_type, check_mode)?
+        }
+    };
+
+    if let Some(proof_path) = proof_path {
+        snark.save_with_encoding(&proof_path, proof_encoding)?;
+    }
+
+    Ok(snark)
+}
 
+/// Proves a single witness over an already loaded circuit, SRS and proving key.
+pub(crate) fn prove_witness(
+    data: GraphWitness,
+    mut circuit: GraphCircuit,
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    proof_type: ProofType,
+    check_mode: CheckMode,
+) -> Result<Snark<Fr, G1Affine>, Box<dyn Error>> {
+    use crate::pfsys::ProofSplitCommit;
+
+    let settings_hash = circuit.settings().hash()?;
+    let multiopen = circuit.settings().run_args.multiopen;
     circuit.load_graph_witness(&data)?;
 
     let public_inputs = circuit.prepare_public_inputs(&data)?;
 
-    let circuit_settings = circuit.settings().clone();
+    let strategy: StrategyType = proof_type.into();
+    let transcript: TranscriptType = proof_type.into();
+    let proof_split_commits: Option<ProofSplitCommit> = data.into();
+
+    if multiopen == MultiOpen::Gwc {
+        if proof_type == ProofType::ForAggr {
+            return Err("gwc proofs can't be aggregated, use the shplonk multiopen scheme".into());
+        }
+        let mut snark = create_proof_circuit_kzg_gwc(
+            circuit,
+            params,
+            Some(public_inputs),
+            pk,
+            transcript,
+            check_mode,
+            proof_split_commits,
+        )?;
+        snark.settings_hash = Some(settings_hash);
+        return Ok(snark);
+    }
+
+    // creates and verifies the proof
+    let mut snark = match strategy {
+        StrategyType::Single => {
+            let strategy = KZGSingleStrategy::new(params);
+            create_proof_circuit_kzg(
+                circuit,
+                params,
+                Some(public_inputs),
+                pk,
+                transcript,
+                strategy,
+                check_mode,
+                proof_split_commits,
+            )?
+        }
+        StrategyType::Accum => {
+            let strategy = AccumulatorStrategy::new(params);
+            create_proof_circuit_kzg(
+                circuit,
+                params,
+                Some(public_inputs),
+                pk,
+                transcript,
+                strategy,
+                check_mode,
+                proof_split_commits,
+            )?
+        }
+    };
+    snark.settings_hash = Some(settings_hash);
+
+    Ok(snark)
+}
+
+/// Proves a single witness with IPA commitments
+pub(crate) fn prove_witness_ipa(
+    data: GraphWitness,
+    mut circuit: GraphCircuit,
+    params: &ParamsIPA<G1Affine>,
+    pk: &ProvingKey<G1Affine>,
+    proof_type: ProofType,
+    check_mode: CheckMode,
+) -> Result<Snark<Fr, G1Affine>, Box<dyn Error>> {
+    let settings_hash = circuit.settings().hash()?;
+    circuit.load_graph_witness(&data)?;
+    let public_inputs = circuit.prepare_public_inputs(&data)?;
+    let mut snark = create_proof_circuit_ipa(
+        circuit,
+        params,
+        Some(public_inputs),
+        pk,
+        proof_type.into(),
+        check_mode,
+        data.into(),
+    )?;
+    snark.settings_hash = Some(settings_hash);
+    Ok(snark)
+}
+
+/// Warns when a proof records the hash of settings other than the ones it is verified with,
+/// which usually means the proof is for a different circuit.
+fn check_settings_hash(
+    proof: &Snark<Fr, G1Affine>,
+    settings: &GraphSettings,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(hash) = &proof.settings_hash {
+        if *hash != settings.hash()? {
+            warn!("proof was generated with different circuit settings than the ones provided");
+        }
+    }
+    Ok(())
+}
+
+/// Logs a summary table of a bulk command.
+fn log_summary_table<const N: usize>(
+    header: [&str; N],
+    rows: impl IntoIterator<Item = [String; N]>,
+) {
+    let mut builder = tabled::builder::Builder::default();
+    builder.set_header(header);
+    for row in rows {
+        builder.push_record(row);
+    }
+    let mut table = builder.build();
+    table.with(tabled::settings::Style::modern());
+    info!("\n {}", table);
+}
+
+/// Returns true if the path contains glob metacharacters.
+fn is_glob(path: &Path) -> bool {
+    path.to_str().map_or(false, |p| p.contains(['*', '?', '[']))
+}
+
+/// Expands a glob pattern into the (sorted) files it matches.
+fn expand_glob(pattern: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let pattern = pattern
+        .to_str()
+        .ok_or_else(|| format!("invalid glob pattern {}", pattern.display()))?;
+    let mut paths = glob::glob(pattern)?
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|p| p.is_file())
+        .collect::<Vec<_>>();
+    paths.sort();
+    if paths.is_empty() {
+        return Err(format!("no files match {}", pattern).into());
+    }
+    Ok(paths)
+}
+
+/// The proof written for a witness by [prove_many]: the witness path with a `.proof` extension.
+fn proof_path_for(witness: &Path) -> PathBuf {
+    witness.with_extension("proof")
+}
+
+/// The outcome of proving a single witness with [prove_many]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProveManyRecord {
+    /// the witness that was proven
+    pub witness: PathBuf,
+    /// where the proof was written
+    pub proof: PathBuf,
+    /// wall clock time to prove in milliseconds
+    pub elapsed_ms: u128,
+    /// the error message if proving failed
+    pub error: Option<String>,
+}
+
+/// Set for each process `prove --devices` spreads a glob of witnesses over, as `index/count`
+const PROVE_SHARD_ENV: &str = "EZKL_PROVE_SHARD";
+
+/// Makes only the gpus `prove --devices` runs on visible to the gpu backend. The backend picks
+/// its devices from `CUDA_VISIBLE_DEVICES` when it initializes, so call this before
+/// [crate::gpu::init_gpu] and before anything touches a gpu.
+pub fn pin_devices(command: &Commands) {
+    if let Commands::Prove {
+        witness, devices, ..
+    } = command
+    {
+        let devices = if !is_glob(witness) {
+            // a single proof runs on the first device
+            &devices[..devices.len().min(1)]
+        } else if devices.len() > 1 {
+            // each child process of prove_on_devices is pinned to its own device
+            return;
+        } else {
+            &devices[..]
+        };
+        if !devices.is_empty() {
+            let devices = devices.iter().map(|d| d.to_string()).join(",");
+            std::env::set_var("CUDA_VISIBLE_DEVICES", devices);
+        }
+    }
+}
+
+/// Proves the witnesses matching `pattern` across several gpus. This is per-proof parallelism:
+/// the MSMs of one proof are not split across devices, instead one child process is pinned to
+/// each device and proves every n-th witness, keeping all the cards busy on bulk proving. A
+/// circuit that doesn't fit in the memory of one card still doesn't fit with more cards.
+#[allow(clippy::too_many_arguments)]
+fn prove_on_devices(
+    devices: &[usize],
+    pattern: PathBuf,
+    compiled_circuit_path: PathBuf,
+    pk_path: PathBuf,
+    srs_path: Option<PathBuf>,
+    proof_type: ProofType,
+    check_mode: CheckMode,
+    proof_encoding: ProofEncoding,
+) -> Result<(), Box<dyn Error>> {
+    let exe = std::env::current_exe()?;
+    if exe.file_stem().map_or(true, |stem| stem != "ezkl") {
+        return Err("proving across several devices is only supported from the ezkl cli".into());
+    }
+    let proof_type = clap::ValueEnum::to_possible_value(&proof_type)
+        .expect("no values are skipped")
+        .get_name()
+        .to_string();
+    let check_mode = match check_mode {
+        CheckMode::SAFE => "safe",
+        CheckMode::UNSAFE => "unsafe",
+    };
+
+    let mut children = vec![];
+    for (shard, device) in devices.iter().enumerate() {
+        let mut cmd = std::process::Command::new(&exe);
+        cmd.arg("prove")
+            .arg("--witness")
+            .arg(&pattern)
+            .arg("--compiled-circuit")
+            .arg(&compiled_circuit_path)
+            .arg("--pk-path")
+            .arg(&pk_path)
+            .arg(format!("--proof-type={}", proof_type))
+            .arg("--check-mode")
+            .arg(check_mode)
+            .arg("--proof-encoding")
+            .arg(proof_encoding.to_string())
+            .env("CUDA_VISIBLE_DEVICES", device.to_string())
+            .env(PROVE_SHARD_ENV, format!("{}/{}", shard, devices.len()));
+        if let Some(srs_path) = &srs_path {
+            cmd.arg("--srs-path").arg(srs_path);
+        }
+        info!(
+            "proving shard {} of {} on device {}",
+            shard + 1,
+            devices.len(),
+            device
+        );
+        children.push((device, cmd.spawn()?));
+    }
+
+    let mut failed = vec![];
+    for (device, mut child) in children {
+        if !child.wait()?.success() {
+            failed.push(device.to_string());
+        }
+    }
+    if !failed.is_empty() {
+        return Err(format!("proving failed on devices {}", failed.join(", ")).into());
+    }
+    Ok(())
+}
+
+/// Proves every witness matching a glob pattern, loading the circuit, SRS and proving key once.
+/// Each proof is written beside its witness and a summary table is logged at the end.
+pub(crate) fn prove_many(
+    pattern: PathBuf,
+    compiled_circuit_path: PathBuf,
+    pk_path: PathBuf,
+    srs_path: Option<PathBuf>,
+    proof_type: ProofType,
+    check_mode: CheckMode,
+    proof_encoding: ProofEncoding,
+) -> Result<Vec<ProveManyRecord>, Box<dyn Error>> {
+    let mut witnesses = expand_glob(&pattern)?;
+    if let Ok(shard) = std::env::var(PROVE_SHARD_ENV) {
+        let (index, count) = shard
+            .split_once('/')
+            .and_then(|(i, n)| Some((i.parse::<usize>().ok()?, n.parse::<usize>().ok()?)))
+            .filter(|(i, n)| i < n)
+            .ok_or_else(|| format!("{} should look like index/count", PROVE_SHARD_ENV))?;
+        witnesses = witnesses
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| i % count == index)
+            .map(|(_, w)| w)
+            .collect();
+    }
+    info!(
+        "proving {} witnesses matching {}",
+        witnesses.len(),
+        pattern.display()
+    );
+
+    let circuit = GraphCircuit::load(compiled_circuit_path)?;
+    check_key_hash(&pk_path, &circuit)?;
+    let circuit_settings = circuit.settings().clone();
+    if circuit_settings.run_args.commitment != Commitments::KZG {
+        return Err("bulk proving only supports kzg commitments".into());
+    }
+    let params = load_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
+    let pk_path = pk_path_or_cached(pk_path, &circuit, &kzg_srs_id(&params));
+    let pk = load_pk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(pk_path, circuit_settings)
+        .map_err(Box::<dyn Error>::from)?;
+
+    let records = witnesses
+        .into_iter()
+        .map(|witness| {
+            let proof = proof_path_for(&witness);
+            let now = Instant::now();
+            let res = GraphWitness::from_path(witness.clone())
+                .and_then(|data| {
+                    prove_witness(data, circuit.clone(), &params, &pk, proof_type, check_mode)
+                })
+                .and_then(|snark| snark.save_with_encoding(&proof, proof_encoding));
+            let elapsed_ms = now.elapsed().as_millis();
+            match &res {
+                Ok(()) => info!("proved {} -> {}", witness.display(), proof.display()),
+                Err(e) => error!("failed to prove {}: {}", witness.display(), e),
+            }
+            ProveManyRecord {
+                witness,
+                proof,
+                elapsed_ms,
+                error: res.err().map(|e| e.to_string()),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    log_summary_table(
+        ["witness", "proof", "time (ms)", "status"],
+        records.iter().map(|r| {
+            [
+                r.witness.display().to_string(),
+                r.proof.display().to_string(),
+                r.elapsed_ms.to_string(),
+                r.error.clone().unwrap_or_else(|| "ok".to_string()),
+            ]
+        }),
+    );
+
+    let failed = records.iter().filter(|r| r.error.is_some()).count();
+    if failed > 0 {
+        return Err(format!("{} of {} proofs failed", failed, records.len()).into());
+    }
+    Ok(records)
+}
+
+/// Generates a witness for every .json input file in a directory and proves up to
+/// `max_parallel` samples at a time, loading the SRS and proving key once. Witnesses and proofs
+/// are written to `output_dir`, named after their input file.
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn prove_batch(
+    data_dir: PathBuf,
+    compiled_circuit_path: PathBuf,
+    pk_path: PathBuf,
+    srs_path: Option<PathBuf>,
+    output_dir: PathBuf,
+    proof_type: ProofType,
+    check_mode: CheckMode,
+    proof_encoding: ProofEncoding,
+    max_parallel: usize,
+) -> Result<Vec<ProveManyRecord>, Box<dyn Error>> {
+    if max_parallel == 0 {
+        return Err("max_parallel must be >= 1".into());
+    }
+    let mut inputs = std::fs::read_dir(&data_dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|p| p.is_file() && p.extension().map_or(false, |ext| ext == "json"))
+        .collect::<Vec<_>>();
+    inputs.sort();
+    if inputs.is_empty() {
+        return Err(format!("no .json inputs in {}", data_dir.display()).into());
+    }
+    std::fs::create_dir_all(&output_dir)?;
+    info!(
+        "proving a batch of {} inputs from {}",
+        inputs.len(),
+        data_dir.display()
+    );
+
+    // witness generation is cheap next to proving so it runs one sample at a time
+    let mut witnesses = vec![];
+    for input in inputs {
+        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+        let witness = output_dir.join(format!("{}.witness.json", stem));
+        let proof = output_dir.join(format!("{}.proof", stem));
+        gen_witness(
+            compiled_circuit_path.clone(),
+            input,
+            Some(witness.clone()),
+            None,
+            srs_path.clone(),
+            None,
+            None,
+        )
+        .await?;
+        witnesses.push((witness, proof));
+    }
+
+    let circuit = GraphCircuit::load(compiled_circuit_path)?;
+    check_key_hash(&pk_path, &circuit)?;
+    let circuit_settings = circuit.settings().clone();
+    let params = CommitmentParams::load(srs_path, &circuit_settings)?;
+    let srs_id = params.kzg().map(kzg_srs_id).unwrap_or_default();
+    let pk_path = pk_path_or_cached(pk_path, &circuit, &srs_id);
+    let pk = load_pk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(pk_path, circuit_settings)
+        .map_err(Box::<dyn Error>::from)?;
+
+    let pb = init_bar(witnesses.len() as u64);
+    pb.set_message("proving batch...");
+    let mut records = vec![];
+    // every proof in flight holds its own polynomials, so only `max_parallel` run at once. Each of
+    // them still spreads its msms and ffts over the whole thread pool
+    for chunk in witnesses.chunks(max_parallel) {
+        records.extend(
+            chunk
+                .into_par_iter()
+                .map(|(witness, proof)| {
+                    let now = Instant::now();
+                    let res = GraphWitness::from_path(witness.clone())
+                        .and_then(|data| {
+                            params.prove(data, circuit.clone(), &pk, proof_type, check_mode)
+                        })
+                        .and_then(|snark| snark.save_with_encoding(proof, proof_encoding))
+                        .map_err(|e| e.to_string());
+                    pb.inc(1);
+                    ProveManyRecord {
+                        witness: witness.clone(),
+                        proof: proof.clone(),
+                        elapsed_ms: now.elapsed().as_millis(),
+                        error: res.err(),
+                    }
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+    pb.finish_with_message("Done.");
+
+    log_summary_table(
+        ["witness", "proof", "time (ms)", "status"],
+        records.iter().map(|r| {
+            [
+                r.witness.display().to_string(),
+                r.proof.display().to_string(),
+                r.elapsed_ms.to_string(),
+                r.error.clone().unwrap_or_else(|| "ok".to_string()),
+            ]
+        }),
+    );
+
+    let failed = records.iter().filter(|r| r.error.is_some()).count();
+    if failed > 0 {
+        return Err(format!("{} of {} proofs failed", failed, records.len()).into());
+    }
+    Ok(records)
+}
 
-    let params = load_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
+/// Runs the forward pass, the circuit layout and the mock prover over seeded random inputs.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn fuzz_inputs(
+    compiled_circuit_path: PathBuf,
+    num_runs: usize,
+    seed: u64,
+    max_abs: f64,
+) -> Result<(), Box<dyn Error>> {
+    use crate::circuit::InputType;
+    use crate::graph::input::DataSource;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    if !max_abs.is_finite() || max_abs < 0.0 {
+        return Err(format!(
+            "max_abs must be a finite non-negative number, got {}",
+            max_abs
+        )
+        .into());
+    }
 
-    let pk = load_pk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(pk_path, circuit_settings)
-        .map_err(Box::<dyn Error>::from)?;
+    let mut circuit = GraphCircuit::load(compiled_circuit_path)?;
+    let shapes = circuit.model().graph.input_shapes()?;
+    let input_types = circuit.model().graph.get_input_types()?;
+    let run_args = circuit.settings().run_args.clone();
 
-    trace!("params computed");
+    let mut num_panics = 0;
+    let mut num_mock_failures = 0;
+    let mut num_divergences = 0;
 
-    let strategy: StrategyType = proof_type.into();
-    let transcript: TranscriptType = proof_type.into();
-    let proof_split_commits: Option<ProofSplitCommit> = data.into();
+    let pb = init_bar(num_runs as u64);
+    pb.set_message("fuzzing inputs");
+    for run in 0..num_runs {
+        pb.inc(1);
+        let run_seed = seed.wrapping_add(run as u64);
+        let mut rng = StdRng::seed_from_u64(run_seed);
+        let data: Vec<Vec<f64>> = shapes
+            .iter()
+            .zip(&input_types)
+            .map(|(shape, input_type)| {
+                (0..shape.iter().product::<usize>())
+                    .map(|_| match input_type {
+                        InputType::Bool => rng.gen_range(0..=1) as f64,
+                        InputType::Int | InputType::TDim => {
+                            rng.gen_range(-max_abs.floor()..=max_abs.floor())
+                        }
+                        _ => rng.gen_range(-max_abs..=max_abs),
+                    })
+                    .collect()
+            })
+            .collect();
 
-    // creates and verifies the proof
-    let snark = match strategy {
-        StrategyType::Single => {
-            let strategy = KZGSingleStrategy::new(&params);
-            create_proof_circuit_kzg(
-                circuit,
-                &params,
-                Some(public_inputs),
-                &pk,
-                transcript,
-                strategy,
-                check_mode,
-                proof_split_commits,
-            )?
-        }
-        StrategyType::Accum => {
-            let strategy = AccumulatorStrategy::new(&params);
-            create_proof_circuit_kzg(
-                circuit,
-                &params,
-                Some(public_inputs),
-                &pk,
-                transcript,
-                strategy,
-                check_mode,
-                proof_split_commits,
-            )?
+        let mut input = circuit
+            .load_graph_input(&GraphData::new(DataSource::from(data)))
+            .await?;
+        let original_input = input.clone();
+
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let witness = circuit.forward_with_ephemeral_keys(&mut input, None, None)?;
+            let layout_outputs = circuit.model().layout_values(&run_args, &original_input)?;
+            let mismatches = diff_outputs(&witness.outputs, &layout_outputs);
+
+            circuit.load_graph_witness(&witness)?;
+            let public_inputs = circuit.prepare_public_inputs(&witness)?;
+            let prover =
+                halo2_proofs::dev::MockProver::run(run_args.logrows, &circuit, vec![public_inputs])
+                    .map_err(Box::<dyn Error>::from)?;
+            Ok::<_, Box<dyn Error>>((mismatches, prover.verify_par()))
+        }));
+
+        match res {
+            Err(_) => {
+                num_panics += 1;
+                error!("run {} (seed {}) panicked", run, run_seed);
+            }
+            Ok(Err(e)) => {
+                num_mock_failures += 1;
+                error!("run {} (seed {}) failed: {}", run, run_seed, e);
+            }
+            Ok(Ok((mismatches, mock_result))) => {
+                if !mismatches.is_empty() {
+                    num_divergences += 1;
+                    error!(
+                        "run {} (seed {}) diverged on {} output cell(s), first: {}",
+                        run,
+                        run_seed,
+                        mismatches.len(),
+                        mismatches[0]
+                    );
+                }
+                if let Err(failures) = mock_result {
+                    num_mock_failures += 1;
+                    error!("run {} (seed {}) failed the mock prover", run, run_seed);
+                    for description in describe_mock_failures(&circuit, &failures)? {
+                        error!("{}", description);
+                    }
+                }
+            }
         }
-    };
-
-    if let Some(proof_path) = proof_path {
-        snark.save(&proof_path)?;
     }
+    pb.finish_with_message("Done.");
 
-    Ok(snark)
+    info!(
+        "{} runs: {} panics, {} constraint failures, {} forward/circuit divergences",
+        num_runs, num_panics, num_mock_failures, num_divergences
+    );
+
+    if num_panics + num_mock_failures + num_divergences > 0 {
+        return Err("fuzzing found failing inputs".into());
+    }
+    Ok(())
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -1507,6 +4032,8 @@ pub(crate) fn fuzz(
             protocol: proof.protocol.clone(),
             transcript_type: transcript,
             split: None,
+            version: proof.version,
+            settings_hash: proof.settings_hash.clone(),
         };
 
         verify_proof_circuit_kzg(
@@ -1539,6 +4066,8 @@ pub(crate) fn fuzz(
             protocol: proof.protocol.clone(),
             transcript_type: transcript,
             split: None,
+            version: proof.version,
+            settings_hash: proof.settings_hash.clone(),
         };
 
         verify_proof_circuit_kzg(
@@ -1587,6 +4116,285 @@ pub(crate) fn run_fuzz_fn(
     );
 }
 
+/// The cost of one phase of `ezkl bench`
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BenchPhase {
+    /// the phase that was timed
+    pub phase: String,
+    /// wall clock time in milliseconds
+    pub elapsed_ms: u128,
+    /// the peak resident memory of the process during the phase in kB, None where it can't be
+    /// read (anywhere but linux)
+    pub peak_memory_kb: Option<u64>,
+}
+
+/// The outcome of `ezkl bench`
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// the benchmarked model
+    pub model: PathBuf,
+    /// the settings the model was benchmarked with
+    pub run_args: RunArgs,
+    /// the size of the circuit
+    pub stats: CircuitStats,
+    /// the cost of each phase, in the order they ran
+    pub phases: Vec<BenchPhase>,
+    /// wall clock time of all the phases in milliseconds
+    pub total_ms: u128,
+}
+
+/// Times a phase of `ezkl bench`. On linux the process's peak memory is reset when the phase
+/// starts so that it is attributed to the phase that reached it.
+#[cfg(not(target_arch = "wasm32"))]
+struct PhaseTimer {
+    phase: &'static str,
+    start: Instant,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PhaseTimer {
+    fn start(phase: &'static str) -> Self {
+        // writing 5 to clear_refs resets the peak resident set size (VmHWM)
+        let _ = std::fs::write("/proc/self/clear_refs", "5");
+        info!("bench: {}...", phase);
+        Self {
+            phase,
+            start: Instant::now(),
+        }
+    }
+
+    fn stop(self) -> BenchPhase {
+        let elapsed_ms = self.start.elapsed().as_millis();
+        let peak_memory_kb = std::fs::read_to_string("/proc/self/status")
+            .ok()
+            .and_then(|status| {
+                status
+                    .lines()
+                    .find_map(|l| l.strip_prefix("VmHWM:"))?
+                    .trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse()
+                    .ok()
+            });
+        BenchPhase {
+            phase: self.phase.to_string(),
+            elapsed_ms,
+            peak_memory_kb,
+        }
+    }
+}
+
+/// Builds the circuit for a model from its settings if there are any, otherwise from `run_args`
+#[cfg(not(target_arch = "wasm32"))]
+fn load_circuit(
+    model_path: &Path,
+    settings_path: Option<&Path>,
+    run_args: &RunArgs,
+) -> Result<GraphCircuit, Box<dyn Error>> {
+    match settings_path {
+        Some(settings_path) => GraphCircuit::from_settings(
+            &GraphSettings::load(&settings_path.to_path_buf())?,
+            model_path,
+            CheckMode::UNSAFE,
+        ),
+        None => GraphCircuit::from_run_args(run_args, model_path),
+    }
+}
+
+/// Runs the whole pipeline on a model, in memory, and writes the wall clock time and peak
+/// memory of each phase to `output` as json. Loading the model includes quantizing its weights;
+/// the quantization phase covers the input data.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn bench(
+    model_path: PathBuf,
+    data: PathBuf,
+    settings_path: Option<PathBuf>,
+    srs_path: Option<PathBuf>,
+    output: PathBuf,
+    run_args: RunArgs,
+) -> Result<(), Box<dyn Error>> {
+    let mut phases = vec![];
+
+    let timer = PhaseTimer::start("load");
+    let mut circuit = load_circuit(&model_path, settings_path.as_deref(), &run_args)?;
+    phases.push(timer.stop());
+
+    let settings = circuit.settings().clone();
+    if settings.run_args.commitment != Commitments::KZG {
+        return Err("bench only supports kzg commitments".into());
+    }
+    let params = load_params_cmd(srs_path, settings.run_args.logrows)?;
+
+    let timer = PhaseTimer::start("quantization");
+    let mut input = circuit
+        .load_graph_input(&GraphData::from_path(data)?)
+        .await?;
+    phases.push(timer.stop());
+
+    let timer = PhaseTimer::start("keygen");
+    let pk = create_keys::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(&circuit, &params)
+        .map_err(Box::<dyn Error>::from)?;
+    phases.push(timer.stop());
+
+    let timer = PhaseTimer::start("witness");
+    let witness =
+        circuit.forward_with_ephemeral_keys(&mut input, Some(pk.get_vk()), Some(&params))?;
+    phases.push(timer.stop());
+
+    let stats = circuit.stats()?;
+    let timer = PhaseTimer::start("prove");
+    let snark = prove_witness(
+        witness,
+        circuit,
+        &params,
+        &pk,
+        ProofType::Single,
+        CheckMode::UNSAFE,
+    )?;
+    phases.push(timer.stop());
+
+    let timer = PhaseTimer::start("verify");
+    let verifier_params = params.verifier_params();
+    match settings.run_args.multiopen {
+        MultiOpen::Shplonk => verify_proof_circuit_kzg(
+            verifier_params,
+            snark,
+            pk.get_vk(),
+            KZGSingleStrategy::new(verifier_params),
+        )?,
+        MultiOpen::Gwc => verify_proof_circuit_kzg_gwc(verifier_params, snark, pk.get_vk())?,
+    };
+    phases.push(timer.stop());
+
+    log_summary_table(
+        ["phase", "elapsed (ms)", "peak memory (kB)"],
+        phases.iter().map(|p| {
+            [
+                p.phase.clone(),
+                p.elapsed_ms.to_string(),
+                p.peak_memory_kb
+                    .map_or_else(|| "-".to_string(), |m| m.to_string()),
+            ]
+        }),
+    );
+
+    let report = BenchReport {
+        model: model_path,
+        run_args: settings.run_args,
+        stats,
+        total_ms: phases.iter().map(|p| p.elapsed_ms).sum(),
+        phases,
+    };
+    crate::stdio::write(&output, serde_json::to_string_pretty(&report)?)?;
+    Ok(())
+}
+
+/// A projection of the resources needed to prove a circuit
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResourceEstimate {
+    /// rows the circuit is laid out over
+    pub rows: u64,
+    /// rows of the extended domain the quotient polynomial is evaluated over
+    pub extended_rows: u64,
+    /// number of polynomials the prover holds
+    pub num_polys: usize,
+    /// projected proving time in seconds
+    pub proving_time_s: f64,
+    /// projected peak memory of the prover in bytes
+    pub proving_memory_bytes: u64,
+    /// the benchmark the projection was scaled from, None if it uses nominal rates
+    pub reference: Option<PathBuf>,
+}
+
+/// The outcome of `ezkl estimate`
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EstimateReport {
+    /// the estimated model
+    pub model: PathBuf,
+    /// the size of the circuit
+    pub stats: CircuitStats,
+    /// the projected cost of proving it
+    pub estimate: ResourceEstimate,
+}
+
+/// Configures the circuit for a model, without keys or a witness, and projects how long proving
+/// it would take and how much memory it would need. The projection scales the prove phase of a
+/// `bench_report` (from `ezkl bench` on this machine) by the relative size of the two circuits,
+/// or falls back to nominal rates.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn estimate(
+    model_path: PathBuf,
+    settings_path: Option<PathBuf>,
+    bench_report: Option<PathBuf>,
+    output: PathBuf,
+    run_args: RunArgs,
+) -> Result<(), Box<dyn Error>> {
+    let circuit = load_circuit(&model_path, settings_path.as_deref(), &run_args)?;
+    let stats = circuit.stats()?;
+
+    let mut estimate = ResourceEstimate {
+        rows: 1 << stats.logrows,
+        extended_rows: 1 << stats.extended_logrows(),
+        num_polys: stats.num_polys(),
+        proving_time_s: stats.proving_work() / crate::graph::NOMINAL_PROVING_WORK_PER_SEC,
+        proving_memory_bytes: stats.proving_memory_bytes(),
+        reference: None,
+    };
+    if let Some(bench_report) = bench_report {
+        let reference: BenchReport = serde_json::from_reader(File::open(&bench_report)?)?;
+        let prove = reference
+            .phases
+            .iter()
+            .find(|p| p.phase == "prove")
+            .ok_or("the bench report has no prove phase")?;
+        estimate.proving_time_s = prove.elapsed_ms as f64 / 1000.0 * stats.proving_work()
+            / reference.stats.proving_work();
+        if let Some(peak_memory_kb) = prove.peak_memory_kb {
+            estimate.proving_memory_bytes =
+                (peak_memory_kb as f64 * 1024.0 * stats.proving_memory_bytes() as f64
+                    / reference.stats.proving_memory_bytes() as f64) as u64;
+        }
+        estimate.reference = Some(bench_report);
+    }
+
+    info!(
+        "{} rows ({} used), {} advice, {} fixed and {} instance columns",
+        estimate.rows,
+        stats.num_rows,
+        stats.num_advice_columns,
+        stats.num_fixed_columns,
+        stats.num_instance_columns
+    );
+    info!(
+        "{} lookup tables of {} entries over {} columns each",
+        stats.num_lookup_tables, stats.lookup_table_size, stats.num_lookup_table_columns
+    );
+    if stats.min_logrows > stats.logrows {
+        warn!(
+            "the model needs at least {} logrows but is configured with {}",
+            stats.min_logrows, stats.logrows
+        );
+    }
+    info!(
+        "proving: ~{:.1}s and ~{} MB of memory",
+        estimate.proving_time_s,
+        estimate.proving_memory_bytes / (1 << 20)
+    );
+
+    let report = EstimateReport {
+        model: model_path,
+        stats,
+        estimate,
+    };
+    serde_json::to_writer_pretty(File::create(output)?, &report)?;
+    Ok(())
+}
+
 pub(crate) fn swap_proof_commitments(
     proof_path: PathBuf,
     witness: PathBuf,
@@ -1609,15 +4417,31 @@ pub(crate) fn swap_proof_commitments(
     Ok(())
 }
 
+/// Loads the snarks to aggregate. They can be proofs of different circuits, but they have to be
+/// passed in the same order at setup and when aggregating as the aggregation keys fix the
+/// circuit of each position.
+fn load_aggregation_snarks(paths: &[PathBuf]) -> Result<Vec<Snark<Fr, G1Affine>>, Box<dyn Error>> {
+    let mut snarks = vec![];
+    for proof_path in paths.iter() {
+        let snark = Snark::load::<KZGCommitmentScheme<Bn256>>(proof_path)?;
+        if snark.transcript_type != TranscriptType::Poseidon {
+            return Err(format!(
+                "{} was not proven for aggregation, re-prove it with `--proof-type for-aggr`",
+                proof_path.display()
+            )
+            .into());
+        }
+        snarks.push(snark);
+    }
+    Ok(snarks)
+}
+
 pub(crate) fn mock_aggregate(
     aggregation_snarks: Vec<PathBuf>,
     logrows: u32,
     split_proofs: bool,
 ) -> Result<(), Box<dyn Error>> {
-    let mut snarks = vec![];
-    for proof_path in aggregation_snarks.iter() {
-        snarks.push(Snark::load::<KZGCommitmentScheme<Bn256>>(proof_path)?);
-    }
+    let snarks = load_aggregation_snarks(&aggregation_snarks)?;
     // proof aggregation
     #[cfg(not(target_arch = "wasm32"))]
     let pb = {
@@ -1649,10 +4473,7 @@ pub(crate) fn setup_aggregate(
     // the K used for the aggregation circuit
     let params = load_params_cmd(srs_path, logrows)?;
 
-    let mut snarks = vec![];
-    for proof_path in sample_snarks.iter() {
-        snarks.push(Snark::load::<KZGCommitmentScheme<Bn256>>(proof_path)?);
-    }
+    let snarks = load_aggregation_snarks(&sample_snarks)?;
 
     let agg_circuit = AggregationCircuit::new(&params.get_g()[0].into(), snarks, split_proofs)?;
     let agg_pk =
@@ -1680,10 +4501,7 @@ pub(crate) fn aggregate(
     // the K used for the aggregation circuit
     let params = load_params_cmd(srs_path, logrows)?;
 
-    let mut snarks = vec![];
-    for proof_path in aggregation_snarks.iter() {
-        snarks.push(Snark::load::<KZGCommitmentScheme<Bn256>>(proof_path)?);
-    }
+    let snarks = load_aggregation_snarks(&aggregation_snarks)?;
 
     let agg_pk = load_pk::<KZGCommitmentScheme<Bn256>, Fr, AggregationCircuit>(pk_path, ())?;
     // proof aggregation
@@ -1723,20 +4541,128 @@ pub(crate) fn aggregate(
     Ok(())
 }
 
+/// Errors if the hashed inputs of a proof aren't the digests of a committed input (as written by
+/// `encrypt-input`), so an input committed to once can be checked by every circuit proving over it.
+fn check_input_commitment(
+    proof: &Snark<Fr, G1Affine>,
+    settings: &GraphSettings,
+    input_commitment: &PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    use crate::graph::modules::ModuleForwardResult;
+
+    let visibility = &settings.run_args.input_visibility;
+    if !visibility.is_hashed_public() {
+        return Err(format!(
+            "input visibility is {}, proofs don't expose an input commitment",
+            visibility
+        )
+        .into());
+    }
+    let committed: ModuleForwardResult =
+        serde_json::from_str(&crate::stdio::read_to_string(input_commitment)?)?;
+    let digests = committed.poseidon_hash.unwrap_or_default();
+    // hashed inputs are the first instances of the proof
+    if digests.is_empty() || !proof.instances.concat().starts_with(&digests) {
+        return Err(format!(
+            "the proof's input hash doesn't match the commitment at {}",
+            input_commitment.display()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Errors if a proof wasn't generated with the params of a params commitment (as written by
+/// `commit-params`). kzg commitments are the first points of the proof, so the proof is left
+/// unchanged by swapping in the pinned commitment only if it already holds it.
+fn check_params_commitment(
+    proof: &Snark<Fr, G1Affine>,
+    settings: &GraphSettings,
+    params_commitment: &PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    use crate::graph::modules::ModuleForwardResult;
+
+    let run_args = &settings.run_args;
+    if !run_args.param_visibility.is_kzgcommit() {
+        return Err(format!(
+            "param visibility is {}, proofs don't commit to the params",
+            run_args.param_visibility
+        )
+        .into());
+    }
+    if run_args.input_visibility.is_kzgcommit() {
+        return Err("params can't be pinned when inputs are also committed to with kzg".into());
+    }
+    let committed: ModuleForwardResult =
+        serde_json::from_str(&crate::stdio::read_to_string(params_commitment)?)?;
+    let commitments = committed.kzg_commit.unwrap_or_default().concat();
+    if commitments.is_empty() {
+        return Err(format!("no kzg commitments in {}", params_commitment.display()).into());
+    }
+    if swap_proof_commitments_kzg(proof, &commitments)?.proof != proof.proof {
+        return Err(format!(
+            "the proof wasn't generated with the params committed to at {}",
+            params_commitment.display()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn verify(
     proof_path: PathBuf,
     settings_path: PathBuf,
     vk_path: PathBuf,
     srs_path: Option<PathBuf>,
+    proof_encoding: ProofEncoding,
+    input_commitment: Option<PathBuf>,
+    params_commitment: Option<PathBuf>,
 ) -> Result<(), Box<dyn Error>> {
     let circuit_settings = GraphSettings::load(&settings_path)?;
+    if circuit_settings.run_args.commitment == Commitments::IPA {
+        if params_commitment.is_some() {
+            return Err("params commitments are kzg commitments, they can't be checked against an ipa proof".into());
+        }
+        let params = load_ipa_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
+        let proof = Snark::load_with_encoding::<IPACommitmentScheme<G1Affine>>(
+            &proof_path,
+            proof_encoding,
+        )?;
+        check_settings_hash(&proof, &circuit_settings)?;
+        if let Some(input_commitment) = &input_commitment {
+            check_input_commitment(&proof, &circuit_settings, input_commitment)?;
+        }
+        let vk =
+            load_vk::<IPACommitmentScheme<G1Affine>, Fr, GraphCircuit>(vk_path, circuit_settings)?;
+        let now = Instant::now();
+        let result = verify_proof_circuit_ipa(&params, proof, &vk);
+        info!("verify took {}ms", now.elapsed().as_millis());
+        info!("verified: {}", result.is_ok());
+        return result;
+    }
     let params = load_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
-    let proof = Snark::load::<KZGCommitmentScheme<Bn256>>(&proof_path)?;
+    let proof =
+        Snark::load_with_encoding::<KZGCommitmentScheme<Bn256>>(&proof_path, proof_encoding)?;
+    check_settings_hash(&proof, &circuit_settings)?;
+    if let Some(input_commitment) = &input_commitment {
+        check_input_commitment(&proof, &circuit_settings, input_commitment)?;
+    }
+    if let Some(params_commitment) = &params_commitment {
+        check_params_commitment(&proof, &circuit_settings, params_commitment)?;
+    }
 
+    let multiopen = circuit_settings.run_args.multiopen;
     let strategy = KZGSingleStrategy::new(params.verifier_params());
     let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, circuit_settings)?;
     let now = Instant::now();
-    let result = verify_proof_circuit_kzg(params.verifier_params(), proof, &vk, strategy);
+    let result: Result<(), Box<dyn Error>> = match multiopen {
+        MultiOpen::Shplonk => {
+            verify_proof_circuit_kzg(params.verifier_params(), proof, &vk, strategy)
+                .map_err(|e| e.into())
+        }
+        MultiOpen::Gwc => verify_proof_circuit_kzg_gwc(params.verifier_params(), proof, &vk),
+    };
     let elapsed = now.elapsed();
     info!(
         "verify took {}.{}",
@@ -1744,7 +4670,229 @@ pub(crate) fn verify(
         elapsed.subsec_millis()
     );
     info!("verified: {}", result.is_ok());
-    result.map_err(|e| e.into())
+    result
+}
+
+/// The srs of the commitment scheme a circuit's settings use, for the bindings and servers that
+/// load the srs once and prove or verify many times
+#[derive(Debug)]
+pub(crate) enum CommitmentParams {
+    /// srs for kzg commitments
+    KZG(ParamsKZG<Bn256>),
+    /// srs for ipa commitments
+    IPA(ParamsIPA<G1Affine>),
+}
+
+impl CommitmentParams {
+    /// Loads the srs at `srs_path`, or the default srs for the settings' logrows
+    pub(crate) fn load(
+        srs_path: Option<PathBuf>,
+        settings: &GraphSettings,
+    ) -> Result<Self, Box<dyn Error>> {
+        let logrows = settings.run_args.logrows;
+        Ok(match settings.run_args.commitment {
+            Commitments::KZG => Self::KZG(load_params_cmd(srs_path, logrows)?),
+            Commitments::IPA => Self::IPA(load_ipa_params_cmd(srs_path, logrows)?),
+        })
+    }
+
+    /// Reads a serialized srs for the settings' commitment
+    pub(crate) fn read<R: std::io::Read>(
+        reader: &mut R,
+        settings: &GraphSettings,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(match settings.run_args.commitment {
+            Commitments::KZG => {
+                Self::KZG(<ParamsKZG<Bn256> as Params<'_, G1Affine>>::read(reader)?)
+            }
+            Commitments::IPA => {
+                Self::IPA(<ParamsIPA<G1Affine> as Params<'_, G1Affine>>::read(reader)?)
+            }
+        })
+    }
+
+    /// The kzg srs, which polycommit visibilities commit with during the forward pass
+    pub(crate) fn kzg(&self) -> Option<&ParamsKZG<Bn256>> {
+        match self {
+            Self::KZG(params) => Some(params),
+            Self::IPA(_) => None,
+        }
+    }
+
+    /// Proves a witness with the commitment and multiopen scheme of the circuit's settings
+    pub(crate) fn prove(
+        &self,
+        witness: GraphWitness,
+        circuit: GraphCircuit,
+        pk: &ProvingKey<G1Affine>,
+        proof_type: ProofType,
+        check_mode: CheckMode,
+    ) -> Result<Snark<Fr, G1Affine>, Box<dyn Error>> {
+        match self {
+            Self::KZG(params) => {
+                prove_witness(witness, circuit, params, pk, proof_type, check_mode)
+            }
+            Self::IPA(params) => {
+                prove_witness_ipa(witness, circuit, params, pk, proof_type, check_mode)
+            }
+        }
+    }
+
+    /// Verifies a proof the way [verify] does, with the `multiopen` scheme for kzg proofs
+    pub(crate) fn verify(
+        &self,
+        proof: Snark<Fr, G1Affine>,
+        vk: &VerifyingKey<G1Affine>,
+        multiopen: MultiOpen,
+    ) -> Result<(), Box<dyn Error>> {
+        match (self, multiopen) {
+            (Self::IPA(params), _) => verify_proof_circuit_ipa(params, proof, vk),
+            (Self::KZG(params), MultiOpen::Gwc) => {
+                verify_proof_circuit_kzg_gwc(params.verifier_params(), proof, vk)
+            }
+            (Self::KZG(params), MultiOpen::Shplonk) => {
+                let strategy = KZGSingleStrategy::new(params.verifier_params());
+                verify_proof_circuit_kzg(params.verifier_params(), proof, vk, strategy)
+                    .map_err(|e| e.into())
+            }
+        }
+    }
+}
+
+/// The outcome of verifying a single proof with [verify_many]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VerifyManyRecord {
+    /// the proof that was verified
+    pub proof: PathBuf,
+    /// wall clock time to verify in milliseconds
+    pub elapsed_ms: u128,
+    /// the error message if the proof was rejected
+    pub error: Option<String>,
+}
+
+/// Verifies every `.proof` file in `proof_dir` against one verification key.
+///
+/// With `batch` set, the proofs' pairing checks are accumulated and decided by a single final
+/// check. A rejected batch doesn't say which proof is bad, so each proof is then verified on its own.
+pub(crate) fn verify_many(
+    proof_dir: PathBuf,
+    settings_path: PathBuf,
+    vk_path: PathBuf,
+    srs_path: Option<PathBuf>,
+    proof_encoding: ProofEncoding,
+    batch: bool,
+) -> Result<Vec<VerifyManyRecord>, Box<dyn Error>> {
+    let mut proof_paths = std::fs::read_dir(&proof_dir)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|p| p.is_file() && p.extension().map_or(false, |e| e == "proof"))
+        .collect::<Vec<_>>();
+    proof_paths.sort();
+    if proof_paths.is_empty() {
+        return Err(format!("no .proof files in {}", proof_dir.display()).into());
+    }
+    info!(
+        "verifying {} proofs in {}",
+        proof_paths.len(),
+        proof_dir.display()
+    );
+
+    let circuit_settings = GraphSettings::load(&settings_path)?;
+    if circuit_settings.run_args.commitment != Commitments::KZG {
+        return Err("bulk verification only supports kzg commitments".into());
+    }
+    let multiopen = circuit_settings.run_args.multiopen;
+    if batch && multiopen != MultiOpen::Shplonk {
+        return Err("batched verification only supports the shplonk multiopen scheme".into());
+    }
+    let params = load_params_cmd(srs_path, circuit_settings.run_args.logrows)?;
+    let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, circuit_settings)?;
+
+    let load = |path: &PathBuf| {
+        Snark::load_with_encoding::<KZGCommitmentScheme<Bn256>>(path, proof_encoding)
+    };
+    let verify_one = |path: &PathBuf| {
+        let now = Instant::now();
+        let res = load(path).and_then(|proof| match multiopen {
+            MultiOpen::Shplonk => {
+                let strategy = KZGSingleStrategy::new(params.verifier_params());
+                verify_proof_circuit_kzg(params.verifier_params(), proof, &vk, strategy)
+                    .map_err(|e| e.into())
+            }
+            MultiOpen::Gwc => verify_proof_circuit_kzg_gwc(params.verifier_params(), proof, &vk),
+        });
+        VerifyManyRecord {
+            proof: path.clone(),
+            elapsed_ms: now.elapsed().as_millis(),
+            error: res.err().map(|e| e.to_string()),
+        }
+    };
+
+    let total = Instant::now();
+    let mut records = vec![];
+
+    if batch {
+        use halo2_proofs::poly::VerificationStrategy;
+
+        let mut strategy = AccumulatorStrategy::new(params.verifier_params());
+        let mut batched = vec![];
+        for path in &proof_paths {
+            let now = Instant::now();
+            let res = load(path).and_then(|proof| {
+                verify_proof_circuit_kzg(params.verifier_params(), proof, &vk, strategy.clone())
+                    .map_err(|e| e.into())
+            });
+            let error = match res {
+                Ok(s) => {
+                    strategy = s;
+                    batched.push(records.len());
+                    None
+                }
+                Err(e) => Some(e.to_string()),
+            };
+            records.push(VerifyManyRecord {
+                proof: path.clone(),
+                elapsed_ms: now.elapsed().as_millis(),
+                error,
+            });
+        }
+        if strategy.finalize() {
+            info!("batch of {} proofs accepted", batched.len());
+        } else {
+            warn!("batch rejected, verifying proofs individually");
+            for i in batched {
+                let path = records[i].proof.clone();
+                records[i] = verify_one(&path);
+            }
+        }
+    } else {
+        records = proof_paths.iter().map(verify_one).collect();
+    }
+
+    let elapsed = total.elapsed();
+    log_summary_table(
+        ["proof", "time (ms)", "status"],
+        records.iter().map(|r| {
+            [
+                r.proof.display().to_string(),
+                r.elapsed_ms.to_string(),
+                r.error.clone().unwrap_or_else(|| "verified".to_string()),
+            ]
+        }),
+    );
+    info!(
+        "verified {} proofs in {}.{}s",
+        records.len(),
+        elapsed.as_secs(),
+        elapsed.subsec_millis()
+    );
+
+    let failed = records.iter().filter(|r| r.error.is_some()).count();
+    if failed > 0 {
+        return Err(format!("{} of {} proofs were rejected", failed, records.len()).into());
+    }
+    Ok(records)
 }
 
 pub(crate) fn verify_aggr(
@@ -1786,3 +4934,27 @@ pub(crate) fn load_params_cmd(
     }
     Ok(params)
 }
+
+/// Loads the IPA params for `logrows`. They need no trusted setup, so if there are none at
+/// `srs_path` (or the default path) they are generated and saved there.
+pub(crate) fn load_ipa_params_cmd(
+    srs_path: Option<PathBuf>,
+    logrows: u32,
+) -> Result<ParamsIPA<G1Affine>, Box<dyn Error>> {
+    let srs_path = srs_path
+        .unwrap_or_else(|| (EZKL_SRS_REPO_PATH.clone() + &format!("/ipa{}.srs", logrows)).into());
+    if !srs_path.exists() {
+        info!("generating ipa params for {} logrows", logrows);
+        if let Some(parent) = srs_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let params = gen_srs::<IPACommitmentScheme<G1Affine>>(logrows);
+        save_params::<IPACommitmentScheme<G1Affine>>(&srs_path, &params)?;
+    }
+    let mut params = load_srs::<IPACommitmentScheme<G1Affine>>(srs_path)?;
+    if logrows < params.k() {
+        info!("downsizing params to {} logrows", logrows);
+        params.downsize(logrows);
+    }
+    Ok(params)
+}
@@ -0,0 +1,395 @@
+//! A C ABI over proving and verification, for hosts (Go, Java, Swift, ...) that link ezkl
+//! directly. Every function returns [EZKL_OK] on success and a nonzero status otherwise, the
+//! message of the last failure on the calling thread can be read with [ezkl_last_error]. Panics
+//! don't unwind into the host, they are reported as [EZKL_PANIC].
+//!
+//! The header for these functions is `include/ezkl.h`.
+
+use crate::circuit::CheckMode;
+use crate::error::EzklError;
+use crate::execute::CommitmentParams;
+use crate::graph::{GraphCircuit, GraphWitness};
+use crate::pfsys::{load_pk, load_vk, ProofType, Snark};
+use halo2_proofs::plonk::{ProvingKey, VerifyingKey};
+use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// The call succeeded
+pub const EZKL_OK: i32 = 0;
+/// A pointer argument was null or a string wasn't valid utf-8
+pub const EZKL_INVALID_ARGUMENT: i32 = -1;
+/// The output buffer is too small, the required length has been written to `out_len`
+pub const EZKL_BUFFER_TOO_SMALL: i32 = -2;
+/// The proof was well formed but did not verify
+pub const EZKL_NOT_VERIFIED: i32 = -3;
+/// ezkl panicked, the handle passed in (if any) should not be used again
+pub const EZKL_PANIC: i32 = -4;
+
+thread_local! {
+    static LAST_ERROR: RefCell<String> = RefCell::new(String::new());
+}
+
+/// Records the error for [ezkl_last_error] and returns its status code
+fn fail(e: EzklError) -> i32 {
+    let code = e.code() as i32;
+    LAST_ERROR.with(|last| *last.borrow_mut() = e.to_string());
+    code
+}
+
+fn invalid_argument(msg: &str) -> i32 {
+    LAST_ERROR.with(|last| *last.borrow_mut() = msg.to_string());
+    EZKL_INVALID_ARGUMENT
+}
+
+fn invalid_buffer(needed: usize, cap: usize) -> i32 {
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = format!("proof needs {} bytes but the buffer has {}", needed, cap)
+    });
+    EZKL_BUFFER_TOO_SMALL
+}
+
+/// Runs the body of an entry point, returning `on_panic` if it panics as unwinding across the C
+/// ABI is undefined behaviour
+fn catch_panic<T>(on_panic: T, f: impl FnOnce() -> T) -> T {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let msg = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".into());
+            LAST_ERROR.with(|last| *last.borrow_mut() = format!("ezkl panicked: {}", msg));
+            on_panic
+        }
+    }
+}
+
+unsafe fn path_arg(ptr: *const c_char) -> Option<PathBuf> {
+    if ptr.is_null() {
+        return None;
+    }
+    CStr::from_ptr(ptr).to_str().ok().map(PathBuf::from)
+}
+
+unsafe fn bytes_arg<'a>(ptr: *const u8, len: usize) -> Option<&'a [u8]> {
+    if ptr.is_null() {
+        return None;
+    }
+    Some(std::slice::from_raw_parts(ptr, len))
+}
+
+/// An opaque handle holding a compiled circuit with its keys and srs
+#[derive(Debug)]
+pub struct EzklProver {
+    circuit: GraphCircuit,
+    params: CommitmentParams,
+    pk: ProvingKey<G1Affine>,
+    vk: VerifyingKey<G1Affine>,
+    /// the witness and proof of the last [ezkl_prove] call whose buffer was too small, handed out
+    /// when the caller retries with the same witness instead of proving again
+    pending_proof: Mutex<Option<(Vec<u8>, Vec<u8>)>>,
+}
+
+impl EzklProver {
+    fn load(
+        compiled_circuit: PathBuf,
+        pk_path: PathBuf,
+        vk_path: PathBuf,
+        srs_path: Option<PathBuf>,
+    ) -> Result<Self, EzklError> {
+        let circuit = GraphCircuit::load(compiled_circuit)?;
+        let settings = circuit.settings().clone();
+        let params = CommitmentParams::load(srs_path, &settings)?;
+        let pk =
+            load_pk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(pk_path, settings.clone())?;
+        let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, settings)?;
+        Ok(Self {
+            circuit,
+            params,
+            pk,
+            vk,
+            pending_proof: Mutex::new(None),
+        })
+    }
+}
+
+/// Loads a compiled circuit, proving key, verifying key and srs (which may be null to use the
+/// default srs path) and writes a handle to `out`. The handle must be released with
+/// [ezkl_prover_free].
+///
+/// # Safety
+/// The paths must be null or nul-terminated strings and `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ezkl_prover_load(
+    compiled_circuit: *const c_char,
+    pk_path: *const c_char,
+    vk_path: *const c_char,
+    srs_path: *const c_char,
+    out: *mut *mut EzklProver,
+) -> i32 {
+    catch_panic(EZKL_PANIC, || {
+        let (Some(compiled_circuit), Some(pk_path), Some(vk_path)) = (
+            path_arg(compiled_circuit),
+            path_arg(pk_path),
+            path_arg(vk_path),
+        ) else {
+            return invalid_argument("compiled circuit, pk and vk paths are required");
+        };
+        if out.is_null() {
+            return invalid_argument("out is null");
+        }
+        match EzklProver::load(compiled_circuit, pk_path, vk_path, path_arg(srs_path)) {
+            Ok(prover) => {
+                *out = Box::into_raw(Box::new(prover));
+                EZKL_OK
+            }
+            Err(e) => fail(e),
+        }
+    })
+}
+
+/// Releases a handle returned by [ezkl_prover_load]. Passing null is a no-op.
+///
+/// # Safety
+/// The handle must have come from [ezkl_prover_load] and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn ezkl_prover_free(prover: *mut EzklProver) {
+    catch_panic((), || {
+        if !prover.is_null() {
+            drop(Box::from_raw(prover));
+        }
+    })
+}
+
+/// Proves a json witness and writes the json proof into the caller allocated `out` buffer of
+/// `out_cap` bytes. The proof length is written to `out_len`; if it exceeds `out_cap` nothing
+/// is written to `out` and [EZKL_BUFFER_TOO_SMALL] is returned so the caller can retry. The
+/// proof is kept on the handle, so retrying with the same witness doesn't prove it again.
+///
+/// # Safety
+/// `prover` must be a live handle, `witness` must point to `witness_len` readable bytes, `out`
+/// to `out_cap` writable bytes and `out_len` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn ezkl_prove(
+    prover: *const EzklProver,
+    witness: *const u8,
+    witness_len: usize,
+    out: *mut u8,
+    out_cap: usize,
+    out_len: *mut usize,
+) -> i32 {
+    catch_panic(EZKL_PANIC, || {
+        let (Some(prover), Some(witness)) = (prover.as_ref(), bytes_arg(witness, witness_len))
+        else {
+            return invalid_argument("prover and witness are required");
+        };
+        if out_len.is_null() || (out.is_null() && out_cap > 0) {
+            return invalid_argument("out and out_len are required");
+        }
+
+        let pending = prover
+            .pending_proof
+            .lock()
+            .unwrap()
+            .take()
+            .filter(|(pending_witness, _)| pending_witness == witness)
+            .map(|(_, proof)| proof);
+        let proof = match pending {
+            Some(proof) => Ok(proof),
+            None => serde_json::from_slice::<GraphWitness>(witness)
+                .map_err(|e| EzklError::Other(format!("invalid witness: {}", e)))
+                .and_then(|witness| {
+                    let snark = prover.params.prove(
+                        witness,
+                        prover.circuit.clone(),
+                        &prover.pk,
+                        ProofType::Single,
+                        CheckMode::UNSAFE,
+                    )?;
+                    serde_json::to_vec(&snark).map_err(|e| EzklError::Other(e.to_string()))
+                }),
+        };
+
+        match proof {
+            Ok(proof) => {
+                *out_len = proof.len();
+                if proof.len() > out_cap {
+                    let needed = proof.len();
+                    *prover.pending_proof.lock().unwrap() = Some((witness.to_vec(), proof));
+                    return invalid_buffer(needed, out_cap);
+                }
+                std::ptr::copy_nonoverlapping(proof.as_ptr(), out, proof.len());
+                EZKL_OK
+            }
+            Err(e) => fail(e),
+        }
+    })
+}
+
+/// Verifies a json proof against the handle's verifying key, with the commitment and multiopen
+/// scheme of the circuit's settings. Returns [EZKL_OK] if the proof verifies and
+/// [EZKL_NOT_VERIFIED] if it doesn't.
+///
+/// # Safety
+/// `prover` must be a live handle and `proof` must point to `proof_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ezkl_verify(
+    prover: *const EzklProver,
+    proof: *const u8,
+    proof_len: usize,
+) -> i32 {
+    catch_panic(EZKL_PANIC, || {
+        let (Some(prover), Some(proof)) = (prover.as_ref(), bytes_arg(proof, proof_len)) else {
+            return invalid_argument("prover and proof are required");
+        };
+        let snark: Snark<Fr, G1Affine> = match serde_json::from_slice(proof) {
+            Ok(snark) => snark,
+            Err(e) => return fail(EzklError::Other(format!("invalid proof: {}", e))),
+        };
+        let multiopen = prover.circuit.settings().run_args.multiopen;
+        match prover.params.verify(snark, &prover.vk, multiopen) {
+            Ok(_) => EZKL_OK,
+            Err(e) => {
+                LAST_ERROR.with(|last| *last.borrow_mut() = e.to_string());
+                EZKL_NOT_VERIFIED
+            }
+        }
+    })
+}
+
+/// Copies the nul-terminated message of the last failure on this thread into `out` (truncated
+/// to `out_cap` bytes) and returns the full message length, excluding the terminator.
+///
+/// # Safety
+/// `out` must be null or point to `out_cap` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ezkl_last_error(out: *mut c_char, out_cap: usize) -> usize {
+    catch_panic(0, || {
+        LAST_ERROR.with(|last| {
+            let last = last.borrow();
+            if !out.is_null() && out_cap > 0 {
+                let n = std::cmp::min(last.len(), out_cap - 1);
+                std::ptr::copy_nonoverlapping(last.as_ptr() as *const c_char, out, n);
+                *out.add(n) = 0;
+            }
+            last.len()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::input::GraphData;
+    use crate::pfsys::srs::gen_srs;
+    use crate::pfsys::{save_params, save_pk, save_vk};
+    use crate::RunArgs;
+    use std::ffi::CString;
+    use std::path::Path;
+
+    fn c_path(path: &Path) -> CString {
+        CString::new(path.to_str().unwrap()).unwrap()
+    }
+
+    fn last_error() -> String {
+        let mut buf = vec![0u8; 256];
+        let len = unsafe { ezkl_last_error(buf.as_mut_ptr() as *mut c_char, buf.len()) };
+        buf.truncate(std::cmp::min(len, buf.len() - 1));
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_prove_and_verify_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let model = Path::new("./examples/onnx/1l_relu/network.onnx");
+        let run_args = RunArgs {
+            logrows: 12,
+            ..Default::default()
+        };
+        let settings = crate::api::gen_settings(model, &run_args).unwrap();
+        let mut circuit = crate::api::compile(model, &settings).unwrap();
+        let params = gen_srs::<KZGCommitmentScheme<Bn256>>(settings.run_args.logrows);
+        let pk = crate::api::setup(&circuit, &params).unwrap();
+        let inputs = GraphData::from_path("./examples/onnx/1l_relu/input.json".into()).unwrap();
+        let witness = crate::api::gen_witness(&mut circuit, &inputs, None, None)
+            .await
+            .unwrap();
+        let witness = serde_json::to_vec(&witness).unwrap();
+
+        let (circuit_path, pk_path, vk_path, srs_path) = (
+            dir.path().join("network.ezkl"),
+            dir.path().join("pk.key"),
+            dir.path().join("vk.key"),
+            dir.path().join("kzg.srs"),
+        );
+        circuit.save(circuit_path.clone()).unwrap();
+        save_pk::<KZGCommitmentScheme<Bn256>>(&pk_path, &pk).unwrap();
+        save_vk::<KZGCommitmentScheme<Bn256>>(&vk_path, pk.get_vk()).unwrap();
+        save_params::<KZGCommitmentScheme<Bn256>>(&srs_path, &params).unwrap();
+
+        unsafe {
+            let mut prover = std::ptr::null_mut();
+            let status = ezkl_prover_load(
+                c_path(&circuit_path).as_ptr(),
+                c_path(&pk_path).as_ptr(),
+                c_path(&vk_path).as_ptr(),
+                c_path(&srs_path).as_ptr(),
+                &mut prover,
+            );
+            assert_eq!(status, EZKL_OK, "{}", last_error());
+
+            // asking for the length first
+            let mut proof_len = 0;
+            let status = ezkl_prove(
+                prover,
+                witness.as_ptr(),
+                witness.len(),
+                std::ptr::null_mut(),
+                0,
+                &mut proof_len,
+            );
+            assert_eq!(status, EZKL_BUFFER_TOO_SMALL);
+            assert!((*prover).pending_proof.lock().unwrap().is_some());
+            let mut proof = vec![0u8; proof_len];
+            let status = ezkl_prove(
+                prover,
+                witness.as_ptr(),
+                witness.len(),
+                proof.as_mut_ptr(),
+                proof.len(),
+                &mut proof_len,
+            );
+            assert_eq!(status, EZKL_OK, "{}", last_error());
+            // the retry hands out the proof made for the length query
+            assert!((*prover).pending_proof.lock().unwrap().is_none());
+            assert_eq!(ezkl_verify(prover, proof.as_ptr(), proof.len()), EZKL_OK);
+
+            // a proof of other outputs doesn't verify
+            let mut snark: Snark<Fr, G1Affine> = serde_json::from_slice(&proof).unwrap();
+            snark.instances[0][0] += Fr::one();
+            let tampered = serde_json::to_vec(&snark).unwrap();
+            assert_eq!(
+                ezkl_verify(prover, tampered.as_ptr(), tampered.len()),
+                EZKL_NOT_VERIFIED
+            );
+
+            assert_eq!(
+                ezkl_verify(prover, std::ptr::null(), 0),
+                EZKL_INVALID_ARGUMENT
+            );
+            ezkl_prover_free(prover);
+        }
+    }
+
+    #[test]
+    fn test_panics_become_errors() {
+        let status = catch_panic(EZKL_PANIC, || -> i32 { panic!("boom") });
+        assert_eq!(status, EZKL_PANIC);
+        assert_eq!(last_error(), "ezkl panicked: boom");
+    }
+}
@@ -0,0 +1,46 @@
+use log::{info, warn};
+use std::path::Path;
+
+/// The environment variable halo2's icicle backend checks before running MSMs and NTTs on the gpu
+pub const ENABLE_ICICLE_GPU: &str = "ENABLE_ICICLE_GPU";
+/// Set to keep proving on the cpu even when a gpu is available
+pub const EZKL_DISABLE_GPU: &str = "EZKL_DISABLE_GPU";
+
+/// Whether an NVIDIA gpu is visible to the process
+fn cuda_device_available() -> bool {
+    Path::new("/dev/nvidia0").exists()
+        || Path::new("/proc/driver/nvidia/gpus")
+            .read_dir()
+            .map_or(false, |mut gpus| gpus.next().is_some())
+}
+
+/// Routes the MSMs and NTTs of proving to the gpu when one is available and to the cpu when
+/// there is none, so a gpu build runs anywhere. An explicit `ENABLE_ICICLE_GPU` is left as is,
+/// otherwise proving stays on the cpu if `EZKL_DISABLE_GPU` is set.
+pub fn init_gpu() {
+    if std::env::var(ENABLE_ICICLE_GPU).is_ok() {
+        if std::env::var(EZKL_DISABLE_GPU).is_ok() {
+            warn!(
+                "both {} and {} are set, keeping {}",
+                ENABLE_ICICLE_GPU, EZKL_DISABLE_GPU, ENABLE_ICICLE_GPU
+            );
+        }
+        if !cuda_device_available() {
+            warn!(
+                "{} is set but no NVIDIA gpu was found, proving may fail",
+                ENABLE_ICICLE_GPU
+            );
+        }
+        info!("Running with ICICLE GPU, {} is set", ENABLE_ICICLE_GPU);
+        return;
+    }
+
+    if std::env::var(EZKL_DISABLE_GPU).is_ok() {
+        info!("Running with CPU, {} is set", EZKL_DISABLE_GPU);
+    } else if cuda_device_available() {
+        std::env::set_var(ENABLE_ICICLE_GPU, "true");
+        info!("Running with ICICLE GPU");
+    } else {
+        info!("Running with CPU");
+    }
+}
@@ -0,0 +1,501 @@
+use std::collections::BTreeMap;
+
+use halo2curves::bn256::Fr as Fp;
+use serde::{Deserialize, Serialize};
+
+use super::model::{Model, NodeIssue, NodeType};
+use super::node::SupportedOp;
+use super::utilities::scale_to_multiplier;
+use super::MAX_LOOKUP_ABS;
+use crate::circuit::hybrid::HybridOp;
+use crate::circuit::lookup::LookupOp;
+use crate::circuit::poly::PolyOp;
+use crate::circuit::Op;
+use crate::fieldutils::{felt_to_i128, i128_to_felt};
+use crate::tensor::Tensor;
+
+/// Tables wider than this are sampled rather than evaluated at every point when bounding the
+/// output of a lookup.
+const MAX_EVALUATED_LOOKUP_INPUTS: i128 = 1 << 20;
+
+/// The range of quantized values a tensor can hold
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Interval {
+    /// the smallest value
+    pub min: i128,
+    /// the largest value
+    pub max: i128,
+}
+
+impl Interval {
+    /// The interval between `a` and `b`, in either order
+    pub fn new(a: i128, b: i128) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// The interval holding only `x`
+    pub fn point(x: i128) -> Self {
+        Self::new(x, x)
+    }
+
+    /// The smallest interval holding both intervals
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    /// True if every value of `other` is in the interval
+    pub fn contains(&self, other: &Self) -> bool {
+        self.min <= other.min && other.max <= self.max
+    }
+
+    /// `x + y` for x and y in each interval, None if it can overflow
+    pub fn add(&self, other: &Self) -> Option<Self> {
+        Some(Self::new(
+            self.min.checked_add(other.min)?,
+            self.max.checked_add(other.max)?,
+        ))
+    }
+
+    /// `x - y` for x and y in each interval, None if it can overflow
+    pub fn sub(&self, other: &Self) -> Option<Self> {
+        Some(Self::new(
+            self.min.checked_sub(other.max)?,
+            self.max.checked_sub(other.min)?,
+        ))
+    }
+
+    /// `x * y` for x and y in each interval, None if it can overflow
+    pub fn mul(&self, other: &Self) -> Option<Self> {
+        let corners = [
+            self.min.checked_mul(other.min)?,
+            self.min.checked_mul(other.max)?,
+            self.max.checked_mul(other.min)?,
+            self.max.checked_mul(other.max)?,
+        ];
+        Some(Self::new(*corners.iter().min()?, *corners.iter().max()?))
+    }
+
+    /// The sum of `n` values in the interval, None if it can overflow
+    pub fn sum(&self, n: usize) -> Option<Self> {
+        self.mul(&Self::point(n as i128))
+    }
+
+    /// `x^n` for x in the interval, None if it can overflow
+    pub fn pow(&self, n: u32) -> Option<Self> {
+        let mut res = Self::point(1);
+        for _ in 0..n {
+            res = res.mul(self)?;
+        }
+        // even powers are never negative
+        if n % 2 == 0 && res.min < 0 {
+            res.min = 0;
+        }
+        Some(res)
+    }
+}
+
+/// The number of products summed into each output of an einsum: the product of the sizes of the
+/// indices that are contracted away.
+pub fn einsum_contraction_len(equation: &str, input_dims: &[Vec<usize>]) -> Option<usize> {
+    let (inputs, output) = equation.split_once("->")?;
+    let mut sizes = BTreeMap::new();
+    for (letters, dims) in inputs.split(',').zip(input_dims) {
+        for (c, d) in letters.chars().zip(dims) {
+            sizes.insert(c, *d);
+        }
+    }
+    Some(
+        sizes
+            .iter()
+            .filter(|(c, _)| !output.contains(**c))
+            .map(|(_, d)| *d)
+            .product(),
+    )
+}
+
+/// The range of a node's outputs (if they are bounded), found by the audit
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NodeRange {
+    /// the node's index in the graph
+    pub idx: usize,
+    /// the node's operation
+    pub op: String,
+    /// the quantized range of the node's first output, None if it can't be bounded
+    pub range: Option<Interval>,
+}
+
+struct Auditor<'a> {
+    lookup_range: Interval,
+    input_bound: f64,
+    nodes: &'a BTreeMap<usize, NodeType>,
+    ranges: BTreeMap<usize, Vec<Option<Interval>>>,
+    issues: Vec<NodeIssue>,
+    lookup_inputs: Vec<Option<Interval>>,
+}
+
+impl<'a> Auditor<'a> {
+    fn issue(&mut self, idx: usize, reason: String) {
+        let node = &self.nodes[&idx];
+        self.issues.push(NodeIssue {
+            idx,
+            name: node.name(),
+            op: node.as_str(),
+            out_dims: node.out_dims(),
+            reason,
+        });
+    }
+
+    /// Flags a lookup whose inputs can fall outside of the table
+    fn check_lookup(&mut self, idx: usize, what: &str, input: Option<Interval>) {
+        self.lookup_inputs.push(input);
+        match input {
+            Some(input) if self.lookup_range.contains(&input) => {}
+            Some(input) => self.issue(
+                idx,
+                format!(
+                    "{} inputs can reach [{}, {}], outside of the lookup range [{}, {}]",
+                    what, input.min, input.max, self.lookup_range.min, self.lookup_range.max
+                ),
+            ),
+            None => self.issue(
+                idx,
+                format!("{} inputs can't be bounded within the lookup range", what),
+            ),
+        }
+    }
+
+    /// The range of a lookup's outputs over the part of its inputs that lies in the table
+    fn lookup_output(&self, op: &LookupOp, input: Option<Interval>) -> Option<Interval> {
+        let min = input?.min.max(self.lookup_range.min);
+        let max = input?.max.min(self.lookup_range.max);
+        if min > max {
+            return None;
+        }
+        let step = ((max - min) / MAX_EVALUATED_LOOKUP_INPUTS).max(1);
+        let points = (0..=(max - min) / step)
+            .map(|i| min + i * step)
+            .chain(std::iter::once(max))
+            .map(i128_to_felt::<Fp>);
+        let res = Op::<Fp>::f(op, &[Tensor::from(points)]).ok()?;
+        let outputs = res.output.iter().map(|x| felt_to_i128(*x));
+        Some(Interval::new(outputs.clone().min()?, outputs.max()?))
+    }
+
+    fn constant_range(values: &Tensor<Fp>) -> Option<Interval> {
+        let values = values.iter().map(|x| felt_to_i128(*x));
+        Some(Interval::new(values.clone().min()?, values.max()?))
+    }
+
+    fn union(inputs: &[Option<Interval>]) -> Option<Interval> {
+        inputs
+            .iter()
+            .copied()
+            .reduce(|a, b| Some(a?.union(&b?)))
+            .flatten()
+    }
+
+    fn poly(
+        &mut self,
+        op: &PolyOp<Fp>,
+        inputs: &[Option<Interval>],
+        input_dims: &[Vec<usize>],
+    ) -> Option<Interval> {
+        let first = *inputs.first()?;
+        match op {
+            PolyOp::Add => inputs
+                .iter()
+                .try_fold(Interval::point(0), |acc, x| acc.add(&(*x)?)),
+            PolyOp::Sub => first?.sub(&(*inputs.get(1)?)?),
+            PolyOp::Neg => Interval::point(0).sub(&first?),
+            PolyOp::Mult => inputs
+                .iter()
+                .try_fold(Interval::point(1), |acc, x| acc.mul(&(*x)?)),
+            PolyOp::Einsum { equation } => inputs
+                .iter()
+                .try_fold(Interval::point(1), |acc, x| acc.mul(&(*x)?))?
+                .sum(einsum_contraction_len(equation, input_dims)?),
+            PolyOp::Conv { kernel, bias, .. } | PolyOp::DeConv { kernel, bias, .. } => {
+                // each output accumulates at most one product per kernel element of an output
+                // channel (deconvolutions are bounded by the whole kernel)
+                let accumulated = match op {
+                    PolyOp::Conv { .. } => kernel.len() / kernel.dims().first().copied()?.max(1),
+                    _ => kernel.len(),
+                };
+                let res = first?
+                    .mul(&Self::constant_range(kernel)?)?
+                    .sum(accumulated)?;
+                match bias {
+                    Some(bias) => res.add(&Self::constant_range(bias)?),
+                    None => Some(res),
+                }
+            }
+            PolyOp::Sum { axes } => {
+                let dims = input_dims.first()?;
+                first?.sum(
+                    axes.iter()
+                        .map(|a| dims.get(*a).copied().unwrap_or(1))
+                        .product(),
+                )
+            }
+            // the first output along the axis holds a single element, the last sums all of them
+            PolyOp::CumSum { axis } => {
+                let first = first?;
+                Some(
+                    first
+                        .sum(input_dims.first()?.get(*axis).copied()?)?
+                        .union(&first),
+                )
+            }
+            PolyOp::GlobalSumPool => first?.sum(input_dims.first()?.iter().skip(2).product()),
+            PolyOp::Prod { len_prod, .. } => first?.pow(*len_prod as u32),
+            PolyOp::Pow(n) => first?.pow(*n),
+            // the interpolation weights of each resized axis sum to 2 * factor
+            PolyOp::ResizeBilinear { scale_factor } => first?.mul(&Interval::point(
+                scale_factor
+                    .iter()
+                    .filter(|s| **s > 1)
+                    .map(|s| 2 * *s as i128)
+                    .product(),
+            )),
+            PolyOp::Iff => Self::union(&inputs[1..]),
+            PolyOp::Not | PolyOp::And | PolyOp::Or | PolyOp::Xor => Some(Interval::new(0, 1)),
+            PolyOp::Pad(_) => Some(first?.union(&Interval::point(0))),
+            // packing folds a whole tensor into a single element
+            PolyOp::Pack(..) => None,
+            PolyOp::MultiBroadcastTo { .. }
+            | PolyOp::Downsample { .. }
+            | PolyOp::Identity
+            | PolyOp::Reshape(_)
+            | PolyOp::MoveAxis { .. }
+            | PolyOp::Flatten(_)
+            | PolyOp::Slice { .. }
+            | PolyOp::Resize { .. }
+            | PolyOp::Concat { .. } => Self::union(inputs),
+        }
+    }
+
+    fn hybrid(
+        &mut self,
+        idx: usize,
+        op: &HybridOp,
+        inputs: &[Option<Interval>],
+        input_dims: &[Vec<usize>],
+    ) -> Option<Interval> {
+        let first = *inputs.first()?;
+        // comparisons look up the difference of their operands
+        let spread = first.and_then(|x| x.sub(&x));
+        match op {
+            HybridOp::ReduceMax { .. }
+            | HybridOp::ReduceMin { .. }
+            | HybridOp::MaxPool2d { .. }
+            | HybridOp::TopK { .. } => {
+                self.check_lookup(idx, "comparison", spread);
+                first
+            }
+            HybridOp::ReduceArgMax { dim }
+            | HybridOp::ReduceArgMin { dim }
+            | HybridOp::TopKIndices { dim, .. } => {
+                self.check_lookup(idx, "comparison", spread);
+                let len = input_dims.first()?.get(*dim).copied()?;
+                Some(Interval::new(0, len.saturating_sub(1) as i128))
+            }
+            HybridOp::ReduceMean { len, .. } => {
+                self.check_lookup(idx, "division", first?.sum(*len));
+                first
+            }
+            HybridOp::SumPool {
+                kernel_shape,
+                normalized,
+                ..
+            } => {
+                let sum = first?.sum(kernel_shape.0 * kernel_shape.1);
+                if *normalized {
+                    self.check_lookup(idx, "division", sum);
+                    first
+                } else {
+                    sum
+                }
+            }
+            HybridOp::Softmax { scale, .. } => {
+                self.check_lookup(idx, "exponential", spread);
+                Some(Interval::new(0, scale.0.ceil() as i128))
+            }
+            HybridOp::RangeCheck(_)
+            | HybridOp::Greater
+            | HybridOp::GreaterEqual
+            | HybridOp::Less
+            | HybridOp::LessEqual
+            | HybridOp::Equals => {
+                let diff = match inputs.get(1) {
+                    Some(second) => first.zip(*second).and_then(|(a, b)| a.sub(&b)),
+                    None => spread,
+                };
+                self.check_lookup(idx, "comparison", diff);
+                Some(Interval::new(0, 1))
+            }
+            HybridOp::OneHot { .. } => Some(Interval::new(0, 1)),
+            HybridOp::Gather { .. } | HybridOp::GatherElements { .. } => first,
+            HybridOp::ScatterElements { .. } => Some(first?.union(&(*inputs.get(2)?)?)),
+        }
+    }
+
+    fn op(
+        &mut self,
+        idx: usize,
+        op: &SupportedOp,
+        inputs: &[Option<Interval>],
+        input_dims: &[Vec<usize>],
+        out_scale: crate::Scale,
+    ) -> Option<Interval> {
+        match op {
+            SupportedOp::Input(_) => {
+                let bound = (self.input_bound * scale_to_multiplier(out_scale)).ceil() as i128;
+                Some(Interval::new(-bound, bound))
+            }
+            SupportedOp::Constant(c) => Self::constant_range(&c.quantized_values),
+            SupportedOp::Linear(op) => self.poly(op, inputs, input_dims),
+            SupportedOp::Nonlinear(op) => {
+                self.check_lookup(idx, "lookup", inputs.first().copied().flatten());
+                self.lookup_output(op, inputs.first().copied().flatten())
+            }
+            SupportedOp::Hybrid(op) => self.hybrid(idx, op, inputs, input_dims),
+            SupportedOp::Rescaled(rescaled) => {
+                let inputs = inputs
+                    .iter()
+                    .zip(rescaled.scale.iter())
+                    .map(|(x, (_, mult))| x.and_then(|x| x.mul(&Interval::point(*mult as i128))))
+                    .collect::<Vec<_>>();
+                self.op(idx, &rescaled.inner, &inputs, input_dims, out_scale)
+            }
+            SupportedOp::RebaseScale(rebase) => {
+                let res = self.op(idx, &rebase.inner, inputs, input_dims, out_scale);
+                self.check_lookup(idx, "rescaling", res);
+                let div = |x: i128| (x as f64 / rebase.multiplier).round() as i128;
+                res.map(|r| Interval::new(div(r.min), div(r.max)))
+            }
+            SupportedOp::Unknown(_) => None,
+        }
+    }
+}
+
+impl Model {
+    /// Bounds the quantized values of every node, given that the model's inputs lie in
+    /// `[-input_bound, input_bound]`, and returns the ranges along with the nodes whose lookups
+    /// can fall outside of `lookup_range` or whose values can overflow the field.
+    ///
+    /// The bounds are conservative (every element is assumed to be able to reach the extremes of
+    /// its inputs at once), so a flagged node may be fine in practice, but an unflagged one can't
+    /// overflow for inputs within the bound.
+    pub fn audit_ranges(
+        &self,
+        lookup_range: (i128, i128),
+        input_bound: f64,
+    ) -> (Vec<NodeRange>, Vec<NodeIssue>) {
+        let (ranges, auditor) = self.audit(lookup_range, input_bound);
+        (ranges, auditor.issues)
+    }
+
+    /// The smallest range holding the inputs of every lookup, given that the model's inputs lie
+    /// in `[-input_bound, input_bound]`, or None if some lookup's inputs can't be bounded.
+    pub fn lookup_input_range(&self, input_bound: f64) -> Option<(i128, i128)> {
+        let (_, auditor) = self.audit((-MAX_LOOKUP_ABS, MAX_LOOKUP_ABS), input_bound);
+        if auditor.lookup_inputs.is_empty() {
+            return Some((0, 0));
+        }
+        let range = Auditor::union(&auditor.lookup_inputs)?;
+        Some((range.min, range.max))
+    }
+
+    fn audit(&self, lookup_range: (i128, i128), input_bound: f64) -> (Vec<NodeRange>, Auditor) {
+        let mut auditor = Auditor {
+            lookup_range: Interval::new(lookup_range.0, lookup_range.1),
+            input_bound,
+            nodes: &self.graph.nodes,
+            ranges: BTreeMap::new(),
+            issues: vec![],
+            lookup_inputs: vec![],
+        };
+
+        let mut ranges = vec![];
+        for (idx, node) in &self.graph.nodes {
+            let inputs = node
+                .inputs()
+                .iter()
+                .map(|(i, outlet)| {
+                    auditor
+                        .ranges
+                        .get(i)
+                        .and_then(|r| r.get(*outlet).copied())
+                        .flatten()
+                })
+                .collect::<Vec<_>>();
+            let input_dims = node
+                .inputs()
+                .iter()
+                .map(|(i, outlet)| {
+                    self.graph
+                        .nodes
+                        .get(i)
+                        .and_then(|n| n.out_dims().get(*outlet).cloned())
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>();
+
+            let range = match node {
+                NodeType::Node(n) => {
+                    let range = auditor.op(*idx, &n.opkind, &inputs, &input_dims, n.out_scale);
+                    if range.is_none() && !matches!(n.opkind, SupportedOp::Unknown(_)) {
+                        auditor.issue(
+                            *idx,
+                            "values can't be bounded and may exceed the field capacity".into(),
+                        );
+                    }
+                    range
+                }
+                // subgraphs are iterated an unknown number of times, so aren't bounded
+                NodeType::SubGraph { .. } => None,
+            };
+
+            ranges.push(NodeRange {
+                idx: *idx,
+                op: node.as_str(),
+                range,
+            });
+            auditor
+                .ranges
+                .insert(*idx, vec![range; node.out_dims().len().max(1)]);
+        }
+
+        (ranges, auditor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_arithmetic() {
+        let a = Interval::new(-2, 3);
+        let b = Interval::new(4, -1);
+        assert_eq!(a.add(&b), Some(Interval::new(-3, 7)));
+        assert_eq!(a.sub(&b), Some(Interval::new(-6, 4)));
+        assert_eq!(a.mul(&b), Some(Interval::new(-8, 12)));
+        assert_eq!(a.pow(2), Some(Interval::new(0, 9)));
+        assert_eq!(a.sum(3), Some(Interval::new(-6, 9)));
+        assert_eq!(Interval::point(i128::MAX).add(&Interval::point(1)), None);
+        assert!(Interval::new(-10, 10).contains(&a));
+        assert!(!a.contains(&b));
+    }
+
+    #[test]
+    fn test_einsum_contraction_len() {
+        let dims = vec![vec![2, 3], vec![3, 5]];
+        assert_eq!(einsum_contraction_len("ij,jk->ik", &dims), Some(3));
+        assert_eq!(einsum_contraction_len("ij,jk->", &dims), Some(30));
+        assert_eq!(einsum_contraction_len("ij,jk", &dims), None);
+    }
+}
@@ -13,7 +13,7 @@ use pyo3::types::PyDict;
 use pyo3::ToPyObject;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::io::Read;
+use std::io::Write;
 use std::panic::UnwindSafe;
 #[cfg(not(target_arch = "wasm32"))]
 use std::thread;
@@ -343,7 +343,8 @@ impl OnChainSource {
 pub struct CallsToAccount {
     /// A vector of tuples, where index 0 of tuples
     /// are the byte strings representing the ABI encoded function calls to
-    /// read the data from the address. This call must return a single
+    /// read the data from the address, or the function signature followed by its
+    /// arguments (e.g. `balanceOf(address) 0x...`) for ezkl to encode. This call must return a single
     /// elementary type (<https://docs.soliditylang.org/en/v0.8.20/abi-spec.html#types>).
     /// The second index of the tuple is the number of decimals for f32 conversion.
     /// We don't support dynamic types currently.
@@ -444,6 +445,35 @@ pub struct GraphData {
 
 impl UnwindSafe for GraphData {}
 
+/// Flattens a numeric or (nested) list arrow column into floats, row by row
+#[cfg(feature = "arrow")]
+fn arrow_to_floats(
+    array: &dyn arrow::array::Array,
+) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
+    use arrow::array::{Array, AsArray};
+    use arrow::datatypes::{DataType, Float64Type};
+
+    let rows: Vec<Option<std::sync::Arc<dyn Array>>> = match array.data_type() {
+        DataType::List(_) => array.as_list::<i32>().iter().collect(),
+        DataType::LargeList(_) => array.as_list::<i64>().iter().collect(),
+        DataType::FixedSizeList(_, _) => array.as_fixed_size_list().iter().collect(),
+        _ => {
+            if array.null_count() > 0 {
+                return Err("arrow input columns can't contain nulls".into());
+            }
+            let floats = arrow::compute::cast(array, &DataType::Float64)?;
+            return Ok(floats.as_primitive::<Float64Type>().values().to_vec());
+        }
+    };
+
+    let mut floats = vec![];
+    for row in rows {
+        let row = row.ok_or("arrow input columns can't contain nulls")?;
+        floats.extend(arrow_to_floats(row.as_ref())?);
+    }
+    Ok(floats)
+}
+
 impl GraphData {
     ///
     pub fn new(input_data: DataSource) -> Self {
@@ -453,18 +483,102 @@ impl GraphData {
         }
     }
 
-    /// Load the model input from a file
+    /// Load the model input from a file (or stdin if the path is `-`). With the `arrow` feature,
+    /// `.arrow`, `.arrows` and `.ipc` files are read as Arrow IPC (see [GraphData::from_arrow_ipc]).
+    /// A directory of such files (e.g a calibration dataset) is read as one input holding all
+    /// their samples, see [GraphData::concat_samples].
     pub fn from_path(path: std::path::PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut file = std::fs::File::open(path.clone())
-            .map_err(|_| format!("failed to open input at {}", path.display()))?;
-        let mut data = String::new();
-        file.read_to_string(&mut data)?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if path.is_dir() {
+            let mut files = std::fs::read_dir(&path)?
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            files.retain(|f| {
+                matches!(
+                    f.extension().and_then(|e| e.to_str()),
+                    Some("json" | "arrow" | "arrows" | "ipc")
+                )
+            });
+            files.sort();
+            if files.is_empty() {
+                return Err(format!("no input files in {}", path.display()).into());
+            }
+            let samples = files
+                .into_iter()
+                .map(Self::from_path)
+                .collect::<Result<Vec<_>, _>>()?;
+            return Self::concat_samples(&samples);
+        }
+        #[cfg(feature = "arrow")]
+        if matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("arrow" | "arrows" | "ipc")
+        ) {
+            let bytes = crate::stdio::read(&path)
+                .map_err(|e| format!("failed to open input at {}: {}", path.display(), e))?;
+            return Self::from_arrow_ipc(&bytes);
+        }
+        let data = crate::stdio::read_to_string(&path)
+            .map_err(|e| format!("failed to open input at {}: {}", path.display(), e))?;
         serde_json::from_str(&data).map_err(|e| e.into())
     }
 
-    /// Save the model input to a file
+    /// Reads Arrow IPC data (stream or file format) as file sourced input. Column `i` feeds
+    /// model input `i` and every row is one sample: a numeric column holds a single element per
+    /// sample, a list column a flattened tensor. Samples are concatenated, the way batches
+    /// are laid out in json input, so [GraphData::split_into_batches] recovers one per row.
+    #[cfg(feature = "arrow")]
+    pub fn from_arrow_ipc(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        use arrow::ipc::reader::{FileReader, StreamReader};
+        use arrow::record_batch::RecordBatch;
+
+        let batches = if bytes.starts_with(b"ARROW1") {
+            FileReader::try_new(std::io::Cursor::new(bytes), None)?
+                .collect::<Result<Vec<RecordBatch>, _>>()?
+        } else {
+            StreamReader::try_new(bytes, None)?.collect::<Result<Vec<RecordBatch>, _>>()?
+        };
+
+        let mut inputs: FileSource = vec![];
+        for batch in batches {
+            inputs.resize(batch.num_columns(), vec![]);
+            for (input, column) in inputs.iter_mut().zip(batch.columns()) {
+                input.extend(
+                    arrow_to_floats(column)?
+                        .into_iter()
+                        .map(FileSourceInner::Float),
+                );
+            }
+        }
+        Ok(GraphData::new(DataSource::File(inputs)))
+    }
+
+    /// Concatenates the file sourced inputs of several samples (or batches of samples), input by
+    /// input, the way batches are laid out in json input, so [GraphData::split_into_batches]
+    /// recovers each sample.
+    pub fn concat_samples(samples: &[Self]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut inputs: FileSource = vec![];
+        for sample in samples {
+            let DataSource::File(data) = &sample.input_data else {
+                return Err("only file sourced inputs can be concatenated".into());
+            };
+            if inputs.is_empty() {
+                inputs.resize(data.len(), vec![]);
+            } else if inputs.len() != data.len() {
+                return Err("samples have different numbers of inputs".into());
+            }
+            for (input, values) in inputs.iter_mut().zip(data) {
+                input.extend(values.iter().cloned());
+            }
+        }
+        Ok(GraphData::new(DataSource::File(inputs)))
+    }
+
+    /// Save the model input to a file (or stdout if the path is `-`)
     pub fn save(&self, path: std::path::PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-        serde_json::to_writer(std::fs::File::create(path)?, &self).map_err(|e| e.into())
+        let mut writer = crate::stdio::writer(&path)?;
+        serde_json::to_writer(&mut writer, &self)?;
+        writer.flush().map_err(|e| e.into())
     }
 
     ///
@@ -650,6 +764,25 @@ mod tests {
         assert_eq!(graph_input3, file);
     }
 
+    #[test]
+    fn test_concat_samples() {
+        let samples = [
+            GraphData::new(DataSource::from(vec![vec![1.0, 2.0], vec![3.0]])),
+            GraphData::new(DataSource::from(vec![vec![4.0, 5.0], vec![6.0]])),
+        ];
+        let data = GraphData::concat_samples(&samples).unwrap();
+        assert_eq!(
+            data,
+            GraphData::new(DataSource::from(vec![
+                vec![1.0, 2.0, 4.0, 5.0],
+                vec![3.0, 6.0]
+            ]))
+        );
+
+        let batches = data.split_into_batches(vec![vec![2], vec![1]]).unwrap();
+        assert_eq!(batches, samples);
+    }
+
     //  test for the compatibility with the serialized elements from the mclbn256 library
     #[test]
     fn test_python_compat() {
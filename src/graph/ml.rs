@@ -0,0 +1,1129 @@
+//! Lowerings for the `ai.onnx.ml` operators that scikit-learn models are exported with
+//! (`TreeEnsembleClassifier`, `TreeEnsembleRegressor`, `LinearClassifier`, `LinearRegressor`
+//! and `Scaler`).
+//!
+//! tract parses these into ops that have no circuit equivalent, so we register our own parsers
+//! which expand each of them into einsums, comparisons and element-wise ops that ezkl already
+//! lays out. A tree ensemble becomes:
+//!
+//! 1. `bits = cmp(x · S, thresholds)`: one comparison per split, where `S` selects the feature
+//!    each split looks at.
+//! 2. `leaf = (bits · D == n_true)`: `D[split, leaf]` is `+1` if the path to the leaf takes the
+//!    true branch of the split, `-1` if it takes the false branch and `0` otherwise, so exactly
+//!    the leaf whose path agrees with every comparison reaches its number of true branches.
+//! 3. `scores = leaf · W + base_values`, followed by the post transform.
+
+use super::GraphError;
+use itertools::Itertools;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use tract_onnx::model::{Onnx, ParsingContext};
+use tract_onnx::pb::NodeProto;
+use tract_onnx::tract_core::ops::cast::cast;
+use tract_onnx::tract_core::ops::change_axes::AxisOp;
+use tract_onnx::tract_core::ops::einsum::EinSum;
+use tract_onnx::tract_core::ops::nn::{Reduce, Reducer};
+use tract_onnx::tract_core::ops::{logic, math, nn};
+use tract_onnx::tract_hir::internal::*;
+
+/// Registers the `ai.onnx.ml` lowerings, replacing tract's own parsers for these ops
+pub fn register(onnx: &mut Onnx) {
+    onnx.op_register
+        .insert("TreeEnsembleClassifier", tree_ensemble_classifier);
+    onnx.op_register
+        .insert("TreeEnsembleRegressor", tree_ensemble_regressor);
+    onnx.op_register
+        .insert("LinearClassifier", linear_classifier);
+    onnx.op_register.insert("LinearRegressor", linear_regressor);
+    onnx.op_register.insert("Scaler", scaler);
+}
+
+fn hash_floats<H: Hasher>(values: &[f32], state: &mut H) {
+    values.iter().for_each(|v| v.to_bits().hash(state));
+}
+
+fn wire(
+    model: &mut TypedModel,
+    name: String,
+    op: impl Into<Box<dyn TypedOp>>,
+    inputs: &[OutletId],
+) -> TractResult<OutletId> {
+    Ok(model.wire_node(name, op, inputs)?[0])
+}
+
+fn wire_const(
+    model: &mut TypedModel,
+    name: String,
+    shape: &[usize],
+    data: &[f32],
+) -> TractResult<OutletId> {
+    model.add_const(name, Tensor::from_shape(shape, data)?)
+}
+
+fn wire_einsum(
+    model: &mut TypedModel,
+    name: String,
+    expr: &str,
+    inputs: &[OutletId],
+) -> TractResult<OutletId> {
+    wire(
+        model,
+        name,
+        EinSum::new(expr.parse()?, f32::datum_type()),
+        inputs,
+    )
+}
+
+/// The number of columns of a `[batch, features]` input
+fn input_width(model: &TypedModel, input: OutletId) -> TractResult<usize> {
+    let fact = model.outlet_fact(input)?;
+    if fact.rank() != 2 {
+        bail!("ai.onnx.ml ops expect a [batch, features] input");
+    }
+    fact.shape[1].to_usize()
+}
+
+/// `x @ weights + bias` for a `[batch, n]` input, a row major `[n, m]` weight matrix and `m`
+/// biases
+fn wire_affine(
+    prefix: &str,
+    model: &mut TypedModel,
+    x: OutletId,
+    weights: &[f32],
+    bias: &[f32],
+) -> TractResult<OutletId> {
+    let m = bias.len();
+    if m == 0 || weights.is_empty() || weights.len() % m != 0 {
+        return Err(GraphError::MisformedParams(format!(
+            "{} has {} weights for {} biases",
+            prefix,
+            weights.len(),
+            m
+        ))
+        .into());
+    }
+    let n = weights.len() / m;
+    let w = wire_const(model, format!("{}.weights", prefix), &[n, m], weights)?;
+    let b = wire_const(model, format!("{}.bias", prefix), &[1, m], bias)?;
+    let xw = wire_einsum(model, format!("{}.matmul", prefix), "nk,km->nm", &[x, w])?;
+    wire(model, format!("{}.add", prefix), math::add(), &[xw, b])
+}
+
+/// Errors unless every attribute in `lengths` has as many entries as the first one
+fn check_lengths(op_type: &str, lengths: &[(&str, usize)]) -> TractResult<()> {
+    let Some((first, n)) = lengths.first() else {
+        return Ok(());
+    };
+    if let Some((name, len)) = lengths.iter().find(|(_, len)| len != n) {
+        return Err(GraphError::MisformedParams(format!(
+            "{} has {} {} but {} {}",
+            op_type, len, name, n, first
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// An id attribute as an index, erroring on negative ids
+fn index_attr(op_type: &str, name: &str, id: i64) -> TractResult<usize> {
+    usize::try_from(id).map_err(|_| {
+        GraphError::MisformedParams(format!("{} has a negative {} {}", op_type, name, id)).into()
+    })
+}
+
+/// The transform applied to raw scores
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+enum PostTransform {
+    None,
+    Logistic,
+    Softmax,
+}
+
+impl PostTransform {
+    fn parse(node: &NodeProto) -> TractResult<Self> {
+        match node
+            .get_attr_opt::<&str>("post_transform")?
+            .unwrap_or("NONE")
+        {
+            "NONE" => Ok(PostTransform::None),
+            "LOGISTIC" => Ok(PostTransform::Logistic),
+            "SOFTMAX" => Ok(PostTransform::Softmax),
+            other => bail!("post_transform {} is not supported", other),
+        }
+    }
+
+    fn wire(self, prefix: &str, model: &mut TypedModel, scores: OutletId) -> TractResult<OutletId> {
+        match self {
+            PostTransform::None => Ok(scores),
+            PostTransform::Logistic => wire(
+                model,
+                format!("{}.sigmoid", prefix),
+                nn::sigmoid(),
+                &[scores],
+            ),
+            PostTransform::Softmax => {
+                let exp = wire(model, format!("{}.exp", prefix), math::exp(), &[scores])?;
+                let sum = wire(
+                    model,
+                    format!("{}.sum", prefix),
+                    Reduce::new(tvec![1], Reducer::Sum),
+                    &[exp],
+                )?;
+                let recip = wire(model, format!("{}.recip", prefix), math::recip(), &[sum])?;
+                wire(
+                    model,
+                    format!("{}.softmax", prefix),
+                    math::mul(),
+                    &[exp, recip],
+                )
+            }
+        }
+    }
+}
+
+/// Reads the integer class labels of a classifier, which must be `0..n` so that the label is the
+/// index of the best score
+fn class_labels(node: &NodeProto) -> TractResult<usize> {
+    let Some(labels) = node
+        .get_attr_opt_vec::<i64>("classlabels_ints")?
+        .or(node.get_attr_opt_vec::<i64>("classlabels_int64s")?)
+    else {
+        bail!("{} only supports integer class labels", node.op_type);
+    };
+    if labels.iter().enumerate().any(|(i, l)| *l != i as i64) {
+        bail!("{} class labels must be 0..{}", node.op_type, labels.len());
+    }
+    Ok(labels.len())
+}
+
+/// A classifier outputs the label of the best class and the (transformed) scores of every class
+fn wire_classifier_outputs(
+    prefix: &str,
+    model: &mut TypedModel,
+    scores: OutletId,
+    post_transform: PostTransform,
+) -> TractResult<TVec<OutletId>> {
+    let scores = post_transform.wire(prefix, model, scores)?;
+    let label = wire(
+        model,
+        format!("{}.argmax", prefix),
+        Reduce::new(tvec![1], Reducer::ArgMax(false)),
+        &[scores],
+    )?;
+    let label = wire(model, format!("{}.label", prefix), AxisOp::Rm(1), &[label])?;
+    Ok(tvec![label, scores])
+}
+
+fn classifier_rules<'r, 'p: 'r>(
+    s: &mut Solver<'r>,
+    inputs: &'p [TensorProxy],
+    outputs: &'p [TensorProxy],
+    n_classes: usize,
+) -> InferenceResult {
+    check_input_arity(inputs, 1)?;
+    check_output_arity(outputs, 2)?;
+    s.equals(&inputs[0].rank, 2)?;
+    s.equals(&outputs[0].datum_type, i64::datum_type())?;
+    s.equals(&outputs[0].rank, 1)?;
+    s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+    s.equals(&outputs[1].datum_type, f32::datum_type())?;
+    s.equals(&outputs[1].rank, 2)?;
+    s.equals(&outputs[1].shape[0], &inputs[0].shape[0])?;
+    s.equals(&outputs[1].shape[1], n_classes.to_dim())?;
+    Ok(())
+}
+
+fn regressor_rules<'r, 'p: 'r>(
+    s: &mut Solver<'r>,
+    inputs: &'p [TensorProxy],
+    outputs: &'p [TensorProxy],
+    n_targets: usize,
+) -> InferenceResult {
+    check_input_arity(inputs, 1)?;
+    check_output_arity(outputs, 1)?;
+    s.equals(&inputs[0].rank, 2)?;
+    s.equals(&outputs[0].datum_type, f32::datum_type())?;
+    s.equals(&outputs[0].rank, 2)?;
+    s.equals(&outputs[0].shape[0], &inputs[0].shape[0])?;
+    s.equals(&outputs[0].shape[1], n_targets.to_dim())?;
+    Ok(())
+}
+
+/// Binary classifiers are exported with the scores of the positive class only, onnxruntime then
+/// reports `[-s, s]` (or `[1 - p, p]` after a logistic transform) as the two class scores
+fn expand_binary(weights: &[f32], bias: &[f32]) -> (Vec<f32>, Vec<f32>) {
+    let weights = weights.iter().flat_map(|w| [-w, *w]).collect();
+    let bias = vec![-bias[0], bias[0]];
+    (weights, bias)
+}
+
+/// The comparison a split applies to its feature, `x <mode> threshold` takes the true branch
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+enum BranchMode {
+    Leq,
+    Lt,
+    Gte,
+    Gt,
+}
+
+impl BranchMode {
+    fn parse(mode: &str) -> TractResult<Self> {
+        match mode {
+            "BRANCH_LEQ" => Ok(BranchMode::Leq),
+            "BRANCH_LT" => Ok(BranchMode::Lt),
+            "BRANCH_GTE" => Ok(BranchMode::Gte),
+            "BRANCH_GT" => Ok(BranchMode::Gt),
+            other => bail!("tree ensemble split mode {} is not supported", other),
+        }
+    }
+
+    fn wire(
+        self,
+        name: String,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<OutletId> {
+        match self {
+            BranchMode::Leq => wire(model, name, logic::less_equal(), inputs),
+            BranchMode::Lt => wire(model, name, logic::less(), inputs),
+            BranchMode::Gte => wire(model, name, logic::greater_equal(), inputs),
+            BranchMode::Gt => wire(model, name, logic::greater(), inputs),
+        }
+    }
+}
+
+/// A tree ensemble flattened into its splits and the paths to its leaves
+#[derive(Clone, Debug)]
+struct TreeEnsemble {
+    /// `TreeEnsembleClassifier` or `TreeEnsembleRegressor`
+    op_type: String,
+    /// the feature each split compares
+    features: Vec<usize>,
+    /// the threshold of each split
+    thresholds: Vec<f32>,
+    mode: BranchMode,
+    /// for each leaf, the splits on its path and whether the path takes their true branch
+    paths: Vec<Vec<(usize, bool)>>,
+    /// row major `[leaves, targets]` leaf weights
+    weights: Vec<f32>,
+    base_values: Vec<f32>,
+    post_transform: PostTransform,
+    /// the number of classes of a classifier, None for a regressor
+    n_classes: Option<usize>,
+}
+
+impl Hash for TreeEnsemble {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.op_type.hash(state);
+        self.features.hash(state);
+        hash_floats(&self.thresholds, state);
+        self.mode.hash(state);
+        self.paths.hash(state);
+        hash_floats(&self.weights, state);
+        hash_floats(&self.base_values, state);
+        self.post_transform.hash(state);
+        self.n_classes.hash(state);
+    }
+}
+
+impl_dyn_hash!(TreeEnsemble);
+
+impl TreeEnsemble {
+    /// Flattens the trees of a node, `target` is the attribute prefix of the leaf weights
+    /// (`class` for classifiers and `target` for regressors)
+    fn parse(node: &NodeProto, target: &str, n_classes: Option<usize>) -> TractResult<Self> {
+        let tree_ids = node.get_attr_vec::<i64>("nodes_treeids")?;
+        let node_ids = node.get_attr_vec::<i64>("nodes_nodeids")?;
+        let feature_ids = node.get_attr_vec::<i64>("nodes_featureids")?;
+        let values = node.get_attr_vec::<f32>("nodes_values")?;
+        let modes = node.get_attr_vec::<&str>("nodes_modes")?;
+        let true_ids = node.get_attr_vec::<i64>("nodes_truenodeids")?;
+        let false_ids = node.get_attr_vec::<i64>("nodes_falsenodeids")?;
+        check_lengths(
+            &node.op_type,
+            &[
+                ("nodes_treeids", tree_ids.len()),
+                ("nodes_nodeids", node_ids.len()),
+                ("nodes_featureids", feature_ids.len()),
+                ("nodes_values", values.len()),
+                ("nodes_modes", modes.len()),
+                ("nodes_truenodeids", true_ids.len()),
+                ("nodes_falsenodeids", false_ids.len()),
+            ],
+        )?;
+
+        let index: HashMap<(i64, i64), usize> = tree_ids
+            .iter()
+            .zip(node_ids.iter())
+            .enumerate()
+            .map(|(i, (t, n))| ((*t, *n), i))
+            .collect();
+
+        let mut mode = None;
+        let mut split_of = HashMap::new();
+        let mut features = vec![];
+        let mut thresholds = vec![];
+        for (i, m) in modes.iter().enumerate() {
+            if *m == "LEAF" {
+                continue;
+            }
+            let m = BranchMode::parse(m)?;
+            if mode.replace(m).is_some_and(|prev| prev != m) {
+                bail!("tree ensembles mixing split modes are not supported");
+            }
+            split_of.insert(i, features.len());
+            features.push(index_attr(&node.op_type, "feature id", feature_ids[i])?);
+            thresholds.push(values[i]);
+        }
+        let Some(mode) = mode else {
+            bail!("{} has no splits", node.op_type);
+        };
+
+        // the root of a tree is the one node that isn't a child of any split
+        let trees: Vec<i64> = tree_ids.iter().copied().unique().collect();
+        let mut leaf_of = HashMap::new();
+        let mut paths = vec![];
+        for tree in trees {
+            let children: Vec<i64> = split_of
+                .keys()
+                .filter(|i| tree_ids[**i] == tree)
+                .flat_map(|i| [true_ids[*i], false_ids[*i]])
+                .collect();
+            let Some(root) = (0..node_ids.len())
+                .find(|i| tree_ids[*i] == tree && !children.contains(&node_ids[*i]))
+            else {
+                bail!("tree {} of {} has no root", tree, node.op_type);
+            };
+
+            let mut visited = vec![false; node_ids.len()];
+            let mut stack = vec![(root, vec![])];
+            while let Some((i, path)) = stack.pop() {
+                // every node of a tree has a single parent, so reaching one twice means a cycle
+                if std::mem::replace(&mut visited[i], true) {
+                    return Err(GraphError::MisformedParams(format!(
+                        "tree {} of {} isn't a tree",
+                        tree, node.op_type
+                    ))
+                    .into());
+                }
+                match split_of.get(&i) {
+                    Some(split) => {
+                        for (child, branch) in [(true_ids[i], true), (false_ids[i], false)] {
+                            let Some(child) = index.get(&(tree, child)) else {
+                                bail!("tree {} of {} has a dangling branch", tree, node.op_type);
+                            };
+                            let mut path = path.clone();
+                            path.push((*split, branch));
+                            stack.push((*child, path));
+                        }
+                    }
+                    None => {
+                        leaf_of.insert((tree, node_ids[i]), paths.len());
+                        paths.push(path);
+                    }
+                }
+            }
+        }
+
+        let weight_trees = node.get_attr_vec::<i64>(&format!("{}_treeids", target))?;
+        let weight_nodes = node.get_attr_vec::<i64>(&format!("{}_nodeids", target))?;
+        let weight_ids = node.get_attr_vec::<i64>(&format!("{}_ids", target))?;
+        let leaf_weights = node.get_attr_vec::<f32>(&format!("{}_weights", target))?;
+        check_lengths(
+            &node.op_type,
+            &[
+                ("weight tree ids", weight_trees.len()),
+                ("weight node ids", weight_nodes.len()),
+                ("weight target ids", weight_ids.len()),
+                ("weights", leaf_weights.len()),
+            ],
+        )?;
+        let weight_ids = weight_ids
+            .iter()
+            .map(|id| index_attr(&node.op_type, "target id", *id))
+            .collect::<TractResult<Vec<_>>>()?;
+        let n_targets = match n_classes {
+            Some(_) => weight_ids.iter().max().map_or(1, |m| m + 1),
+            None => index_attr(
+                &node.op_type,
+                "n_targets",
+                node.get_attr_opt::<i64>("n_targets")?.unwrap_or(1),
+            )?,
+        };
+        if n_targets == 0 {
+            return Err(
+                GraphError::MisformedParams(format!("{} has no targets", node.op_type)).into(),
+            );
+        }
+        if weight_ids.iter().any(|id| *id >= n_targets) {
+            return Err(GraphError::MisformedParams(format!(
+                "{} has weights for targets beyond its {} targets",
+                node.op_type, n_targets
+            ))
+            .into());
+        }
+
+        let mut weights = vec![0f32; paths.len() * n_targets];
+        for (((tree, node_id), id), w) in weight_trees
+            .iter()
+            .zip(weight_nodes.iter())
+            .zip(weight_ids.iter())
+            .zip(leaf_weights.iter())
+        {
+            let Some(leaf) = leaf_of.get(&(*tree, *node_id)) else {
+                bail!("{} has weights for a node that isn't a leaf", node.op_type);
+            };
+            weights[leaf * n_targets + *id] += w;
+        }
+
+        match node
+            .get_attr_opt::<&str>("aggregate_function")?
+            .unwrap_or("SUM")
+        {
+            "SUM" => {}
+            "AVERAGE" => {
+                let n_trees = tree_ids.iter().unique_count() as f32;
+                weights.iter_mut().for_each(|w| *w /= n_trees);
+            }
+            other => bail!("tree ensemble aggregate {} is not supported", other),
+        }
+
+        let mut base_values = node
+            .get_attr_opt_vec::<f32>("base_values")?
+            .unwrap_or_else(|| vec![0.; n_targets]);
+        if base_values.len() != n_targets {
+            bail!(
+                "{} base_values don't match its number of targets",
+                node.op_type
+            );
+        }
+        if n_classes == Some(2) && n_targets == 1 {
+            (weights, base_values) = expand_binary(&weights, &base_values);
+        } else if n_classes.is_some_and(|c| c != n_targets) {
+            bail!(
+                "{} has weights for {} of its classes",
+                node.op_type,
+                n_targets
+            );
+        }
+
+        Ok(TreeEnsemble {
+            op_type: node.op_type.clone(),
+            features,
+            thresholds,
+            mode,
+            paths,
+            weights,
+            base_values,
+            post_transform: PostTransform::parse(node)?,
+            n_classes,
+        })
+    }
+
+    fn n_targets(&self) -> usize {
+        self.base_values.len()
+    }
+}
+
+impl Expansion for TreeEnsemble {
+    fn name(&self) -> Cow<str> {
+        self.op_type.clone().into()
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(if self.n_classes.is_some() { 2 } else { 1 })
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        match self.n_classes {
+            Some(n_classes) => classifier_rules(s, inputs, outputs, n_classes),
+            None => regressor_rules(s, inputs, outputs, self.n_targets()),
+        }
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let n_features = input_width(model, inputs[0])?;
+        let (n_splits, n_leaves) = (self.features.len(), self.paths.len());
+        if self.features.iter().any(|f| *f >= n_features) {
+            bail!(
+                "{} splits on a feature the input doesn't have",
+                self.op_type
+            );
+        }
+
+        let x = wire(
+            model,
+            format!("{}.cast", prefix),
+            cast(f32::datum_type()),
+            &inputs[..1],
+        )?;
+
+        // 1. compare every split's feature against its threshold
+        let mut select = vec![0f32; n_features * n_splits];
+        for (split, feature) in self.features.iter().enumerate() {
+            select[feature * n_splits + split] = 1.;
+        }
+        let select = wire_const(
+            model,
+            format!("{}.select", prefix),
+            &[n_features, n_splits],
+            &select,
+        )?;
+        let split_values = wire_einsum(
+            model,
+            format!("{}.features", prefix),
+            "nf,fs->ns",
+            &[x, select],
+        )?;
+        let thresholds = wire_const(
+            model,
+            format!("{}.thresholds", prefix),
+            &[1, n_splits],
+            &self.thresholds,
+        )?;
+        let bits = self.mode.wire(
+            format!("{}.split", prefix),
+            model,
+            &[split_values, thresholds],
+        )?;
+        let bits = wire(
+            model,
+            format!("{}.split.cast", prefix),
+            cast(f32::datum_type()),
+            &[bits],
+        )?;
+
+        // 2. find the leaf every tree lands on
+        let mut directions = vec![0f32; n_splits * n_leaves];
+        let mut n_true = vec![0f32; n_leaves];
+        for (leaf, path) in self.paths.iter().enumerate() {
+            for (split, branch) in path {
+                directions[split * n_leaves + leaf] = if *branch { 1. } else { -1. };
+                n_true[leaf] += *branch as u8 as f32;
+            }
+        }
+        let directions = wire_const(
+            model,
+            format!("{}.directions", prefix),
+            &[n_splits, n_leaves],
+            &directions,
+        )?;
+        let agreement = wire_einsum(
+            model,
+            format!("{}.paths", prefix),
+            "ns,sl->nl",
+            &[bits, directions],
+        )?;
+        let n_true = wire_const(model, format!("{}.n_true", prefix), &[1, n_leaves], &n_true)?;
+        let leaves = wire(
+            model,
+            format!("{}.leaves", prefix),
+            logic::equals(),
+            &[agreement, n_true],
+        )?;
+        let leaves = wire(
+            model,
+            format!("{}.leaves.cast", prefix),
+            cast(f32::datum_type()),
+            &[leaves],
+        )?;
+
+        // 3. sum the weights of the leaves that were reached
+        let scores = wire_affine(
+            &format!("{}.scores", prefix),
+            model,
+            leaves,
+            &self.weights,
+            &self.base_values,
+        )?;
+        match self.n_classes {
+            Some(_) => wire_classifier_outputs(prefix, model, scores, self.post_transform),
+            None => Ok(tvec![self.post_transform.wire(prefix, model, scores)?]),
+        }
+    }
+}
+
+/// `LinearClassifier` and `LinearRegressor`, an affine map of the input followed by the post
+/// transform
+#[derive(Clone, Debug)]
+struct Linear {
+    op_type: String,
+    /// row major `[targets, features]` coefficients
+    coefficients: Vec<f32>,
+    intercepts: Vec<f32>,
+    post_transform: PostTransform,
+    /// the number of classes of a classifier, None for a regressor
+    n_classes: Option<usize>,
+}
+
+impl Hash for Linear {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.op_type.hash(state);
+        hash_floats(&self.coefficients, state);
+        hash_floats(&self.intercepts, state);
+        self.post_transform.hash(state);
+        self.n_classes.hash(state);
+    }
+}
+
+impl_dyn_hash!(Linear);
+
+impl Linear {
+    fn parse(node: &NodeProto, n_classes: Option<usize>) -> TractResult<Self> {
+        let mut coefficients = node.get_attr_vec::<f32>("coefficients")?;
+        let n_targets = match n_classes {
+            Some(_) => node
+                .get_attr_opt_vec::<f32>("intercepts")?
+                .map_or(1, |i| i.len()),
+            None => node.get_attr_opt::<i64>("targets")?.unwrap_or(1) as usize,
+        };
+        if n_targets == 0 || coefficients.is_empty() {
+            return Err(GraphError::MisformedParams(format!(
+                "{} has no coefficients or targets",
+                node.op_type
+            ))
+            .into());
+        }
+        let mut intercepts = node
+            .get_attr_opt_vec::<f32>("intercepts")?
+            .unwrap_or_else(|| vec![0.; n_targets]);
+        if intercepts.len() != n_targets || coefficients.len() % n_targets != 0 {
+            bail!(
+                "{} coefficients don't match its number of targets",
+                node.op_type
+            );
+        }
+
+        // store the coefficients feature major so they can be used as a [features, targets]
+        // weight matrix
+        let n_features = coefficients.len() / n_targets;
+        coefficients = (0..n_features)
+            .flat_map(|f| (0..n_targets).map(move |t| (f, t)))
+            .map(|(f, t)| coefficients[t * n_features + f])
+            .collect();
+
+        if n_classes == Some(2) && n_targets == 1 {
+            (coefficients, intercepts) = expand_binary(&coefficients, &intercepts);
+        } else if n_classes.is_some_and(|c| c != n_targets) {
+            bail!(
+                "{} has coefficients for {} of its classes",
+                node.op_type,
+                n_targets
+            );
+        }
+
+        Ok(Linear {
+            op_type: node.op_type.clone(),
+            coefficients,
+            intercepts,
+            post_transform: PostTransform::parse(node)?,
+            n_classes,
+        })
+    }
+}
+
+impl Expansion for Linear {
+    fn name(&self) -> Cow<str> {
+        self.op_type.clone().into()
+    }
+
+    fn nboutputs(&self) -> TractResult<usize> {
+        Ok(if self.n_classes.is_some() { 2 } else { 1 })
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        match self.n_classes {
+            Some(n_classes) => classifier_rules(s, inputs, outputs, n_classes),
+            None => regressor_rules(s, inputs, outputs, self.intercepts.len()),
+        }
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        if input_width(model, inputs[0])? * self.intercepts.len() != self.coefficients.len() {
+            bail!("{} coefficients don't match its input", self.op_type);
+        }
+        let x = wire(
+            model,
+            format!("{}.cast", prefix),
+            cast(f32::datum_type()),
+            &inputs[..1],
+        )?;
+        let scores = wire_affine(prefix, model, x, &self.coefficients, &self.intercepts)?;
+        match self.n_classes {
+            Some(_) => wire_classifier_outputs(prefix, model, scores, self.post_transform),
+            None => Ok(tvec![self.post_transform.wire(prefix, model, scores)?]),
+        }
+    }
+}
+
+/// `Scaler`, `(x - offset) * scale` with per feature (or shared) offsets and scales
+#[derive(Clone, Debug)]
+struct Scaler {
+    offset: Vec<f32>,
+    scale: Vec<f32>,
+}
+
+impl Hash for Scaler {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_floats(&self.offset, state);
+        hash_floats(&self.scale, state);
+    }
+}
+
+impl_dyn_hash!(Scaler);
+
+impl Expansion for Scaler {
+    fn name(&self) -> Cow<str> {
+        "Scaler".into()
+    }
+
+    fn rules<'r, 'p: 'r, 's: 'r>(
+        &'s self,
+        s: &mut Solver<'r>,
+        inputs: &'p [TensorProxy],
+        outputs: &'p [TensorProxy],
+    ) -> InferenceResult {
+        check_input_arity(inputs, 1)?;
+        check_output_arity(outputs, 1)?;
+        s.equals(&outputs[0].datum_type, f32::datum_type())?;
+        s.equals(&outputs[0].shape, &inputs[0].shape)?;
+        Ok(())
+    }
+
+    fn wire(
+        &self,
+        prefix: &str,
+        model: &mut TypedModel,
+        inputs: &[OutletId],
+    ) -> TractResult<TVec<OutletId>> {
+        let x = wire(
+            model,
+            format!("{}.cast", prefix),
+            cast(f32::datum_type()),
+            &inputs[..1],
+        )?;
+        let offset = wire_const(
+            model,
+            format!("{}.offset", prefix),
+            &[1, self.offset.len()],
+            &self.offset,
+        )?;
+        let scale = wire_const(
+            model,
+            format!("{}.scale", prefix),
+            &[1, self.scale.len()],
+            &self.scale,
+        )?;
+        let centered = wire(model, format!("{}.sub", prefix), math::sub(), &[x, offset])?;
+        Ok(tvec![wire(
+            model,
+            format!("{}.mul", prefix),
+            math::mul(),
+            &[centered, scale]
+        )?])
+    }
+}
+
+fn tree_ensemble_classifier(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let n_classes = class_labels(node)?;
+    Ok((
+        expand(TreeEnsemble::parse(node, "class", Some(n_classes))?),
+        vec![],
+    ))
+}
+
+fn tree_ensemble_regressor(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    Ok((expand(TreeEnsemble::parse(node, "target", None)?), vec![]))
+}
+
+fn linear_classifier(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let n_classes = class_labels(node)?;
+    Ok((expand(Linear::parse(node, Some(n_classes))?), vec![]))
+}
+
+fn linear_regressor(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    Ok((expand(Linear::parse(node, None)?), vec![]))
+}
+
+fn scaler(
+    _ctx: &ParsingContext,
+    node: &NodeProto,
+) -> TractResult<(Box<dyn InferenceOp>, Vec<String>)> {
+    let offset = node.get_attr_opt_vec::<f32>("offset")?.unwrap_or(vec![0.]);
+    let scale = node.get_attr_opt_vec::<f32>("scale")?.unwrap_or(vec![1.]);
+    Ok((expand(Scaler { offset, scale }), vec![]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tract_onnx::pb;
+    use tract_onnx::pb::attribute_proto::AttributeType;
+    use tract_onnx::prelude::{Framework, InferenceModelExt};
+
+    fn attr(name: &str, r#type: AttributeType) -> pb::AttributeProto {
+        pb::AttributeProto {
+            name: name.into(),
+            r#type: r#type as i32,
+            ..Default::default()
+        }
+    }
+
+    fn ints(name: &str, values: &[i64]) -> pb::AttributeProto {
+        pb::AttributeProto {
+            ints: values.to_vec(),
+            ..attr(name, AttributeType::Ints)
+        }
+    }
+
+    fn floats(name: &str, values: &[f32]) -> pb::AttributeProto {
+        pb::AttributeProto {
+            floats: values.to_vec(),
+            ..attr(name, AttributeType::Floats)
+        }
+    }
+
+    fn strings(name: &str, values: &[&str]) -> pb::AttributeProto {
+        pb::AttributeProto {
+            strings: values.iter().map(|s| s.as_bytes().to_vec()).collect(),
+            ..attr(name, AttributeType::Strings)
+        }
+    }
+
+    fn int(name: &str, value: i64) -> pb::AttributeProto {
+        pb::AttributeProto {
+            i: value,
+            ..attr(name, AttributeType::Int)
+        }
+    }
+
+    fn tensor_info(name: &str, elem_type: i32, shape: &[i64]) -> pb::ValueInfoProto {
+        use pb::tensor_shape_proto::{dimension, Dimension};
+        pb::ValueInfoProto {
+            name: name.into(),
+            r#type: Some(pb::TypeProto {
+                value: Some(pb::type_proto::Value::TensorType(pb::type_proto::Tensor {
+                    elem_type,
+                    shape: Some(pb::TensorShapeProto {
+                        dim: shape
+                            .iter()
+                            .map(|d| Dimension {
+                                value: Some(dimension::Value::DimValue(*d)),
+                                ..Default::default()
+                            })
+                            .collect(),
+                    }),
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// A model with a single `ai.onnx.ml` node reading a `[2, 2]` float input
+    fn load(
+        op_type: &str,
+        attribute: Vec<pb::AttributeProto>,
+        outputs: Vec<pb::ValueInfoProto>,
+    ) -> TractResult<InferenceModel> {
+        let proto = pb::ModelProto {
+            ir_version: 8,
+            opset_import: vec![
+                pb::OperatorSetIdProto {
+                    domain: "".into(),
+                    version: 13,
+                },
+                pb::OperatorSetIdProto {
+                    domain: "ai.onnx.ml".into(),
+                    version: 3,
+                },
+            ],
+            graph: Some(pb::GraphProto {
+                node: vec![pb::NodeProto {
+                    op_type: op_type.into(),
+                    domain: "ai.onnx.ml".into(),
+                    input: vec!["X".into()],
+                    output: outputs.iter().map(|o| o.name.clone()).collect(),
+                    attribute,
+                    ..Default::default()
+                }],
+                input: vec![tensor_info("X", 1, &[2, 2])],
+                output: outputs,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut onnx = tract_onnx::onnx();
+        register(&mut onnx);
+        onnx.model_for_proto_model(&proto)
+    }
+
+    fn run(model: InferenceModel, x: &[f32]) -> TVec<TValue> {
+        model
+            .into_typed()
+            .unwrap()
+            .into_decluttered()
+            .unwrap()
+            .into_runnable()
+            .unwrap()
+            .run(tvec!(Tensor::from_shape(&[2, 2], x).unwrap().into()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_tree_ensemble_regressor() {
+        // two stumps, x0 <= 0.5 ? 10 : 20 and x1 <= 0 ? -1 : 1, on top of a base value of 0.5
+        let model = load(
+            "TreeEnsembleRegressor",
+            vec![
+                ints("nodes_treeids", &[0, 0, 0, 1, 1, 1]),
+                ints("nodes_nodeids", &[0, 1, 2, 0, 1, 2]),
+                ints("nodes_featureids", &[0, 0, 0, 1, 0, 0]),
+                floats("nodes_values", &[0.5, 0., 0., 0., 0., 0.]),
+                strings(
+                    "nodes_modes",
+                    &["BRANCH_LEQ", "LEAF", "LEAF", "BRANCH_LEQ", "LEAF", "LEAF"],
+                ),
+                ints("nodes_truenodeids", &[1, 0, 0, 1, 0, 0]),
+                ints("nodes_falsenodeids", &[2, 0, 0, 2, 0, 0]),
+                ints("target_treeids", &[0, 0, 1, 1]),
+                ints("target_nodeids", &[1, 2, 1, 2]),
+                ints("target_ids", &[0, 0, 0, 0]),
+                floats("target_weights", &[10., 20., -1., 1.]),
+                int("n_targets", 1),
+                floats("base_values", &[0.5]),
+            ],
+            vec![tensor_info("Y", 1, &[2, 1])],
+        )
+        .unwrap();
+
+        let outputs = run(model, &[0.2, -1., 0.7, 3.]);
+        assert_eq!(outputs[0].as_slice::<f32>().unwrap(), &[9.5, 21.5]);
+    }
+
+    /// A regressor stump, x0 <= 0.5 ? 10 : 20, with `overrides` replacing its attributes
+    fn stump(overrides: Vec<pb::AttributeProto>) -> TractResult<InferenceModel> {
+        let mut attributes = vec![
+            ints("nodes_treeids", &[0, 0, 0]),
+            ints("nodes_nodeids", &[0, 1, 2]),
+            ints("nodes_featureids", &[0, 0, 0]),
+            floats("nodes_values", &[0.5, 0., 0.]),
+            strings("nodes_modes", &["BRANCH_LEQ", "LEAF", "LEAF"]),
+            ints("nodes_truenodeids", &[1, 0, 0]),
+            ints("nodes_falsenodeids", &[2, 0, 0]),
+            ints("target_treeids", &[0, 0]),
+            ints("target_nodeids", &[1, 2]),
+            ints("target_ids", &[0, 0]),
+            floats("target_weights", &[10., 20.]),
+            int("n_targets", 1),
+        ];
+        for o in overrides {
+            attributes.retain(|a| a.name != o.name);
+            attributes.push(o);
+        }
+        load(
+            "TreeEnsembleRegressor",
+            attributes,
+            vec![tensor_info("Y", 1, &[2, 1])],
+        )
+    }
+
+    #[test]
+    fn test_malformed_tree_ensemble() {
+        assert!(stump(vec![]).is_ok());
+        let malformed = [
+            // fewer feature ids than nodes
+            vec![ints("nodes_featureids", &[0, 0])],
+            vec![floats("nodes_values", &[0.5])],
+            vec![ints("nodes_falsenodeids", &[2])],
+            vec![ints("nodes_featureids", &[-1, 0, 0])],
+            // the split is its own true branch
+            vec![
+                strings("nodes_modes", &["BRANCH_LEQ", "BRANCH_LEQ", "LEAF"]),
+                ints("nodes_truenodeids", &[1, 1, 0]),
+                ints("nodes_falsenodeids", &[2, 2, 0]),
+            ],
+            vec![floats("target_weights", &[10.])],
+            vec![ints("target_ids", &[0, 1])],
+            vec![ints("target_ids", &[0, -1])],
+        ];
+        for overrides in malformed {
+            let names = overrides.iter().map(|a| a.name.clone()).collect_vec();
+            assert!(stump(overrides).is_err(), "{:?}", names);
+        }
+    }
+
+    #[test]
+    fn test_linear_classifier() {
+        let model = load(
+            "LinearClassifier",
+            vec![
+                floats("coefficients", &[1., 0., 0., 1., -1., -1.]),
+                floats("intercepts", &[0., 0.5, 0.]),
+                ints("classlabels_ints", &[0, 1, 2]),
+            ],
+            vec![
+                tensor_info("label", 7, &[2]),
+                tensor_info("scores", 1, &[2, 3]),
+            ],
+        )
+        .unwrap();
+
+        let outputs = run(model, &[2., 1., 0., 3.]);
+        assert_eq!(outputs[0].as_slice::<i64>().unwrap(), &[0, 1]);
+        assert_eq!(
+            outputs[1].as_slice::<f32>().unwrap(),
+            &[2., 1.5, -3., 0., 3.5, -3.]
+        );
+    }
+
+    #[test]
+    fn test_linear_without_intercepts() {
+        let model = load(
+            "LinearClassifier",
+            vec![
+                floats("coefficients", &[1., 0.]),
+                floats("intercepts", &[]),
+                ints("classlabels_ints", &[]),
+            ],
+            vec![
+                tensor_info("label", 7, &[2]),
+                tensor_info("scores", 1, &[2, 0]),
+            ],
+        );
+        assert!(model.is_err());
+    }
+}
@@ -1,11 +1,22 @@
+/// Conservative bounds on the values of every node, to catch overflows before keygen.
+pub mod audit;
 /// Representations of a computational graph's inputs.
 pub mod input;
+/// Lowerings for the `ai.onnx.ml` operators of exported scikit-learn models.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ml;
 /// Crate for defining a computational graph and building a ZK-circuit from it.
 pub mod model;
 /// Representations of a computational graph's modules.
 pub mod modules;
 /// Inner elements of a computational graph that represent a single operation / constraints.
 pub mod node;
+/// Rewrites of the tract graph applied before quantization.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod rewrite;
+/// Splitting onnx models into chains of smaller models.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod split;
 /// Helper functions
 pub mod utilities;
 /// Representations of a computational graph's variables.
@@ -21,7 +32,7 @@ use itertools::Itertools;
 use self::input::OnChainSource;
 use self::input::{FileSource, GraphData};
 use self::modules::{
-    GraphModules, ModuleConfigs, ModuleForwardResult, ModuleSettings, ModuleSizes,
+    GraphModules, ModuleConfigs, ModuleForwardResult, ModuleSettings, ModuleSizes, ProviderKeys,
 };
 use crate::circuit::lookup::LookupOp;
 use crate::circuit::modules::ModulePlanner;
@@ -46,7 +57,7 @@ use pyo3::types::PyDict;
 use pyo3::ToPyObject;
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
-use std::io::{Read, Write};
+use std::io::Write;
 use std::ops::Deref;
 use thiserror::Error;
 pub use utilities::*;
@@ -127,6 +138,9 @@ pub const MAX_PUBLIC_SRS: u32 = bn256::Fr::S - 2;
 /// Lookup deg
 pub const LOOKUP_DEG: usize = 5;
 
+/// The most advice columns per block [GraphCircuit::fit_to_logrows] tries
+pub const MAX_INNER_COLS: usize = 16;
+
 use std::cell::RefCell;
 
 thread_local!(
@@ -200,18 +214,18 @@ impl GraphWitness {
         Ok(serialized)
     }
 
-    /// Load the model input from a file
+    /// Load the model input from a file (or stdin if the path is `-`)
     pub fn from_path(path: std::path::PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut file = std::fs::File::open(path.clone())
-            .map_err(|_| format!("failed to load model at {}", path.display()))?;
-        let mut data = String::new();
-        file.read_to_string(&mut data)?;
+        let data = crate::stdio::read_to_string(&path)
+            .map_err(|e| format!("failed to load witness at {}: {}", path.display(), e))?;
         serde_json::from_str(&data).map_err(|e| e.into())
     }
 
-    /// Save the model input to a file
+    /// Save the model input to a file (or stdout if the path is `-`)
     pub fn save(&self, path: std::path::PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-        serde_json::to_writer(std::fs::File::create(path)?, &self).map_err(|e| e.into())
+        let mut writer = crate::stdio::writer(&path)?;
+        serde_json::to_writer(&mut writer, &self)?;
+        writer.flush().map_err(|e| e.into())
     }
 
     ///
@@ -233,6 +247,36 @@ impl GraphWitness {
     }
 }
 
+/// A self-contained regression fixture for a compiled model: an input, the quantized outputs and
+/// instances it must produce, and a hash of the settings it was generated with.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TestVector {
+    /// ezkl version the vector was generated with
+    pub version: String,
+    /// hex encoded keccak256 hash of the circuit settings (excluding the ezkl version)
+    pub settings_hash: String,
+    /// the input to the model
+    pub input: GraphData,
+    /// the expected quantized outputs of the forward pass
+    pub outputs: Vec<Vec<Fp>>,
+    /// the expected public instances of a proof over the input
+    pub instances: Vec<Fp>,
+}
+
+impl TestVector {
+    /// Save the test vector to a file
+    pub fn save(&self, path: std::path::PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+        serde_json::to_writer(std::fs::File::create(path)?, &self).map_err(|e| e.into())
+    }
+
+    /// Load a test vector from a file
+    pub fn load(path: std::path::PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let data = std::fs::read_to_string(&path)
+            .map_err(|_| format!("failed to load test vector at {}", path.display()))?;
+        serde_json::from_str(&data).map_err(|e| e.into())
+    }
+}
+
 #[cfg(feature = "python-bindings")]
 impl ToPyObject for GraphWitness {
     fn to_object(&self, py: Python) -> PyObject {
@@ -398,6 +442,8 @@ pub struct GraphSettings {
     pub version: String,
     /// num blinding factors
     pub num_blinding_factors: Option<usize>,
+    /// the [GraphCircuit::key_hash] of the compiled circuit, set when the circuit is compiled
+    pub key_hash: Option<String>,
 }
 
 impl GraphSettings {
@@ -413,24 +459,57 @@ impl GraphSettings {
         instances
     }
 
-    /// save params to file
+    /// The poseidon hashes of the inputs among the flattened instances of a proof, if the inputs
+    /// are publicly hashed. These are the first instances, one per input.
+    pub fn hashed_inputs<'a>(&self, instances: &'a [Fp]) -> Option<&'a [Fp]> {
+        if !self.run_args.input_visibility.is_hashed_public() {
+            return None;
+        }
+        instances.get(..self.model_input_scales.len())
+    }
+
+    /// The poseidon hashes of the outputs among the flattened instances of a proof, if the outputs
+    /// are publicly hashed. These are the last instances, one per output, followed only by the
+    /// signer's public key when the outputs are signed.
+    pub fn hashed_outputs<'a>(&self, instances: &'a [Fp]) -> Option<&'a [Fp]> {
+        if !self.run_args.output_visibility.is_hashed_public() {
+            return None;
+        }
+        let mut end = instances.len();
+        if self.run_args.output_visibility.is_signed() {
+            end = end.checked_sub(crate::circuit::modules::ecdsa::NUM_SIGNATURE_INSTANCES)?;
+        }
+        let start = end.checked_sub(self.model_output_scales.len())?;
+        instances.get(start..end)
+    }
+
+    /// save params to file (or stdout if the path is `-`)
     pub fn save(&self, path: &std::path::PathBuf) -> Result<(), std::io::Error> {
         let encoded = serde_json::to_string(&self)?;
-        let mut file = std::fs::File::create(path)?;
-        file.write_all(encoded.as_bytes())
+        crate::stdio::write(path, encoded)
     }
-    /// load params from file
+    /// load params from file (or stdin if the path is `-`)
     pub fn load(path: &std::path::PathBuf) -> Result<Self, std::io::Error> {
-        let mut file = std::fs::File::open(path).map_err(|e| {
+        let data = crate::stdio::read_to_string(path).map_err(|e| {
             error!("failed to open settings file at {}", e);
             e
         })?;
-        let mut data = String::new();
-        file.read_to_string(&mut data)?;
         let res = serde_json::from_str(&data)?;
         Ok(res)
     }
 
+    /// hex encoded keccak256 hash of the settings, the ezkl version and key hash are excluded so
+    /// the hash is stable across releases
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn hash(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let mut settings = self.clone();
+        settings.version = String::new();
+        settings.key_hash = None;
+        Ok(hex::encode(ethers::utils::keccak256(
+            settings.as_json()?.as_bytes(),
+        )))
+    }
+
     /// Export the ezkl configuration as json
     pub fn as_json(&self) -> Result<String, Box<dyn std::error::Error>> {
         let serialized = match serde_json::to_string(&self) {
@@ -525,6 +604,36 @@ impl GraphCircuit {
     pub fn model(&self) -> &Model {
         &self.core.model
     }
+    /// hex encoded keccak256 hash of the model, its settings and the circuit's constraint system,
+    /// which identifies the proving and verifying keys generated for the circuit. The constraint
+    /// system (gates, lookups and columns) changes with the version of ezkl that lays the circuit
+    /// out, so keys generated before a change to the gates aren't reused. Compiled circuits carry
+    /// the hash in their settings, it is only recomputed for circuits that weren't compiled.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn key_hash(&self) -> Result<String, Box<dyn std::error::Error>> {
+        if let Some(key_hash) = &self.settings().key_hash {
+            return Ok(key_hash.clone());
+        }
+        self.compute_key_hash()
+    }
+
+    /// Computes the [GraphCircuit::key_hash] once and stores it in the settings, so loading the
+    /// compiled circuit doesn't have to serialize the model again.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn store_key_hash(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.settings_mut().key_hash = Some(self.compute_key_hash()?);
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn compute_key_hash(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let mut bytes = bincode::serialize(self.model())?;
+        bytes.extend(self.settings().hash()?.as_bytes());
+        let mut cs = ConstraintSystem::<Fp>::default();
+        GraphCircuit::configure_with_params(&mut cs, self.settings().clone());
+        bytes.extend(format!("{:?}", cs.pinned()).as_bytes());
+        Ok(hex::encode(ethers::utils::keccak256(bytes)))
+    }
     ///
     pub fn save(&self, path: std::path::PathBuf) -> Result<(), Box<dyn std::error::Error>> {
         let f = std::fs::File::create(path)?;
@@ -535,12 +644,9 @@ impl GraphCircuit {
 
     ///
     pub fn load(path: std::path::PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
-        // read bytes from file
-        let mut f = std::fs::File::open(&path)?;
-        let metadata = std::fs::metadata(&path)?;
-        let mut buffer = vec![0; metadata.len() as usize];
-        f.read_exact(&mut buffer)?;
-        let result = bincode::deserialize(&buffer)?;
+        // deserialize straight from the file so the raw bytes and the circuit are never both in memory
+        let reader = std::io::BufReader::new(std::fs::File::open(&path)?);
+        let result = bincode::deserialize_from(reader)?;
         Ok(result)
     }
 }
@@ -883,10 +989,10 @@ impl GraphCircuit {
         (ASSUMED_BLINDING_FACTORS + RESERVED_BLINDING_ROWS_PAD) as f64
     }
 
-    fn calc_safe_range(res: &GraphWitness) -> (i128, i128) {
+    fn calc_safe_range(lookup_inputs: (i128, i128)) -> (i128, i128) {
         (
-            RANGE_MULTIPLIER * res.min_lookup_inputs,
-            RANGE_MULTIPLIER * res.max_lookup_inputs,
+            RANGE_MULTIPLIER * lookup_inputs.0,
+            RANGE_MULTIPLIER * lookup_inputs.1,
         )
     }
 
@@ -900,7 +1006,7 @@ impl GraphCircuit {
 
     fn calc_min_logrows(
         &mut self,
-        res: &GraphWitness,
+        lookup_inputs: (i128, i128),
         max_logrows: Option<u32>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // load the max logrows
@@ -910,14 +1016,14 @@ impl GraphCircuit {
 
         let reserved_blinding_rows = Self::reserved_blinding_rows();
         // check if has overflowed max lookup input
-        if res.max_lookup_inputs > MAX_LOOKUP_ABS / RANGE_MULTIPLIER
-            || res.min_lookup_inputs < -MAX_LOOKUP_ABS / RANGE_MULTIPLIER
+        if lookup_inputs.1 > MAX_LOOKUP_ABS / RANGE_MULTIPLIER
+            || lookup_inputs.0 < -MAX_LOOKUP_ABS / RANGE_MULTIPLIER
         {
-            let err_string = format!("max lookup input ({}) is too large", res.max_lookup_inputs);
+            let err_string = format!("lookup inputs ({:?}) are too large", lookup_inputs);
             return Err(err_string.into());
         }
 
-        let safe_range = Self::calc_safe_range(res);
+        let safe_range = Self::calc_safe_range(lookup_inputs);
         let mut min_logrows = MIN_LOGROWS;
         // degrade the max logrows until the extended k is small enough
         while min_logrows < max_logrows
@@ -997,11 +1103,8 @@ impl GraphCircuit {
             .settings()
             .clone();
 
-        // recalculate the total const size give nthe new logrows
-        let total_const_len = settings_mut.total_const_size;
-        let const_len_logrows = (total_const_len as f64).log2().ceil() as u32;
-        settings_mut.run_args.logrows =
-            std::cmp::max(settings_mut.run_args.logrows, const_len_logrows);
+        // constants that don't fit in a single fixed column are chunked across several (see
+        // VarTensor::constant_cols), so they don't need to raise logrows
         // recalculate the total number of constraints given the new logrows
         let min_rows_from_constraints = (settings_mut.num_rows as f64 + reserved_blinding_rows)
             .log2()
@@ -1036,22 +1139,146 @@ impl GraphCircuit {
         extended_k <= bn256::Fr::S
     }
 
+    /// Picks the fewest advice columns per block (`num_inner_cols`) that fold the model's layers
+    /// into `2^logrows` rows, instead of growing logrows until the widest layers fit. Wider blocks
+    /// raise the degree of the gates, so past the point where the rows fit they only add cost.
+    /// If no width up to [MAX_INNER_COLS] fits, the width using the fewest rows is kept and the
+    /// layers that still overflow wrap into duplicated advice blocks.
+    pub fn fit_to_logrows(&mut self, logrows: u32) -> Result<(), Box<dyn std::error::Error>> {
+        let reserved_blinding_rows = Self::reserved_blinding_rows();
+        let num_lookup_cols = Self::calc_num_cols(self.settings().run_args.lookup_range, logrows);
+        let model = self.model().clone();
+        let mut run_args = self.settings().run_args.clone();
+        run_args.logrows = logrows;
+
+        let mut fewest_rows: Option<GraphCircuit> = None;
+        for num_inner_cols in 1..=MAX_INNER_COLS {
+            run_args.num_inner_cols = num_inner_cols;
+            let circuit = GraphCircuit::new(model.clone(), &run_args)?;
+            if !circuit.extended_k_is_small_enough(logrows, num_lookup_cols) {
+                break;
+            }
+            let rows = circuit.settings().num_rows as f64 + reserved_blinding_rows;
+            if rows <= (1u64 << logrows) as f64 {
+                info!(
+                    "the circuit fits in 2^{} rows with {} advice columns per block",
+                    logrows, num_inner_cols
+                );
+                *self = circuit;
+                return Ok(());
+            }
+            if fewest_rows.as_ref().map_or(true, |c| {
+                circuit.settings().num_rows < c.settings().num_rows
+            }) {
+                fewest_rows = Some(circuit);
+            }
+        }
+
+        let circuit = fewest_rows.ok_or_else(|| {
+            format!(
+                "the gates are too wide for 2^{} rows, try a larger logrows",
+                logrows
+            )
+        })?;
+        warn!(
+            "the circuit needs {} rows with {} advice columns per block, layers that don't fit in 2^{} rows wrap into extra advice blocks",
+            circuit.settings().num_rows,
+            circuit.settings().run_args.num_inner_cols,
+            logrows
+        );
+        *self = circuit;
+        Ok(())
+    }
+
     /// Calibrate the circuit to the supplied data.
     pub fn calibrate(
         &mut self,
         input: &[Tensor<Fp>],
         max_logrows: Option<u32>,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let res = self.forward(&mut input.to_vec(), None, None)?;
-        self.calc_min_logrows(&res, max_logrows)
+        let res = self.forward_with_ephemeral_keys(&mut input.to_vec(), None, None)?;
+        self.calc_min_logrows((res.min_lookup_inputs, res.max_lookup_inputs), max_logrows)
+    }
+
+    /// Sizes the lookup range and logrows from a bound on the absolute value (before
+    /// quantization) of the model's inputs rather than from data, using the value ranges found by
+    /// the audit.
+    pub fn size_to_input_bound(
+        &mut self,
+        input_bound: f64,
+        max_logrows: Option<u32>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let lookup_inputs = self
+            .model()
+            .lookup_input_range(input_bound)
+            .ok_or("some lookup inputs can't be bounded, calibrate on data instead")?;
+        self.calc_min_logrows(lookup_inputs, max_logrows)
     }
 
     /// Runs the forward pass of the model / graph of computations and any associated hashing.
+    /// Encrypted inputs are encrypted under fresh variables, to prove the ciphertext a data
+    /// provider published pass their key to [GraphCircuit::forward_with_keys].
     pub fn forward(
         &self,
         inputs: &mut [Tensor<Fp>],
         vk: Option<&VerifyingKey<G1Affine>>,
         srs: Option<&ParamsKZG<Bn256>>,
+    ) -> Result<GraphWitness, Box<dyn std::error::Error>> {
+        if self
+            .settings()
+            .run_args
+            .input_visibility
+            .is_encrypted_to_recipient()
+        {
+            return Err(
+                "inputs encrypted to a recipient need the recipient's ElGamal public key".into(),
+            );
+        }
+        self.forward_with_ephemeral_keys(inputs, vk, srs)
+    }
+
+    /// Runs the forward pass encrypting encrypted inputs under fresh variables, for passes whose
+    /// ciphertexts are never published (calibration, accuracy checks, benchmarks, ...).
+    pub fn forward_with_ephemeral_keys(
+        &self,
+        inputs: &mut [Tensor<Fp>],
+        vk: Option<&VerifyingKey<G1Affine>>,
+        srs: Option<&ParamsKZG<Bn256>>,
+    ) -> Result<GraphWitness, Box<dyn std::error::Error>> {
+        self.forward_with_keys(inputs, vk, srs, &ProviderKeys::ephemeral())
+    }
+
+    /// Hashes, encrypts or commits to the model's parameters, flattened into a single tensor, as
+    /// their visibility requires. Returns None for models without parameters.
+    pub fn process_params(
+        &self,
+        visibility: Visibility,
+        vk: Option<&VerifyingKey<G1Affine>>,
+        srs: Option<&ParamsKZG<Bn256>>,
+    ) -> Result<Option<ModuleForwardResult>, Box<dyn std::error::Error>> {
+        let params = self.model().get_all_params();
+        if params.is_empty() {
+            return Ok(None);
+        }
+        let flattened_params = Tensor::new(Some(&params), &[params.len()])?.combine()?;
+        Ok(Some(GraphModules::forward(
+            &[flattened_params],
+            visibility,
+            vk,
+            srs,
+        )?))
+    }
+
+    /// Runs the forward pass with the data provider's keys: encrypted inputs are encrypted under
+    /// their ElGamal variables (as written by `encrypt-input`) so the ciphertext in the proof is
+    /// the one they published, and signed inputs are checked against their signatures. Params
+    /// and outputs are still encrypted under fresh variables.
+    pub fn forward_with_keys(
+        &self,
+        inputs: &mut [Tensor<Fp>],
+        vk: Option<&VerifyingKey<G1Affine>>,
+        srs: Option<&ParamsKZG<Bn256>>,
+        input_keys: &ProviderKeys,
     ) -> Result<GraphWitness, Box<dyn std::error::Error>> {
         let original_inputs = inputs.to_vec();
 
@@ -1067,7 +1294,13 @@ impl GraphCircuit {
                 for outlet in &module_outlets {
                     module_inputs.push(inputs[*outlet].clone());
                 }
-                let res = GraphModules::forward(&module_inputs, visibility.input.clone(), vk, srs)?;
+                let res = GraphModules::forward_with_keys(
+                    &module_inputs,
+                    visibility.input.clone(),
+                    vk,
+                    srs,
+                    input_keys,
+                )?;
                 processed_inputs = Some(res.clone());
                 let module_results = res.get_result(visibility.input.clone());
 
@@ -1075,23 +1308,20 @@ impl GraphCircuit {
                     inputs[*outlet] = Tensor::from(module_results[i].clone().into_iter());
                 }
             } else {
-                processed_inputs = Some(GraphModules::forward(inputs, visibility.input, vk, srs)?);
-            }
-        }
-
-        if visibility.params.requires_processing() {
-            let params = self.model().get_all_params();
-            if !params.is_empty() {
-                let flattened_params = Tensor::new(Some(&params), &[params.len()])?.combine()?;
-                processed_params = Some(GraphModules::forward(
-                    &[flattened_params],
-                    visibility.params,
+                processed_inputs = Some(GraphModules::forward_with_keys(
+                    inputs,
+                    visibility.input,
                     vk,
                     srs,
+                    input_keys,
                 )?);
             }
         }
 
+        if visibility.params.requires_processing() {
+            processed_params = self.process_params(visibility.params, vk, srs)?;
+        }
+
         let mut model_results = self.model().forward(inputs)?;
 
         if visibility.output.requires_processing() {
@@ -1280,34 +1510,149 @@ impl CircuitSize {
     }
 }
 
-impl Circuit<Fp> for GraphCircuit {
-    type Config = GraphConfig;
-    type FloorPlanner = ModulePlanner;
-    type Params = GraphSettings;
+/// Statistics about a compiled circuit, combining the dry-run estimates stored in its settings
+/// with the ground truth from an actual configure pass.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct CircuitStats {
+    /// rows used by the model, estimated by the dummy layout
+    pub num_rows: usize,
+    /// total number of assigned cells, estimated by the dummy layout
+    pub total_assignments: usize,
+    /// number of constants, estimated by the dummy layout
+    pub total_const_size: usize,
+    /// the logrows the circuit is configured with
+    pub logrows: u32,
+    /// a lower bound on the logrows needed to fit the rows, constants and blinding factors
+    pub min_logrows: u32,
+    /// number of instance columns
+    pub num_instance_columns: usize,
+    /// number of advice columns
+    pub num_advice_columns: usize,
+    /// number of fixed columns
+    pub num_fixed_columns: usize,
+    /// number of selectors
+    pub num_selectors: usize,
+    /// number of custom gates
+    pub num_gates: usize,
+    /// number of lookup arguments
+    pub num_lookup_arguments: usize,
+    /// degree of the constraint system
+    pub degree: usize,
+    /// number of lookup tables (one per distinct non-linearity)
+    pub num_lookup_tables: usize,
+    /// number of entries in each lookup table
+    pub lookup_table_size: usize,
+    /// number of columns each lookup table is split over
+    pub num_lookup_table_columns: usize,
+    /// number of columns in the permutation (copy constraint) argument
+    #[serde(default)]
+    pub num_permutation_columns: usize,
+}
 
-    fn without_witnesses(&self) -> Self {
-        self.clone()
+/// Bytes per field element held by the prover
+const FIELD_ELEMENT_BYTES: u64 = 32;
+
+/// Rough rate, in [CircuitStats::proving_work] units per second, at which a single machine
+/// proves. Only used when there is no benchmark of a reference circuit to scale from.
+pub const NOMINAL_PROVING_WORK_PER_SEC: f64 = 1e8;
+
+impl CircuitStats {
+    /// Export the stats as json
+    pub fn as_json(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(serde_json::to_string(&self)?)
     }
 
-    fn params(&self) -> Self::Params {
-        // safe to clone because the model is Arc'd
-        self.settings().clone()
+    /// The log2 size of the extended domain the quotient polynomial is evaluated over
+    pub fn extended_logrows(&self) -> u32 {
+        self.logrows + (self.degree.max(2) as f64 - 1.0).log2().ceil() as u32
     }
 
-    fn configure_with_params(cs: &mut ConstraintSystem<Fp>, params: Self::Params) -> Self::Config {
-        let mut params = params.clone();
+    /// Number of polynomials the prover holds: every column, the permutation's columns and grand
+    /// products (one per `degree - 2` columns), and the permuted input, permuted table and grand
+    /// product of each lookup argument.
+    pub fn num_polys(&self) -> usize {
+        let chunk = self.degree.saturating_sub(2).max(1);
+        self.num_instance_columns
+            + self.num_advice_columns
+            + self.num_fixed_columns
+            + self.num_selectors
+            + self.num_permutation_columns
+            + (self.num_permutation_columns + chunk - 1) / chunk
+            + 3 * self.num_lookup_arguments
+    }
+
+    /// Projected peak memory of the prover in bytes, counting each polynomial in its lagrange and
+    /// coefficient forms and its evaluations over the extended domain.
+    pub fn proving_memory_bytes(&self) -> u64 {
+        let n = 1u64 << self.logrows;
+        let extended_n = 1u64 << self.extended_logrows();
+        self.num_polys() as u64 * (2 * n + extended_n) * FIELD_ELEMENT_BYTES
+    }
+
+    /// Relative cost of proving, dominated by an FFT over the extended domain per polynomial.
+    /// Only meaningful when compared between circuits.
+    pub fn proving_work(&self) -> f64 {
+        let extended_logrows = self.extended_logrows();
+        self.num_polys() as f64 * (1u64 << extended_logrows) as f64 * extended_logrows as f64
+    }
+}
+
+impl GraphCircuit {
+    /// Collects [CircuitStats] for the circuit by running a configure pass over its settings.
+    pub fn stats(&self) -> Result<CircuitStats, Box<dyn std::error::Error>> {
+        let settings = self.settings();
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let visibility = VarVisibility::from_args(&settings.run_args)?;
+        GraphCircuit::configure_graph(&mut cs, settings.clone(), visibility)?;
+
+        let reserved_blinding_rows = Self::reserved_blinding_rows();
+        let min_logrows = [
+            (settings.num_rows as f64 + reserved_blinding_rows)
+                .log2()
+                .ceil() as u32,
+            MIN_LOGROWS,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(MIN_LOGROWS);
+
+        let lookup_range = settings.run_args.lookup_range;
+        let col_size = Table::<Fp>::cal_col_size(
+            settings.run_args.logrows as usize,
+            reserved_blinding_rows as usize,
+        );
+
+        Ok(CircuitStats {
+            num_rows: settings.num_rows,
+            total_assignments: settings.total_assignments,
+            total_const_size: settings.total_const_size,
+            logrows: settings.run_args.logrows,
+            min_logrows,
+            num_instance_columns: cs.num_instance_columns(),
+            num_advice_columns: cs.num_advice_columns(),
+            num_fixed_columns: cs.num_fixed_columns(),
+            num_selectors: cs.num_selectors(),
+            num_gates: cs.gates().len(),
+            num_lookup_arguments: cs.lookups().len(),
+            degree: cs.degree(),
+            num_lookup_tables: settings.required_lookups.len(),
+            lookup_table_size: (lookup_range.1 - lookup_range.0 + 1) as usize,
+            num_lookup_table_columns: Table::<Fp>::num_cols_required(lookup_range, col_size),
+            num_permutation_columns: cs.permutation().get_columns().len(),
+        })
+    }
+
+    /// Configures the circuit for `params`, as [Circuit::configure_with_params] does, but returns
+    /// the error if the model's ops can't be configured.
+    fn configure_graph(
+        cs: &mut ConstraintSystem<Fp>,
+        mut params: GraphSettings,
+        visibility: VarVisibility,
+    ) -> Result<GraphConfig, Box<dyn std::error::Error>> {
         params.set_num_blinding_factors(cs.blinding_factors());
         GLOBAL_SETTINGS.with(|settings| {
             *settings.borrow_mut() = Some(params.clone());
         });
-        let visibility = match VarVisibility::from_args(&params.run_args) {
-            Ok(v) => v,
-            Err(e) => {
-                log::error!("failed to create visibility: {:?}", e);
-                log::warn!("using default visibility");
-                VarVisibility::default()
-            }
-        };
 
         let mut module_configs = ModuleConfigs::from_visibility(
             cs,
@@ -1340,8 +1685,7 @@ impl Circuit<Fp> for GraphCircuit {
             params.run_args.logrows as usize,
             params.required_lookups,
             params.check_mode,
-        )
-        .unwrap();
+        )?;
 
         let model_config = ModelConfig { base, vars };
 
@@ -1354,17 +1698,42 @@ impl Circuit<Fp> for GraphCircuit {
         #[cfg(not(target_arch = "wasm32"))]
         info!(
             "circuit size: \n {}",
-            CircuitSize::from_cs(cs)
-                .as_json()
-                .unwrap()
-                .to_colored_json_auto()
-                .unwrap()
+            CircuitSize::from_cs(cs).as_json()?.to_colored_json_auto()?
         );
 
-        GraphConfig {
+        Ok(GraphConfig {
             model_config,
             module_configs,
-        }
+        })
+    }
+}
+
+impl Circuit<Fp> for GraphCircuit {
+    type Config = GraphConfig;
+    type FloorPlanner = ModulePlanner;
+    type Params = GraphSettings;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn params(&self) -> Self::Params {
+        // safe to clone because the model is Arc'd
+        self.settings().clone()
+    }
+
+    #[tracing::instrument(name = "configure", skip_all)]
+    fn configure_with_params(cs: &mut ConstraintSystem<Fp>, params: Self::Params) -> Self::Config {
+        let visibility = match VarVisibility::from_args(&params.run_args) {
+            Ok(v) => v,
+            Err(e) => {
+                log::error!("failed to create visibility: {:?}", e);
+                log::warn!("using default visibility");
+                VarVisibility::default()
+            }
+        };
+
+        Self::configure_graph(cs, params, visibility).unwrap()
     }
 
     fn configure(_: &mut ConstraintSystem<Fp>) -> Self::Config {
@@ -1376,6 +1745,7 @@ impl Circuit<Fp> for GraphCircuit {
         config: Self::Config,
         mut layouter: impl Layouter<Fp>,
     ) -> Result<(), PlonkError> {
+        let _span = tracing::info_span!("synthesize").entered();
         trace!("Setting input in synthesize");
         let input_vis = &self.settings().run_args.input_visibility;
         let output_vis = &self.settings().run_args.output_visibility;
@@ -1550,3 +1920,40 @@ impl Circuit<Fp> for GraphCircuit {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+
+    #[tokio::test]
+    async fn test_layer_larger_than_column_lays_out() {
+        let model_path = std::path::Path::new("./examples/onnx/1l_concat/network.onnx");
+        let data_path = std::path::PathBuf::from("./examples/onnx/1l_concat/input.json");
+
+        // instances live in a single column, so keep them out of the circuit
+        let run_args = RunArgs {
+            logrows: 6,
+            input_visibility: Visibility::Private,
+            output_visibility: Visibility::Private,
+            ..Default::default()
+        };
+
+        let mut circuit = GraphCircuit::from_run_args(&run_args, model_path).unwrap();
+        // the concat layer alone is larger than a column, so its cells wrap across advice blocks
+        let output_len: usize = circuit.model().graph.output_shapes().unwrap()[0]
+            .iter()
+            .product();
+        assert!(output_len > 1 << run_args.logrows);
+        assert!(circuit.settings().num_rows > 1 << run_args.logrows);
+
+        let data = GraphData::from_path(data_path).unwrap();
+        let mut inputs = circuit.load_graph_input(&data).await.unwrap();
+        let witness = circuit.forward(&mut inputs, None, None).unwrap();
+        circuit.load_graph_witness(&witness).unwrap();
+        let public_inputs = circuit.prepare_public_inputs(&witness).unwrap();
+
+        let prover = MockProver::run(run_args.logrows, &circuit, vec![public_inputs]).unwrap();
+        prover.assert_satisfied();
+    }
+}
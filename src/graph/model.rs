@@ -1,4 +1,3 @@
-use super::extract_const_quantized_values;
 use super::node::*;
 use super::scale_to_multiplier;
 use super::vars::*;
@@ -9,6 +8,7 @@ use crate::circuit::region::RegionCtx;
 use crate::circuit::Input;
 use crate::circuit::InputType;
 use crate::circuit::Unknown;
+use crate::error::EzklError;
 use crate::fieldutils::felt_to_i128;
 use crate::tensor::ValType;
 use crate::{
@@ -37,15 +37,16 @@ use tract_onnx::prelude::{
 #[cfg(not(target_arch = "wasm32"))]
 use tract_onnx::tract_hir::ops::scan::Scan;
 
+#[cfg(not(target_arch = "wasm32"))]
+use indicatif::ProgressBar;
 use log::error;
 use log::{debug, info, trace};
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use std::collections::BTreeMap;
 #[cfg(not(target_arch = "wasm32"))]
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::error::Error;
-use std::fs;
-use std::io::Read;
 use std::path::PathBuf;
 #[cfg(not(target_arch = "wasm32"))]
 use tabled::Table;
@@ -140,22 +141,38 @@ pub enum InputMapping {
     },
 }
 
-fn number_of_iterations(mappings: &[InputMapping], dims: Vec<&[usize]>) -> usize {
-    let mut number_of_iterations =
-        dims.iter()
-            .zip(mappings)
-            .filter_map(|(dims, mapping)| match mapping {
-                InputMapping::Stacked { axis, chunk } => Some(
-                    // number of iterations given the dim size along the axis
-                    // and the chunk size
-                    (dims[*axis] + chunk - 1) / chunk,
-                ),
-                _ => None,
-            });
-    // assert all collected number of iterations are equal
-    assert!(number_of_iterations.clone().all_equal());
+fn empty_lookup_input(idx: usize) -> EzklError {
+    EzklError::Graph(format!("node {} has an empty lookup input", idx))
+}
+
+fn number_of_iterations(
+    idx: usize,
+    mappings: &[InputMapping],
+    dims: Vec<&[usize]>,
+) -> Result<usize, EzklError> {
+    let number_of_iterations = dims
+        .iter()
+        .zip(mappings)
+        .filter_map(|(dims, mapping)| match mapping {
+            InputMapping::Stacked { axis, chunk } => Some(
+                // number of iterations given the dim size along the axis
+                // and the chunk size
+                dims.get(*axis)
+                    .map(|dim| (dim + chunk - 1) / chunk)
+                    .ok_or(GraphError::InvalidDims(idx, "subgraph input".to_string())),
+            ),
+            _ => None,
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // all stacked inputs must be iterated over the same number of times
+    if !number_of_iterations.iter().all_equal() {
+        return Err(EzklError::Graph(format!(
+            "subgraph node {} has stacked inputs with differing iteration counts {:?}",
+            idx, number_of_iterations
+        )));
+    }
 
-    number_of_iterations.next().unwrap_or(1)
+    Ok(number_of_iterations.first().copied().unwrap_or(1))
 }
 
 fn input_state_idx(input_mappings: &[InputMapping]) -> Vec<usize> {
@@ -175,6 +192,112 @@ fn output_state_idx(output_mappings: &[Vec<OutputMapping>]) -> Vec<usize> {
         .collect::<Vec<_>>()
 }
 
+/// The rows of the model region a node is laid out in
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct NodeSpan {
+    /// the node's index in the graph
+    pub idx: usize,
+    /// the node's name in the source onnx graph
+    pub name: String,
+    /// the node's operation
+    pub op: String,
+    /// the first row (relative to the start of the model region) used by the node
+    pub start_row: usize,
+    /// one past the last row used by the node
+    pub end_row: usize,
+}
+
+impl NodeSpan {
+    /// Returns true if the span covers the given row of the model region
+    pub fn contains(&self, row: usize) -> bool {
+        self.start_row <= row && row < self.end_row
+    }
+
+    /// Returns true if the span uses the given offset of a column, once the model region's rows
+    /// wrap around columns of `col_size` rows
+    pub fn covers_offset(&self, offset: usize, col_size: usize) -> bool {
+        let len = self.end_row.saturating_sub(self.start_row);
+        if len >= col_size {
+            return len > 0;
+        }
+        let start = self.start_row % col_size;
+        let rows = start..start + len;
+        rows.contains(&offset) || rows.contains(&(offset + col_size))
+    }
+}
+
+/// A node that `ezkl check` found can't be laid out as it stands
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NodeIssue {
+    /// the node's index in the graph (or in its subgraph)
+    pub idx: usize,
+    /// the node's name in the source onnx graph
+    pub name: String,
+    /// the node's operation
+    pub op: String,
+    /// the dimensions of the node's outputs
+    pub out_dims: Vec<Vec<usize>>,
+    /// why the node can't be laid out
+    pub reason: String,
+}
+
+/// A flat, serializable description of a node, used to export the model table
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct NodeSummary {
+    /// the node's index in the graph
+    pub idx: usize,
+    /// the node's name in the source onnx graph
+    pub name: String,
+    /// the node's operation
+    pub op: String,
+    /// the node's inputs as (node, outlet) pairs
+    pub inputs: Vec<Outlet>,
+    /// the dimensions of the node's inputs
+    pub in_dims: Vec<Vec<usize>>,
+    /// the dimensions of the node's outputs
+    pub out_dims: Vec<Vec<usize>>,
+    /// the scales of the node's outputs
+    pub out_scales: Vec<crate::Scale>,
+    /// the advice column block the node starts in
+    pub block: usize,
+    /// the estimated number of rows the node uses
+    pub rows: usize,
+    /// the lookups the node requires
+    pub required_lookups: Vec<String>,
+}
+
+impl NodeSummary {
+    /// The csv header matching [NodeSummary::csv_record]
+    pub fn csv_header() -> String {
+        "idx,name,op,inputs,in_dims,out_dims,out_scales,block,rows,required_lookups".to_string()
+    }
+
+    /// Formats the summary as a single csv record
+    pub fn csv_record(&self) -> String {
+        [
+            self.idx.to_string(),
+            self.name.clone(),
+            self.op.clone(),
+            format!("{:?}", self.inputs),
+            format!("{:?}", self.in_dims),
+            format!("{:?}", self.out_dims),
+            format!("{:?}", self.out_scales),
+            self.block.to_string(),
+            self.rows.to_string(),
+            self.required_lookups.join(";"),
+        ]
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .join(",")
+    }
+}
+
 /// Enables model as subnode of other models
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum NodeType {
@@ -253,6 +376,14 @@ impl NodeType {
         }
     }
 
+    /// Returns the node's name in the source onnx graph.
+    pub fn name(&self) -> String {
+        match self {
+            NodeType::Node(n) => n.name.clone(),
+            NodeType::SubGraph { .. } => "SUBGRAPH".into(),
+        }
+    }
+
     /// Returns true if the operation is a rebase
     pub fn is_rebase(&self) -> bool {
         match self {
@@ -327,6 +458,41 @@ pub struct ParsedNodes {
 }
 
 impl ParsedNodes {
+    /// Groups the nodes into buckets that only depend on nodes in earlier buckets, in execution order
+    pub fn forward_buckets(&self) -> Vec<Vec<(&usize, &NodeType)>> {
+        let mut depths: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut buckets: Vec<Vec<(&usize, &NodeType)>> = vec![];
+        for (idx, n) in self.nodes.iter() {
+            let depth = n
+                .inputs()
+                .iter()
+                .filter_map(|(i, _)| depths.get(i).map(|d| d + 1))
+                .max()
+                .unwrap_or(0);
+            depths.insert(*idx, depth);
+            if buckets.len() <= depth {
+                buckets.resize_with(depth + 1, Vec::new);
+            }
+            buckets[depth].push((idx, n));
+        }
+        buckets
+    }
+
+    /// The last node (in execution order) reading each node's outputs. Nodes read by the graph's
+    /// outputs, or not read at all, are left out as they have to outlive every other node.
+    pub fn last_uses(&self) -> BTreeMap<usize, usize> {
+        let mut last_uses = BTreeMap::new();
+        for (idx, n) in self.nodes.iter() {
+            for (input, _) in n.inputs() {
+                last_uses.insert(input, *idx);
+            }
+        }
+        for (output, _) in self.outputs.iter() {
+            last_uses.remove(output);
+        }
+        last_uses
+    }
+
     /// Returns the number of the computational graph's inputs
     pub fn num_inputs(&self) -> usize {
         let input_nodes = self.inputs.iter();
@@ -434,7 +600,8 @@ impl Model {
     /// * `reader` - A reader for an Onnx file.
     /// * `run_args` - [RunArgs]
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn new(reader: &mut dyn std::io::Read, run_args: &RunArgs) -> Result<Self, Box<dyn Error>> {
+    #[tracing::instrument(name = "load", skip_all)]
+    pub fn new(reader: &mut dyn std::io::Read, run_args: &RunArgs) -> Result<Self, EzklError> {
         let visibility = VarVisibility::from_args(run_args)?;
 
         let graph = Self::load_onnx_model(reader, run_args, &visibility)?;
@@ -456,12 +623,9 @@ impl Model {
 
     ///
     pub fn load(path: PathBuf) -> Result<Self, Box<dyn Error>> {
-        // read bytes from file
-        let mut f = std::fs::File::open(&path)?;
-        let metadata = fs::metadata(&path)?;
-        let mut buffer = vec![0; metadata.len() as usize];
-        f.read_exact(&mut buffer)?;
-        let result = bincode::deserialize(&buffer)?;
+        // deserialize straight from the file so the raw bytes and the model are never both in memory
+        let reader = std::io::BufReader::new(std::fs::File::open(&path)?);
+        let result = bincode::deserialize_from(reader)?;
         Ok(result)
     }
 
@@ -514,6 +678,7 @@ impl Model {
             check_mode,
             version: env!("CARGO_PKG_VERSION").to_string(),
             num_blinding_factors: None,
+            key_hash: None,
         })
     }
 
@@ -522,7 +687,8 @@ impl Model {
     /// * `reader` - A reader for an Onnx file.
     /// * `model_inputs` - A vector of [Tensor]s to use as inputs to the model.
     /// * `run_args` - [RunArgs]
-    pub fn forward(&self, model_inputs: &[Tensor<Fp>]) -> Result<ForwardResult, Box<dyn Error>> {
+    #[tracing::instrument(name = "forward", skip_all)]
+    pub fn forward(&self, model_inputs: &[Tensor<Fp>]) -> Result<ForwardResult, EzklError> {
         let mut results: BTreeMap<&usize, Vec<Tensor<Fp>>> = BTreeMap::new();
         let mut max_lookup_inputs = 0;
         let mut min_lookup_inputs = 0;
@@ -535,190 +701,248 @@ impl Model {
             results.insert(input_idx, vec![input]);
         }
 
-        for (idx, n) in self.graph.nodes.iter() {
-            let mut inputs = vec![];
-            if n.is_input() {
-                let t = results.get(idx).ok_or(GraphError::MissingResults)?[0].clone();
-                inputs.push(t);
-            } else {
-                for (idx, outlet) in n.inputs().iter() {
-                    match results.get(&idx) {
-                        Some(value) => inputs.push(value[*outlet].clone()),
-                        None => return Err(Box::new(GraphError::MissingNode(*idx))),
-                    }
+        let buckets = self.graph.forward_buckets();
+        // the last bucket reading each node's outputs, after which they can be dropped
+        let mut last_uses: BTreeMap<usize, usize> = BTreeMap::new();
+        for (i, bucket) in buckets.iter().enumerate() {
+            for (_, n) in bucket {
+                for (input, _) in n.inputs() {
+                    last_uses.insert(input, i);
                 }
-            };
+            }
+        }
+        for (output, _) in self.graph.outputs.iter() {
+            last_uses.remove(output);
+        }
 
-            debug!("executing {}: {}", idx, n.as_str());
-            debug!("dims: {:?}", n.out_dims());
-            debug!(
-                "input_dims: {:?}",
-                inputs.iter().map(|x| x.dims()).collect::<Vec<_>>()
-            );
+        // nodes in the same bucket only depend on earlier buckets, so they can run in parallel
+        for (i, bucket) in buckets.into_iter().enumerate() {
+            let bucket_results = bucket
+                .par_iter()
+                .map(|(idx, n)| -> Result<_, EzklError> {
+                    let mut inputs = vec![];
+                    if n.is_input() {
+                        let t = results.get(idx).ok_or(GraphError::MissingResults)?[0].clone();
+                        inputs.push(t);
+                    } else {
+                        for (idx, outlet) in n.inputs().iter() {
+                            match results.get(&idx) {
+                                Some(value) => inputs.push(value[*outlet].clone()),
+                                None => return Err(GraphError::MissingNode(*idx).into()),
+                            }
+                        }
+                    };
+                    Self::forward_node(**idx, n, inputs)
+                })
+                .collect::<Result<Vec<_>, EzklError>>()?;
 
-            if n.is_lookup() {
-                let (mut min, mut max) = (0, 0);
-                for i in &inputs {
-                    max = max.max(
-                        i.iter()
-                            .map(|x| felt_to_i128(*x))
-                            .max()
-                            .ok_or("missing max")?,
-                    );
-                    min = min.min(
-                        i.iter()
-                            .map(|x| felt_to_i128(*x))
-                            .min()
-                            .ok_or("missing min")?,
-                    );
-                }
+            for ((idx, _), (res, min, max)) in bucket.into_iter().zip(bucket_results) {
                 max_lookup_inputs = max_lookup_inputs.max(max);
                 min_lookup_inputs = min_lookup_inputs.min(min);
-                debug!("max lookup inputs: {}", max);
-                debug!("min lookup inputs: {}", min);
+                results.insert(idx, res);
             }
+            results.retain(|idx, _| last_uses.get(*idx).map_or(true, |last| *last > i));
+        }
 
-            match n {
-                NodeType::Node(n) => {
-                    // execute the op
-                    let start = instant::Instant::now();
-                    let res = Op::<Fp>::f(&n.opkind, &inputs)?;
-                    let elapsed = start.elapsed();
-                    trace!("op took: {:?}", elapsed);
-                    // see if any of the intermediate lookup calcs are the max
-                    if !res.intermediate_lookups.is_empty() {
-                        let (mut min, mut max) = (0, 0);
-                        for i in &res.intermediate_lookups {
-                            max = max.max(i.clone().into_iter().max().ok_or("missing max")?);
-                            min = min.min(i.clone().into_iter().min().ok_or("missing min")?);
-                        }
-                        max_lookup_inputs = max_lookup_inputs.max(max);
-                        min_lookup_inputs = min_lookup_inputs.min(min);
-                        debug!("intermediate max lookup inputs: {}", max);
-                        debug!("intermediate min lookup inputs: {}", min);
-                    }
-                    debug!(
-                        "------------ output node int {}: {} \n ------------ float: {} \n ------------ max: {} \n ------------ min: {}",
-                        idx,
-                        res.output.map(crate::fieldutils::felt_to_i32).show(),
-                        res.output
-                            .map(|x| crate::fieldutils::felt_to_f64(x)
-                                / scale_to_multiplier(n.out_scale))
-                            .show(),
-                        res.output.clone().into_iter().map(crate::fieldutils::felt_to_i128).max().unwrap_or(0),
-                        res.output.clone().into_iter().map(crate::fieldutils::felt_to_i128).min().unwrap_or(0),
-                    );
-                    results.insert(idx, vec![res.output]);
-                }
-                NodeType::SubGraph {
-                    model,
-                    output_mappings,
-                    input_mappings,
-                    inputs: input_tuple,
-                    ..
-                } => {
-                    let orig_inputs = inputs.clone();
-                    let input_mappings = input_mappings.clone();
+        let output_nodes = self.graph.outputs.iter();
+        debug!(
+            "model outputs are nodes: {:?}",
+            output_nodes.clone().collect_vec()
+        );
+        let outputs = output_nodes
+            .map(|(idx, outlet)| {
+                Ok(results.get(&idx).ok_or(GraphError::MissingResults)?[*outlet].clone())
+            })
+            .collect::<Result<Vec<_>, GraphError>>()?;
+
+        let res = ForwardResult {
+            outputs,
+            max_lookup_inputs,
+            min_lookup_inputs,
+        };
 
-                    let input_dims = inputs.iter().map(|inp| inp.dims());
-                    let num_iter = number_of_iterations(&input_mappings, input_dims.collect());
+        Ok(res)
+    }
 
-                    debug!(
-                        "{} iteration(s) in a subgraph with inputs {:?} and sources {:?}",
-                        num_iter, input_tuple, model.graph.inputs
-                    );
+    /// Runs a single node on its inputs, returning its outputs and the smallest and largest
+    /// lookup inputs it saw.
+    fn forward_node(
+        idx: usize,
+        n: &NodeType,
+        mut inputs: Vec<Tensor<Fp>>,
+    ) -> Result<(Vec<Tensor<Fp>>, i128, i128), EzklError> {
+        let mut max_lookup_inputs = 0;
+        let mut min_lookup_inputs = 0;
 
-                    debug!("input_mappings: {:?}", input_mappings);
+        debug!("executing {}: {}", idx, n.as_str());
+        debug!("dims: {:?}", n.out_dims());
+        debug!(
+            "input_dims: {:?}",
+            inputs.iter().map(|x| x.dims()).collect::<Vec<_>>()
+        );
 
-                    let mut full_results: Vec<Tensor<Fp>> = vec![];
+        if n.is_lookup() {
+            let (mut min, mut max) = (0, 0);
+            for i in &inputs {
+                max = max.max(
+                    i.par_iter()
+                        .map(|x| felt_to_i128(*x))
+                        .max()
+                        .ok_or_else(|| empty_lookup_input(idx))?,
+                );
+                min = min.min(
+                    i.par_iter()
+                        .map(|x| felt_to_i128(*x))
+                        .min()
+                        .ok_or_else(|| empty_lookup_input(idx))?,
+                );
+            }
+            max_lookup_inputs = max_lookup_inputs.max(max);
+            min_lookup_inputs = min_lookup_inputs.min(min);
+            debug!("max lookup inputs: {}", max);
+            debug!("min lookup inputs: {}", min);
+        }
 
-                    for i in 0..num_iter {
-                        // replace the Stacked input with the current chunk iter
-                        for ((mapping, inp), og_input) in
-                            input_mappings.iter().zip(&mut inputs).zip(&orig_inputs)
-                        {
-                            if let InputMapping::Stacked { axis, chunk } = mapping {
-                                let start = i * chunk;
-                                let end = (i + 1) * chunk;
-                                let t = crate::tensor::ops::slice(og_input, axis, &start, &end)?;
-                                *inp = t;
-                            }
+        match n {
+            NodeType::Node(n) => {
+                // execute the op
+                let res = {
+                    let _span =
+                        tracing::trace_span!("forward_node", idx = idx, op = %n.opkind.as_string())
+                            .entered();
+                    Op::<Fp>::f(&n.opkind, &inputs)?
+                };
+                // see if any of the intermediate lookup calcs are the max
+                if !res.intermediate_lookups.is_empty() {
+                    let (mut min, mut max) = (0, 0);
+                    for i in &res.intermediate_lookups {
+                        max = max.max(
+                            i.par_iter()
+                                .copied()
+                                .max()
+                                .ok_or_else(|| empty_lookup_input(idx))?,
+                        );
+                        min = min.min(
+                            i.par_iter()
+                                .copied()
+                                .min()
+                                .ok_or_else(|| empty_lookup_input(idx))?,
+                        );
+                    }
+                    max_lookup_inputs = max_lookup_inputs.max(max);
+                    min_lookup_inputs = min_lookup_inputs.min(min);
+                    debug!("intermediate max lookup inputs: {}", max);
+                    debug!("intermediate min lookup inputs: {}", min);
+                }
+                debug!(
+                    "------------ output node int {}: {} \n ------------ float: {} \n ------------ max: {} \n ------------ min: {}",
+                    idx,
+                    res.output.map(crate::fieldutils::felt_to_i32).show(),
+                    res.output
+                        .map(|x| crate::fieldutils::felt_to_f64(x)
+                            / scale_to_multiplier(n.out_scale))
+                        .show(),
+                    res.output.clone().into_iter().map(crate::fieldutils::felt_to_i128).max().unwrap_or(0),
+                    res.output.clone().into_iter().map(crate::fieldutils::felt_to_i128).min().unwrap_or(0),
+                );
+                Ok((vec![res.output], min_lookup_inputs, max_lookup_inputs))
+            }
+            NodeType::SubGraph {
+                model,
+                output_mappings,
+                input_mappings,
+                inputs: input_tuple,
+                ..
+            } => {
+                let orig_inputs = inputs.clone();
+                let input_mappings = input_mappings.clone();
+
+                let input_dims = inputs.iter().map(|inp| inp.dims());
+                let num_iter = number_of_iterations(idx, &input_mappings, input_dims.collect())?;
+
+                debug!(
+                    "{} iteration(s) in a subgraph with inputs {:?} and sources {:?}",
+                    num_iter, input_tuple, model.graph.inputs
+                );
+
+                debug!("input_mappings: {:?}", input_mappings);
+
+                let mut full_results: Vec<Tensor<Fp>> = vec![];
+
+                for i in 0..num_iter {
+                    // replace the Stacked input with the current chunk iter
+                    for ((mapping, inp), og_input) in
+                        input_mappings.iter().zip(&mut inputs).zip(&orig_inputs)
+                    {
+                        if let InputMapping::Stacked { axis, chunk } = mapping {
+                            let start = i * chunk;
+                            let end = (i + 1) * chunk;
+                            let t = crate::tensor::ops::slice(og_input, axis, &start, &end)?;
+                            *inp = t;
                         }
+                    }
 
-                        let res = model.forward(&inputs)?;
-                        // recursively get the max lookup inputs for subgraphs
-                        max_lookup_inputs = max_lookup_inputs.max(res.max_lookup_inputs);
-                        min_lookup_inputs = min_lookup_inputs.min(res.min_lookup_inputs);
-
-                        let mut outlets = BTreeMap::new();
-                        for (mappings, outlet_res) in output_mappings.iter().zip(res.outputs) {
-                            for mapping in mappings {
-                                match mapping {
-                                    OutputMapping::Single { outlet, .. } => {
+                    let res = model.forward(&inputs)?;
+                    // recursively get the max lookup inputs for subgraphs
+                    max_lookup_inputs = max_lookup_inputs.max(res.max_lookup_inputs);
+                    min_lookup_inputs = min_lookup_inputs.min(res.min_lookup_inputs);
+
+                    let mut outlets = BTreeMap::new();
+                    for (mappings, outlet_res) in output_mappings.iter().zip(res.outputs) {
+                        for mapping in mappings {
+                            match mapping {
+                                OutputMapping::Single { outlet, .. } => {
+                                    outlets.insert(outlet, outlet_res.clone());
+                                }
+                                OutputMapping::Stacked { outlet, axis, .. } => {
+                                    if !full_results.is_empty() {
+                                        let stacked_res = crate::tensor::ops::concat(
+                                            &[&full_results[*outlet], &outlet_res],
+                                            *axis,
+                                        )?;
+
+                                        outlets.insert(outlet, stacked_res);
+                                    } else {
                                         outlets.insert(outlet, outlet_res.clone());
                                     }
-                                    OutputMapping::Stacked { outlet, axis, .. } => {
-                                        if !full_results.is_empty() {
-                                            let stacked_res = crate::tensor::ops::concat(
-                                                &[&full_results[*outlet], &outlet_res],
-                                                *axis,
-                                            )?;
-
-                                            outlets.insert(outlet, stacked_res);
-                                        } else {
-                                            outlets.insert(outlet, outlet_res.clone());
-                                        }
-                                    }
                                 }
                             }
                         }
+                    }
 
-                        full_results = outlets.into_values().collect_vec();
+                    full_results = outlets.into_values().collect_vec();
 
-                        let output_states = output_state_idx(output_mappings);
-                        let input_states = input_state_idx(&input_mappings);
+                    let output_states = output_state_idx(output_mappings);
+                    let input_states = input_state_idx(&input_mappings);
 
-                        assert_eq!(input_states.len(), output_states.len());
+                    if input_states.len() != output_states.len() {
+                        return Err(EzklError::Graph(format!(
+                            "subgraph node {} has {} input states but {} output states",
+                            idx,
+                            input_states.len(),
+                            output_states.len()
+                        )));
+                    }
 
-                        for (input_idx, output_idx) in input_states.iter().zip(output_states) {
-                            inputs[*input_idx] = full_results[output_idx].clone();
-                        }
+                    for (input_idx, output_idx) in input_states.iter().zip(output_states) {
+                        inputs[*input_idx] = full_results[output_idx].clone();
                     }
+                }
 
-                    trace!(
-                        "------------ output subgraph node {}: {:?}",
-                        idx,
-                        full_results
-                            .iter()
-                            .map(|x|
-                            // convert to tensor i32
-                            x.map(crate::fieldutils::felt_to_i32).show())
-                            .collect_vec()
-                    );
+                trace!(
+                    "------------ output subgraph node {}: {:?}",
+                    idx,
+                    full_results
+                        .iter()
+                        .map(|x|
+                        // convert to tensor i32
+                        x.map(crate::fieldutils::felt_to_i32).show())
+                        .collect_vec()
+                );
 
-                    results.insert(idx, full_results);
-                }
+                Ok((full_results, min_lookup_inputs, max_lookup_inputs))
             }
         }
-
-        let output_nodes = self.graph.outputs.iter();
-        debug!(
-            "model outputs are nodes: {:?}",
-            output_nodes.clone().collect_vec()
-        );
-        let outputs = output_nodes
-            .map(|(idx, outlet)| {
-                Ok(results.get(&idx).ok_or(GraphError::MissingResults)?[*outlet].clone())
-            })
-            .collect::<Result<Vec<_>, GraphError>>()?;
-
-        let res = ForwardResult {
-            outputs,
-            max_lookup_inputs,
-            min_lookup_inputs,
-        };
-
-        Ok(res)
     }
 
     /// Loads an Onnx model from a specified path.
@@ -732,27 +956,169 @@ impl Model {
         run_args: &RunArgs,
         visibility: &VarVisibility,
     ) -> Result<ParsedNodes, Box<dyn Error>> {
-        use tract_onnx::tract_hir::internal::GenericFactoid;
-
-        let start_time = instant::Instant::now();
-
-        let mut model = tract_onnx::onnx().model_for_read(reader).map_err(|e| {
+        let model = Self::parse_onnx(reader).map_err(|e| {
             error!("Error loading model: {}", e);
             GraphError::ModelLoad
         })?;
+        Self::load_inference_model(model, run_args, visibility)
+    }
+
+    /// Parses an onnx model with tract, widening any bfloat16 tensors (which tract can't load) to
+    /// f32 first
+    #[cfg(not(target_arch = "wasm32"))]
+    fn parse_onnx(
+        reader: &mut dyn std::io::Read,
+    ) -> Result<tract_onnx::prelude::InferenceModel, Box<dyn Error>> {
+        let mut onnx = tract_onnx::onnx();
+        super::ml::register(&mut onnx);
+        let mut proto = onnx.proto_model_for_read(reader)?;
+        super::rewrite::widen_bf16(&mut proto);
+        Ok(onnx.model_for_proto_model(&proto)?)
+    }
+
+    /// Creates a `Model` from a frozen TensorFlow graph (a `.pb` GraphDef with its variables
+    /// converted to constants). tract lowers it to the same typed ops as an onnx model, so the
+    /// rest of the pipeline is shared.
+    /// # Arguments
+    /// * `reader` - A reader for a frozen GraphDef.
+    /// * `run_args` - [RunArgs]
+    #[cfg(all(feature = "tensorflow", not(target_arch = "wasm32")))]
+    pub fn new_tensorflow(
+        reader: &mut dyn std::io::Read,
+        run_args: &RunArgs,
+    ) -> Result<Self, EzklError> {
+        let visibility = VarVisibility::from_args(run_args)?;
+
+        let model = tract_tensorflow::tensorflow()
+            .model_for_read(reader)
+            .map_err(|e| {
+                error!("Error loading model: {}", e);
+                GraphError::ModelLoad
+            })?;
+        let graph = Self::load_inference_model(model, run_args, &visibility)?;
+
+        let om = Model { graph, visibility };
+
+        debug!("\n {}", om.table_nodes());
+
+        Ok(om)
+    }
+
+    /// Creates a `Model` from an NNEF model (a directory or tarball holding `graph.nnef` and its
+    /// `.dat` tensors), such as one tract has already decluttered and dumped. NNEF models are
+    /// typed, so they skip the inference-fact resolution onnx models go through.
+    /// # Arguments
+    /// * `path` - The path of the NNEF directory or tarball.
+    /// * `run_args` - [RunArgs]
+    #[cfg(all(feature = "nnef", not(target_arch = "wasm32")))]
+    pub fn new_nnef(path: &std::path::Path, run_args: &RunArgs) -> Result<Self, EzklError> {
+        use tract_onnx::WithOnnx;
+
+        let visibility = VarVisibility::from_args(run_args)?;
+
+        let model = tract_nnef::nnef()
+            .with_onnx()
+            .model_for_path(path)
+            .and_then(|m| m.into_decluttered())
+            .map_err(|e| {
+                error!("Error loading model: {}", e);
+                GraphError::ModelLoad
+            })?;
+        let graph = Self::load_typed_model(model, run_args, &visibility)?;
+
+        let om = Model { graph, visibility };
+
+        debug!("\n {}", om.table_nodes());
+
+        Ok(om)
+    }
+
+    /// Runs the float onnx model through tract on each sample, as a reference for the quantized
+    /// forward pass. The flat inputs of a sample are reshaped to `input_shapes`, which also fix any
+    /// symbolic dimensions of the model.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn run_float_onnx(
+        path: &std::path::Path,
+        input_shapes: &[Vec<usize>],
+        samples: &[Vec<Vec<f32>>],
+    ) -> Result<Vec<Vec<Vec<f64>>>, Box<dyn Error>> {
+        use tract_onnx::prelude::{tract_ndarray, Datum, IntoTensor, TValue, TVec};
+
+        let mut model = Self::parse_onnx(&mut std::fs::File::open(path)?)?;
+        let mut datum_types = vec![];
+        for (i, shape) in input_shapes.iter().enumerate() {
+            let dt = model
+                .input_fact(i)?
+                .datum_type
+                .concretize()
+                .unwrap_or_else(f32::datum_type);
+            model.set_input_fact(i, InferenceFact::dt_shape(dt, shape.clone()))?;
+            datum_types.push(dt);
+        }
+        let model = super::rewrite::inline_ifs(model)?
+            .into_typed()?
+            .into_decluttered()?
+            .into_runnable()?;
+
+        samples
+            .iter()
+            .map(|inputs| {
+                let inputs = inputs
+                    .iter()
+                    .zip(input_shapes)
+                    .zip(&datum_types)
+                    .map(|((input, shape), dt)| {
+                        let tensor =
+                            tract_ndarray::Array::from_shape_vec(shape.clone(), input.clone())?
+                                .into_tensor();
+                        Ok(TValue::from(tensor.cast_to_dt(*dt)?.into_owned()))
+                    })
+                    .collect::<Result<TVec<_>, Box<dyn Error>>>()?;
+                model
+                    .run(inputs)?
+                    .iter()
+                    .map(|output| {
+                        let output = output.cast_to::<f32>()?;
+                        Ok(output
+                            .as_slice::<f32>()?
+                            .iter()
+                            .map(|v| *v as f64)
+                            .collect())
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Concretizes the input shapes of a parsed model and converts its nodes
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_inference_model(
+        mut model: tract_onnx::prelude::InferenceModel,
+        run_args: &RunArgs,
+        visibility: &VarVisibility,
+    ) -> Result<ParsedNodes, Box<dyn Error>> {
+        use tract_onnx::tract_hir::internal::GenericFactoid;
 
         let variables: std::collections::HashMap<String, usize> =
-            std::collections::HashMap::from_iter(run_args.variables.clone());
+            std::collections::HashMap::from_iter(run_args.graph_variables());
 
         for (i, id) in model.clone().inputs.iter().enumerate() {
             let input = model.node_mut(id.node);
             let mut fact: InferenceFact = input.outputs[0].fact.clone();
 
             for (i, x) in fact.clone().shape.dims().enumerate() {
-                if matches!(x, GenericFactoid::Any) {
+                // unknown dimensions, and leading symbolic ones (e.g "batch" or "N") no variable
+                // sets, are the batch dimension
+                let is_batch = match x {
+                    GenericFactoid::Any => true,
+                    GenericFactoid::Only(d) => {
+                        i == 0 && d.to_i64().is_err() && !variables.contains_key(&d.to_string())
+                    }
+                };
+                if is_batch {
                     let batch_size = match variables.get("batch_size") {
                         Some(x) => x,
-                        None => return Err("Unknown dimension batch_size in model inputs, set batch_size in variables".into()),
+                        None => return Err("Unknown dimension batch_size in model inputs, set --batch-size or batch_size in variables".into()),
                     };
                     fact.shape
                         .set_dim(i, tract_onnx::prelude::TDim::Val(*batch_size as i64));
@@ -765,17 +1131,73 @@ impl Model {
         for (i, _) in model.clone().outputs.iter().enumerate() {
             model.set_output_fact(i, InferenceFact::default())?;
         }
+        let model = super::rewrite::inline_ifs(model)?;
         // Note: do not optimize the model, as the layout will depend on underlying hardware
-        let mut model = model.into_typed()?.into_decluttered()?;
+        let model = model.into_typed()?.into_decluttered()?;
+        Self::load_typed_model(model, run_args, visibility)
+    }
+
+    /// Concretizes the symbolic dimensions of a typed (decluttered) model, folds the graph and
+    /// converts its nodes
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_typed_model(
+        mut model: tract_onnx::prelude::TypedModel,
+        run_args: &RunArgs,
+        visibility: &VarVisibility,
+    ) -> Result<ParsedNodes, Box<dyn Error>> {
+        let mut graph_variables = run_args.graph_variables();
+        // leading symbolic dimensions of the inputs that no variable sets are the batch
+        // dimension (onnx models had them set on their input facts already)
+        let batch_size = graph_variables
+            .iter()
+            .find(|(name, _)| name == "batch_size")
+            .map(|(_, v)| *v);
+        for input in model.input_outlets()? {
+            let fact = model.outlet_fact(*input)?;
+            if let (Some(tract_onnx::prelude::TDim::Sym(symbol)), Some(batch_size)) =
+                (fact.shape.first(), batch_size)
+            {
+                let name = symbol.to_string();
+                if !graph_variables.iter().any(|(n, _)| *n == name) {
+                    graph_variables.push((name, batch_size));
+                }
+            }
+        }
+
         let mut symbol_values = SymbolValues::default();
-        for (symbol, value) in run_args.variables.iter() {
+        for (symbol, value) in graph_variables.iter() {
             let symbol = model.symbol_table.sym(symbol);
             symbol_values = symbol_values.with(&symbol, *value as i64);
             info!("set {} to {}", symbol, value);
         }
-        model = model.concretize_dims(&symbol_values)?;
+        // decluttering again once the dims are concrete folds the shape computations (Shape,
+        // Gather, Concat ... feeding reshapes) exporters emit for dynamic dims into constants
+        model = model.concretize_dims(&symbol_values)?.into_decluttered()?;
+        model = super::rewrite::fold_batch_norms(model)?;
+        model = super::rewrite::fold_prelus(model)?;
+        model = super::rewrite::split_topk_indices(model)?;
+
+        // nodes are overridden by index or by name
+        let scale_overrides = run_args
+            .scale_overrides
+            .iter()
+            .map(|(node, scale)| {
+                let idx = match node.parse::<usize>() {
+                    Ok(idx) if idx < model.nodes.len() => idx,
+                    _ => model
+                        .nodes
+                        .iter()
+                        .position(|n| n.name == *node)
+                        .ok_or_else(|| format!("no node {} to override the scale of", node))?,
+                };
+                Ok((idx, *scale))
+            })
+            .collect::<Result<HashMap<_, _>, Box<dyn Error>>>()?;
 
         let scales = VarScales::from_args(run_args)?;
+        // one bar for the whole model, subgraphs add their nodes to it
+        let pb = crate::execute::init_bar(model.nodes.len() as u64);
+        pb.set_message("quantizing nodes");
         let nodes = Self::nodes_from_graph(
             &model,
             run_args,
@@ -783,8 +1205,10 @@ impl Model {
             visibility,
             &symbol_values,
             None,
-            None,
+            (!scale_overrides.is_empty()).then_some(scale_overrides),
+            &pb,
         )?;
+        pb.finish_and_clear();
 
         debug!("\n {}", model);
 
@@ -794,9 +1218,6 @@ impl Model {
             outputs: model.outputs.iter().map(|o| (o.node, o.slot)).collect(),
         };
 
-        let duration = start_time.elapsed();
-        trace!("model loading took: {:?}", duration);
-
         Ok(parsed_nodes)
     }
 
@@ -836,14 +1257,105 @@ impl Model {
         format!("{} \n{}", string, table)
     }
 
+    /// Summarizes each node of the model, with row estimates taken from a dummy layout.
+    pub fn node_summaries(&self, run_args: &RunArgs) -> Result<Vec<NodeSummary>, Box<dyn Error>> {
+        let spans = self.node_spans(run_args, &self.graph.input_shapes()?)?;
+        let col_size =
+            crate::tensor::VarTensor::dummy(run_args.logrows as usize, run_args.num_inner_cols)
+                .col_size();
+
+        self.graph
+            .nodes
+            .iter()
+            .map(|(idx, node)| {
+                let in_dims = node
+                    .inputs()
+                    .iter()
+                    .map(|(input, outlet)| {
+                        let input = self
+                            .graph
+                            .nodes
+                            .get(input)
+                            .ok_or(GraphError::MissingNode(*input))?;
+                        Ok(input.out_dims()[*outlet].clone())
+                    })
+                    .collect::<Result<Vec<_>, GraphError>>()?;
+                let span = spans
+                    .iter()
+                    .find(|s| s.idx == *idx)
+                    .cloned()
+                    .unwrap_or_default();
+                Ok(NodeSummary {
+                    idx: *idx,
+                    name: node.name(),
+                    op: node.as_str(),
+                    inputs: node.inputs(),
+                    in_dims,
+                    out_dims: node.out_dims(),
+                    out_scales: node.out_scales(),
+                    block: span.start_row / col_size,
+                    rows: span.end_row - span.start_row,
+                    required_lookups: node
+                        .required_lookups()
+                        .iter()
+                        .map(<LookupOp as Op<Fp>>::as_string)
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Walks every node (including those of subgraphs) and returns the ones whose op has no
+    /// circuit lowering, and the ones whose output shapes can't be laid out.
+    pub fn node_issues(&self) -> (Vec<NodeIssue>, Vec<NodeIssue>) {
+        fn walk(
+            nodes: &BTreeMap<usize, NodeType>,
+            unsupported: &mut Vec<NodeIssue>,
+            shapes: &mut Vec<NodeIssue>,
+        ) {
+            for (idx, node) in nodes {
+                if let NodeType::SubGraph { model, .. } = node {
+                    walk(&model.graph.nodes, unsupported, shapes);
+                    continue;
+                }
+                let issue = |reason: &str| NodeIssue {
+                    idx: *idx,
+                    name: node.name(),
+                    op: node.as_str(),
+                    out_dims: node.out_dims(),
+                    reason: reason.to_string(),
+                };
+                if matches!(node.opkind(), SupportedOp::Unknown(_)) {
+                    unsupported.push(issue("op has no circuit lowering"));
+                }
+                let out_dims = node.out_dims();
+                if out_dims.is_empty() {
+                    shapes.push(issue("node has no outputs"));
+                } else if out_dims
+                    .iter()
+                    .any(|d| !d.is_empty() && d.iter().product::<usize>() == 0)
+                {
+                    shapes.push(issue("output has a zero sized dimension"));
+                }
+            }
+        }
+
+        let mut unsupported = vec![];
+        let mut shapes = vec![];
+        walk(&self.graph.nodes, &mut unsupported, &mut shapes);
+        (unsupported, shapes)
+    }
+
     /// Creates ezkl nodes from a tract graph
     /// # Arguments
     /// * `graph` - A tract graph.
     /// * `run_args` - [RunArgs]
     /// * `visibility` - Which inputs to the model are public and private (params, inputs, outputs) using [VarVisibility].
     /// * `input_scales` - The scales of the model's inputs.
+    /// * `pb` - The progress bar to count the quantized nodes on, shared with any subgraphs.
 
     #[cfg(not(target_arch = "wasm32"))]
+    #[allow(clippy::too_many_arguments)]
     pub fn nodes_from_graph(
         graph: &Graph<TypedFact, Box<dyn TypedOp>>,
         run_args: &RunArgs,
@@ -852,14 +1364,21 @@ impl Model {
         symbol_values: &SymbolValues,
         override_input_scales: Option<Vec<crate::Scale>>,
         override_output_scales: Option<HashMap<usize, crate::Scale>>,
+        pb: &ProgressBar,
     ) -> Result<BTreeMap<usize, NodeType>, Box<dyn Error>> {
         use crate::graph::node_output_shapes;
 
         let mut nodes = BTreeMap::<usize, NodeType>::new();
         let mut input_idx = 0;
         for (i, n) in graph.nodes.iter().enumerate() {
+            pb.inc(1);
             // Extract the slope layer hyperparams
-            match n.op().downcast_ref::<Scan>() {
+            // cumulative sums are laid out directly rather than unrolled as a subgraph
+            match n
+                .op()
+                .downcast_ref::<Scan>()
+                .filter(|scan| crate::graph::utilities::cumsum_axis(scan).is_none())
+            {
                 Some(b) => {
                     let model = b.body.clone();
                     let input_scales = n
@@ -930,6 +1449,7 @@ impl Model {
                         }
                     }
 
+                    pb.inc_length(model.nodes.len() as u64);
                     let subgraph_nodes = Self::nodes_from_graph(
                         &model,
                         run_args,
@@ -938,6 +1458,7 @@ impl Model {
                         symbol_values,
                         Some(input_scales.clone()),
                         Some(output_scale_override),
+                        pb,
                     )?;
 
                     let subgraph = ParsedNodes {
@@ -957,15 +1478,16 @@ impl Model {
                         .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
 
                     let mut output_scales = BTreeMap::new();
+                    let body_output_scales = om.graph.get_output_scales()?;
 
-                    for (i, _mapping) in b.output_mapping.iter().enumerate() {
-                        for mapping in b.output_mapping.iter() {
-                            if let Some(outlet) = mapping.last_value_slot {
-                                output_scales.insert(outlet, om.graph.get_output_scales()?[i]);
-                            }
-                            if let Some(last) = mapping.scan {
-                                output_scales.insert(last.0, om.graph.get_output_scales()?[i]);
-                            }
+                    // each output of the body feeds the scan outlets of its own mapping (e.g the
+                    // hidden and cell states of an LSTM sit at different scales)
+                    for (i, mapping) in b.output_mapping.iter().enumerate() {
+                        if let Some(outlet) = mapping.last_value_slot {
+                            output_scales.insert(outlet, body_output_scales[i]);
+                        }
+                        if let Some(last) = mapping.scan {
+                            output_scales.insert(last.0, body_output_scales[i]);
                         }
                     }
 
@@ -1047,13 +1569,45 @@ impl Model {
     /// # Arguments
     /// * `params` - A [GraphSettings] struct holding parsed CLI arguments.
     #[cfg(not(target_arch = "wasm32"))]
-    pub fn from_run_args(
-        run_args: &RunArgs,
-        model: &std::path::Path,
-    ) -> Result<Self, Box<dyn Error>> {
+    pub fn from_run_args(run_args: &RunArgs, model: &std::path::Path) -> Result<Self, EzklError> {
+        if model.is_dir() && model.join("saved_model.pb").exists() {
+            return Err(EzklError::Graph(format!(
+                "{} is a TensorFlow SavedModel, freeze it into a .pb graph (variables converted to constants) first",
+                model.display()
+            )));
+        }
+        #[cfg(feature = "nnef")]
+        if model.join("graph.nnef").exists()
+            || model
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map_or(false, |n| {
+                    n.ends_with(".nnef.tgz") || n.ends_with(".nnef.tar")
+                })
+        {
+            return Model::new_nnef(model, run_args);
+        }
+        #[cfg(feature = "tensorflow")]
+        if model.extension().and_then(|e| e.to_str()) == Some("pb") {
+            return Model::new_tensorflow(
+                &mut std::fs::File::open(model).map_err(|e| {
+                    EzklError::Io(format!(
+                        "failed to load model at {}: {}",
+                        model.display(),
+                        e
+                    ))
+                })?,
+                run_args,
+            );
+        }
         Model::new(
-            &mut std::fs::File::open(model)
-                .map_err(|_| format!("failed to load model at {}", model.display()))?,
+            &mut std::fs::File::open(model).map_err(|e| {
+                EzklError::Io(format!(
+                    "failed to load model at {}: {}",
+                    model.display(),
+                    e
+                ))
+            })?,
             run_args,
         )
     }
@@ -1073,6 +1627,7 @@ impl Model {
         check_mode: CheckMode,
     ) -> Result<PolyConfig<Fp>, Box<dyn Error>> {
         info!("configuring model");
+        let _span = tracing::info_span!("configure_model").entered();
 
         let mut base_gate = PolyConfig::configure(
             meta,
@@ -1085,6 +1640,7 @@ impl Model {
         let output = &vars.advices[1];
         let index = &vars.advices[2];
         for op in required_lookups {
+            let _span = tracing::debug_span!("configure_lookup", op = ?op).entered();
             base_gate.configure_lookup(meta, input, output, index, lookup_range, logrows, &op)?;
         }
 
@@ -1107,8 +1663,7 @@ impl Model {
         witnessed_outputs: &[ValTensor<Fp>],
     ) -> Result<Vec<ValTensor<Fp>>, Box<dyn Error>> {
         info!("model layout...");
-
-        let start_time = instant::Instant::now();
+        let _span = tracing::info_span!("layout").entered();
 
         let mut results = BTreeMap::<usize, Vec<ValTensor<Fp>>>::new();
 
@@ -1141,7 +1696,7 @@ impl Model {
                 vars.set_instance_idx(instance_idx);
 
                 let outputs = self
-                    .layout_nodes(&mut config, &mut thread_safe_region, &mut results)
+                    .layout_nodes(&mut config, &mut thread_safe_region, &mut results, &mut vec![])
                     .map_err(|e| {
                         error!("{}", e);
                         halo2_proofs::plonk::Error::Synthesis
@@ -1206,9 +1761,6 @@ impl Model {
             total_const_size.to_string().red()
         );
 
-        let duration = start_time.elapsed();
-        trace!("model layout took: {:?}", duration);
-
         Ok(outputs)
     }
 
@@ -1217,6 +1769,7 @@ impl Model {
         config: &mut ModelConfig,
         region: &mut RegionCtx<Fp>,
         results: &mut BTreeMap<usize, Vec<ValTensor<Fp>>>,
+        spans: &mut Vec<NodeSpan>,
     ) -> Result<Vec<ValTensor<Fp>>, Box<dyn Error>> {
         // index over results to get original inputs
         let orig_inputs: BTreeMap<usize, _> = results
@@ -1224,8 +1777,15 @@ impl Model {
             .into_iter()
             .filter(|(idx, _)| self.graph.inputs.contains(idx))
             .collect();
+        let last_uses = self.graph.last_uses();
 
+        // unlike `forward` this can't run the independent buckets in parallel: every node starts at
+        // the offset the previous one ended on, and flushes depend on that offset's alignment. The
+        // per-element work within a node goes through `RegionCtx::apply_in_loop`, which is parallel
+        // in the dummy layout.
         for (idx, node) in self.graph.nodes.iter() {
+            let _span =
+                tracing::debug_span!("layout_node", idx = *idx, op = %node.as_str()).entered();
             let mut values: Vec<ValTensor<Fp>> = if !node.is_input() {
                 node.inputs()
                     .iter()
@@ -1237,6 +1797,12 @@ impl Model {
                 // we re-assign inputs, always from the 0 outlet
                 vec![results.get(idx).ok_or(GraphError::MissingResults)?[0].clone()]
             };
+            // drop the intermediates no later node reads
+            for (input, _) in node.inputs() {
+                if last_uses.get(&input) == Some(idx) {
+                    results.remove(&input);
+                }
+            }
 
             debug!(
                 "laying out {}: {}, row:{}, coord:{}, total_constants: {}",
@@ -1246,6 +1812,7 @@ impl Model {
                 region.linear_coord(),
                 region.total_constants()
             );
+            let start_row = region.row();
             debug!("dims: {:?}", node.out_dims());
             debug!(
                 "input_dims {:?}",
@@ -1287,7 +1854,8 @@ impl Model {
                     let input_mappings = input_mappings.clone();
 
                     let input_dims = values.iter().map(|inp| inp.dims());
-                    let num_iter = number_of_iterations(&input_mappings, input_dims.collect());
+                    let num_iter =
+                        number_of_iterations(*idx, &input_mappings, input_dims.collect())?;
 
                     debug!(
                         "{} iteration(s) in a subgraph with inputs {:?} and sources {:?}",
@@ -1320,7 +1888,12 @@ impl Model {
                                 .zip(values.clone().into_iter().map(|v| vec![v])),
                         );
 
-                        let res = model.layout_nodes(config, region, &mut subgraph_results)?;
+                        let res = model.layout_nodes(
+                            config,
+                            region,
+                            &mut subgraph_results,
+                            &mut vec![],
+                        )?;
 
                         let mut outlets = BTreeMap::new();
 
@@ -1350,7 +1923,15 @@ impl Model {
                         let output_states = output_state_idx(output_mappings);
                         let input_states = input_state_idx(&input_mappings);
 
-                        assert_eq!(input_states.len(), output_states.len());
+                        if input_states.len() != output_states.len() {
+                            return Err(EzklError::Graph(format!(
+                                "subgraph node {} has {} input states but {} output states",
+                                idx,
+                                input_states.len(),
+                                output_states.len()
+                            ))
+                            .into());
+                        }
 
                         for (input_idx, output_idx) in input_states.iter().zip(output_states) {
                             values[*input_idx] = full_results[output_idx].clone();
@@ -1367,6 +1948,14 @@ impl Model {
                     results.insert(*idx, full_results);
                 }
             }
+
+            spans.push(NodeSpan {
+                idx: *idx,
+                name: node.name(),
+                op: node.as_str(),
+                start_row,
+                end_row: region.row(),
+            });
         }
 
         // we do this so we can support multiple passes of the same model and have deterministic results (Non-assigned inputs etc... etc...)
@@ -1394,9 +1983,30 @@ impl Model {
         run_args: &RunArgs,
         input_shapes: &[Vec<usize>],
     ) -> Result<(usize, usize, usize), Box<dyn Error>> {
-        info!("calculating num of constraints using dummy model layout...");
+        self.dummy_layout_with_spans(run_args, input_shapes, &mut vec![])
+    }
+
+    /// Returns the rows of the model region each node is laid out in, using a dummy layout.
+    /// # Arguments
+    /// * `input_shapes` - The shapes of the inputs to the model.
+    pub fn node_spans(
+        &self,
+        run_args: &RunArgs,
+        input_shapes: &[Vec<usize>],
+    ) -> Result<Vec<NodeSpan>, Box<dyn Error>> {
+        let mut spans = vec![];
+        self.dummy_layout_with_spans(run_args, input_shapes, &mut spans)?;
+        Ok(spans)
+    }
 
-        let start_time = instant::Instant::now();
+    fn dummy_layout_with_spans(
+        &self,
+        run_args: &RunArgs,
+        input_shapes: &[Vec<usize>],
+        spans: &mut Vec<NodeSpan>,
+    ) -> Result<(usize, usize, usize), Box<dyn Error>> {
+        info!("calculating num of constraints using dummy model layout...");
+        let _span = tracing::info_span!("dummy_layout").entered();
 
         let mut results = BTreeMap::<usize, Vec<ValTensor<Fp>>>::new();
         let default_value = if !self.visibility.input.is_fixed() {
@@ -1428,7 +2038,7 @@ impl Model {
 
         let mut region = RegionCtx::new_dummy(0, run_args.num_inner_cols);
 
-        let outputs = self.layout_nodes(&mut model_config, &mut region, &mut results)?;
+        let outputs = self.layout_nodes(&mut model_config, &mut region, &mut results, spans)?;
 
         if self.visibility.output.is_public() || self.visibility.output.is_fixed() {
             let default_value = if !self.visibility.output.is_fixed() {
@@ -1464,9 +2074,6 @@ impl Model {
             }
         }
 
-        let duration = start_time.elapsed();
-        trace!("dummy model layout took: {:?}", duration);
-
         // Then number of columns in the circuits
         #[cfg(not(target_arch = "wasm32"))]
         info!(
@@ -1485,14 +2092,51 @@ impl Model {
         ))
     }
 
+    /// Lays out the model with known input values in a dummy region and returns the output values
+    /// the circuit's layouts compute. Unlike [Model::forward] these come from the constraint lowering of each op,
+    /// so comparing the two surfaces ops whose layout and forward pass disagree.
+    /// # Arguments
+    /// * `inputs` - The quantized inputs to the model.
+    pub fn layout_values(
+        &self,
+        run_args: &RunArgs,
+        inputs: &[Tensor<Fp>],
+    ) -> Result<Vec<Tensor<Fp>>, Box<dyn Error>> {
+        let mut results = BTreeMap::<usize, Vec<ValTensor<Fp>>>::new();
+        for (input_idx, input) in self.graph.inputs.iter().zip(inputs) {
+            results.insert(*input_idx, vec![input.map(Value::known).into()]);
+        }
+
+        let mut base = PolyConfig::dummy(run_args.logrows as usize, run_args.num_inner_cols);
+        // we want to report disagreements, not panic on them
+        base.check_mode = CheckMode::UNSAFE;
+        let mut model_config = ModelConfig {
+            base,
+            vars: ModelVars::new_dummy(),
+        };
+        let mut region = RegionCtx::new_dummy(0, run_args.num_inner_cols);
+
+        let outputs =
+            self.layout_nodes(&mut model_config, &mut region, &mut results, &mut vec![])?;
+
+        outputs
+            .iter()
+            .map(|output| {
+                let mut evals = output.get_felt_evals()?;
+                evals.reshape(output.dims())?;
+                Ok(evals)
+            })
+            .collect()
+    }
+
     /// Retrieves all constants from the model.
     pub fn get_all_params(&self) -> Vec<Tensor<Fp>> {
         let mut params = vec![];
         for node in self.graph.nodes.values() {
             match node {
-                NodeType::Node(_) => {
-                    if let Some(constant) = extract_const_quantized_values(node.opkind()) {
-                        params.push(constant);
+                NodeType::Node(n) => {
+                    if let SupportedOp::Constant(c) = &n.opkind {
+                        params.push(c.quantized_values.clone());
                     }
                 }
                 NodeType::SubGraph { model, .. } => {
@@ -1508,9 +2152,9 @@ impl Model {
         let mut const_shapes = vec![];
         for node in self.graph.nodes.values() {
             match node {
-                NodeType::Node(_) => {
-                    if let Some(constant) = extract_const_quantized_values(node.opkind()) {
-                        const_shapes.push(constant.dims().to_vec());
+                NodeType::Node(n) => {
+                    if let SupportedOp::Constant(c) = &n.opkind {
+                        const_shapes.push(c.quantized_values.dims().to_vec());
                     };
                 }
                 NodeType::SubGraph { model, .. } => {
@@ -1527,14 +2171,8 @@ impl Model {
         for node in self.graph.nodes.values_mut() {
             match node {
                 NodeType::Node(n) => {
-                    if let SupportedOp::Constant(c) = &n.opkind {
-                        let mut op = crate::circuit::Constant::new(
-                            c.quantized_values.clone(),
-                            c.raw_values.clone(),
-                        );
-                        op.pre_assign(consts[const_idx].clone());
-                        n.opkind = SupportedOp::Constant(op);
-
+                    if let SupportedOp::Constant(c) = &mut n.opkind {
+                        c.pre_assign(consts[const_idx].clone());
                         const_idx += 1;
                     }
                 }
@@ -1,3 +1,7 @@
+use crate::circuit::modules::ecdsa::{
+    public_key_instances, SignatureChip, SignatureConfig, SignatureVariables,
+    NUM_SIGNATURE_INSTANCES,
+};
 use crate::circuit::modules::elgamal::{ElGamalConfig, ElGamalGadget, ElGamalVariables};
 use crate::circuit::modules::kzg::{KZGChip, KZGConfig};
 use crate::circuit::modules::poseidon::spec::{PoseidonSpec, POSEIDON_RATE, POSEIDON_WIDTH};
@@ -21,6 +25,8 @@ pub const POSEIDON_LEN_GRAPH: usize = 32;
 pub const ELGAMAL_INSTANCES: usize = 4;
 /// Poseidon number of instancess
 pub const POSEIDON_INSTANCES: usize = 1;
+/// The module space of the signature chip (past the kzg modules, which start at 3)
+const SIGNATURE_MODULE: usize = 1000;
 
 /// Poseidon module type
 pub type ModulePoseidon =
@@ -37,6 +43,8 @@ pub struct ModuleConfigs {
     poseidon: Option<ModulePoseidonConfig>,
     /// ElGamal
     elgamal: Option<ElGamalConfig>,
+    /// ECDSA signatures
+    signature: Option<SignatureConfig>,
     /// Instance
     pub instance: Option<Column<Instance>>,
 }
@@ -100,6 +108,14 @@ impl ModuleConfigs {
                 self.poseidon = Some(ModulePoseidon::configure_with_optional_instance(cs, None));
             }
         };
+
+        // signatures are checked against the digests exposed by the poseidon module, so they
+        // share its instance column
+        if visibility.input.is_signed() && module_size.signature.0 > 0 {
+            if let Some(instance) = self.instance {
+                self.signature = Some(SignatureConfig::configure(cs, instance));
+            }
+        }
     }
 }
 
@@ -108,6 +124,12 @@ impl ModuleConfigs {
 pub struct ModuleVarSettings {
     ///
     elgamal: Option<ElGamalVariables>,
+    /// the signatures of signed inputs
+    #[serde(default)]
+    signature: Option<SignatureVariables>,
+    /// the poseidon digests the signatures are over
+    #[serde(default)]
+    digests: Vec<Fp>,
 }
 
 impl ModuleVarSettings {
@@ -115,6 +137,7 @@ impl ModuleVarSettings {
     pub fn new(elgamal: ElGamalVariables) -> Self {
         ModuleVarSettings {
             elgamal: Some(elgamal),
+            ..Default::default()
         }
     }
 }
@@ -124,6 +147,8 @@ impl Default for ModuleVarSettings {
         let dummy_elgamal = ElGamalVariables::default();
         ModuleVarSettings {
             elgamal: Some(dummy_elgamal),
+            signature: None,
+            digests: vec![],
         }
     }
 }
@@ -147,6 +172,10 @@ impl From<&GraphWitness> for ModuleSettings {
             if let Some(elgamal_result) = &processed_inputs.elgamal {
                 settings.input = ModuleVarSettings::new(elgamal_result.variables.clone());
             }
+            if let Some(signature) = &processed_inputs.signature {
+                settings.input.signature = Some(signature.clone());
+                settings.input.digests = processed_inputs.poseidon_hash.clone().unwrap_or_default();
+            }
         }
         if let Some(processed_params) = &graph_input.processed_params {
             if let Some(elgamal_result) = &processed_params.elgamal {
@@ -163,6 +192,27 @@ impl From<&GraphWitness> for ModuleSettings {
     }
 }
 
+/// Keys supplied by whoever provides the data, rather than generated while processing it
+#[derive(Clone, Debug, Default)]
+pub struct ProviderKeys {
+    /// the ElGamal variables to encrypt encrypted elements under
+    pub elgamal: Option<ElGamalVariables>,
+    /// the signatures of signed elements
+    pub signature: Option<SignatureVariables>,
+}
+
+impl ProviderKeys {
+    /// Fresh ElGamal variables held by the prover, for the elements it encrypts itself (params
+    /// and outputs) and for forward passes whose ciphertexts are never published or proven
+    /// (calibration, accuracy checks, ...). Their secret key ends up in the witness.
+    pub fn ephemeral() -> Self {
+        Self {
+            elgamal: Some(ElGamalVariables::gen_random(&mut rand::thread_rng())),
+            signature: None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 /// Result from ElGamal
 pub struct ElGamalResult {
@@ -183,6 +233,9 @@ pub struct ModuleForwardResult {
     pub elgamal: Option<ElGamalResult>,
     /// The outputs of the forward pass for KZG
     pub kzg_commit: Option<Vec<Vec<G1Affine>>>,
+    /// The signatures over the poseidon hashes of signed elements
+    #[serde(default)]
+    pub signature: Option<SignatureVariables>,
 }
 
 impl ModuleForwardResult {
@@ -205,7 +258,12 @@ impl ModuleForwardResult {
     /// get instances
     pub fn get_instances(&self) -> Vec<Vec<Fp>> {
         if let Some(poseidon) = &self.poseidon_hash {
-            poseidon.iter().map(|x| vec![*x]).collect()
+            let mut instances = poseidon.iter().map(|x| vec![*x]).collect_vec();
+            // the signer's public key follows the hashes it signed
+            if let Some(signature) = &self.signature {
+                instances.push(public_key_instances(&signature.public_key).to_vec());
+            }
+            instances
         } else if let Some(elgamal) = &self.elgamal {
             elgamal.ciphertexts.clone()
         } else {
@@ -220,6 +278,8 @@ pub struct ModuleSizes {
     kzg: Vec<usize>,
     poseidon: (usize, Vec<usize>),
     elgamal: (usize, Vec<usize>),
+    #[serde(default)]
+    signature: (usize, Vec<usize>),
 }
 
 impl ModuleSizes {
@@ -235,12 +295,13 @@ impl ModuleSizes {
                 0,
                 vec![0; crate::circuit::modules::elgamal::NUM_INSTANCE_COLUMNS],
             ),
+            signature: (0, vec![0]),
         }
     }
 
     /// Get the number of constraints
     pub fn max_constraints(&self) -> usize {
-        self.poseidon.0.max(self.elgamal.0)
+        self.poseidon.0.max(self.elgamal.0).max(self.signature.0)
     }
     /// Get the number of instances
     pub fn num_instances(&self) -> Vec<usize> {
@@ -249,6 +310,7 @@ impl ModuleSizes {
             .1
             .iter()
             .chain(self.elgamal.1.iter())
+            .chain(self.signature.1.iter())
             .copied()
             .collect_vec()
     }
@@ -277,6 +339,15 @@ impl GraphModules {
         shapes: Vec<Vec<usize>>,
         sizes: &mut ModuleSizes,
     ) {
+        let num_signed = shapes
+            .iter()
+            .filter(|shape| shape.iter().product::<usize>() > 0)
+            .count();
+        if visibility.is_signed() && num_signed > 0 {
+            // one signature per hashed input, all by the same public key
+            sizes.signature.0 += SignatureChip::num_rows(num_signed);
+            sizes.signature.1[0] += NUM_SIGNATURE_INSTANCES;
+        }
         for shape in shapes {
             let total_len = shape.iter().product::<usize>();
             if total_len > 0 {
@@ -287,6 +358,9 @@ impl GraphModules {
                     sizes.poseidon.0 += ModulePoseidon::num_rows(total_len);
                     // 1 constraints for hash
                     sizes.poseidon.1[0] += 1;
+                } else if visibility.is_encrypted_to_recipient() {
+                    sizes.elgamal.0 += ElGamalGadget::num_rows_to_recipient(total_len);
+                    sizes.elgamal.1[0] += 4;
                 } else if visibility.is_encrypted() {
                     // add the 1 time fixed cost of maingate + ecc chips
                     sizes.elgamal.0 += ElGamalGadget::num_rows(total_len);
@@ -367,6 +441,9 @@ impl GraphModules {
             });
         }
 
+        // the digests of signed elements are exposed from this row onwards
+        let digest_row = *instance_offset;
+
         // If the module is hashed, then we need to hash the inputs
         if element_visibility.is_hashed() && !values.is_empty() {
             if let Some(config) = &mut configs.poseidon {
@@ -398,13 +475,19 @@ impl GraphModules {
                 // load the variables
                 let variables = module_settings.elgamal.as_ref().unwrap().clone();
                 chip.load_variables(variables.clone());
-                // load the sk:
-                let sk: Tensor<ValType<Fp>> =
-                    Tensor::new(Some(&[Value::known(variables.sk).into()]), &[1]).unwrap();
-                // concat values and sk to get the inputs
+                // load the sk, or the randomness when encrypting to a recipient's key
+                let secret = if element_visibility.is_encrypted_to_recipient() {
+                    chip.to_recipient();
+                    variables.r
+                } else {
+                    variables.sk
+                };
+                let secret: Tensor<ValType<Fp>> =
+                    Tensor::new(Some(&[Value::known(secret).into()]), &[1]).unwrap();
+                // concat values and the secret to get the inputs
                 let mut inputs = values
                     .iter_mut()
-                    .map(|x| vec![x.clone(), sk.clone().into()])
+                    .map(|x| vec![x.clone(), secret.clone().into()])
                     .collect_vec();
                 // layout the module
                 inputs.iter_mut().for_each(|x| {
@@ -423,17 +506,64 @@ impl GraphModules {
             }
         }
 
+        // If the module is signed, check the signatures over the hashes laid out above
+        if element_visibility.is_signed() && !values.is_empty() {
+            if let Some(config) = &mut configs.signature {
+                layouter
+                    .assign_region(|| format!("_enter_module_{}", SIGNATURE_MODULE), |_| Ok(()))?;
+                let chip = SignatureChip::new(config.clone());
+                let variables = module_settings.signature.clone().unwrap_or_default();
+                let digests = (0..values.len())
+                    .map(|i| match module_settings.digests.get(i) {
+                        Some(d) => Value::known(*d),
+                        None => Value::unknown(),
+                    })
+                    .collect_vec();
+                chip.layout(layouter, &variables, &digests, digest_row, *instance_offset)?;
+                *instance_offset += NUM_SIGNATURE_INSTANCES;
+
+                config.initialized = true;
+            } else {
+                log::error!("Signature config not initialized");
+                return Err(Error::Synthesis);
+            }
+        }
+
         Ok(())
     }
 
-    /// Run forward pass
+    /// Run forward pass, encrypting encrypted elements under fresh variables held by the prover
+    /// ([ProviderKeys::ephemeral]). Elements encrypted to a recipient need the recipient's key and
+    /// go through [GraphModules::forward_with_keys].
     pub fn forward(
         inputs: &[Tensor<Fp>],
         element_visibility: Visibility,
         vk: Option<&VerifyingKey<G1Affine>>,
         srs: Option<&ParamsKZG<Bn256>>,
     ) -> Result<ModuleForwardResult, Box<dyn std::error::Error>> {
-        let mut rng = &mut rand::thread_rng();
+        if element_visibility.is_encrypted_to_recipient() {
+            return Err(
+                "elements encrypted to a recipient need the recipient's ElGamal public key".into(),
+            );
+        }
+        let keys = if element_visibility.is_encrypted() {
+            ProviderKeys::ephemeral()
+        } else {
+            ProviderKeys::default()
+        };
+        Self::forward_with_keys(inputs, element_visibility, vk, srs, &keys)
+    }
+
+    /// Run forward pass with the data provider's keys: encrypting under their ElGamal variables
+    /// (rather than freshly generated ones) if the elements are encrypted, and checking their
+    /// signatures if the elements are signed
+    pub fn forward_with_keys(
+        inputs: &[Tensor<Fp>],
+        element_visibility: Visibility,
+        vk: Option<&VerifyingKey<G1Affine>>,
+        srs: Option<&ParamsKZG<Bn256>>,
+        keys: &ProviderKeys,
+    ) -> Result<ModuleForwardResult, Box<dyn std::error::Error>> {
         let mut poseidon_hash = None;
         let mut elgamal = None;
         let mut kzg_commit = None;
@@ -472,30 +602,63 @@ impl GraphModules {
         }
 
         if element_visibility.is_encrypted() {
-            let variables = ElGamalVariables::gen_random(&mut rng);
-            let ciphertexts = inputs.iter().fold(vec![], |mut acc, x| {
-                let res = ElGamalGadget::run((x.to_vec(), variables.clone())).unwrap();
-                acc.extend(res);
-                acc
-            });
-
-            let encrypted_messages = inputs.iter().fold(vec![], |mut acc, x| {
-                let res = ElGamalGadget::encrypt(variables.pk, x.to_vec(), variables.r).c2;
-                acc.push(res);
-                acc
-            });
-
-            elgamal = Some(ElGamalResult {
+            // encrypting under generated variables would yield a valid proof of a ciphertext
+            // nobody can decrypt
+            let Some(variables) = keys.elgamal.clone() else {
+                return Err("encrypted elements need the ElGamal key they are encrypted under (the secret written by encrypt-input, passed with --key-path)".into());
+            };
+            elgamal = Some(Self::encrypt(
+                inputs,
                 variables,
-                ciphertexts,
-                encrypted_messages,
-            });
+                element_visibility.is_encrypted_to_recipient(),
+            ));
+        }
+
+        let mut signature = None;
+        if element_visibility.is_signed() {
+            match (&keys.signature, &poseidon_hash) {
+                (Some(variables), Some(digests)) => {
+                    variables.verify(digests)?;
+                    signature = Some(variables.clone());
+                }
+                _ => log::warn!(
+                    "no signatures provided for signed elements, the witness can't be proven"
+                ),
+            }
         }
 
         Ok(ModuleForwardResult {
             poseidon_hash,
             elgamal,
             kzg_commit,
+            signature,
         })
     }
+
+    /// Encrypt the inputs under the given ElGamal variables, producing the ciphertexts and
+    /// encrypted messages expected by the encrypted visibility modes
+    pub fn encrypt(
+        inputs: &[Tensor<Fp>],
+        variables: ElGamalVariables,
+        to_recipient: bool,
+    ) -> ElGamalResult {
+        let key_hash = ElGamalGadget::key_hash(&variables, to_recipient);
+        let ciphertexts = inputs.iter().fold(vec![], |mut acc, x| {
+            let cipher = ElGamalGadget::encrypt(variables.pk, x.to_vec(), variables.r);
+            acc.extend(ElGamalGadget::get_instances(&cipher, key_hash));
+            acc
+        });
+
+        let encrypted_messages = inputs.iter().fold(vec![], |mut acc, x| {
+            let res = ElGamalGadget::encrypt(variables.pk, x.to_vec(), variables.r).c2;
+            acc.push(res);
+            acc
+        });
+
+        ElGamalResult {
+            variables,
+            ciphertexts,
+            encrypted_messages,
+        }
+    }
 }
@@ -529,6 +529,8 @@ pub struct Node {
     pub idx: usize,
     /// The node's num of uses
     pub num_uses: usize,
+    /// The node's name in the source onnx graph
+    pub name: String,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -692,7 +694,8 @@ impl Node {
         let mut out_scale = opkind.out_scale(in_scales.clone())?;
         // rescale the inputs if necessary to get consistent fixed points, we select the largest scale (highest precision)
         let global_scale = scales.get_max();
-        opkind = RebaseScale::rebase(opkind, global_scale, out_scale, scales.rebase_multiplier);
+        let rebase_multiplier = scales.rebase_multiplier_for(&opkind.as_string());
+        opkind = RebaseScale::rebase(opkind, global_scale, out_scale, rebase_multiplier);
 
         out_scale = opkind.out_scale(in_scales)?;
 
@@ -723,6 +726,7 @@ impl Node {
             out_dims,
             out_scale,
             num_uses,
+            name: node.name.clone(),
         })
     }
 }
@@ -739,8 +743,12 @@ fn rescale_const_with_single_use(
         let scale_max = in_scales.iter().max().ok_or("no scales")?;
         if scale_max > &current_scale {
             let raw_values = constant.raw_values.clone();
-            constant.quantized_values =
-                super::quantize_tensor(raw_values, *scale_max, param_visibility)?;
+            constant.quantized_values = super::quantize_tensor(
+                raw_values,
+                *scale_max,
+                param_visibility,
+                constant.rounding,
+            )?;
         }
     }
 
@@ -0,0 +1,955 @@
+use log::debug;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+use tract_onnx::ops::logic::If;
+use tract_onnx::pb;
+use tract_onnx::prelude::{
+    tract_ndarray::Axis, InferenceModel, IntoTensor, OutletId, Tensor as TractTensor, TypedModel,
+};
+use tract_onnx::tract_core::ops::array::Topk;
+use tract_onnx::tract_core::ops::einsum::EinSum;
+use tract_onnx::tract_hir::internal::{
+    as_op, impl_dyn_hash, op_as_typed_op, tvec, EvalOp, Op, TValue, TVec, TractResult, TypedFact,
+    TypedOp,
+};
+use tract_onnx::tract_hir::{
+    ops::cnn::ConvUnary,
+    ops::expandable::expand,
+    ops::konst::Const,
+    ops::logic::Iff,
+    ops::nn::{leaky_relu, DataFormat},
+    tract_core::ops::cnn::conv::KernelFormat,
+};
+
+/// A per-channel constant applied to a node's output
+struct ChannelConstant {
+    /// the axis of the node's output the constant varies along (None for a scalar)
+    axis: Option<usize>,
+    values: Vec<f32>,
+}
+
+impl ChannelConstant {
+    /// The value for channel `c`
+    fn get(&self, c: usize) -> f32 {
+        if self.values.len() == 1 {
+            self.values[0]
+        } else {
+            self.values[c]
+        }
+    }
+}
+
+/// The constant input of a binary `op` (e.g. "Mul") node, if the node applies a per-channel
+/// constant to `input` and nothing else reads `input`.
+fn channel_constant(
+    model: &TypedModel,
+    node: usize,
+    op: &str,
+    input: OutletId,
+) -> Option<ChannelConstant> {
+    let n = model.node(node);
+    if n.op().name() != op || n.inputs.len() != 2 || !n.inputs.contains(&input) {
+        return None;
+    }
+    if model.outlet_successors(input).len() != 1 {
+        return None;
+    }
+    let other = n.inputs.iter().find(|i| **i != input)?;
+    let konst = model.node(other.node).op_as::<Const>()?;
+    let values = konst
+        .0
+        .cast_to::<f32>()
+        .ok()?
+        .as_slice::<f32>()
+        .ok()?
+        .to_vec();
+
+    // right align the constant against the output to find the axis it varies along
+    let rank = model.outlet_fact(input).ok()?.rank();
+    let shape = konst.0.shape();
+    if shape.len() > rank {
+        return None;
+    }
+    let varying = shape
+        .iter()
+        .enumerate()
+        .filter(|(_, d)| **d > 1)
+        .map(|(i, _)| rank - shape.len() + i)
+        .collect::<Vec<_>>();
+    match varying.as_slice() {
+        [] => Some(ChannelConstant { axis: None, values }),
+        [axis] => Some(ChannelConstant {
+            axis: Some(*axis),
+            values,
+        }),
+        _ => None,
+    }
+}
+
+/// The single node reading the output of `node`
+fn single_successor(model: &TypedModel, node: usize) -> Option<usize> {
+    match model.outlet_successors(OutletId::new(node, 0)) {
+        [inlet] => Some(inlet.node),
+        _ => None,
+    }
+}
+
+/// The value of the scalar constant at `outlet`
+fn scalar_constant(model: &TypedModel, outlet: OutletId) -> Option<f32> {
+    let konst = model.node(outlet.node).op_as::<Const>()?;
+    if konst.0.len() != 1 {
+        return None;
+    }
+    konst.0.cast_to_scalar::<f32>().ok()
+}
+
+/// Scales `tensor` by `scale` along `axis`
+fn scale_along(
+    tensor: &TractTensor,
+    axis: usize,
+    scale: &ChannelConstant,
+) -> Result<TractTensor, Box<dyn Error>> {
+    let mut array = tensor.cast_to::<f32>()?.to_array_view::<f32>()?.to_owned();
+    for (c, mut lane) in array.axis_iter_mut(Axis(axis)).enumerate() {
+        lane *= scale.get(c);
+    }
+    Ok(array.into_tensor())
+}
+
+/// Reroutes every reader of `from` to `outlet`
+fn reroute(model: &mut TypedModel, from: OutletId, outlet: OutletId) -> Result<(), Box<dyn Error>> {
+    for inlet in model.outlet_successors(from).to_vec() {
+        model.add_edge(outlet, inlet)?;
+    }
+    for output in model.outputs.iter_mut() {
+        if *output == from {
+            *output = outlet;
+        }
+    }
+    Ok(())
+}
+
+/// Reroutes every reader of `node`'s output to `outlet`, leaving `node` dangling
+fn bypass(model: &mut TypedModel, node: usize, outlet: OutletId) -> Result<(), Box<dyn Error>> {
+    reroute(model, OutletId::new(node, 0), outlet)
+}
+
+/// Folds a `Mul` (and a following `Add`) by per-channel constants into the kernel and bias of
+/// the convolution they follow. Returns true if the graph changed.
+fn fold_into_conv(model: &mut TypedModel, conv: usize) -> Result<bool, Box<dyn Error>> {
+    let Some(op) = model.node(conv).op_as::<ConvUnary>() else {
+        return Ok(false);
+    };
+    if op.kernel_fmt != KernelFormat::OIHW
+        || !matches!(op.pool_spec.data_format, DataFormat::NCHW | DataFormat::CHW)
+    {
+        return Ok(false);
+    }
+    let channel_axis = if op.pool_spec.data_format == DataFormat::NCHW {
+        1
+    } else {
+        0
+    };
+    let output = OutletId::new(conv, 0);
+    let Some(mul) = single_successor(model, conv) else {
+        return Ok(false);
+    };
+    let Some(scale) = channel_constant(model, mul, "Mul", output) else {
+        return Ok(false);
+    };
+    if scale.axis.map_or(false, |a| a != channel_axis) {
+        return Ok(false);
+    }
+    let shift = single_successor(model, mul)
+        .and_then(|add| {
+            Some((
+                add,
+                channel_constant(model, add, "Add", OutletId::new(mul, 0))?,
+            ))
+        })
+        .filter(|(_, shift)| shift.axis.map_or(true, |a| a == channel_axis));
+
+    let mut op = op.clone();
+    let out_channels = op.kernel.shape()[0];
+    op.kernel = Arc::new(scale_along(&op.kernel, 0, &scale)?);
+    let bias = match &op.bias {
+        Some(bias) => bias
+            .cast_to::<f32>()?
+            .as_slice::<f32>()?
+            .iter()
+            .copied()
+            .cycle()
+            .take(out_channels)
+            .collect::<Vec<_>>(),
+        None => vec![0.; out_channels],
+    };
+    let bias = (0..out_channels)
+        .map(|c| bias[c] * scale.get(c) + shift.as_ref().map_or(0., |(_, shift)| shift.get(c)))
+        .collect::<Vec<_>>();
+    op.bias = Some(Arc::new(TractTensor::from_shape(&[out_channels], &bias)?));
+    model.node_mut(conv).op = Box::new(op);
+
+    bypass(model, mul, output)?;
+    if let Some((add, _)) = shift {
+        bypass(model, add, output)?;
+    }
+    debug!("folded the batch norm after node {} into its kernel", conv);
+    Ok(true)
+}
+
+/// Folds a `Mul` by per-channel constants into the constant weights of the dense layer (einsum)
+/// it follows. Any shift stays as an `Add`. Returns true if the graph changed.
+fn fold_into_dense(model: &mut TypedModel, dense: usize) -> Result<bool, Box<dyn Error>> {
+    let Some(op) = model.node(dense).op_as::<EinSum>() else {
+        return Ok(false);
+    };
+    let equation = op.axes.to_string();
+    let Some((inputs, output_letters)) = equation.split_once("->") else {
+        return Ok(false);
+    };
+    let output = OutletId::new(dense, 0);
+    let Some(mul) = single_successor(model, dense) else {
+        return Ok(false);
+    };
+    let Some(scale) = channel_constant(model, mul, "Mul", output) else {
+        return Ok(false);
+    };
+
+    // the weights are the constant input that carries the output channel axis (and no other
+    // node reads)
+    let inputs = inputs.split(',').collect::<Vec<_>>();
+    let weights = model
+        .node(dense)
+        .inputs
+        .iter()
+        .zip(&inputs)
+        .find_map(|(i, letters)| {
+            let konst = model.node(i.node).op_as::<Const>()?;
+            if model.outlet_successors(*i).len() != 1 {
+                return None;
+            }
+            let axis = match scale.axis {
+                Some(axis) => {
+                    let letter = output_letters.chars().nth(axis)?;
+                    letters.chars().position(|c| c == letter)?
+                }
+                None => 0,
+            };
+            Some((i.node, konst.0.clone(), axis))
+        });
+    let Some((weights, tensor, axis)) = weights else {
+        return Ok(false);
+    };
+
+    let tensor = scale_along(&tensor, axis, &scale)?;
+    model.node_mut(weights).op = Box::new(Const(Arc::new(tensor)));
+    bypass(model, mul, output)?;
+    debug!(
+        "folded the batch norm scale after node {} into its weights",
+        dense
+    );
+    Ok(true)
+}
+
+/// Replaces a PReLU with a single shared slope, which tract expands into
+/// `Iff(Less(x, 0), Mul(x, slope), x)`, by a LeakyReLU. Returns true if the graph changed.
+fn fold_into_leaky_relu(model: &mut TypedModel, iff: usize) -> Result<bool, Box<dyn Error>> {
+    let node = model.node(iff);
+    if node.op().name() != "Iff" || node.inputs.len() != 3 {
+        return Ok(false);
+    }
+    let name = format!("{}.leaky_relu", node.name);
+    let (cond, scaled, x) = (node.inputs[0], node.inputs[1], node.inputs[2]);
+    if single_successor(model, cond.node) != Some(iff)
+        || single_successor(model, scaled.node) != Some(iff)
+    {
+        return Ok(false);
+    }
+
+    // the condition compares x to 0
+    let less = model.node(cond.node);
+    if less.op().name() != "Less"
+        || less.inputs.len() != 2
+        || less.inputs[0] != x
+        || scalar_constant(model, less.inputs[1]) != Some(0.)
+    {
+        return Ok(false);
+    }
+
+    // the negative branch scales x by a single slope
+    let mul = model.node(scaled.node);
+    if mul.op().name() != "Mul" || mul.inputs.len() != 2 || !mul.inputs.contains(&x) {
+        return Ok(false);
+    }
+    let Some(slope) = mul
+        .inputs
+        .iter()
+        .find(|i| **i != x)
+        .and_then(|i| scalar_constant(model, *i))
+    else {
+        return Ok(false);
+    };
+
+    let leaky = model.wire_node(name, leaky_relu(slope), &[x])?[0];
+    bypass(model, iff, leaky)?;
+    debug!("folded the prelu at node {} into a leaky relu", iff);
+    Ok(true)
+}
+
+/// Folds the per-channel scale and shift left by a BatchNorm (once tract has decomposed it into
+/// a `Mul` and `Add` by constants) into the weights of the convolution or dense layer before it,
+/// so that it costs neither constraints nor a rescaling lookup. Runs before quantization.
+pub fn fold_batch_norms(mut model: TypedModel) -> Result<TypedModel, Box<dyn Error>> {
+    let mut folded = false;
+    for node in model.eval_order()? {
+        folded |= fold_into_conv(&mut model, node)? || fold_into_dense(&mut model, node)?;
+    }
+    if folded {
+        // drop the bypassed nodes
+        model = model.into_compact()?;
+    }
+    Ok(model)
+}
+
+/// Replaces PReLUs that share a single slope across channels by a LeakyReLU, so that they cost a
+/// single lookup instead of a comparison, a division and a select. Per-channel slopes keep the
+/// decomposition tract gives them.
+pub fn fold_prelus(mut model: TypedModel) -> Result<TypedModel, Box<dyn Error>> {
+    let mut folded = false;
+    for node in model.eval_order()? {
+        folded |= fold_into_leaky_relu(&mut model, node)?;
+    }
+    if folded {
+        // drop the bypassed nodes
+        model = model.into_compact()?;
+    }
+    Ok(model)
+}
+
+/// The indices output of a `Topk`, as a node of its own
+#[derive(Debug, Clone, Hash)]
+pub struct TopkIndices(pub Topk);
+
+impl_dyn_hash!(TopkIndices);
+
+impl Op for TopkIndices {
+    fn name(&self) -> Cow<str> {
+        "TopkIndices".into()
+    }
+
+    op_as_typed_op!();
+}
+
+impl EvalOp for TopkIndices {
+    fn is_stateless(&self) -> bool {
+        true
+    }
+
+    fn eval(&self, inputs: TVec<TValue>) -> TractResult<TVec<TValue>> {
+        let mut outputs = self.0.eval(inputs)?;
+        Ok(tvec!(outputs.remove(1)))
+    }
+}
+
+impl TypedOp for TopkIndices {
+    fn output_facts(&self, inputs: &[&TypedFact]) -> TractResult<TVec<TypedFact>> {
+        let mut facts = self.0.output_facts(inputs)?;
+        Ok(tvec!(facts.remove(1)))
+    }
+
+    as_op!();
+}
+
+/// Moves the indices output of every `Topk` whose indices are read onto a [TopkIndices] node, as
+/// the circuit lays out nodes with a single output. The `Topk` keeps the values (and is dropped if
+/// nothing reads them).
+pub fn split_topk_indices(mut model: TypedModel) -> Result<TypedModel, Box<dyn Error>> {
+    let mut split = false;
+    for node in model.eval_order()? {
+        let Some(op) = model.node(node).op_as::<Topk>().cloned() else {
+            continue;
+        };
+        let indices = OutletId::new(node, 1);
+        if model.outlet_successors(indices).is_empty() && !model.outputs.contains(&indices) {
+            continue;
+        }
+        let name = format!("{}.indices", model.node(node).name);
+        let inputs = model.node(node).inputs.clone();
+        let split_indices = model.wire_node(name, TopkIndices(op), &inputs)?[0];
+        reroute(&mut model, indices, split_indices)?;
+        split = true;
+        debug!("split the indices of the topk at node {}", node);
+    }
+    if split {
+        // drop the topks only their indices were read from
+        model = model.into_compact()?;
+    }
+    Ok(model)
+}
+
+/// The onnx element type of bfloat16 tensors
+const ONNX_BFLOAT16: i32 = 16;
+/// The onnx element type of f32 tensors
+const ONNX_FLOAT: i32 = 1;
+
+/// Widens a bfloat16 tensor to f32. A bfloat16 is the top half of the f32 with the same value, so
+/// no precision is lost. Returns true if the tensor changed.
+fn widen_bf16_tensor(tensor: &mut pb::TensorProto) -> bool {
+    if tensor.data_type != ONNX_BFLOAT16 {
+        return false;
+    }
+    let halves: Vec<u16> = if !tensor.raw_data.is_empty() {
+        tensor
+            .raw_data
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect()
+    } else {
+        // without raw data each bfloat16 is held in the low bits of an int32
+        tensor.int32_data.iter().map(|x| *x as u16).collect()
+    };
+    tensor.float_data = halves
+        .into_iter()
+        .map(|h| f32::from_bits((h as u32) << 16))
+        .collect();
+    tensor.raw_data.clear();
+    tensor.int32_data.clear();
+    tensor.data_type = ONNX_FLOAT;
+    true
+}
+
+fn widen_bf16_graph(graph: &mut pb::GraphProto) -> bool {
+    let mut widened = false;
+    for tensor in graph.initializer.iter_mut() {
+        widened |= widen_bf16_tensor(tensor);
+    }
+    for info in graph
+        .input
+        .iter_mut()
+        .chain(graph.output.iter_mut())
+        .chain(graph.value_info.iter_mut())
+    {
+        if let Some(pb::type_proto::Value::TensorType(t)) =
+            info.r#type.as_mut().and_then(|t| t.value.as_mut())
+        {
+            if t.elem_type == ONNX_BFLOAT16 {
+                t.elem_type = ONNX_FLOAT;
+                widened = true;
+            }
+        }
+    }
+    for node in graph.node.iter_mut() {
+        for attr in node.attribute.iter_mut() {
+            if node.op_type == "Cast" && attr.name == "to" && attr.i == ONNX_BFLOAT16 as i64 {
+                attr.i = ONNX_FLOAT as i64;
+                widened = true;
+            }
+            for tensor in attr.t.iter_mut().chain(attr.tensors.iter_mut()) {
+                widened |= widen_bf16_tensor(tensor);
+            }
+            for body in attr.g.iter_mut().chain(attr.graphs.iter_mut()) {
+                widened |= widen_bf16_graph(body);
+            }
+        }
+    }
+    widened
+}
+
+/// Widens every bfloat16 tensor, value and cast of an onnx model (including its subgraphs) to
+/// f32, as tract can't load bfloat16. Weights keep their exact values and are then quantized like
+/// any other f32 weights. Runs on the protobuf, before tract parses it.
+pub fn widen_bf16(model: &mut pb::ModelProto) {
+    if let Some(graph) = model.graph.as_mut() {
+        if widen_bf16_graph(graph) {
+            debug!("widened the model's bfloat16 tensors to f32");
+        }
+    }
+}
+
+/// Wires the nodes of an `If` branch into `model`, feeding the branch's sources from the `If`'s
+/// `inputs` through `mapping`. Returns the outlets of the branch's outputs.
+fn inline_branch(
+    model: &mut InferenceModel,
+    prefix: &str,
+    body: &InferenceModel,
+    mapping: &[usize],
+    inputs: &[OutletId],
+) -> Result<Vec<OutletId>, Box<dyn Error>> {
+    let mut wired = HashMap::new();
+    for (source, input) in body.inputs.iter().zip(mapping) {
+        wired.insert(*source, *inputs.get(*input).ok_or("if input out of range")?);
+    }
+    for n in body.eval_order()? {
+        if body.inputs.iter().any(|i| i.node == n) {
+            continue;
+        }
+        let node = body.node(n);
+        let node_inputs = node
+            .inputs
+            .iter()
+            .map(|i| wired.get(i).copied().ok_or("if branch input not wired"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let outputs = model.wire_node(
+            format!("{}.{}", prefix, node.name),
+            node.op.clone(),
+            &node_inputs,
+        )?;
+        for (slot, outlet) in outputs.into_iter().enumerate() {
+            wired.insert(OutletId::new(n, slot), outlet);
+        }
+    }
+    Ok(body
+        .outputs
+        .iter()
+        .map(|o| wired.get(o).copied().ok_or("if branch output not wired"))
+        .collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Lays out `If`s whose condition is only known at runtime (tract can only type the ones with a
+/// constant condition): both branches are wired into the graph and an `Iff` on the condition
+/// selects between their outputs. Runs on the inference model, before typing.
+pub fn inline_ifs(mut model: InferenceModel) -> Result<InferenceModel, Box<dyn Error>> {
+    // propagate what we can so constant conditions are left to tract
+    model.analyse(false)?;
+    let mut inlined = false;
+    for node in model.eval_order()? {
+        let Some(op) = model.node(node).op_as::<If>().cloned() else {
+            continue;
+        };
+        let inputs = model.node(node).inputs.clone();
+        let cond = inputs[0];
+        if model.outlet_fact(cond)?.value.concretize().is_some() {
+            continue;
+        }
+        let name = model.node(node).name.clone();
+        let then_outputs = inline_branch(
+            &mut model,
+            &format!("{}.then", name),
+            &op.then_body,
+            &op.then_input_mapping,
+            &inputs,
+        )?;
+        let else_outputs = inline_branch(
+            &mut model,
+            &format!("{}.else", name),
+            &op.else_body,
+            &op.else_input_mapping,
+            &inputs,
+        )?;
+        if then_outputs.len() != else_outputs.len() {
+            return Err(format!("the branches of {} have different outputs", name).into());
+        }
+
+        for (slot, (then_output, else_output)) in then_outputs.iter().zip(&else_outputs).enumerate()
+        {
+            let selected = model.wire_node(
+                format!("{}.select.{}", name, slot),
+                expand(Iff),
+                &[cond, *then_output, *else_output],
+            )?[0];
+            let from = OutletId::new(node, slot);
+            for inlet in model.outlet_successors(from).to_vec() {
+                model.add_edge(selected, inlet)?;
+            }
+            for output in model.outputs.iter_mut() {
+                if *output == from {
+                    *output = selected;
+                }
+            }
+        }
+        inlined = true;
+        debug!("inlined both branches of the if at node {}", node);
+    }
+    if inlined {
+        // drop the inlined ifs
+        model = model.into_compact()?;
+    }
+    Ok(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tract_onnx::pb::attribute_proto::AttributeType;
+    use tract_onnx::prelude::{Framework, InferenceModelExt};
+    use tract_onnx::tract_hir::internal::tensor0;
+
+    fn tensor_info(name: &str, elem_type: i32, shape: &[i64]) -> pb::ValueInfoProto {
+        use pb::tensor_shape_proto::{dimension, Dimension};
+        pb::ValueInfoProto {
+            name: name.into(),
+            r#type: Some(pb::TypeProto {
+                value: Some(pb::type_proto::Value::TensorType(pb::type_proto::Tensor {
+                    elem_type,
+                    shape: Some(pb::TensorShapeProto {
+                        dim: shape
+                            .iter()
+                            .map(|d| Dimension {
+                                value: Some(dimension::Value::DimValue(*d)),
+                                ..Default::default()
+                            })
+                            .collect(),
+                    }),
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn constant(name: &str, dims: &[i64], values: &[f32]) -> pb::TensorProto {
+        pb::TensorProto {
+            name: name.into(),
+            dims: dims.to_vec(),
+            data_type: ONNX_FLOAT,
+            float_data: values.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn node(op_type: &str, inputs: &[&str], output: &str) -> pb::NodeProto {
+        pb::NodeProto {
+            op_type: op_type.into(),
+            input: inputs.iter().map(|i| i.to_string()).collect(),
+            output: vec![output.into()],
+            ..Default::default()
+        }
+    }
+
+    fn load(graph: pb::GraphProto) -> InferenceModel {
+        let proto = pb::ModelProto {
+            ir_version: 8,
+            opset_import: vec![pb::OperatorSetIdProto {
+                domain: "".into(),
+                version: 13,
+            }],
+            graph: Some(graph),
+            ..Default::default()
+        };
+        tract_onnx::onnx().model_for_proto_model(&proto).unwrap()
+    }
+
+    fn typed(graph: pb::GraphProto) -> TypedModel {
+        load(graph)
+            .into_typed()
+            .unwrap()
+            .into_decluttered()
+            .unwrap()
+    }
+
+    fn run(model: TypedModel, inputs: TVec<TValue>) -> Vec<f32> {
+        model.into_runnable().unwrap().run(inputs).unwrap()[0]
+            .cast_to::<f32>()
+            .unwrap()
+            .as_slice::<f32>()
+            .unwrap()
+            .to_vec()
+    }
+
+    fn count_ops(model: &TypedModel, name: &str) -> usize {
+        model
+            .nodes()
+            .iter()
+            .filter(|n| n.op().name() == name)
+            .count()
+    }
+
+    fn assert_close(a: &[f32], b: &[f32]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-4, "{:?} != {:?}", a, b);
+        }
+    }
+
+    /// The nodes after a layer writing "Y" that normalize it into "Z": a batch norm with a
+    /// per-channel scale over `channels`, or a multiplication and addition by scalars
+    fn norm(per_channel: bool, channels: usize) -> (Vec<pb::NodeProto>, Vec<pb::TensorProto>) {
+        if per_channel {
+            let c = &[channels as i64];
+            let ramp = |start: f32, step: f32| {
+                (0..channels)
+                    .map(|i| start + step * i as f32)
+                    .collect::<Vec<_>>()
+            };
+            (
+                vec![node(
+                    "BatchNormalization",
+                    &["Y", "scale", "bias", "mean", "var"],
+                    "Z",
+                )],
+                vec![
+                    constant("scale", c, &ramp(2., -2.5)),
+                    constant("bias", c, &ramp(0.1, 0.9)),
+                    constant("mean", c, &ramp(0.3, -0.5)),
+                    constant("var", c, &ramp(4., -3.75)),
+                ],
+            )
+        } else {
+            (
+                vec![node("Mul", &["Y", "s"], "S"), node("Add", &["S", "t"], "Z")],
+                vec![constant("s", &[], &[3.]), constant("t", &[], &[-1.])],
+            )
+        }
+    }
+
+    /// Runs a model before and after folding its batch norms, checking the fold removed every
+    /// `Mul` and left `adds` `Add`s
+    fn check_fold(model: TypedModel, input: TractTensor, adds: usize) {
+        let folded = fold_batch_norms(model.clone()).unwrap();
+        assert_eq!(count_ops(&folded, "Mul"), 0);
+        assert_eq!(count_ops(&folded, "Add"), adds);
+        assert_close(
+            &run(model, tvec!(input.clone().into())),
+            &run(folded, tvec!(input.into())),
+        );
+    }
+
+    fn conv_with_norm(per_channel: bool) -> TypedModel {
+        let (nodes, mut constants) = norm(per_channel, 2);
+        constants.push(constant(
+            "W",
+            &[2, 1, 2, 2],
+            &[1., -2., 0.5, 3., -1., 0.25, 2., -0.5],
+        ));
+        constants.push(constant("B", &[2], &[0.5, -1.5]));
+        typed(pb::GraphProto {
+            node: [vec![node("Conv", &["X", "W", "B"], "Y")], nodes].concat(),
+            initializer: constants,
+            input: vec![tensor_info("X", ONNX_FLOAT, &[1, 1, 3, 3])],
+            output: vec![tensor_info("Z", ONNX_FLOAT, &[1, 2, 2, 2])],
+            ..Default::default()
+        })
+    }
+
+    fn dense_with_norm(per_channel: bool) -> TypedModel {
+        let (nodes, mut constants) = norm(per_channel, 2);
+        constants.push(constant("W", &[3, 2], &[1., -2., 0.5, 3., -1., 0.25]));
+        typed(pb::GraphProto {
+            node: [vec![node("MatMul", &["X", "W"], "Y")], nodes].concat(),
+            initializer: constants,
+            input: vec![tensor_info("X", ONNX_FLOAT, &[2, 3])],
+            output: vec![tensor_info("Z", ONNX_FLOAT, &[2, 2])],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_fold_batch_norm_into_conv() {
+        let input = TractTensor::from_shape(
+            &[1, 1, 3, 3],
+            &[0.1f32, -0.7, 1.2, 2., 0., -1.1, 0.4, 3., -2.],
+        )
+        .unwrap();
+        for per_channel in [true, false] {
+            // the scale goes into the kernel and the shift into the bias
+            check_fold(conv_with_norm(per_channel), input.clone(), 0);
+        }
+    }
+
+    #[test]
+    fn test_fold_batch_norm_into_dense() {
+        let input = TractTensor::from_shape(&[2, 3], &[0.1f32, -0.7, 1.2, 2., 0., -1.1]).unwrap();
+        for per_channel in [true, false] {
+            // the scale goes into the weights and the shift stays
+            check_fold(dense_with_norm(per_channel), input.clone(), 1);
+        }
+    }
+
+    fn prelu(slope: Option<f32>) -> TypedModel {
+        let (initializer, mut input) = match slope {
+            Some(slope) => (vec![constant("A", &[1], &[slope])], vec![]),
+            None => (vec![], vec![tensor_info("A", ONNX_FLOAT, &[1])]),
+        };
+        input.insert(0, tensor_info("X", ONNX_FLOAT, &[1, 4]));
+        typed(pb::GraphProto {
+            node: vec![node("PRelu", &["X", "A"], "Z")],
+            initializer,
+            input,
+            output: vec![tensor_info("Z", ONNX_FLOAT, &[1, 4])],
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_fold_prelu_into_leaky_relu() {
+        let input = TractTensor::from_shape(&[1, 4], &[-2f32, -0.5, 0., 3.]).unwrap();
+        let model = prelu(Some(0.2));
+        assert_eq!(count_ops(&model, "Iff"), 1);
+
+        let folded = fold_prelus(model.clone()).unwrap();
+        assert_eq!(count_ops(&folded, "Iff"), 0);
+        assert_eq!(count_ops(&folded, "LeakyRelu"), 1);
+        let outputs = run(folded, tvec!(input.clone().into()));
+        assert_close(&outputs, &run(model, tvec!(input.into())));
+        assert_close(&outputs, &[-0.4, -0.1, 0., 3.]);
+    }
+
+    #[test]
+    fn test_fold_prelu_keeps_non_constant_slope() {
+        let model = prelu(None);
+        let folded = fold_prelus(model.clone()).unwrap();
+        assert_eq!(count_ops(&folded, "Iff"), 1);
+        assert_eq!(count_ops(&folded, "LeakyRelu"), 0);
+
+        let inputs = || {
+            tvec!(
+                TractTensor::from_shape(&[1, 4], &[-2f32, -0.5, 0., 3.])
+                    .unwrap()
+                    .into(),
+                TractTensor::from_shape(&[1], &[0.2f32]).unwrap().into()
+            )
+        };
+        assert_close(&run(folded, inputs()), &run(model, inputs()));
+    }
+
+    /// The onnx element type of bools
+    const ONNX_BOOL: i32 = 9;
+    /// The onnx element type of i64s
+    const ONNX_INT64: i32 = 7;
+
+    #[test]
+    fn test_split_topk_indices() {
+        let model = typed(pb::GraphProto {
+            node: vec![pb::NodeProto {
+                output: vec!["V".into(), "I".into()],
+                ..node("TopK", &["X", "k"], "")
+            }],
+            initializer: vec![pb::TensorProto {
+                name: "k".into(),
+                dims: vec![1],
+                data_type: ONNX_INT64,
+                int64_data: vec![2],
+                ..Default::default()
+            }],
+            input: vec![tensor_info("X", ONNX_FLOAT, &[4])],
+            output: vec![tensor_info("I", ONNX_INT64, &[2])],
+            ..Default::default()
+        });
+        let split = split_topk_indices(model.clone()).unwrap();
+        assert_eq!(count_ops(&split, "TopkIndices"), 1);
+        // nothing reads the values
+        assert_eq!(count_ops(&split, "Topk"), 0);
+
+        let input = || {
+            tvec!(TractTensor::from_shape(&[4], &[3f32, 5., 5., 1.])
+                .unwrap()
+                .into())
+        };
+        let indices = run(split, input());
+        assert_close(&indices, &run(model, input()));
+        assert_close(&indices, &[1., 2.]);
+    }
+
+    /// An `If` on "C" that negates "X" in its then branch and applies a relu in its else branch
+    fn if_model(condition: Option<bool>) -> InferenceModel {
+        let branch = |name: &str, op_type: &str, output: &str| pb::AttributeProto {
+            name: name.into(),
+            r#type: AttributeType::Graph as i32,
+            g: Some(pb::GraphProto {
+                node: vec![node(op_type, &["X"], output)],
+                output: vec![tensor_info(output, ONNX_FLOAT, &[2])],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let (initializer, mut input) = match condition {
+            Some(condition) => (
+                vec![pb::TensorProto {
+                    name: "C".into(),
+                    data_type: ONNX_BOOL,
+                    int32_data: vec![condition as i32],
+                    ..Default::default()
+                }],
+                vec![],
+            ),
+            None => (vec![], vec![tensor_info("C", ONNX_BOOL, &[])]),
+        };
+        input.insert(0, tensor_info("X", ONNX_FLOAT, &[2]));
+        load(pb::GraphProto {
+            node: vec![pb::NodeProto {
+                attribute: vec![
+                    branch("then_branch", "Neg", "T"),
+                    branch("else_branch", "Relu", "E"),
+                ],
+                ..node("If", &["C"], "Z")
+            }],
+            initializer,
+            input,
+            output: vec![tensor_info("Z", ONNX_FLOAT, &[2])],
+            ..Default::default()
+        })
+    }
+
+    fn count_ifs(model: &InferenceModel) -> usize {
+        model
+            .nodes()
+            .iter()
+            .filter(|n| n.op_as::<If>().is_some())
+            .count()
+    }
+
+    fn x() -> TValue {
+        TractTensor::from_shape(&[2], &[-1f32, 2.]).unwrap().into()
+    }
+
+    #[test]
+    fn test_inline_ifs_leaves_constant_conditions() {
+        let model = inline_ifs(if_model(Some(true))).unwrap();
+        assert_eq!(count_ifs(&model), 1);
+
+        let model = model.into_typed().unwrap().into_decluttered().unwrap();
+        assert_close(&run(model, tvec!(x())), &[1., -2.]);
+    }
+
+    #[test]
+    fn test_inline_ifs_selects_between_branches() {
+        let model = inline_ifs(if_model(None)).unwrap();
+        assert_eq!(count_ifs(&model), 0);
+
+        let model = model.into_typed().unwrap().into_decluttered().unwrap();
+        assert_close(
+            &run(model.clone(), tvec!(x(), tensor0(true).into())),
+            &[1., -2.],
+        );
+        assert_close(&run(model, tvec!(x(), tensor0(false).into())), &[0., 2.]);
+    }
+
+    #[test]
+    fn test_widen_bf16() {
+        // 1.5 and -2.0 as bfloat16
+        let raw = [0x3fc0u16, 0xc000u16]
+            .iter()
+            .flat_map(|h| h.to_le_bytes())
+            .collect::<Vec<_>>();
+        let mut model = pb::ModelProto {
+            graph: Some(pb::GraphProto {
+                initializer: vec![pb::TensorProto {
+                    data_type: ONNX_BFLOAT16,
+                    raw_data: raw,
+                    ..Default::default()
+                }],
+                node: vec![pb::NodeProto {
+                    op_type: "Cast".into(),
+                    attribute: vec![pb::AttributeProto {
+                        name: "to".into(),
+                        i: ONNX_BFLOAT16 as i64,
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        widen_bf16(&mut model);
+
+        let graph = model.graph.unwrap();
+        let weights = &graph.initializer[0];
+        assert_eq!(weights.data_type, ONNX_FLOAT);
+        assert_eq!(weights.float_data, vec![1.5, -2.0]);
+        assert!(weights.raw_data.is_empty());
+        assert_eq!(graph.node[0].attribute[0].i, ONNX_FLOAT as i64);
+    }
+}
@@ -0,0 +1,489 @@
+//! Splits an onnx model into a chain of smaller models that can each be proven in their own
+//! circuit.
+//!
+//! The nodes of an onnx graph are topologically sorted, so a cut between two consecutive nodes
+//! partitions the graph. The tensors that cross a cut (produced before it and used after it)
+//! become the outputs of the piece before the cut and, in the same order, the inputs of the
+//! piece after it. Tensors that skip over a piece are passed through it. Initializers and
+//! `Constant` nodes are copied into every piece that uses them instead of crossing a cut.
+//!
+//! Proving the pieces with `--output-visibility polycommit` / `--input-visibility polycommit`
+//! and aggregating them with `--split-proofs` (or with `hashed` visibilities and
+//! `link-proofs`) then binds each piece's outputs to the next piece's inputs.
+
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use tract_onnx::pb::{self, GraphProto, ModelProto, NodeProto, ValueInfoProto};
+
+/// The name of the manifest [split_model] writes next to the pieces
+pub const SPLIT_MANIFEST: &str = "split.json";
+
+/// A piece of a split model
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ModelPiece {
+    /// the path to the piece's onnx model
+    pub model: PathBuf,
+    /// the tensors the piece takes from the previous piece (or the model's inputs for the first piece)
+    pub inputs: Vec<String>,
+    /// the tensors the piece hands to the next piece (or the model's outputs for the last piece)
+    pub outputs: Vec<String>,
+    /// the number of nodes of the original model that the piece runs
+    pub num_nodes: usize,
+}
+
+fn is_constant(node: &NodeProto) -> bool {
+    node.op_type == "Constant" && node.input.is_empty()
+}
+
+/// The tensors a node reads, including the outer scope tensors its subgraphs (If / Loop / Scan
+/// bodies) read
+fn node_inputs(node: &NodeProto) -> Vec<&str> {
+    fn graph_inputs<'a>(graph: &'a GraphProto, inputs: &mut Vec<&'a str>) {
+        let produced: HashSet<&str> = graph
+            .node
+            .iter()
+            .flat_map(|n| n.output.iter().map(|o| o.as_str()))
+            .chain(graph.input.iter().map(|i| i.name.as_str()))
+            .chain(graph.initializer.iter().map(|i| i.name.as_str()))
+            .collect();
+        for node in &graph.node {
+            inputs.extend(
+                node_inputs(node)
+                    .into_iter()
+                    .filter(|i| !produced.contains(i)),
+            );
+        }
+    }
+
+    let mut inputs: Vec<&str> = node
+        .input
+        .iter()
+        .map(|i| i.as_str())
+        .filter(|i| !i.is_empty())
+        .collect();
+    for attr in &node.attribute {
+        if let Some(g) = &attr.g {
+            graph_inputs(g, &mut inputs);
+        }
+        for g in &attr.graphs {
+            graph_inputs(g, &mut inputs);
+        }
+    }
+    inputs
+}
+
+/// Where and how long each tensor of a graph lives
+struct Liveness<'a> {
+    /// the tensors that can cross a cut, in the order they are produced
+    tensors: Vec<&'a str>,
+    /// the index of the node producing each tensor, the graph inputs are produced before the
+    /// first node
+    produced: HashMap<&'a str, usize>,
+    /// one past the index of the last node using each tensor, graph outputs are used until the
+    /// end of the graph
+    last_use: HashMap<&'a str, usize>,
+}
+
+impl<'a> Liveness<'a> {
+    fn new(graph: &'a GraphProto, nodes: &[&'a NodeProto], free: &HashSet<&str>) -> Self {
+        let mut tensors = vec![];
+        let mut produced = HashMap::new();
+        for input in &graph.input {
+            if !free.contains(input.name.as_str()) {
+                tensors.push(input.name.as_str());
+                produced.insert(input.name.as_str(), 0);
+            }
+        }
+        let mut last_use = HashMap::new();
+        for (i, node) in nodes.iter().enumerate() {
+            for input in node_inputs(node) {
+                last_use.insert(input, i + 1);
+            }
+            for output in node.output.iter().filter(|o| !o.is_empty()) {
+                tensors.push(output.as_str());
+                produced.insert(output.as_str(), i + 1);
+            }
+        }
+        for output in &graph.output {
+            last_use.insert(output.name.as_str(), nodes.len() + 1);
+        }
+        Self {
+            tensors,
+            produced,
+            last_use,
+        }
+    }
+
+    /// The tensors produced before node `cut` and used from node `cut` onwards
+    fn crossing(&self, cut: usize) -> Vec<&'a str> {
+        self.tensors
+            .iter()
+            .filter(|t| {
+                self.produced[*t] <= cut && self.last_use.get(*t).map_or(false, |u| *u > cut)
+            })
+            .copied()
+            .collect()
+    }
+}
+
+/// Picks `num_pieces - 1` cuts, each the cut crossed by the fewest tensors within half a piece
+/// of evenly spaced nodes
+fn auto_cuts(liveness: &Liveness, num_nodes: usize, num_pieces: usize) -> Vec<usize> {
+    let window = std::cmp::max(num_nodes / (2 * num_pieces), 1);
+    let mut cuts: Vec<usize> = (1..num_pieces)
+        .map(|k| {
+            let target = k * num_nodes / num_pieces;
+            let lo = std::cmp::max(target.saturating_sub(window), 1);
+            let hi = std::cmp::min(target + window, num_nodes - 1);
+            (lo..=hi)
+                .min_by_key(|c| (liveness.crossing(*c).len(), c.abs_diff(target)))
+                .unwrap_or(target)
+        })
+        .collect();
+    cuts.dedup();
+    cuts
+}
+
+fn onnx_elem_type(dt: tract_onnx::prelude::DatumType) -> Option<i32> {
+    use tract_onnx::prelude::DatumType;
+    Some(match dt {
+        DatumType::F32 => 1,
+        DatumType::U8 => 2,
+        DatumType::I8 => 3,
+        DatumType::U16 => 4,
+        DatumType::I16 => 5,
+        DatumType::I32 => 6,
+        DatumType::I64 => 7,
+        DatumType::Bool => 9,
+        DatumType::F16 => 10,
+        DatumType::F64 => 11,
+        DatumType::U32 => 12,
+        DatumType::U64 => 13,
+        _ => return None,
+    })
+}
+
+/// Runs tract's type and shape analysis over the model to describe the `names` tensors that the
+/// model itself has no value info for
+fn infer_value_infos(
+    model_path: &Path,
+    names: &HashSet<&str>,
+) -> Result<HashMap<String, ValueInfoProto>, Box<dyn Error>> {
+    use pb::tensor_shape_proto::{dimension, Dimension};
+    use tract_onnx::prelude::{Framework, InferenceModelExt, OutletId};
+
+    let mut onnx = tract_onnx::onnx();
+    super::ml::register(&mut onnx);
+    let mut model = onnx.model_for_path(model_path)?;
+    model.analyse(false)?;
+
+    let mut infos = HashMap::new();
+    for node in model.nodes() {
+        for slot in 0..node.outputs.len() {
+            let outlet = OutletId::new(node.id, slot);
+            let name = match model.outlet_label(outlet) {
+                Some(name) if names.contains(name) => name,
+                _ => continue,
+            };
+            let fact = model.outlet_fact(outlet)?;
+            let (Some(dt), Some(shape)) = (fact.datum_type.concretize(), fact.shape.concretize())
+            else {
+                continue;
+            };
+            let Some(elem_type) = onnx_elem_type(dt) else {
+                continue;
+            };
+            let dim = shape
+                .iter()
+                .map(|d| Dimension {
+                    value: Some(match d.to_i64() {
+                        Ok(v) => dimension::Value::DimValue(v),
+                        Err(_) => dimension::Value::DimParam(d.to_string()),
+                    }),
+                    ..Default::default()
+                })
+                .collect();
+            let info = ValueInfoProto {
+                name: name.to_string(),
+                r#type: Some(pb::TypeProto {
+                    value: Some(pb::type_proto::Value::TensorType(pb::type_proto::Tensor {
+                        elem_type,
+                        shape: Some(pb::TensorShapeProto { dim }),
+                    })),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            infos.insert(name.to_string(), info);
+        }
+    }
+    Ok(infos)
+}
+
+/// Splits the onnx model at `model_path` into a chain of models, written to `output_dir` with a
+/// [SPLIT_MANIFEST] describing them.
+///
+/// The model is cut after each node named in `cut_after` (a node name or the name of one of its
+/// outputs), or, when `num_pieces` is set instead, into that many pieces of roughly as many nodes
+/// each, cutting where the fewest tensors cross.
+pub fn split_model(
+    model_path: &Path,
+    cut_after: &[String],
+    num_pieces: Option<usize>,
+    output_dir: &Path,
+) -> Result<Vec<ModelPiece>, Box<dyn Error>> {
+    let mut model = ModelProto::decode(std::fs::read(model_path)?.as_slice())?;
+    let graph = model.graph.take().ok_or("the model has no graph")?;
+
+    let initializers: HashSet<&str> = graph.initializer.iter().map(|t| t.name.as_str()).collect();
+    let constants: HashMap<&str, &NodeProto> = graph
+        .node
+        .iter()
+        .filter(|n| is_constant(n))
+        .flat_map(|n| n.output.iter().map(move |o| (o.as_str(), n)))
+        .collect();
+    let free: HashSet<&str> = initializers
+        .iter()
+        .chain(constants.keys())
+        .copied()
+        .collect();
+    let nodes: Vec<&NodeProto> = graph.node.iter().filter(|n| !is_constant(n)).collect();
+    if nodes.len() < 2 {
+        return Err("the model needs at least two nodes to be split".into());
+    }
+    let liveness = Liveness::new(&graph, &nodes, &free);
+
+    let mut cuts = match (cut_after.is_empty(), num_pieces) {
+        (false, None) => cut_after
+            .iter()
+            .map(|name| {
+                nodes
+                    .iter()
+                    .position(|n| n.name == *name || n.output.iter().any(|o| o == name))
+                    .map(|i| i + 1)
+                    .ok_or_else(|| format!("no node named {} to cut after", name))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        (true, Some(num_pieces)) if (2..=nodes.len()).contains(&num_pieces) => {
+            auto_cuts(&liveness, nodes.len(), num_pieces)
+        }
+        (true, Some(num_pieces)) => {
+            return Err(format!(
+                "can't split a model of {} nodes into {} pieces",
+                nodes.len(),
+                num_pieces
+            )
+            .into())
+        }
+        _ => return Err("set either the nodes to cut after or the number of pieces".into()),
+    };
+    cuts.sort();
+    cuts.dedup();
+    if cuts.last() == Some(&nodes.len()) {
+        return Err("can't cut after the last node".into());
+    }
+
+    let mut value_infos: HashMap<String, ValueInfoProto> = graph
+        .input
+        .iter()
+        .chain(&graph.output)
+        .chain(&graph.value_info)
+        .filter(|v| v.r#type.is_some())
+        .map(|v| (v.name.clone(), v.clone()))
+        .collect();
+    let missing: HashSet<&str> = cuts
+        .iter()
+        .flat_map(|c| liveness.crossing(*c))
+        .filter(|t| !value_infos.contains_key(*t))
+        .collect();
+    if !missing.is_empty() {
+        value_infos.extend(infer_value_infos(model_path, &missing)?);
+    }
+    let value_info = |name: &str| {
+        value_infos.get(name).cloned().ok_or_else(|| {
+            format!(
+                "couldn't infer the type and shape of {}, run onnx shape inference on the model first",
+                name
+            )
+        })
+    };
+
+    std::fs::create_dir_all(output_dir)?;
+    let stem = model_path
+        .file_stem()
+        .map_or("model".into(), |s| s.to_string_lossy());
+    let bounds = std::iter::once(0)
+        .chain(cuts.iter().copied())
+        .chain(std::iter::once(nodes.len()))
+        .collect::<Vec<_>>();
+
+    let mut pieces = vec![];
+    for (i, range) in bounds.windows(2).enumerate() {
+        let (start, end) = (range[0], range[1]);
+        let body = &nodes[start..end];
+
+        let inputs: Vec<ValueInfoProto> = if start == 0 {
+            graph
+                .input
+                .iter()
+                .filter(|v| !initializers.contains(v.name.as_str()))
+                .cloned()
+                .collect()
+        } else {
+            liveness
+                .crossing(start)
+                .into_iter()
+                .map(value_info)
+                .collect::<Result<_, _>>()?
+        };
+        let input_names: HashSet<&str> = inputs.iter().map(|v| v.name.as_str()).collect();
+
+        let used: HashSet<&str> = body.iter().flat_map(|n| node_inputs(n)).collect();
+        let mut piece_nodes: Vec<NodeProto> = graph
+            .node
+            .iter()
+            .filter(|n| is_constant(n) && n.output.iter().any(|o| used.contains(o.as_str())))
+            .cloned()
+            .collect();
+        piece_nodes.extend(body.iter().map(|n| (*n).clone()));
+
+        let mut outputs = vec![];
+        let boundary = if end == nodes.len() {
+            graph.output.iter().map(|o| o.name.as_str()).collect()
+        } else {
+            liveness.crossing(end)
+        };
+        for name in boundary {
+            let mut info = value_info(name)?;
+            // tensors that skip over this piece are passed through it
+            if input_names.contains(name) {
+                info.name = format!("{}__piece_{}", name, i);
+                piece_nodes.push(NodeProto {
+                    name: info.name.clone(),
+                    op_type: "Identity".to_string(),
+                    input: vec![name.to_string()],
+                    output: vec![info.name.clone()],
+                    ..Default::default()
+                });
+            }
+            outputs.push(info);
+        }
+
+        let piece_graph = GraphProto {
+            name: format!("{}_{}", graph.name, i),
+            initializer: graph
+                .initializer
+                .iter()
+                .filter(|t| used.contains(t.name.as_str()))
+                .cloned()
+                .collect(),
+            value_info: graph
+                .value_info
+                .iter()
+                .filter(|v| body.iter().any(|n| n.output.contains(&v.name)))
+                .cloned()
+                .collect(),
+            node: piece_nodes,
+            input: inputs,
+            output: outputs,
+            ..Default::default()
+        };
+
+        let path = output_dir.join(format!("{}_{}.onnx", stem, i));
+        let piece = ModelProto {
+            graph: Some(piece_graph),
+            ..model.clone()
+        };
+        std::fs::write(&path, piece.encode_to_vec())?;
+
+        let written = piece.graph.as_ref().expect("just set");
+        pieces.push(ModelPiece {
+            model: path,
+            inputs: written.input.iter().map(|v| v.name.clone()).collect(),
+            outputs: written.output.iter().map(|v| v.name.clone()).collect(),
+            num_nodes: body.len(),
+        });
+    }
+
+    serde_json::to_writer_pretty(
+        std::fs::File::create(output_dir.join(SPLIT_MANIFEST))?,
+        &pieces,
+    )?;
+    Ok(pieces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tensor_info(name: &str) -> ValueInfoProto {
+        use pb::tensor_shape_proto::{dimension, Dimension};
+        ValueInfoProto {
+            name: name.to_string(),
+            r#type: Some(pb::TypeProto {
+                value: Some(pb::type_proto::Value::TensorType(pb::type_proto::Tensor {
+                    elem_type: 1,
+                    shape: Some(pb::TensorShapeProto {
+                        dim: vec![1, 4]
+                            .into_iter()
+                            .map(|d| Dimension {
+                                value: Some(dimension::Value::DimValue(d)),
+                                ..Default::default()
+                            })
+                            .collect(),
+                    }),
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn node(name: &str, op_type: &str, input: &[&str], output: &str) -> NodeProto {
+        NodeProto {
+            name: name.to_string(),
+            op_type: op_type.to_string(),
+            input: input.iter().map(|i| i.to_string()).collect(),
+            output: vec![output.to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_split_passes_skip_connections_through() {
+        let tmp_dir = tempfile::Builder::new().prefix("split").tempdir().unwrap();
+        let model = ModelProto {
+            graph: Some(GraphProto {
+                name: "skip".to_string(),
+                node: vec![
+                    node("relu_0", "Relu", &["x"], "a"),
+                    node("relu_1", "Relu", &["a"], "b"),
+                    node("add", "Add", &["b", "x"], "y"),
+                ],
+                input: vec![tensor_info("x")],
+                output: vec![tensor_info("y")],
+                value_info: vec![tensor_info("a"), tensor_info("b")],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let model_path = tmp_dir.path().join("skip.onnx");
+        std::fs::write(&model_path, model.encode_to_vec()).unwrap();
+
+        let output_dir = tmp_dir.path().join("split");
+        let pieces = split_model(&model_path, &["relu_0".to_string()], None, &output_dir).unwrap();
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces[0].inputs, vec!["x"]);
+        assert_eq!(pieces[0].outputs, vec!["x__piece_0", "a"]);
+        assert_eq!(pieces[1].inputs, vec!["x", "a"]);
+        assert_eq!(pieces[1].outputs, vec!["y"]);
+        assert_eq!(pieces[1].num_nodes, 2);
+
+        let auto = split_model(&model_path, &[], Some(2), &output_dir).unwrap();
+        assert_eq!(auto.len(), 2);
+        assert!(output_dir.join(SPLIT_MANIFEST).exists());
+    }
+}
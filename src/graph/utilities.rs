@@ -2,7 +2,7 @@
 use super::GraphError;
 #[cfg(not(target_arch = "wasm32"))]
 use super::VarScales;
-use super::{Rescaled, SupportedOp, Visibility};
+use super::{Rescaled, RoundingMode, SupportedOp, Visibility};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::circuit::hybrid::HybridOp;
 #[cfg(not(target_arch = "wasm32"))]
@@ -37,10 +37,82 @@ use tract_onnx::tract_hir::{
     ops::cnn::{ConvUnary, PoolSpec},
     ops::konst::Const,
     ops::nn::DataFormat,
+    ops::scan::{InputMapping, Scan},
     tract_core::ops::cast::Cast,
     tract_core::ops::cnn::{conv::KernelFormat, MaxPool, PaddingSpec, SumPool},
 };
 
+/// The axis a scan accumulates along, if the scan is a cumulative sum: its body adds each slice
+/// of the (forward) scanned input to the state and stacks the running total, which is how tract
+/// expands ONNX CumSum. Such scans are laid out as a [PolyOp::CumSum] instead of being unrolled.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn cumsum_axis(scan: &Scan) -> Option<usize> {
+    let body = &scan.body;
+    let [first, second] = scan.input_mapping.as_slice() else {
+        return None;
+    };
+    let axis = match (first, second) {
+        (InputMapping::Scan(info), InputMapping::State)
+        | (InputMapping::State, InputMapping::Scan(info))
+            if info.chunk == 1 =>
+        {
+            info.axis
+        }
+        _ => return None,
+    };
+
+    // the body is a single Add of its two sources
+    let mut compute = body
+        .nodes
+        .iter()
+        .filter(|n| !body.inputs.iter().any(|i| i.node == n.id));
+    let (Some(add), None) = (compute.next(), compute.next()) else {
+        return None;
+    };
+    if add.op().name() != "Add"
+        || add.inputs.len() != 2
+        || !body.inputs.iter().all(|i| add.inputs.contains(i))
+    {
+        return None;
+    }
+
+    // the running total is both the next state and the only stacked output
+    if body.outputs.iter().any(|o| o.node != add.id) {
+        return None;
+    }
+    let stacked = scan
+        .output_mapping
+        .iter()
+        .filter(|m| m.scan.is_some())
+        .count();
+    if stacked != 1
+        || scan
+            .output_mapping
+            .iter()
+            .any(|m| m.last_value_slot.is_some())
+    {
+        return None;
+    }
+    Some(axis)
+}
+
+/// Dequantizes `values` laid out channel first (e.g an OIHW kernel) with per tensor or per
+/// channel zero points and scales, as embedded in the ops of pre-quantized onnx models
+#[cfg(not(target_arch = "wasm32"))]
+fn dequantize_channels(
+    values: &Tensor<f32>,
+    zero_points: &Tensor<f32>,
+    scales: &Tensor<f32>,
+) -> Result<Tensor<f32>, TensorError> {
+    let channels = values.dims().first().copied().unwrap_or(1).max(1);
+    let channel_len = (values.len() / channels).max(1);
+    let channel_value = |t: &Tensor<f32>, c: usize| if t.len() > 1 { t[c] } else { t[0] };
+    values.enum_map(|i, v| {
+        let c = i / channel_len;
+        Ok::<_, TensorError>((v - channel_value(zero_points, c)) * channel_value(scales, c))
+    })
+}
+
 // Warning: currently ignores stride information
 /// Quantizes an iterable of f32s to a [Tensor] of i32s using a fixed point representation.
 /// Arguments
@@ -50,6 +122,18 @@ use tract_onnx::tract_hir::{
 /// * `shift` - offset used in the fixed point representation.
 /// * `scale` - `2^scale` used in the fixed point representation.
 pub fn quantize_float(elem: &f64, shift: f64, scale: crate::Scale) -> Result<i128, TensorError> {
+    quantize_float_rounded(elem, shift, scale, RoundingMode::Nearest, 0)
+}
+
+/// Converts a float to a fixed point integer with a given [RoundingMode], `salt` seeds stochastic
+/// rounding.
+pub fn quantize_float_rounded(
+    elem: &f64,
+    shift: f64,
+    scale: crate::Scale,
+    rounding: RoundingMode,
+    salt: u64,
+) -> Result<i128, TensorError> {
     let mult = scale_to_multiplier(scale);
     let max_value = ((i128::MAX as f64 - shift) / mult).round(); // the maximum value that can be represented w/o sig bit truncation
 
@@ -58,7 +142,7 @@ pub fn quantize_float(elem: &f64, shift: f64, scale: crate::Scale) -> Result<i12
     }
 
     // we parallelize the quantization process as it seems to be quite slow at times
-    let scaled = (mult * *elem + shift).round() as i128;
+    let scaled = rounding.round(mult * *elem + shift, salt) as i128;
 
     Ok(scaled)
 }
@@ -214,6 +298,39 @@ fn load_op<C: tract_onnx::prelude::Op + Clone>(
     Ok(op.clone())
 }
 
+/// Resolves implicit (`Valid` and `Same*`) pooling and convolution padding against the spatial
+/// dimensions of the input, as `[(before_h, before_w), (after_h, after_w)]`.
+#[cfg(not(target_arch = "wasm32"))]
+fn implicit_padding(
+    pool_spec: &PoolSpec,
+    input_dims: &[usize],
+) -> Result<[(usize, usize); 2], Box<dyn std::error::Error>> {
+    let spatial = input_dims
+        .get(2..)
+        .ok_or(GraphError::MissingParams("padding".to_string()))?;
+    let ones = vec![1; spatial.len()];
+    let dilations = pool_spec
+        .dilations
+        .as_ref()
+        .map(|d| d.to_vec())
+        .unwrap_or_else(|| ones.clone());
+    let strides = pool_spec
+        .strides
+        .as_ref()
+        .map(|s| s.to_vec())
+        .unwrap_or(ones);
+    let computed =
+        pool_spec
+            .padding
+            .compute(spatial, &pool_spec.kernel_shape, &dilations, &strides);
+    match computed.as_slice() {
+        [h, w] => Ok([(h.pad_before, w.pad_before), (h.pad_after, w.pad_after)]),
+        // 1d pooling and convolutions run along the width
+        [w] => Ok([(0, w.pad_before), (0, w.pad_after)]),
+        _ => Err(Box::new(GraphError::MissingParams("padding".to_string()))),
+    }
+}
+
 /// Matches an onnx node to a [crate::circuit::Op].
 /// Arguments
 /// * `idx` - the index of the node in the graph.
@@ -258,7 +375,9 @@ pub fn new_op_from_onnx(
                 }
             }
 
-            assert_eq!(input_ops.len(), 3, "Range requires 3 inputs");
+            if input_ops.len() != 3 {
+                return Err(Box::new(GraphError::InvalidDims(idx, "range".to_string())));
+            }
             let input_ops = input_ops
                 .iter()
                 .map(|x| x.get_constant().ok_or("Range requires constant inputs"))
@@ -271,7 +390,12 @@ pub fn new_op_from_onnx(
             let range = (start..end).step_by(delta).collect::<Vec<_>>();
             let raw_value = range.iter().map(|x| *x as f32).collect::<Tensor<_>>();
             // Quantize the raw value (integers)
-            let quantized_value = quantize_tensor(raw_value.clone(), 0, &Visibility::Fixed)?;
+            let quantized_value = quantize_tensor(
+                raw_value.clone(),
+                0,
+                &Visibility::Fixed,
+                RoundingMode::Nearest,
+            )?;
 
             let c = crate::circuit::ops::Constant::new(quantized_value, raw_value);
             // Create a constant op
@@ -313,8 +437,11 @@ pub fn new_op_from_onnx(
 
             // Extract the max value
         }
-        "Topk" => {
-            let op = load_op::<Topk>(node.op(), idx, node.op().name().to_string())?;
+        "Topk" | "TopkIndices" => {
+            let op = match node.op().downcast_ref::<super::rewrite::TopkIndices>() {
+                Some(indices) => indices.0.clone(),
+                None => load_op::<Topk>(node.op(), idx, node.op().name().to_string())?,
+            };
             let axis = op.axis;
             // if param_visibility.is_public() {
             let k = if let Some(c) = inputs[1].opkind().get_mutable_constant() {
@@ -325,7 +452,14 @@ pub fn new_op_from_onnx(
                 op.fallback_k.to_i64()? as usize
             };
 
-            SupportedOp::Hybrid(crate::circuit::ops::hybrid::HybridOp::TopK { dim: axis, k })
+            if node.op().name() == "TopkIndices" {
+                SupportedOp::Hybrid(crate::circuit::ops::hybrid::HybridOp::TopKIndices {
+                    dim: axis,
+                    k,
+                })
+            } else {
+                SupportedOp::Hybrid(crate::circuit::ops::hybrid::HybridOp::TopK { dim: axis, k })
+            }
         }
         "Onehot" => {
             let op = load_op::<OneHot>(node.op(), idx, node.op().name().to_string())?;
@@ -438,7 +572,20 @@ pub fn new_op_from_onnx(
         "Concat" | "InferenceConcat" => {
             let op = load_op::<TypedConcat>(node.op(), idx, node.op().name().to_string())?;
             let axis = op.axis;
-            SupportedOp::Linear(crate::circuit::ops::poly::PolyOp::Concat { axis })
+            // the branches joined by a skip connection can sit at different scales, bring them
+            // all to the largest one so the concatenated values share a fixed point
+            let input_scales = node
+                .inputs
+                .iter()
+                .zip(inputs.iter())
+                .map(|(outlet, input)| input.out_scales()[outlet.slot])
+                .collect::<Vec<_>>();
+            homogenize_input_scales(
+                Box::new(PolyOp::<Fp>::Concat { axis }),
+                input_scales,
+                (0..inputs.len()).collect(),
+            )?
+            .into()
         }
         "Slice" => {
             let slice = load_op::<Slice>(node.op(), idx, node.op().name().to_string())?;
@@ -471,9 +618,14 @@ pub fn new_op_from_onnx(
             };
 
             // Quantize the raw value
-            let quantized_value =
-                quantize_tensor(raw_value.clone(), constant_scale, param_visibility)?;
-            let c = crate::circuit::ops::Constant::new(quantized_value, raw_value);
+            let quantized_value = quantize_tensor(
+                raw_value.clone(),
+                constant_scale,
+                param_visibility,
+                scales.rounding,
+            )?;
+            let mut c = crate::circuit::ops::Constant::new(quantized_value, raw_value);
+            c.rounding = scales.rounding;
             // Create a constant op
             SupportedOp::Constant(c)
         }
@@ -483,7 +635,12 @@ pub fn new_op_from_onnx(
             };
             let op = load_op::<Reduce>(node.op(), idx, node.op().name().to_string())?;
             let axes: Vec<usize> = op.axes.into_iter().collect();
-            assert_eq!(axes.len(), 1, "only support argmax over one axis");
+            if axes.len() != 1 {
+                return Err(Box::new(GraphError::InvalidDims(
+                    idx,
+                    "argmax over more than one axis".to_string(),
+                )));
+            }
 
             SupportedOp::Hybrid(HybridOp::ReduceArgMax { dim: axes[0] })
         }
@@ -493,7 +650,12 @@ pub fn new_op_from_onnx(
             };
             let op = load_op::<Reduce>(node.op(), idx, node.op().name().to_string())?;
             let axes: Vec<usize> = op.axes.into_iter().collect();
-            assert_eq!(axes.len(), 1, "only support argmin over one axis");
+            if axes.len() != 1 {
+                return Err(Box::new(GraphError::InvalidDims(
+                    idx,
+                    "argmin over more than one axis".to_string(),
+                )));
+            }
 
             SupportedOp::Hybrid(HybridOp::ReduceArgMin { dim: axes[0] })
         }
@@ -532,6 +694,31 @@ pub fn new_op_from_onnx(
 
             SupportedOp::Linear(PolyOp::Prod { axes, len_prod })
         }
+        "Scan" => {
+            let scan = load_op::<Scan>(node.op(), idx, node.op().name().to_string())?;
+            let Some(axis) = cumsum_axis(&scan) else {
+                return Err(Box::new(GraphError::OpMismatch(idx, "cumsum".to_string())));
+            };
+            // the running sum has to start from zero for the chain of additions to match
+            let state = scan
+                .input_mapping
+                .iter()
+                .position(|m| matches!(m, InputMapping::State))
+                .ok_or(GraphError::OpMismatch(idx, "cumsum".to_string()))?;
+            match inputs[state].opkind().get_mutable_constant() {
+                Some(c) if c.raw_values.iter().all(|v| *v == 0.) => {}
+                _ => {
+                    return Err(Box::new(GraphError::OpMismatch(
+                        idx,
+                        "cumsum with a non zero initial value".to_string(),
+                    )))
+                }
+            }
+            inputs[state].decrement_use();
+            deleted_indices.push(state);
+
+            SupportedOp::Linear(PolyOp::CumSum { axis })
+        }
         "Reduce<Sum>" => {
             if inputs.len() != 1 {
                 return Err(Box::new(GraphError::InvalidDims(idx, "sum".to_string())));
@@ -618,10 +805,30 @@ pub fn new_op_from_onnx(
                     deleted_indices.push(const_idx);
                 }
 
-                SupportedOp::Nonlinear(LookupOp::Min {
-                    scale: scale_to_multiplier(inputs[0].out_scales()[0]).into(),
-                    a: crate::circuit::utils::F32(unit),
-                })
+                let input = &mut inputs[1 - const_idx];
+                let scale = scale_to_multiplier(input.out_scales()[0]).into();
+
+                // a Clip (e.g ReLU6) reaches us as a Max by its lower bound followed by a Min by
+                // its upper bound, fold both into a single lookup
+                let lower = match input.opkind() {
+                    SupportedOp::Nonlinear(LookupOp::ReLU) => Some(0.),
+                    SupportedOp::Nonlinear(LookupOp::Max { a, .. }) => Some(a.0),
+                    _ => None,
+                };
+                match lower {
+                    Some(lower) if input.num_uses() == 1 && lower <= unit => {
+                        input.replace_opkind(SupportedOp::Nonlinear(LookupOp::Clip {
+                            scale,
+                            min: crate::circuit::utils::F32(lower),
+                            max: crate::circuit::utils::F32(unit),
+                        }));
+                        SupportedOp::Linear(PolyOp::Identity)
+                    }
+                    _ => SupportedOp::Nonlinear(LookupOp::Min {
+                        scale,
+                        a: crate::circuit::utils::F32(unit),
+                    }),
+                }
             } else {
                 return Err(Box::new(GraphError::InvalidDims(idx, "min".to_string())));
             }
@@ -662,12 +869,19 @@ pub fn new_op_from_onnx(
         "Scan" => {
             return Err("scan should never be analyzed explicitly".into());
         }
-        "QuantizeLinearU8" | "DequantizeLinearF32" => SupportedOp::Linear(PolyOp::Identity),
+        // quantize / dequantize pairs only simulate the quantization of a pre-quantized model,
+        // ops in between run on the dequantized values
+        "QuantizeLinearU8" | "QuantizeLinearI8" | "DequantizeLinearF32" => {
+            SupportedOp::Linear(PolyOp::Identity)
+        }
         "Abs" => SupportedOp::Nonlinear(LookupOp::Abs),
         "Neg" => SupportedOp::Linear(PolyOp::Neg),
         "Sigmoid" => SupportedOp::Nonlinear(LookupOp::Sigmoid {
             scale: scale_to_multiplier(inputs[0].out_scales()[0]).into(),
         }),
+        "HardSwish" => SupportedOp::Nonlinear(LookupOp::HardSwish {
+            scale: scale_to_multiplier(inputs[0].out_scales()[0]).into(),
+        }),
         "Sqrt" => SupportedOp::Nonlinear(LookupOp::Sqrt {
             scale: scale_to_multiplier(inputs[0].out_scales()[0]).into(),
         }),
@@ -745,7 +959,9 @@ pub fn new_op_from_onnx(
                 .iter()
                 .flat_map(|x| x.out_scales())
                 .collect::<Vec<_>>();
-            assert_eq!(input_scales.len(), 1);
+            if input_scales.len() != 1 {
+                return Err(Box::new(GraphError::InvalidDims(idx, "cast".to_string())));
+            }
 
             let mut constant = inputs[0].opkind();
             let constant = constant.get_mutable_constant();
@@ -754,6 +970,8 @@ pub fn new_op_from_onnx(
                                  default_op: SupportedOp|
              -> Result<SupportedOp, Box<dyn std::error::Error>> {
                 if let Some(c) = constant {
+                    // casts to integers truncate towards zero
+                    c.raw_values = c.raw_values.map(|x| x.trunc());
                     inputs[0].bump_scale(scale);
                     c.rebase_scale(scale)?;
                     inputs[0].replace_opkind(SupportedOp::Constant(c.clone()));
@@ -774,14 +992,11 @@ pub fn new_op_from_onnx(
                 | DatumType::U16
                 | DatumType::U32
                 | DatumType::U64 => {
-                    if input_scales[0] != 0 {
+                    if input_scales[0] > 0 {
                         replace_const(
                             0,
-                            SupportedOp::Nonlinear(LookupOp::Div {
-                                denom: crate::circuit::utils::F32(scale_to_multiplier(
-                                    input_scales[0],
-                                )
-                                    as f32),
+                            SupportedOp::Nonlinear(LookupOp::Trunc {
+                                scale: scale_to_multiplier(input_scales[0]).into(),
                             }),
                         )?
                     } else {
@@ -819,7 +1034,22 @@ pub fn new_op_from_onnx(
                         op = SupportedOp::Nonlinear(LookupOp::Div {
                             // we invert the constant for division
                             denom: crate::circuit::utils::F32(1. / c.raw_values[0]),
-                        })
+                        });
+
+                        // a mean (ReduceMean, GlobalAveragePool) reaches us as a sum scaled by
+                        // the reciprocal of the number of summed elements, fold it into a single
+                        // mean op
+                        let denom = 1. / c.raw_values[0];
+                        let sum = &mut inputs[1 - const_idx];
+                        if let SupportedOp::Linear(PolyOp::Sum { axes }) = sum.opkind() {
+                            if sum.num_uses() == 1 && (denom - denom.round()).abs() < 1e-3 {
+                                sum.replace_opkind(SupportedOp::Hybrid(HybridOp::ReduceMean {
+                                    axes,
+                                    len: denom.round() as usize,
+                                }));
+                                op = SupportedOp::Linear(PolyOp::Identity);
+                            }
+                        }
                     }
                 }
             }
@@ -888,8 +1118,24 @@ pub fn new_op_from_onnx(
                 }
             };
 
+            let mut scale = scale_to_multiplier(inputs[0].out_scales()[0]);
+            // attention scores are divided by sqrt(d) before the softmax, when that is a power of
+            // two (head dims of 16, 64, 256 ...) the division is free: the scores are read at a
+            // finer scale instead of going through a division lookup
+            if let SupportedOp::Nonlinear(LookupOp::Div { denom }) = inputs[0].opkind() {
+                let denom = denom.0 as f64;
+                if inputs[0].num_uses() == 1
+                    && inputs[0].out_scales()[0] > 0
+                    && denom > 1.
+                    && denom.log2().fract() == 0.
+                {
+                    inputs[0].replace_opkind(SupportedOp::Linear(PolyOp::Identity));
+                    scale *= denom;
+                }
+            }
+
             SupportedOp::Hybrid(HybridOp::Softmax {
-                scale: scale_to_multiplier(inputs[0].out_scales()[0]).into(),
+                scale: scale.into(),
                 axes: softmax_op.axes.to_vec(),
             })
         }
@@ -930,9 +1176,7 @@ pub fn new_op_from_onnx(
                         return Err(Box::new(GraphError::MissingParams("padding".to_string())));
                     }
                 }
-                _ => {
-                    return Err(Box::new(GraphError::MissingParams("padding".to_string())));
-                }
+                _ => implicit_padding(pool_spec, &inputs[0].out_dims()[0])?,
             };
             let kernel_shape = &pool_spec.kernel_shape;
 
@@ -958,6 +1202,10 @@ pub fn new_op_from_onnx(
                 pool_dims: (kernel_height, kernel_width),
             })
         }
+        // an input at scale 0 (or coarser) only holds integers, which rounding leaves unchanged
+        "Ceil" | "Floor" | "Round" | "RoundHalfToEven" if inputs[0].out_scales()[0] <= 0 => {
+            SupportedOp::Linear(PolyOp::Identity)
+        }
         "Ceil" => SupportedOp::Nonlinear(LookupOp::Ceil {
             scale: scale_to_multiplier(inputs[0].out_scales()[0]).into(),
         }),
@@ -972,22 +1220,27 @@ pub fn new_op_from_onnx(
         }),
         "Sign" => SupportedOp::Nonlinear(LookupOp::Sign),
         "Pow" => {
-            // Extract the slope layer hyperparams from a const
-
-            // if param_visibility.is_public() {
-            if let Some(c) = inputs[1].opkind().get_mutable_constant() {
-                inputs[1].decrement_use();
-                deleted_indices.push(inputs.len() - 1);
-                if c.raw_values.len() > 1 {
-                    unimplemented!("only support scalar pow")
-                }
-                SupportedOp::Nonlinear(LookupOp::Pow {
-                    scale: scale_to_multiplier(inputs[0].out_scales()[0]).into(),
-                    a: crate::circuit::utils::F32(c.raw_values[0]),
-                })
-            } else {
-                unimplemented!("only support constant pow for now")
+            // the exponent is folded into the lookup table, so it has to be a constant shared by
+            // every element (it can be fractional)
+            let Some(c) = inputs[1].opkind().get_mutable_constant() else {
+                return Err(Box::new(GraphError::OpMismatch(
+                    idx,
+                    "pow with a non-constant exponent".to_string(),
+                )));
+            };
+            let exponent = c.raw_values[0];
+            if c.raw_values.iter().any(|e| *e != exponent) {
+                return Err(Box::new(GraphError::OpMismatch(
+                    idx,
+                    "pow with a per-element exponent".to_string(),
+                )));
             }
+            inputs[1].decrement_use();
+            deleted_indices.push(inputs.len() - 1);
+            SupportedOp::Nonlinear(LookupOp::Pow {
+                scale: scale_to_multiplier(inputs[0].out_scales()[0]).into(),
+                a: crate::circuit::utils::F32(exponent),
+            })
         }
         "Cube" => SupportedOp::Linear(PolyOp::Pow(3)),
         "Square" => SupportedOp::Linear(PolyOp::Pow(2)),
@@ -1045,22 +1298,69 @@ pub fn new_op_from_onnx(
                         return Err(Box::new(GraphError::MissingParams("padding".to_string())));
                     }
                 }
-                _ => {
-                    return Err(Box::new(GraphError::MissingParams("padding".to_string())));
-                }
+                _ => implicit_padding(&conv_node.pool_spec, &inputs[0].out_dims()[0])?,
             };
 
-            let kernel = extract_tensor_value(conv_node.kernel.clone(), symbol_values)?;
-            let kernel = quantize_tensor(kernel, scales.params, param_visibility)?;
+            let mut kernel = extract_tensor_value(conv_node.kernel.clone(), symbol_values)?;
+            let mut bias = match conv_node.bias.clone() {
+                Some(b) => Some(extract_tensor_value(b, symbol_values)?),
+                None => None,
+            };
 
-            let bias = match conv_node.bias.clone() {
-                Some(b) => {
-                    let const_value = extract_tensor_value(b, symbol_values)?;
+            // a QLinearConv carries an integer kernel and bias, along with the zero points and
+            // scales of its input, kernel and output as constant inputs. We dequantize the
+            // kernel and bias with them so the conv runs on dequantized values like the
+            // quantize / dequantize nodes around it.
+            if conv_node.q_params.is_some() {
+                let quant_params = inputs[1..]
+                    .iter()
+                    .map(|i| {
+                        i.opkind()
+                            .get_mutable_constant()
+                            .map(|c| c.raw_values.clone())
+                            .ok_or_else(|| {
+                                GraphError::OpMismatch(
+                                    idx,
+                                    "qlinearconv with non constant quantization params".to_string(),
+                                )
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let [_, input_scale, kernel_zero_point, kernel_scale, _, _] =
+                    quant_params.as_slice()
+                else {
+                    return Err(Box::new(GraphError::MissingParams(
+                        "qlinearconv quantization params".to_string(),
+                    )));
+                };
+
+                kernel = dequantize_channels(&kernel, kernel_zero_point, kernel_scale)?;
+                // the bias is quantized at the product of the input and kernel scales
+                let bias_scale = kernel_scale.map(|s| s * input_scale[0]);
+                bias = match bias {
+                    Some(b) => Some(dequantize_channels(
+                        &b,
+                        &Tensor::from([0.].into_iter()),
+                        &bias_scale,
+                    )?),
+                    None => None,
+                };
+
+                for (i, input) in inputs.iter_mut().enumerate().skip(1) {
+                    input.decrement_use();
+                    deleted_indices.push(i);
+                }
+            }
 
+            let kernel = quantize_tensor(kernel, scales.params, param_visibility, scales.rounding)?;
+
+            let bias = match bias {
+                Some(const_value) => {
                     let val = quantize_tensor(
                         const_value,
                         scales.params + inputs[0].out_scales()[0],
                         param_visibility,
+                        scales.rounding,
                     )?;
                     Some(val)
                 }
@@ -1129,7 +1429,7 @@ pub fn new_op_from_onnx(
             };
 
             let kernel = extract_tensor_value(deconv_node.kernel.clone(), symbol_values)?;
-            let kernel = quantize_tensor(kernel, scales.params, param_visibility)?;
+            let kernel = quantize_tensor(kernel, scales.params, param_visibility, scales.rounding)?;
 
             let bias = match deconv_node.bias.clone() {
                 Some(b) => {
@@ -1139,6 +1439,7 @@ pub fn new_op_from_onnx(
                         const_value,
                         scales.params + inputs[0].out_scales()[0],
                         param_visibility,
+                        scales.rounding,
                     )?;
                     Some(val)
                 }
@@ -1181,10 +1482,24 @@ pub fn new_op_from_onnx(
 
             let resize_node = format!("{:?}", node);
 
-            if !resize_node.contains("interpolator: Nearest")
+            let bilinear = resize_node.contains("interpolator: Linear");
+            if bilinear {
+                if !resize_node.contains("coord_transformer: HalfPixel")
+                    && !resize_node.contains("coord_transformer: PytorchHalfPixel")
+                {
+                    return Err(Box::new(GraphError::OpMismatch(
+                        idx,
+                        "Resize (linear interpolation only supports half pixel coordinates)"
+                            .to_string(),
+                    )));
+                }
+            } else if !resize_node.contains("interpolator: Nearest")
                 && !resize_node.contains("nearest: Floor")
             {
-                unimplemented!("Only nearest neighbor interpolation is supported")
+                return Err(Box::new(GraphError::OpMismatch(
+                    idx,
+                    "Resize (only nearest and linear interpolation are supported)".to_string(),
+                )));
             }
             // check if optional scale factor is present
             if inputs.len() != 2 && inputs.len() != 3 {
@@ -1225,7 +1540,19 @@ pub fn new_op_from_onnx(
                 }
             }
 
-            SupportedOp::Linear(PolyOp::Resize { scale_factor })
+            if bilinear {
+                // the interpolation weights are only rescaled exactly for power of two factors
+                if scale_factor.iter().any(|s| !s.is_power_of_two()) {
+                    return Err(Box::new(GraphError::OpMismatch(
+                        idx,
+                        "Resize (linear interpolation needs power of two scale factors)"
+                            .to_string(),
+                    )));
+                }
+                SupportedOp::Linear(PolyOp::ResizeBilinear { scale_factor })
+            } else {
+                SupportedOp::Linear(PolyOp::Resize { scale_factor })
+            }
         }
 
         "SumPool" => {
@@ -1265,9 +1592,7 @@ pub fn new_op_from_onnx(
                         return Err(Box::new(GraphError::MissingParams("padding".to_string())));
                     }
                 }
-                _ => {
-                    return Err(Box::new(GraphError::MissingParams("padding".to_string())));
-                }
+                _ => implicit_padding(pool_spec, &inputs[0].out_dims()[0])?,
             };
             let kernel_shape = &pool_spec.kernel_shape;
 
@@ -1296,11 +1621,6 @@ pub fn new_op_from_onnx(
                 normalized: sumpool_node.normalize,
             })
         }
-        // "GlobalAvgPool" => SupportedOp::Linear(PolyOp::SumPool {
-        //     padding: [(0, 0); 2],
-        //     stride: (1, 1),
-        //     kernel_shape: (inputs[0].out_dims()[0][1], inputs[0].out_dims()[0][2]),
-        // }),
         "Pad" => {
             let pad_node: &Pad = match node.op().downcast_ref::<Pad>() {
                 Some(b) => b,
@@ -1377,16 +1697,6 @@ pub fn extract_const_raw_values(op: SupportedOp) -> Option<Tensor<f32>> {
     }
 }
 
-/// Extracts the quantized values from a [crate::circuit::ops::Constant] op.
-pub fn extract_const_quantized_values(op: SupportedOp) -> Option<Tensor<Fp>> {
-    match op {
-        SupportedOp::Constant(crate::circuit::ops::Constant {
-            quantized_values, ..
-        }) => Some(quantized_values),
-        _ => None,
-    }
-}
-
 /// Extract the quantized values from a conv op
 pub fn extract_conv_values(boxed_op: Box<dyn crate::circuit::Op<Fp>>) -> [Option<Tensor<Fp>>; 2] {
     let op = boxed_op
@@ -1404,14 +1714,13 @@ pub fn quantize_tensor<F: PrimeField + TensorType + PartialOrd>(
     const_value: Tensor<f32>,
     scale: crate::Scale,
     visibility: &Visibility,
+    rounding: RoundingMode,
 ) -> Result<Tensor<F>, Box<dyn std::error::Error>> {
-    let mut value: Tensor<F> = const_value.par_enum_map(|_, x| {
-        Ok::<_, TensorError>(crate::fieldutils::i128_to_felt::<F>(quantize_float(
-            &(x).into(),
-            0.0,
-            scale,
-        )?))
-    })?;
+    // same as quantize_float_rounded on each element, with the multiplier and bound worked out once
+    let mult = scale_to_multiplier(scale);
+    let max_value = (i128::MAX as f64 / mult).round();
+    let mut value: Tensor<F> =
+        const_value.par_quantize(mult, max_value, |x, salt| rounding.round(x, salt))?;
 
     value.set_scale(scale);
     value.set_visibility(visibility);
@@ -3,6 +3,7 @@ use std::error::Error;
 use crate::tensor::TensorType;
 use crate::tensor::{ValTensor, VarTensor};
 use crate::RunArgs;
+use clap::ValueEnum;
 use halo2_proofs::plonk::{Column, ConstraintSystem, Instance};
 use halo2curves::ff::PrimeField;
 use itertools::Itertools;
@@ -38,6 +39,10 @@ pub enum Visibility {
     KZGCommit,
     /// Mark an item as encrypted (public key and encrypted message sent in the proof submitted for verificatio)
     Encrypted,
+    /// Mark an item as encrypted to a recipient's public key, the prover only knows the encryption randomness and the recipient alone can decrypt (hash of the recipient's public key and encrypted message sent in the proof submitted for verification)
+    EncryptedToRecipient,
+    /// Mark an item as signed: it is hashed as with `hashed/public` and a secp256k1 ECDSA signature over each hash is verified in-circuit (hash and signer's public key sent in the proof submitted for verification)
+    Signed,
     /// assigned as a constant in the circuit
     Fixed,
 }
@@ -50,12 +55,22 @@ impl<'a> From<&'a str> for Visibility {
             let outlets = outlets
                 .trim_start_matches('/')
                 .split(',')
-                .map(|s| s.parse::<usize>().unwrap())
-                .collect_vec();
-
-            return Visibility::Hashed {
-                hash_is_public: false,
-                outlets,
+                .map(|s| s.parse::<usize>())
+                .collect::<Result<Vec<_>, _>>();
+
+            return match outlets {
+                Ok(outlets) => Visibility::Hashed {
+                    hash_is_public: false,
+                    outlets,
+                },
+                Err(_) => {
+                    log::error!(
+                        "Invalid value for Visibility: {} (expected hashed/private/<outlet>,<outlet>,...)",
+                        s
+                    );
+                    log::warn!("Defaulting to private");
+                    Visibility::Private
+                }
             };
         }
         match s {
@@ -68,6 +83,8 @@ impl<'a> From<&'a str> for Visibility {
                 outlets: vec![],
             },
             "encrypted" => Visibility::Encrypted,
+            "encrypted/recipient" => Visibility::EncryptedToRecipient,
+            "signed" => Visibility::Signed,
             _ => {
                 log::error!("Invalid value for Visibility: {}", s);
                 log::warn!("Defaulting to private");
@@ -102,6 +119,8 @@ impl IntoPy<PyObject> for Visibility {
                 }
             }
             Visibility::Encrypted => "encrypted".to_object(py),
+            Visibility::EncryptedToRecipient => "encrypted/recipient".to_object(py),
+            Visibility::Signed => "signed".to_object(py),
         }
     }
 }
@@ -121,8 +140,9 @@ impl<'source> FromPyObject<'source> for Visibility {
             let outlets = outlets
                 .trim_start_matches('/')
                 .split(',')
-                .map(|s| s.parse::<usize>().unwrap())
-                .collect_vec();
+                .map(|s| s.parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| PyValueError::new_err("Invalid outlets for hashed/private"))?;
 
             return Ok(Visibility::Hashed {
                 hash_is_public: false,
@@ -144,6 +164,8 @@ impl<'source> FromPyObject<'source> for Visibility {
             }),
             "fixed" => Ok(Visibility::Fixed),
             "encrypted" => Ok(Visibility::Encrypted),
+            "encrypted/recipient" => Ok(Visibility::EncryptedToRecipient),
+            "signed" => Ok(Visibility::Signed),
             _ => Err(PyValueError::new_err("Invalid value for Visibility")),
         }
     }
@@ -165,7 +187,11 @@ impl Visibility {
     }
     #[allow(missing_docs)]
     pub fn is_hashed(&self) -> bool {
-        matches!(&self, Visibility::Hashed { .. })
+        matches!(&self, Visibility::Hashed { .. }) || self.is_signed()
+    }
+    #[allow(missing_docs)]
+    pub fn is_signed(&self) -> bool {
+        matches!(&self, Visibility::Signed)
     }
     #[allow(missing_docs)]
     pub fn is_kzgcommit(&self) -> bool {
@@ -174,6 +200,9 @@ impl Visibility {
 
     #[allow(missing_docs)]
     pub fn is_hashed_public(&self) -> bool {
+        if self.is_signed() {
+            return true;
+        }
         if let Visibility::Hashed {
             hash_is_public: true,
             ..
@@ -196,13 +225,18 @@ impl Visibility {
     }
     #[allow(missing_docs)]
     pub fn is_encrypted(&self) -> bool {
-        matches!(&self, Visibility::Encrypted)
+        matches!(&self, Visibility::Encrypted) || self.is_encrypted_to_recipient()
+    }
+    #[allow(missing_docs)]
+    pub fn is_encrypted_to_recipient(&self) -> bool {
+        matches!(&self, Visibility::EncryptedToRecipient)
     }
     #[allow(missing_docs)]
     pub fn requires_processing(&self) -> bool {
-        matches!(&self, Visibility::Encrypted)
+        self.is_encrypted()
             | matches!(&self, Visibility::Hashed { .. })
             | matches!(&self, Visibility::KZGCommit)
+            | matches!(&self, Visibility::Signed)
     }
     #[allow(missing_docs)]
     pub fn overwrites_inputs(&self) -> Vec<usize> {
@@ -221,10 +255,90 @@ impl std::fmt::Display for Visibility {
             Visibility::Fixed => write!(f, "fixed"),
             Visibility::Hashed { .. } => write!(f, "hashed"),
             Visibility::Encrypted => write!(f, "encrypted"),
+            Visibility::EncryptedToRecipient => write!(f, "encrypted/recipient"),
+            Visibility::Signed => write!(f, "signed"),
         }
     }
 }
 
+/// How a float is rounded to its fixed point representation when it is quantized
+#[derive(
+    ValueEnum, Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd,
+)]
+pub enum RoundingMode {
+    /// round to the nearest integer, halfway values away from zero
+    #[default]
+    Nearest,
+    /// round to the nearest integer, halfway values to the even one, so ties aren't biased
+    NearestEven,
+    /// round up with a probability equal to the fractional part, which is unbiased on average.
+    /// The randomness is derived from the value and its position, so quantization is repeatable.
+    Stochastic,
+}
+
+impl RoundingMode {
+    /// Rounds `x`, `salt` (eg. the index of the element) seeds stochastic rounding
+    pub fn round(&self, x: f64, salt: u64) -> f64 {
+        match self {
+            RoundingMode::Nearest => x.round(),
+            RoundingMode::NearestEven => {
+                let floor = x.floor();
+                if x - floor == 0.5 {
+                    if floor % 2.0 == 0.0 {
+                        floor
+                    } else {
+                        floor + 1.0
+                    }
+                } else {
+                    x.round()
+                }
+            }
+            RoundingMode::Stochastic => {
+                // splitmix64, so the draws don't depend on the platform or the std hasher
+                let mut z = (x.to_bits() ^ salt.rotate_left(32)).wrapping_add(0x9e3779b97f4a7c15);
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+                z ^= z >> 31;
+                let draw = (z >> 11) as f64 / (1u64 << 53) as f64;
+                let floor = x.floor();
+                if draw < x - floor {
+                    floor + 1.0
+                } else {
+                    floor
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for RoundingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_possible_value()
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+/// Converts RoundingMode into a PyObject (Required for RoundingMode to be compatible with Python)
+impl IntoPy<PyObject> for RoundingMode {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_string().to_object(py)
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+/// Obtains RoundingMode from PyObject (Required for RoundingMode to be compatible with Python)
+impl<'source> FromPyObject<'source> for RoundingMode {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        let trystr = <PyString as PyTryFrom>::try_from(ob)?;
+        let strval = trystr.to_string();
+        RoundingMode::from_str(&strval, true)
+            .map_err(|_| PyValueError::new_err("Invalid value for RoundingMode"))
+    }
+}
+
 /// Represents the scale of the model input, model parameters.
 #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
 pub struct VarScales {
@@ -234,6 +348,10 @@ pub struct VarScales {
     pub params: crate::Scale,
     ///
     pub rebase_multiplier: u32,
+    /// ops whose outputs are always rebased to the max scale
+    pub rebase_ops: Vec<String>,
+    /// how parameters are rounded when they are quantized
+    pub rounding: RoundingMode,
 }
 
 impl std::fmt::Display for VarScales {
@@ -254,8 +372,20 @@ impl VarScales {
             input: args.input_scale,
             params: args.param_scale,
             rebase_multiplier: args.scale_rebase_multiplier,
+            rebase_ops: args.rebase_ops.clone(),
+            rounding: args.rounding,
         })
     }
+
+    /// The multiplier of the max scale above which an op's outputs are rebased
+    pub fn rebase_multiplier_for(&self, op: &str) -> u32 {
+        let name = op.split_whitespace().next().unwrap_or_default();
+        if self.rebase_ops.iter().any(|o| o.eq_ignore_ascii_case(name)) {
+            1
+        } else {
+            self.rebase_multiplier
+        }
+    }
 }
 
 /// Represents whether the model input, model parameters, and model output are Public or Private to the prover.
@@ -302,6 +432,10 @@ impl VarVisibility {
             );
         }
 
+        if params_vis.is_signed() || output_vis.is_signed() {
+            return Err("signed visibility only applies to inputs".into());
+        }
+
         if !output_vis.is_public()
             & !params_vis.is_public()
             & !input_vis.is_public()
@@ -0,0 +1,249 @@
+use crate::graph::GraphWitness;
+use crate::pfsys::Snark;
+use crate::server::ProverState;
+use halo2curves::bn256::{Fr, G1Affine};
+use log::{error, info};
+use proto::proof_status::State;
+use proto::prover_server::{Prover, ProverServer};
+use proto::{
+    GetProofRequest, ProofStatus, SubmitWitnessRequest, SubmitWitnessResponse, VerifyRequest,
+    VerifyResponse,
+};
+use std::collections::HashMap;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio_stream::wrappers::WatchStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+/// Types and service traits generated from `proto/ezkl.proto`
+#[allow(missing_docs, unused_qualifications, trivial_casts, clippy::all)]
+pub mod proto {
+    tonic::include_proto!("ezkl");
+}
+
+/// How long the result of a finished job is kept for `GetProof`
+pub const FINISHED_JOB_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// A proving job and when it finished, if it has
+#[derive(Debug)]
+struct Job {
+    status: watch::Receiver<ProofStatus>,
+    finished: Option<Instant>,
+}
+
+type Jobs = Arc<Mutex<HashMap<String, Job>>>;
+
+/// The number of jobs that are queued or proving
+fn pending_jobs(jobs: &HashMap<String, Job>) -> usize {
+    jobs.values().filter(|job| job.finished.is_none()).count()
+}
+
+/// Drops the jobs that finished more than [FINISHED_JOB_TTL] before `now`
+fn prune_jobs(jobs: &mut HashMap<String, Job>, now: Instant) {
+    jobs.retain(|_, job| {
+        job.finished.map_or(true, |finished| {
+            now.duration_since(finished) < FINISHED_JOB_TTL
+        })
+    });
+}
+
+fn status(state: State) -> ProofStatus {
+    ProofStatus {
+        state: state as i32,
+        ..Default::default()
+    }
+}
+
+/// A gRPC [Prover] backed by a [ProverState] loaded once at startup.
+///
+/// Submitted witnesses are proven one at a time (each proof is itself
+/// parallelized), and the status of every job is kept so it can be streamed by `GetProof`,
+/// for up to [FINISHED_JOB_TTL] once the job is done. At most `max_queued` jobs can be
+/// unfinished at once, further submissions are rejected with `RESOURCE_EXHAUSTED`.
+#[derive(Debug)]
+pub struct ProverService {
+    state: Arc<ProverState>,
+    /// held while a proof is being generated
+    proving: Arc<Mutex<()>>,
+    jobs: Jobs,
+    next_job: AtomicU64,
+    max_queued: usize,
+}
+
+impl ProverService {
+    /// Creates a service over the loaded prover state that holds up to `max_queued` unfinished jobs
+    pub fn new(state: ProverState, max_queued: usize) -> Self {
+        Self {
+            state: Arc::new(state),
+            proving: Arc::new(Mutex::new(())),
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            next_job: AtomicU64::new(0),
+            max_queued,
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Prover for ProverService {
+    async fn submit_witness(
+        &self,
+        request: Request<SubmitWitnessRequest>,
+    ) -> Result<Response<SubmitWitnessResponse>, Status> {
+        let witness: GraphWitness = serde_json::from_slice(&request.into_inner().witness)
+            .map_err(|e| Status::invalid_argument(format!("invalid witness: {}", e)))?;
+
+        let (tx, rx) = watch::channel(status(State::Queued));
+        let job_id = {
+            let mut jobs = self.jobs.lock().unwrap();
+            prune_jobs(&mut jobs, Instant::now());
+            if pending_jobs(&jobs) >= self.max_queued {
+                return Err(Status::resource_exhausted(format!(
+                    "{} proving jobs are already pending",
+                    self.max_queued
+                )));
+            }
+            let job_id = self.next_job.fetch_add(1, Ordering::SeqCst).to_string();
+            jobs.insert(
+                job_id.clone(),
+                Job {
+                    status: rx,
+                    finished: None,
+                },
+            );
+            job_id
+        };
+        info!("queued proving job {}", job_id);
+
+        let state = self.state.clone();
+        let proving = self.proving.clone();
+        let jobs = self.jobs.clone();
+        let id = job_id.clone();
+        // the sender is dropped once the job finishes, which ends any GetProof streams
+        tokio::task::spawn_blocking(move || {
+            let _guard = proving.lock().unwrap_or_else(|e| e.into_inner());
+            let _ = tx.send(status(State::Proving));
+            let res = state
+                .prove_witness(witness)
+                .and_then(|snark| Ok(serde_json::to_vec(&snark)?));
+            let _ = tx.send(match res {
+                Ok(proof) => ProofStatus {
+                    state: State::Done as i32,
+                    proof,
+                    ..Default::default()
+                },
+                Err(e) => {
+                    error!("proving job {} failed: {}", id, e);
+                    ProofStatus {
+                        state: State::Failed as i32,
+                        error: e.to_string(),
+                        ..Default::default()
+                    }
+                }
+            });
+            if let Some(job) = jobs.lock().unwrap().get_mut(&id) {
+                job.finished = Some(Instant::now());
+            }
+        });
+
+        Ok(Response::new(SubmitWitnessResponse { job_id }))
+    }
+
+    type GetProofStream = Pin<Box<dyn Stream<Item = Result<ProofStatus, Status>> + Send>>;
+
+    async fn get_proof(
+        &self,
+        request: Request<GetProofRequest>,
+    ) -> Result<Response<Self::GetProofStream>, Status> {
+        let job_id = request.into_inner().job_id;
+        let rx = {
+            let mut jobs = self.jobs.lock().unwrap();
+            prune_jobs(&mut jobs, Instant::now());
+            jobs.get(&job_id)
+                .map(|job| job.status.clone())
+                .ok_or_else(|| Status::not_found(format!("no proving job {}", job_id)))?
+        };
+        Ok(Response::new(Box::pin(WatchStream::new(rx).map(Ok))))
+    }
+
+    async fn verify(
+        &self,
+        request: Request<VerifyRequest>,
+    ) -> Result<Response<VerifyResponse>, Status> {
+        let snark: Snark<Fr, G1Affine> = serde_json::from_slice(&request.into_inner().proof)
+            .map_err(|e| Status::invalid_argument(format!("invalid proof: {}", e)))?;
+        let state = self.state.clone();
+        let res =
+            tokio::task::spawn_blocking(move || state.verify(snark).map_err(|e| e.to_string()))
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(VerifyResponse {
+            verified: res.is_ok(),
+            error: res.err().unwrap_or_default(),
+        }))
+    }
+}
+
+/// Serves the [Prover] gRPC service on `addr` until the process is stopped, holding up to
+/// `max_queued` unfinished jobs. Client deadlines (`grpc-timeout`) are honored by the transport.
+pub async fn serve_grpc(
+    addr: SocketAddr,
+    state: ProverState,
+    max_queued: usize,
+) -> Result<(), Box<dyn Error>> {
+    info!("listening for gRPC on {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(ProverServer::new(ProverService::new(state, max_queued)))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(finished: Option<Instant>) -> Job {
+        Job {
+            status: watch::channel(status(State::Queued)).1,
+            finished,
+        }
+    }
+
+    #[test]
+    fn test_prune_jobs() {
+        // the clock is passed in, so it is moved forward rather than looking back from the
+        // current instant
+        let start = Instant::now();
+        let now = start + FINISHED_JOB_TTL + Duration::from_secs(1);
+        let mut jobs = HashMap::from([
+            ("running".to_string(), job(None)),
+            (
+                "recent".to_string(),
+                job(Some(now - Duration::from_secs(1))),
+            ),
+            ("stale".to_string(), job(Some(start))),
+        ]);
+
+        prune_jobs(&mut jobs, now);
+        let mut left = jobs.into_keys().collect::<Vec<_>>();
+        left.sort();
+        assert_eq!(left, vec!["recent", "running"]);
+    }
+
+    #[test]
+    fn test_pending_jobs() {
+        let now = Instant::now();
+        let jobs = HashMap::from([
+            ("queued".to_string(), job(None)),
+            ("proving".to_string(), job(None)),
+            ("done".to_string(), job(Some(now))),
+        ]);
+        assert_eq!(pending_jobs(&jobs), 2);
+    }
+}
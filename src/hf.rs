@@ -0,0 +1,209 @@
+//! Models and input files on the Hugging Face Hub. Input files are fetched as is and have to be
+//! ezkl input data (e.g. token ids a tokenizer already produced), no tokenizer is run here.
+
+use crate::commands::Commands;
+use crate::execute::EZKL_REPO_PATH;
+use log::info;
+use std::error::Error;
+use std::path::PathBuf;
+
+/// The scheme of paths that point at a file in a Hugging Face Hub repository,
+/// e.g. `hf://org/repo/model.onnx` or `hf://org/repo@<revision>/inputs/input.json`.
+pub const HF_SCHEME: &str = "hf://";
+
+const DEFAULT_ENDPOINT: &str = "https://huggingface.co";
+const DEFAULT_REVISION: &str = "main";
+
+/// Whether `part` can be used as a single directory or file name in the cache, i.e. it can't
+/// climb out of (or point outside) the cache directory
+fn is_safe_component(part: &str) -> bool {
+    !part.is_empty() && part != "." && part != ".." && !part.contains(['/', '\\'])
+}
+
+/// A file in a Hugging Face Hub repository, pinned to a revision
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HfPath {
+    /// the repository id (`org/repo`)
+    pub repo: String,
+    /// the branch, tag or commit to fetch the file at
+    pub revision: String,
+    /// the path of the file within the repository
+    pub file: String,
+}
+
+impl HfPath {
+    /// Parses an `hf://org/repo[@revision]/path/to/file` path, returning None if the path
+    /// doesn't use the `hf://` scheme.
+    pub fn parse(path: &str) -> Option<Result<Self, String>> {
+        let rest = path.strip_prefix(HF_SCHEME)?;
+        let mut parts = rest.splitn(3, '/');
+        let (Some(org), Some(repo), Some(file)) = (parts.next(), parts.next(), parts.next()) else {
+            return Some(Err(format!(
+                "{} should look like hf://org/repo[@revision]/file",
+                path
+            )));
+        };
+        let (repo, revision) = match repo.split_once('@') {
+            Some((repo, revision)) => (repo, revision),
+            None => (repo, DEFAULT_REVISION),
+        };
+        if org.is_empty() || repo.is_empty() || revision.is_empty() || file.is_empty() {
+            return Some(Err(format!(
+                "{} should look like hf://org/repo[@revision]/file",
+                path
+            )));
+        }
+        // every part ends up in the cache path, so none of them can escape the cache
+        if !is_safe_component(org)
+            || !is_safe_component(repo)
+            || !is_safe_component(revision)
+            || !file.split('/').all(is_safe_component)
+        {
+            return Some(Err(format!(
+                "{} can't contain absolute paths, empty, '.' or '..' components, or separators in the revision",
+                path
+            )));
+        }
+        Some(Ok(Self {
+            repo: format!("{}/{}", org, repo),
+            revision: revision.to_string(),
+            file: file.to_string(),
+        }))
+    }
+
+    /// The download url, `HF_ENDPOINT` overrides the public hub
+    pub fn url(&self) -> String {
+        let endpoint = std::env::var("HF_ENDPOINT").unwrap_or_else(|_| DEFAULT_ENDPOINT.into());
+        format!(
+            "{}/{}/resolve/{}/{}",
+            endpoint.trim_end_matches('/'),
+            self.repo,
+            self.revision,
+            self.file
+        )
+    }
+
+    /// Where the file is cached locally
+    pub fn cache_path(&self) -> PathBuf {
+        PathBuf::from(format!(
+            "{}/hf/{}/{}/{}",
+            *EZKL_REPO_PATH, self.repo, self.revision, self.file
+        ))
+    }
+
+    /// A commit hash always refers to the same file, so it never has to be fetched twice.
+    /// Branches and tags can move and are fetched again.
+    fn is_pinned(&self) -> bool {
+        self.revision.len() == 40 && self.revision.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    /// Downloads the file (authenticating with `HF_TOKEN` if it is set) and returns its local path
+    pub async fn download(&self) -> Result<PathBuf, Box<dyn Error>> {
+        let path = self.cache_path();
+        if self.is_pinned() && path.exists() {
+            return Ok(path);
+        }
+
+        info!("downloading {} from {}", self.file, self.url());
+        let mut request = reqwest::Client::new().get(self.url());
+        if let Ok(token) = std::env::var("HF_TOKEN") {
+            request = request.bearer_auth(token);
+        }
+        let resp = request.send().await?.error_for_status()?;
+        let bytes = resp.bytes().await?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, bytes)?;
+        Ok(path)
+    }
+}
+
+fn collect_hf_paths(value: &serde_json::Value, paths: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(s) if s.starts_with(HF_SCHEME) => paths.push(s.clone()),
+        serde_json::Value::Array(values) => values.iter().for_each(|v| collect_hf_paths(v, paths)),
+        serde_json::Value::Object(map) => map.values().for_each(|v| collect_hf_paths(v, paths)),
+        _ => {}
+    }
+}
+
+fn replace_paths(value: &mut serde_json::Value, resolved: &[(String, PathBuf)]) {
+    match value {
+        serde_json::Value::String(s) => {
+            if let Some((_, local)) = resolved.iter().find(|(remote, _)| remote == s) {
+                *s = local.to_string_lossy().to_string();
+            }
+        }
+        serde_json::Value::Array(values) => {
+            values.iter_mut().for_each(|v| replace_paths(v, resolved))
+        }
+        serde_json::Value::Object(map) => map.values_mut().for_each(|v| replace_paths(v, resolved)),
+        _ => {}
+    }
+}
+
+/// Downloads every `hf://` path among the arguments of a command and points the command at
+/// the local copies, so any command can take models and inputs straight from the hub.
+pub async fn resolve_command(command: Commands) -> Result<Commands, Box<dyn Error>> {
+    let mut value = serde_json::to_value(&command)?;
+    let mut remote = vec![];
+    collect_hf_paths(&value, &mut remote);
+    if remote.is_empty() {
+        return Ok(command);
+    }
+
+    let mut resolved = vec![];
+    for path in remote {
+        let hf_path = HfPath::parse(&path).expect("path has the hf scheme")?;
+        resolved.push((path, hf_path.download().await?));
+    }
+    replace_paths(&mut value, &resolved);
+    Ok(serde_json::from_value(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hf_path() {
+        let path = HfPath::parse("hf://org/repo@v1.0/onnx/model.onnx")
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.repo, "org/repo");
+        assert_eq!(path.revision, "v1.0");
+        assert_eq!(path.file, "onnx/model.onnx");
+
+        let path = HfPath::parse("hf://org/repo/input.json").unwrap().unwrap();
+        assert_eq!(path.revision, DEFAULT_REVISION);
+
+        assert!(HfPath::parse("model.onnx").is_none());
+        assert!(HfPath::parse("hf://org/model.onnx").unwrap().is_err());
+    }
+
+    #[test]
+    fn test_parse_hf_path_stays_in_cache() {
+        for path in [
+            "hf://org/repo/../../../.ssh/id_rsa",
+            "hf://org/repo/onnx/../../model.onnx",
+            "hf://org/repo//etc/passwd",
+            "hf://org/repo@../../model.onnx",
+            "hf://org/repo@..\\..\\x/model.onnx",
+            "hf://../repo/model.onnx",
+            "hf://org/..@main/model.onnx",
+            "hf://org/repo/onnx\\..\\..\\model.onnx",
+            "hf://org/repo/./model.onnx",
+        ] {
+            assert!(HfPath::parse(path).unwrap().is_err(), "{}", path);
+        }
+
+        let path = HfPath::parse("hf://org/repo@0123abc/onnx/model.onnx")
+            .unwrap()
+            .unwrap();
+        assert!(path
+            .cache_path()
+            .starts_with(format!("{}/hf/org/repo/0123abc", *EZKL_REPO_PATH)));
+    }
+}
@@ -0,0 +1,226 @@
+use crate::commands::Cli;
+use crate::execute::run_with_output;
+use clap::Parser;
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::convert::Infallible;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+/// The CLI verbs that can be called as JSON-RPC methods
+pub const RPC_METHODS: [&str; 5] = ["mock", "gen-witness", "prove", "verify", "aggregate"];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The command ran and failed, the error's `data` holds the ezkl error code, kind and message
+const COMMAND_FAILED: i64 = -32000;
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    id: Value,
+}
+
+#[derive(Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+    id: Value,
+}
+
+impl RpcResponse {
+    fn result(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            result: Some(result),
+            error: None,
+            id,
+        }
+    }
+
+    fn error(id: Value, code: i64, message: String, data: Option<Value>) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            result: None,
+            error: Some(RpcError {
+                code,
+                message,
+                data,
+            }),
+            id,
+        }
+    }
+}
+
+/// Turns the params object of a call into the equivalent command line, so that the defaults
+/// and validation of the CLI apply as is. Keys are argument names (`compiled_circuit` or
+/// `compiled-circuit`), `true` passes a flag and arrays repeat the argument.
+fn parse_command(method: &str, params: &Value) -> Result<crate::commands::Commands, String> {
+    let mut argv = vec!["ezkl".to_string(), method.to_string()];
+    match params {
+        Value::Null => {}
+        Value::Object(map) => {
+            for (key, value) in map {
+                let flag = format!("--{}", key.replace('_', "-"));
+                let values = match value {
+                    Value::Array(values) => values.clone(),
+                    v => vec![v.clone()],
+                };
+                for value in values {
+                    match value {
+                        Value::Bool(true) => argv.push(flag.clone()),
+                        Value::Bool(false) | Value::Null => {}
+                        Value::String(s) => argv.push(format!("{}={}", flag, s)),
+                        v => argv.push(format!("{}={}", flag, v)),
+                    }
+                }
+            }
+        }
+        _ => return Err("params must be an object of named arguments".to_string()),
+    }
+    Cli::try_parse_from(argv)
+        .map(|cli| cli.command)
+        .map_err(|e| e.to_string())
+}
+
+async fn call(lock: Arc<Mutex<()>>, request: Value) -> Option<RpcResponse> {
+    let request: RpcRequest = match serde_json::from_value(request) {
+        Ok(r) => r,
+        Err(e) => {
+            return Some(RpcResponse::error(
+                Value::Null,
+                INVALID_REQUEST,
+                e.to_string(),
+                None,
+            ))
+        }
+    };
+    // requests without an id are notifications and get no response
+    let is_notification = request.id.is_null();
+    let id = request.id;
+
+    let response = if request.jsonrpc != "2.0" {
+        RpcResponse::error(id, INVALID_REQUEST, "jsonrpc must be 2.0".into(), None)
+    } else if !RPC_METHODS.contains(&request.method.as_str()) {
+        RpcResponse::error(
+            id,
+            METHOD_NOT_FOUND,
+            format!("unknown method {}", request.method),
+            None,
+        )
+    } else {
+        match parse_command(&request.method, &request.params) {
+            Err(e) => RpcResponse::error(id, INVALID_PARAMS, e, None),
+            Ok(command) => {
+                info!("rpc {}", request.method);
+                // commands change the working directory and thread local settings so they
+                // run one at a time, off the async executor
+                let output = tokio::task::spawn_blocking(move || {
+                    let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+                    tokio::runtime::Handle::current().block_on(run_with_output(command))
+                })
+                .await;
+                match output {
+                    Ok(output) => match output.error {
+                        None => RpcResponse::result(id, output.result),
+                        Some(e) => {
+                            error!("rpc {} failed: {}", request.method, e);
+                            RpcResponse::error(id, COMMAND_FAILED, e.to_string(), Some(e.to_json()))
+                        }
+                    },
+                    Err(e) => RpcResponse::error(id, COMMAND_FAILED, e.to_string(), None),
+                }
+            }
+        }
+    };
+    (!is_notification).then_some(response)
+}
+
+async fn handle(lock: Arc<Mutex<()>>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    if req.method() != Method::POST {
+        return Ok(Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::empty())
+            .unwrap());
+    }
+
+    let body = match hyper::body::to_bytes(req.into_body()).await {
+        Ok(body) => body,
+        Err(e) => {
+            return Ok(Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .body(Body::from(e.to_string()))
+                .unwrap())
+        }
+    };
+
+    let response = match serde_json::from_slice::<Value>(&body) {
+        Err(e) => serde_json::to_value(RpcResponse::error(
+            Value::Null,
+            PARSE_ERROR,
+            e.to_string(),
+            None,
+        ))
+        .ok(),
+        Ok(Value::Array(batch)) => {
+            let mut responses = vec![];
+            for request in batch {
+                responses.extend(call(lock.clone(), request).await);
+            }
+            (!responses.is_empty())
+                .then(|| serde_json::to_value(responses).ok())
+                .flatten()
+        }
+        Ok(request) => call(lock, request)
+            .await
+            .and_then(|r| serde_json::to_value(r).ok()),
+    };
+
+    Ok(match response {
+        Some(response) => Response::builder()
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(response.to_string()))
+            .unwrap(),
+        None => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .unwrap(),
+    })
+}
+
+/// Serves JSON-RPC 2.0 over http POST on `addr` until the process is stopped. The methods are
+/// the CLI verbs in [RPC_METHODS], their params the command's named arguments, and the result
+/// the same json that `--format json` prints for the command.
+pub async fn serve_rpc(addr: SocketAddr) -> Result<(), Box<dyn Error>> {
+    let lock = Arc::new(Mutex::new(()));
+    let make_svc = make_service_fn(move |_| {
+        let lock = lock.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(lock.clone(), req))) }
+    });
+    let server = Server::try_bind(&addr)?.serve(make_svc);
+    info!("listening for json-rpc on http://{}", addr);
+    server.await?;
+    Ok(())
+}
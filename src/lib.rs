@@ -31,14 +31,20 @@
 
 use circuit::Tolerance;
 use clap::Args;
-use graph::Visibility;
+use graph::{RoundingMode, Visibility};
+use pfsys::{Commitments, MultiOpen};
 use serde::{Deserialize, Serialize};
 
+/// Proving and verifying from other programs, without going through the CLI.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod api;
 /// Methods for configuring tensor operations and assigning values to them in a Halo2 circuit.
 pub mod circuit;
 /// CLI commands.
 #[cfg(not(target_arch = "wasm32"))]
 pub mod commands;
+/// Typed errors that serialize to json with error codes.
+pub mod error;
 #[cfg(not(target_arch = "wasm32"))]
 // abigen doesn't generate docs for this module
 #[allow(missing_docs)]
@@ -48,23 +54,46 @@ pub mod eth;
 ///
 #[cfg(not(target_arch = "wasm32"))]
 pub mod execute;
+/// A C ABI for linking ezkl from other languages
+#[cfg(all(feature = "ffi", not(target_arch = "wasm32")))]
+pub mod ffi;
 /// Utilities for converting from Halo2 Field types to integers (and vice-versa).
 pub mod fieldutils;
+/// Picking the gpu or the cpu for proving in builds with icicle
+#[cfg(all(feature = "icicle", not(target_arch = "wasm32")))]
+pub mod gpu;
 /// Methods for loading onnx format models and automatically laying them out in
 /// a Halo2 circuit.
 #[cfg(feature = "onnx")]
 pub mod graph;
+/// gRPC proving service
+#[cfg(all(feature = "grpc", not(target_arch = "wasm32")))]
+pub mod grpc;
+/// Fetching models and inputs from the Hugging Face Hub through `hf://` paths
+#[cfg(not(target_arch = "wasm32"))]
+pub mod hf;
 /// Methods for deploying and interacting with the ezkl hub
 #[cfg(not(target_arch = "wasm32"))]
 pub mod hub;
+/// JSON-RPC 2.0 server mirroring the proving commands
+#[cfg(all(feature = "server", not(target_arch = "wasm32")))]
+pub mod jsonrpc;
 /// beautiful logging
 #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 pub mod logger;
+/// Node.js bindings
+#[cfg(all(feature = "node-bindings", not(target_arch = "wasm32")))]
+pub mod node;
 /// Tools for proofs and verification used by cli
 pub mod pfsys;
 /// Python bindings
 #[cfg(feature = "python-bindings")]
 pub mod python;
+/// HTTP server exposing proving and verification endpoints
+#[cfg(all(feature = "server", not(target_arch = "wasm32")))]
+pub mod server;
+/// Reading and writing artifacts through stdin/stdout when a path is `-`.
+pub mod stdio;
 /// An implementation of multi-dimensional tensors.
 pub mod tensor;
 /// wasm prover and verifier
@@ -89,6 +118,14 @@ pub struct RunArgs {
     /// if the scale is ever > scale_rebase_multiplier * input_scale then the scale is rebased to input_scale (this a more advanced parameter, use with caution)
     #[arg(long, default_value = "1")]
     pub scale_rebase_multiplier: u32,
+    /// Ops (eg. einsum,conv) whose outputs are always rebased to input_scale, whatever the scale_rebase_multiplier, so that chains of them keep a bounded scale
+    #[arg(long, value_delimiter = ',')]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rebase_ops: Vec<String>,
+    /// How parameters are rounded when they are quantized. Rounding to the nearest even or stochastically avoids the bias ties rounded away from zero introduce at small scales
+    #[arg(long, default_value = "nearest")]
+    #[serde(default)]
+    pub rounding: RoundingMode,
     /// The min and max elements in the lookup table input column
     #[arg(short = 'B', long, value_parser = parse_tuple::<i128>, default_value = "(-32768,32768)")]
     pub lookup_range: (i128, i128),
@@ -101,6 +138,17 @@ pub struct RunArgs {
     /// Hand-written parser for graph variables, eg. batch_size=1
     #[arg(short = 'V', long, value_parser = parse_key_val::<String, usize>, default_value = "batch_size=1", value_delimiter = ',')]
     pub variables: Vec<(String, usize)>,
+    /// The size of the batch dimension of the model's inputs, overrides the batch_size variable.
+    /// Symbolic leading dimensions of the inputs that no variable sets are fixed to it too.
+    #[arg(long)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<usize>,
+    /// Scales to rebase the outputs of individual nodes to (mixed precision), as node=scale
+    /// pairs where a node is its index or name in the graph, eg. 12=10. Rescaling ops are
+    /// inserted where the scale changes.
+    #[arg(long, value_parser = parse_key_val::<String, Scale>, value_delimiter = ',', allow_hyphen_values = true)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scale_overrides: Vec<(String, Scale)>,
     /// Flags whether inputs are public, private, hashed
     #[arg(long, default_value = "private")]
     pub input_visibility: Visibility,
@@ -110,6 +158,14 @@ pub struct RunArgs {
     /// Flags whether params are public, private, hashed
     #[arg(long, default_value = "private")]
     pub param_visibility: Visibility,
+    /// The commitment scheme to prove with: kzg (needs a universal srs) or ipa (no trusted setup, but no evm verifier or aggregation)
+    #[arg(long, default_value = "kzg")]
+    #[serde(default)]
+    pub commitment: Commitments,
+    /// How kzg proofs are opened: shplonk (smaller proofs for wide circuits, needed for aggregation) or gwc (can be cheaper to verify, also on-chain)
+    #[arg(long, default_value = "shplonk")]
+    #[serde(default)]
+    pub multiopen: MultiOpen,
 }
 
 impl Default for RunArgs {
@@ -119,10 +175,16 @@ impl Default for RunArgs {
             input_scale: 7,
             param_scale: 7,
             scale_rebase_multiplier: 1,
+            rebase_ops: vec![],
+            rounding: RoundingMode::default(),
+            commitment: Commitments::default(),
+            multiopen: MultiOpen::default(),
             lookup_range: (-32768, 32768),
             logrows: 17,
             num_inner_cols: 2,
             variables: vec![("batch_size".to_string(), 1)],
+            batch_size: None,
+            scale_overrides: vec![],
             input_visibility: Visibility::Private,
             output_visibility: Visibility::Public,
             param_visibility: Visibility::Private,
@@ -145,9 +207,25 @@ impl RunArgs {
         if self.num_inner_cols < 1 {
             return Err("num_inner_cols must be >= 1".into());
         }
+        if self.multiopen != MultiOpen::default() && self.commitment != Commitments::KZG {
+            return Err("the multiopen scheme only applies to kzg commitments".into());
+        }
+        if self.batch_size == Some(0) {
+            return Err("batch_size must be >= 1".into());
+        }
         Ok(())
     }
 
+    /// The graph variables, with the batch_size variable replaced by `batch_size` if it is set
+    pub fn graph_variables(&self) -> Vec<(String, usize)> {
+        let mut variables = self.variables.clone();
+        if let Some(batch_size) = self.batch_size {
+            variables.retain(|(name, _)| name != "batch_size");
+            variables.push(("batch_size".to_string(), batch_size));
+        }
+        variables
+    }
+
     /// Export the ezkl configuration as json
     pub fn as_json(&self) -> Result<String, Box<dyn std::error::Error>> {
         let serialized = match serde_json::to_string(&self) {
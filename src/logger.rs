@@ -68,6 +68,11 @@ pub fn format(buf: &mut Formatter, record: &Record<'_>) -> Result<(), std::fmt::
 
 /// initializes the logger
 pub fn init_logger() {
+    init_logger_with_target(env_logger::Target::Stdout)
+}
+
+/// initializes the logger, writing to the given target
+pub fn init_logger_with_target(target: env_logger::Target) {
     let start = Instant::now();
     let mut builder = Builder::new();
 
@@ -82,10 +87,30 @@ pub fn init_logger() {
                 .replace('\n', &format!("\n{} ", " | ".white().bold()))
         )
     });
-    builder.target(env_logger::Target::Stdout);
+    builder.target(target);
     builder.filter(None, LevelFilter::Info);
     if env::var("RUST_LOG").is_ok() {
         builder.parse_filters(&env::var("RUST_LOG").unwrap());
     }
     builder.init();
 }
+
+/// Records the `tracing` spans around each phase (loading, configuring, laying out each node,
+/// keygen, proving) as a Chrome trace at `path`, which can be opened in `chrome://tracing` or
+/// Perfetto or folded into a flamegraph. The trace is written when the returned guard drops.
+pub fn init_chrome_trace(path: &std::path::Path) -> tracing_chrome::FlushGuard {
+    use tracing_subscriber::prelude::*;
+
+    let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new()
+        .file(path)
+        .include_args(true)
+        .build();
+    if tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer)).is_err()
+    {
+        log::warn!(
+            "a tracing subscriber is already set, {} won't be written",
+            path.display()
+        );
+    }
+    guard
+}
@@ -0,0 +1,103 @@
+use crate::circuit::CheckMode;
+use crate::execute::CommitmentParams;
+use crate::graph::input::GraphData;
+use crate::graph::{GraphCircuit, GraphSettings, GraphWitness};
+use crate::pfsys::{ProofType, Snark};
+use halo2_proofs::plonk::{ProvingKey, VerifyingKey};
+use halo2curves::bn256::{Fr, G1Affine};
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+
+fn js_error(context: &str, e: impl std::fmt::Display) -> napi::Error {
+    napi::Error::from_reason(format!("{}: {}", context, e))
+}
+
+/// Reads the srs for the commitment scheme in `settings`
+fn read_params(srs: &[u8], settings: &GraphSettings) -> napi::Result<CommitmentParams> {
+    let mut reader = std::io::BufReader::new(srs);
+    CommitmentParams::read(&mut reader, settings)
+        .map_err(|e| js_error("Failed to deserialize srs", e))
+}
+
+fn read_circuit(compiled_circuit: &[u8]) -> napi::Result<GraphCircuit> {
+    bincode::deserialize(compiled_circuit)
+        .map_err(|e| js_error("Failed to deserialize compiled circuit", e))
+}
+
+/// Generates a witness (as json) from a compiled circuit and json input data
+#[napi]
+pub fn gen_witness(compiled_circuit: Buffer, input: Buffer) -> napi::Result<Buffer> {
+    let mut circuit = read_circuit(&compiled_circuit)?;
+    let input: GraphData =
+        serde_json::from_slice(&input).map_err(|e| js_error("Failed to deserialize input", e))?;
+
+    // on-chain data sources are fetched asynchronously, node's own event loop isn't available here
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| js_error("Failed to start runtime", e))?;
+    let mut input = runtime
+        .block_on(circuit.load_graph_input(&input))
+        .map_err(|e| js_error("Failed to load input", e))?;
+    let witness = circuit
+        .forward(&mut input, None, None)
+        .map_err(|e| js_error("Failed to generate witness", e))?;
+
+    serde_json::to_vec(&witness)
+        .map(Buffer::from)
+        .map_err(|e| js_error("Failed to serialize witness", e))
+}
+
+/// Proves a json witness and returns the json serialized proof
+#[napi]
+pub fn prove(
+    witness: Buffer,
+    pk: Buffer,
+    compiled_circuit: Buffer,
+    srs: Buffer,
+) -> napi::Result<Buffer> {
+    let circuit = read_circuit(&compiled_circuit)?;
+    let witness: GraphWitness = serde_json::from_slice(&witness)
+        .map_err(|e| js_error("Failed to deserialize witness", e))?;
+    let params = read_params(&srs, circuit.settings())?;
+
+    let mut reader = std::io::BufReader::new(&pk[..]);
+    let pk = ProvingKey::<G1Affine>::read::<_, GraphCircuit>(
+        &mut reader,
+        halo2_proofs::SerdeFormat::RawBytes,
+        circuit.settings().clone(),
+    )
+    .map_err(|e| js_error("Failed to deserialize proving key", e))?;
+
+    let snark = params
+        .prove(witness, circuit, &pk, ProofType::Single, CheckMode::UNSAFE)
+        .map_err(|e| js_error("Failed to prove", e))?;
+
+    serde_json::to_vec(&snark)
+        .map(Buffer::from)
+        .map_err(|e| js_error("Failed to serialize proof", e))
+}
+
+/// Verifies a json proof against a verifying key and settings
+#[napi]
+pub fn verify(proof: Buffer, vk: Buffer, settings: Buffer, srs: Buffer) -> napi::Result<bool> {
+    let settings: GraphSettings = serde_json::from_slice(&settings)
+        .map_err(|e| js_error("Failed to deserialize settings", e))?;
+    let snark: Snark<Fr, G1Affine> =
+        serde_json::from_slice(&proof).map_err(|e| js_error("Failed to deserialize proof", e))?;
+    let params = read_params(&srs, &settings)?;
+    let multiopen = settings.run_args.multiopen;
+
+    let mut reader = std::io::BufReader::new(&vk[..]);
+    let vk = VerifyingKey::<G1Affine>::read::<_, GraphCircuit>(
+        &mut reader,
+        halo2_proofs::SerdeFormat::RawBytes,
+        settings,
+    )
+    .map_err(|e| js_error("Failed to deserialize vk", e))?;
+
+    params
+        .verify(snark, &vk, multiopen)
+        .map(|_| true)
+        .map_err(|e| js_error("Failed to verify", e))
+}
@@ -1,4 +1,4 @@
-use crate::pfsys::{Snark, SnarkWitness};
+use crate::pfsys::{Snark, SnarkWitness, TranscriptType};
 use halo2_proofs::circuit::AssignedCell;
 use halo2_proofs::plonk::{self};
 use halo2_proofs::{
@@ -70,6 +70,15 @@ pub enum AggregationError {
     /// proof creation errors
     #[error("Failed to create proof")]
     ProofCreate,
+    /// a snark was saved without the protocol of the circuit it proves
+    #[error("snark {0} has no protocol to aggregate over")]
+    MissingProtocol(usize),
+    /// a snark was not proven with the transcript the aggregation circuit reads
+    #[error("snark {0} uses a {1:?} transcript, it should be proven with `--proof-type for-aggr`")]
+    IncompatibleTranscript(usize, TranscriptType),
+    /// split proofs need the commits that link each segment to the previous one
+    #[error("snark {0} is missing the commits needed to aggregate split proofs")]
+    MissingSplit(usize),
 }
 
 type AggregationResult<'a> = (
@@ -91,7 +100,8 @@ type LoadedProof<'a> = verifier::plonk::PlonkProof<
     KzgAs<Bn256, Bdfg21>,
 >;
 
-/// Aggregate one or more application snarks of the same shape into a KzgAccumulator
+/// Aggregate one or more application snarks into a KzgAccumulator. The snarks can come from
+/// different circuits, each is verified against its own protocol.
 pub fn aggregate<'a>(
     svk: &Svk,
     loader: &Rc<Halo2Loader<'a>>,
@@ -238,29 +248,43 @@ impl AggregationCircuit {
         let snarks = snarks.into_iter().collect_vec();
 
         let mut accumulators = vec![];
+        let mut circuits: Vec<&Vec<G1Affine>> = vec![];
 
-        for snark in snarks.iter() {
+        for (i, snark) in snarks.iter().enumerate() {
             trace!("Aggregating with snark instances {:?}", snark.instances);
+            let protocol = snark
+                .protocol
+                .as_ref()
+                .ok_or(AggregationError::MissingProtocol(i))?;
+            if snark.transcript_type != TranscriptType::Poseidon {
+                return Err(AggregationError::IncompatibleTranscript(
+                    i,
+                    snark.transcript_type,
+                ));
+            }
+            if split_proof && snark.split.is_none() {
+                return Err(AggregationError::MissingSplit(i));
+            }
+            if !circuits.contains(&&protocol.preprocessed) {
+                circuits.push(&protocol.preprocessed);
+            }
+
             let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(snark.proof.as_slice());
-            let proof = PlonkSuccinctVerifier::read_proof(
-                svk,
-                snark.protocol.as_ref().unwrap(),
-                &snark.instances,
-                &mut transcript,
-            )
-            .map_err(|e| {
-                log::error!("{:?}", e);
-                AggregationError::ProofRead
-            })?;
-            let mut accum = PlonkSuccinctVerifier::verify(
-                svk,
-                snark.protocol.as_ref().unwrap(),
-                &snark.instances,
-                &proof,
-            )
-            .map_err(|_| AggregationError::ProofVerify)?;
+            let proof =
+                PlonkSuccinctVerifier::read_proof(svk, protocol, &snark.instances, &mut transcript)
+                    .map_err(|e| {
+                        log::error!("{:?}", e);
+                        AggregationError::ProofRead
+                    })?;
+            let mut accum = PlonkSuccinctVerifier::verify(svk, protocol, &snark.instances, &proof)
+                .map_err(|_| AggregationError::ProofVerify)?;
             accumulators.append(&mut accum);
         }
+        log::info!(
+            "aggregating {} snarks of {} distinct circuits",
+            snarks.len(),
+            circuits.len()
+        );
 
         trace!("Accumulator");
         let (accumulator, as_proof) = {
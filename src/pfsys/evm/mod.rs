@@ -29,3 +29,5 @@ pub enum EvmVerificationError {
 }
 /// YulCode type which is just an alias of string
 pub type YulCode = String;
+/// Name of the object the generated Yul verifier is compiled from
+pub const YUL_VERIFIER_NAME: &str = "plonk_verifier";
@@ -4,14 +4,15 @@ use halo2_proofs::{plonk::VerifyingKey, poly::kzg::commitment::ParamsKZG};
 use halo2curves::bn256::{Bn256, Fq, Fr, G1Affine};
 use snark_verifier::{
     loader::evm::EvmLoader,
-    pcs::kzg::{Gwc19, KzgAs},
+    pcs::kzg::{Bdfg21, KzgAs},
     system::halo2::{compile, transcript::evm::EvmTranscript, Config},
     verifier::{self, SnarkVerifier},
 };
 use std::rc::Rc;
 use thiserror::Error;
 
-type PlonkVerifier = verifier::plonk::PlonkVerifier<KzgAs<Bn256, Gwc19>>;
+// proofs are opened with SHPLONK (Bdfg21), so the verifier has to match
+type PlonkVerifier = verifier::plonk::PlonkVerifier<KzgAs<Bn256, Bdfg21>>;
 
 #[derive(Error, Debug)]
 /// Errors related to simple evm verifier generation
@@ -24,7 +25,8 @@ pub enum SimpleError {
     ProofVerify,
 }
 
-/// Create EVM verifier yulcode
+/// Create EVM verifier yulcode. The verifier is a bare Yul object that reads the instances followed by
+/// the proof straight from calldata and reverts if the proof is invalid.
 pub fn gen_evm_verifier(
     params: &ParamsKZG<Bn256>,
     vk: &VerifyingKey<G1Affine>,
@@ -14,10 +14,17 @@ use halo2_proofs::plonk::{
     create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ProvingKey, VerifyingKey,
 };
 use halo2_proofs::poly::commitment::{CommitmentScheme, Params, ParamsProver, Prover, Verifier};
+use halo2_proofs::poly::ipa::commitment::{IPACommitmentScheme, ParamsIPA};
+use halo2_proofs::poly::ipa::multiopen::{ProverIPA, VerifierIPA};
+use halo2_proofs::poly::ipa::strategy::SingleStrategy as IPASingleStrategy;
 use halo2_proofs::poly::kzg::commitment::{KZGCommitmentScheme, ParamsKZG};
-use halo2_proofs::poly::kzg::multiopen::{ProverSHPLONK, VerifierSHPLONK};
+use halo2_proofs::poly::kzg::multiopen::{ProverGWC, ProverSHPLONK, VerifierGWC, VerifierSHPLONK};
+use halo2_proofs::poly::kzg::strategy::SingleStrategy as KZGSingleStrategy;
 use halo2_proofs::poly::VerificationStrategy;
-use halo2_proofs::transcript::{EncodedChallenge, TranscriptReadBuffer, TranscriptWriterBuffer};
+use halo2_proofs::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, TranscriptReadBuffer,
+    TranscriptWriterBuffer,
+};
 use halo2curves::ff::{FromUniformBytes, PrimeField, WithSmallOrderMulGroup};
 use halo2curves::serde::SerdeObject;
 use halo2curves::CurveAffine;
@@ -50,6 +57,9 @@ pub enum ProofType {
     #[default]
     Single,
     ForAggr,
+    /// a proof only verified off-chain (by ezkl or another halo2 verifier), with a blake2b
+    /// transcript the evm verifier can't check
+    OffChain,
 }
 
 impl From<ProofType> for TranscriptType {
@@ -57,6 +67,7 @@ impl From<ProofType> for TranscriptType {
         match val {
             ProofType::Single => TranscriptType::EVM,
             ProofType::ForAggr => TranscriptType::Poseidon,
+            ProofType::OffChain => TranscriptType::Blake2b,
         }
     }
 }
@@ -64,7 +75,7 @@ impl From<ProofType> for TranscriptType {
 impl From<ProofType> for StrategyType {
     fn from(val: ProofType) -> Self {
         match val {
-            ProofType::Single => StrategyType::Single,
+            ProofType::Single | ProofType::OffChain => StrategyType::Single,
             ProofType::ForAggr => StrategyType::Accum,
         }
     }
@@ -76,6 +87,7 @@ impl ToPyObject for ProofType {
         match self {
             ProofType::Single => "Single".to_object(py),
             ProofType::ForAggr => "ForAggr".to_object(py),
+            ProofType::OffChain => "OffChain".to_object(py),
         }
     }
 }
@@ -89,6 +101,7 @@ impl<'source> pyo3::FromPyObject<'source> for ProofType {
         match strval.to_lowercase().as_str() {
             "single" => Ok(ProofType::Single),
             "for-aggr" => Ok(ProofType::ForAggr),
+            "off-chain" => Ok(ProofType::OffChain),
             _ => Err(pyo3::exceptions::PyValueError::new_err(
                 "Invalid value for ProofType",
             )),
@@ -152,6 +165,7 @@ pub enum TranscriptType {
     Poseidon,
     #[default]
     EVM,
+    Blake2b,
 }
 
 #[cfg(feature = "python-bindings")]
@@ -160,10 +174,139 @@ impl ToPyObject for TranscriptType {
         match self {
             TranscriptType::Poseidon => "Poseidon".to_object(py),
             TranscriptType::EVM => "EVM".to_object(py),
+            TranscriptType::Blake2b => "Blake2b".to_object(py),
         }
     }
 }
 
+#[derive(
+    ValueEnum, Default, Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize, PartialOrd,
+)]
+/// How a proof artifact is encoded on disk
+pub enum ProofEncoding {
+    /// raw bincode bytes
+    Bin,
+    /// bincode bytes as a hex string
+    Hex,
+    /// bincode bytes as a base64 string
+    Base64,
+    /// serde json (default)
+    #[default]
+    Json,
+}
+
+impl std::fmt::Display for ProofEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_possible_value()
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+/// Converts ProofEncoding into a PyObject (Required for ProofEncoding to be compatible with Python)
+impl pyo3::IntoPy<PyObject> for ProofEncoding {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_string().to_object(py)
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+/// Obtains ProofEncoding from PyObject (Required for ProofEncoding to be compatible with Python)
+impl<'source> pyo3::FromPyObject<'source> for ProofEncoding {
+    fn extract(ob: &'source pyo3::PyAny) -> pyo3::PyResult<Self> {
+        let trystr = <pyo3::types::PyString as pyo3::PyTryFrom>::try_from(ob)?;
+        let strval = trystr.to_string();
+        ProofEncoding::from_str(&strval, true)
+            .map_err(|_| pyo3::exceptions::PyValueError::new_err("Invalid value for ProofEncoding"))
+    }
+}
+
+#[derive(
+    ValueEnum, Default, Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize, PartialOrd,
+)]
+/// The polynomial commitment scheme proofs are made with
+pub enum Commitments {
+    /// KZG commitments: proofs are small and cheap to verify (also on-chain) but need a
+    /// universal trusted setup (SRS)
+    #[default]
+    KZG,
+    /// inner product argument commitments: the params are transparent (no trusted setup) but
+    /// verification is linear in the circuit size, and there is no evm verifier or aggregation
+    IPA,
+}
+
+impl std::fmt::Display for Commitments {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_possible_value()
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+/// Converts Commitments into a PyObject (Required for Commitments to be compatible with Python)
+impl pyo3::IntoPy<PyObject> for Commitments {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_string().to_object(py)
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+/// Obtains Commitments from PyObject (Required for Commitments to be compatible with Python)
+impl<'source> pyo3::FromPyObject<'source> for Commitments {
+    fn extract(ob: &'source pyo3::PyAny) -> pyo3::PyResult<Self> {
+        let trystr = <pyo3::types::PyString as pyo3::PyTryFrom>::try_from(ob)?;
+        let strval = trystr.to_string();
+        Commitments::from_str(&strval, true)
+            .map_err(|_| pyo3::exceptions::PyValueError::new_err("Invalid value for Commitments"))
+    }
+}
+
+#[derive(
+    ValueEnum, Default, Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize, PartialOrd,
+)]
+/// How kzg proofs open their committed polynomials at the evaluation points
+pub enum MultiOpen {
+    /// SHPLONK (Bdfg21): a single opening proof for all points, so proofs stay small for wide
+    /// circuits. Needed for aggregation
+    #[default]
+    Shplonk,
+    /// GWC (Gwc19): an opening proof per distinct point, which can be cheaper to verify when the
+    /// circuit queries few rotations
+    Gwc,
+}
+
+impl std::fmt::Display for MultiOpen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_possible_value()
+            .expect("no values are skipped")
+            .get_name()
+            .fmt(f)
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+/// Converts MultiOpen into a PyObject (Required for MultiOpen to be compatible with Python)
+impl pyo3::IntoPy<PyObject> for MultiOpen {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_string().to_object(py)
+    }
+}
+
+#[cfg(feature = "python-bindings")]
+/// Obtains MultiOpen from PyObject (Required for MultiOpen to be compatible with Python)
+impl<'source> pyo3::FromPyObject<'source> for MultiOpen {
+    fn extract(ob: &'source pyo3::PyAny) -> pyo3::PyResult<Self> {
+        let trystr = <pyo3::types::PyString as pyo3::PyTryFrom>::try_from(ob)?;
+        let strval = trystr.to_string();
+        MultiOpen::from_str(&strval, true)
+            .map_err(|_| pyo3::exceptions::PyValueError::new_err("Invalid value for MultiOpen"))
+    }
+}
+
 #[cfg(feature = "python-bindings")]
 ///
 pub fn g1affine_to_pydict(g1affine_dict: &PyDict, g1affine: &G1Affine) {
@@ -202,6 +345,24 @@ pub fn vecu64_to_field_montgomery<F: PrimeField + SerdeObject + Serialize + Dese
     fp
 }
 
+/// The layout version of the proof artifacts written by this release. Bump it whenever [Snark]
+/// changes shape and keep decoding the older layouts in [Snark::from_bytes].
+pub const PROOF_VERSION: u32 = 1;
+
+/// Prefixes the binary encodings of versioned proofs, proofs without it predate versioning.
+const PROOF_MAGIC: &[u8; 4] = b"EZKP";
+
+fn check_proof_version(version: u32) -> Result<(), Box<dyn Error>> {
+    if version > PROOF_VERSION {
+        return Err(format!(
+            "proof has artifact version {} but this release of ezkl reads up to version {}, upgrade ezkl to load it",
+            version, PROOF_VERSION
+        )
+        .into());
+    }
+    Ok(())
+}
+
 /// An application snark with proof and instance variables ready for aggregation (raw field element)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Snark<F: PrimeField + SerdeObject, C: CurveAffine>
@@ -209,6 +370,12 @@ where
     C::Scalar: Serialize + DeserializeOwned,
     C::ScalarExt: Serialize + DeserializeOwned,
 {
+    /// the layout version of the artifact, 0 for proofs written before proofs were versioned
+    #[serde(default)]
+    pub version: u32,
+    /// hex encoded keccak256 hash of the settings of the circuit that was proven
+    #[serde(default)]
+    pub settings_hash: Option<String>,
     /// the protocol
     pub protocol: Option<PlonkProtocol<C>>,
     /// public instances of the snark
@@ -241,6 +408,9 @@ where
         dict.set_item("proof", hex_proof).unwrap();
         dict.set_item("transcript_type", self.transcript_type)
             .unwrap();
+        dict.set_item("version", self.version).unwrap();
+        dict.set_item("settings_hash", self.settings_hash.clone())
+            .unwrap();
         dict.to_object(py)
     }
 }
@@ -262,6 +432,8 @@ where
         split: Option<ProofSplitCommit>,
     ) -> Self {
         Self {
+            version: PROOF_VERSION,
+            settings_hash: None,
             protocol: Some(protocol),
             instances,
             proof,
@@ -270,15 +442,38 @@ where
         }
     }
 
-    /// Saves the Proof to a specified `proof_path`.
+    /// Serializes the proof with bincode, behind a header recording the artifact version.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut bytes = PROOF_MAGIC.to_vec();
+        bytes.extend(self.version.to_le_bytes());
+        bytes.extend(bincode::serialize(&self)?);
+        Ok(bytes)
+    }
+
+    /// Deserializes a proof written by [Snark::to_bytes], or the bare bincode of a proof that
+    /// predates versioning.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let Some(rest) = bytes.strip_prefix(PROOF_MAGIC.as_slice()) else {
+            let legacy: SnarkV0<F, C> = bincode::deserialize(bytes)?;
+            return Ok(legacy.into());
+        };
+        if rest.len() < 4 {
+            return Err("proof header is truncated".into());
+        }
+        let (version, body) = rest.split_at(4);
+        check_proof_version(u32::from_le_bytes(version.try_into()?))?;
+        bincode::deserialize(body).map_err(|e| e.into())
+    }
+
+    /// Saves the Proof to a specified `proof_path` (or stdout if the path is `-`).
     pub fn save(&self, proof_path: &PathBuf) -> Result<(), Box<dyn Error>> {
-        let file = std::fs::File::create(proof_path)?;
-        let mut writer = BufWriter::new(file);
+        let mut writer = crate::stdio::writer(proof_path)?;
         serde_json::to_writer(&mut writer, &self)?;
+        writer.flush()?;
         Ok(())
     }
 
-    /// Load a json serialized proof from the provided path.
+    /// Load a json serialized proof from the provided path (or stdin if the path is `-`).
     pub fn load<Scheme: CommitmentScheme<Curve = C, Scalar = F>>(
         proof_path: &PathBuf,
     ) -> Result<Self, Box<dyn Error>>
@@ -286,8 +481,99 @@ where
         <C as CurveAffine>::ScalarExt: FromUniformBytes<64>,
     {
         trace!("reading proof");
-        let data = std::fs::read_to_string(proof_path)?;
-        serde_json::from_str(&data).map_err(|e| e.into())
+        let data = crate::stdio::read_to_string(proof_path)?;
+        let snark: Self = serde_json::from_str(&data)?;
+        check_proof_version(snark.version)?;
+        Ok(snark)
+    }
+
+    /// Saves the Proof to a specified `proof_path` using the given [ProofEncoding].
+    pub fn save_with_encoding(
+        &self,
+        proof_path: &PathBuf,
+        encoding: ProofEncoding,
+    ) -> Result<(), Box<dyn Error>> {
+        match encoding {
+            ProofEncoding::Json => self.save(proof_path),
+            ProofEncoding::Bin => {
+                let mut writer = crate::stdio::writer(proof_path)?;
+                writer.write_all(&self.to_bytes()?)?;
+                writer.flush()?;
+                Ok(())
+            }
+            ProofEncoding::Hex => {
+                crate::stdio::write(proof_path, hex::encode(self.to_bytes()?))?;
+                Ok(())
+            }
+            ProofEncoding::Base64 => {
+                use base64::Engine;
+                let bytes = self.to_bytes()?;
+                crate::stdio::write(
+                    proof_path,
+                    base64::engine::general_purpose::STANDARD.encode(bytes),
+                )?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Load a proof from the provided path, decoding it with the given [ProofEncoding].
+    pub fn load_with_encoding<Scheme: CommitmentScheme<Curve = C, Scalar = F>>(
+        proof_path: &PathBuf,
+        encoding: ProofEncoding,
+    ) -> Result<Self, Box<dyn Error>>
+    where
+        <C as CurveAffine>::ScalarExt: FromUniformBytes<64>,
+    {
+        let bytes = match encoding {
+            ProofEncoding::Json => return Self::load::<Scheme>(proof_path),
+            ProofEncoding::Bin => crate::stdio::read(proof_path)?,
+            ProofEncoding::Hex => {
+                let data = crate::stdio::read_to_string(proof_path)?;
+                let data = data.trim();
+                hex::decode(data.strip_prefix("0x").unwrap_or(data))?
+            }
+            ProofEncoding::Base64 => {
+                use base64::Engine;
+                let data = crate::stdio::read_to_string(proof_path)?;
+                base64::engine::general_purpose::STANDARD.decode(data.trim())?
+            }
+        };
+        trace!("reading {} encoded proof", encoding);
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// The layout of proofs written before proofs were versioned, kept so their binary encodings
+/// still load.
+#[derive(Deserialize)]
+struct SnarkV0<F: PrimeField + SerdeObject, C: CurveAffine>
+where
+    C::Scalar: Serialize + DeserializeOwned,
+    C::ScalarExt: Serialize + DeserializeOwned,
+{
+    protocol: Option<PlonkProtocol<C>>,
+    instances: Vec<Vec<F>>,
+    proof: Vec<u8>,
+    transcript_type: TranscriptType,
+    split: Option<ProofSplitCommit>,
+}
+
+impl<F: PrimeField + SerdeObject, C: CurveAffine> From<SnarkV0<F, C>> for Snark<F, C>
+where
+    C::Scalar: Serialize + DeserializeOwned,
+    C::ScalarExt: Serialize + DeserializeOwned,
+{
+    fn from(snark: SnarkV0<F, C>) -> Self {
+        Self {
+            version: 0,
+            settings_hash: None,
+            protocol: snark.protocol,
+            instances: snark.instances,
+            proof: snark.proof,
+            transcript_type: snark.transcript_type,
+            split: snark.split,
+        }
     }
 }
 
@@ -399,18 +685,27 @@ where
     //	Real proof
     let empty_circuit = <C as Circuit<F>>::without_witnesses(circuit);
 
+    #[cfg(not(target_arch = "wasm32"))]
+    let pb = crate::execute::init_spinner();
+
     // Initialize verifying key
     let now = Instant::now();
     trace!("preparing VK");
-    let vk = keygen_vk(params, &empty_circuit)?;
+    #[cfg(not(target_arch = "wasm32"))]
+    pb.set_message("generating verifying key (1/2)");
+    let vk = tracing::info_span!("keygen_vk").in_scope(|| keygen_vk(params, &empty_circuit))?;
     let elapsed = now.elapsed();
     info!("VK took {}.{}", elapsed.as_secs(), elapsed.subsec_millis());
 
     // Initialize the proving key
     let now = Instant::now();
-    let pk = keygen_pk(params, vk, &empty_circuit)?;
+    #[cfg(not(target_arch = "wasm32"))]
+    pb.set_message("generating proving key (2/2)");
+    let pk = tracing::info_span!("keygen_pk").in_scope(|| keygen_pk(params, vk, &empty_circuit))?;
     let elapsed = now.elapsed();
     info!("PK took {}.{}", elapsed.as_secs(), elapsed.subsec_millis());
+    #[cfg(not(target_arch = "wasm32"))]
+    pb.finish_and_clear();
     Ok(pk)
 }
 
@@ -474,9 +769,14 @@ where
     );
 
     info!("proof started...");
+    let _span = tracing::info_span!("prove").entered();
     // not wasm32 unknown
     let now = Instant::now();
 
+    #[cfg(not(target_arch = "wasm32"))]
+    let pb = crate::execute::init_spinner();
+    #[cfg(not(target_arch = "wasm32"))]
+    pb.set_message("creating proof");
     create_proof::<Scheme, P, _, _, TW, _>(
         params,
         pk,
@@ -487,6 +787,11 @@ where
     )?;
     let proof = transcript.finalize();
 
+    #[cfg(not(target_arch = "wasm32"))]
+    if check_mode == CheckMode::SAFE {
+        pb.set_message("verifying generated proof");
+    }
+
     let checkable_pf = Snark::new(protocol, instances, proof, transcript_type, split);
 
     // sanity check that the generated proof is valid
@@ -500,6 +805,8 @@ where
             strategy,
         )?;
     }
+    #[cfg(not(target_arch = "wasm32"))]
+    pb.finish_and_clear();
     let elapsed = now.elapsed();
     info!(
         "proof took {}.{}",
@@ -566,6 +873,12 @@ pub fn swap_proof_commitments_kzg(
             _,
             PoseidonTranscript<NativeLoader, _>,
         >(snark, commitments)?,
+        TranscriptType::Blake2b => swap_proof_commitments::<
+            Fr,
+            KZGCommitmentScheme<Bn256>,
+            _,
+            Blake2bWrite<_, G1Affine, Challenge255<_>>,
+        >(snark, commitments)?,
     };
     Ok(proof)
 }
@@ -767,9 +1080,221 @@ pub fn create_proof_circuit_kzg<
             split,
         )
         .map_err(Box::<dyn Error>::from),
+        TranscriptType::Blake2b => create_proof_circuit::<
+            KZGCommitmentScheme<_>,
+            Fr,
+            _,
+            ProverSHPLONK<_>,
+            VerifierSHPLONK<_>,
+            _,
+            _,
+            Blake2bWrite<_, G1Affine, Challenge255<_>>,
+            Blake2bRead<_, G1Affine, Challenge255<_>>,
+        >(
+            circuit,
+            public_inputs,
+            params,
+            pk,
+            strategy,
+            check_mode,
+            transcript,
+            split,
+        )
+        .map_err(Box::<dyn Error>::from),
+    }
+}
+
+/// Creates a kzg proof opened with GWC rather than SHPLONK. The aggregation circuit only
+/// accumulates SHPLONK openings, so these proofs are checked with a single strategy and can't
+/// use the [TranscriptType::Poseidon] transcript.
+pub fn create_proof_circuit_kzg_gwc<C: Circuit<Fr>>(
+    circuit: C,
+    params: &ParamsKZG<Bn256>,
+    public_inputs: Option<Vec<Fr>>,
+    pk: &ProvingKey<G1Affine>,
+    transcript: TranscriptType,
+    check_mode: CheckMode,
+    split: Option<ProofSplitCommit>,
+) -> Result<Snark<Fr, G1Affine>, Box<dyn Error>> {
+    let public_inputs = match public_inputs {
+        Some(public_inputs) if !public_inputs.is_empty() => vec![public_inputs],
+        Some(_) => vec![vec![]],
+        None => vec![],
+    };
+    let strategy = KZGSingleStrategy::new(params);
+
+    match transcript {
+        TranscriptType::EVM => create_proof_circuit::<
+            KZGCommitmentScheme<_>,
+            Fr,
+            _,
+            ProverGWC<_>,
+            VerifierGWC<_>,
+            _,
+            _,
+            EvmTranscript<G1Affine, _, _, _>,
+            EvmTranscript<G1Affine, _, _, _>,
+        >(
+            circuit,
+            public_inputs,
+            params,
+            pk,
+            strategy,
+            check_mode,
+            transcript,
+            split,
+        ),
+        TranscriptType::Blake2b => create_proof_circuit::<
+            KZGCommitmentScheme<_>,
+            Fr,
+            _,
+            ProverGWC<_>,
+            VerifierGWC<_>,
+            _,
+            _,
+            Blake2bWrite<_, G1Affine, Challenge255<_>>,
+            Blake2bRead<_, G1Affine, Challenge255<_>>,
+        >(
+            circuit,
+            public_inputs,
+            params,
+            pk,
+            strategy,
+            check_mode,
+            transcript,
+            split,
+        ),
+        TranscriptType::Poseidon => {
+            Err("gwc proofs can't be aggregated, so can't use the poseidon transcript".into())
+        }
+    }
+}
+
+/// Verifies a proof made with [create_proof_circuit_kzg_gwc]
+pub(crate) fn verify_proof_circuit_kzg_gwc(
+    params: &ParamsKZG<Bn256>,
+    proof: Snark<Fr, G1Affine>,
+    vk: &VerifyingKey<G1Affine>,
+) -> Result<(), Box<dyn Error>> {
+    let strategy = KZGSingleStrategy::new(params);
+    match proof.transcript_type {
+        TranscriptType::EVM => verify_proof_circuit::<
+            Fr,
+            VerifierGWC<'_, Bn256>,
+            _,
+            _,
+            _,
+            EvmTranscript<G1Affine, _, _, _>,
+        >(&proof, params, vk, strategy)?,
+        TranscriptType::Blake2b => verify_proof_circuit::<
+            Fr,
+            VerifierGWC<'_, Bn256>,
+            _,
+            _,
+            _,
+            Blake2bRead<_, G1Affine, Challenge255<_>>,
+        >(&proof, params, vk, strategy)?,
+        TranscriptType::Poseidon => {
+            return Err("gwc proofs can't use the poseidon transcript".into())
+        }
+    };
+    Ok(())
+}
+
+/// Creates a proof with [IPACommitmentScheme] commitments. IPA proofs can't be aggregated, so they
+/// are checked with a single strategy and can't use the [TranscriptType::Poseidon] transcript.
+pub fn create_proof_circuit_ipa<C: Circuit<Fr>>(
+    circuit: C,
+    params: &ParamsIPA<G1Affine>,
+    public_inputs: Option<Vec<Fr>>,
+    pk: &ProvingKey<G1Affine>,
+    transcript: TranscriptType,
+    check_mode: CheckMode,
+    split: Option<ProofSplitCommit>,
+) -> Result<Snark<Fr, G1Affine>, Box<dyn Error>> {
+    let public_inputs = match public_inputs {
+        Some(public_inputs) if !public_inputs.is_empty() => vec![public_inputs],
+        Some(_) => vec![vec![]],
+        None => vec![],
+    };
+    let strategy = IPASingleStrategy::new(params);
+
+    match transcript {
+        TranscriptType::EVM => create_proof_circuit::<
+            IPACommitmentScheme<_>,
+            Fr,
+            _,
+            ProverIPA<_>,
+            VerifierIPA<_>,
+            _,
+            _,
+            EvmTranscript<G1Affine, _, _, _>,
+            EvmTranscript<G1Affine, _, _, _>,
+        >(
+            circuit,
+            public_inputs,
+            params,
+            pk,
+            strategy,
+            check_mode,
+            transcript,
+            split,
+        ),
+        TranscriptType::Blake2b => create_proof_circuit::<
+            IPACommitmentScheme<_>,
+            Fr,
+            _,
+            ProverIPA<_>,
+            VerifierIPA<_>,
+            _,
+            _,
+            Blake2bWrite<_, G1Affine, Challenge255<_>>,
+            Blake2bRead<_, G1Affine, Challenge255<_>>,
+        >(
+            circuit,
+            public_inputs,
+            params,
+            pk,
+            strategy,
+            check_mode,
+            transcript,
+            split,
+        ),
+        TranscriptType::Poseidon => Err("ipa proofs can't use the poseidon transcript".into()),
     }
 }
 
+/// Verifies a proof made with [create_proof_circuit_ipa]
+pub(crate) fn verify_proof_circuit_ipa(
+    params: &ParamsIPA<G1Affine>,
+    proof: Snark<Fr, G1Affine>,
+    vk: &VerifyingKey<G1Affine>,
+) -> Result<(), Box<dyn Error>> {
+    let strategy = IPASingleStrategy::new(params);
+    match proof.transcript_type {
+        TranscriptType::EVM => verify_proof_circuit::<
+            Fr,
+            VerifierIPA<'_, G1Affine>,
+            _,
+            _,
+            _,
+            EvmTranscript<G1Affine, _, _, _>,
+        >(&proof, params, vk, strategy)?,
+        TranscriptType::Blake2b => verify_proof_circuit::<
+            Fr,
+            VerifierIPA<'_, G1Affine>,
+            _,
+            _,
+            _,
+            Blake2bRead<_, G1Affine, Challenge255<_>>,
+        >(&proof, params, vk, strategy)?,
+        TranscriptType::Poseidon => {
+            return Err("ipa proofs can't use the poseidon transcript".into())
+        }
+    };
+    Ok(())
+}
+
 #[allow(unused)]
 /// helper function
 pub(crate) fn verify_proof_circuit_kzg<
@@ -798,6 +1323,14 @@ pub(crate) fn verify_proof_circuit_kzg<
             _,
             PoseidonTranscript<NativeLoader, _>,
         >(&proof, params, vk, strategy),
+        TranscriptType::Blake2b => verify_proof_circuit::<
+            Fr,
+            VerifierSHPLONK<'_, Bn256>,
+            _,
+            _,
+            _,
+            Blake2bRead<_, G1Affine, Challenge255<_>>,
+        >(&proof, params, vk, strategy),
     }
 }
 
@@ -856,6 +1389,8 @@ mod tests {
             transcript_type: TranscriptType::EVM,
             protocol: None,
             split: None,
+            version: PROOF_VERSION,
+            settings_hash: None,
         };
 
         snark
@@ -869,4 +1404,60 @@ mod tests {
         assert_eq!(snark.proof, snark2.proof);
         assert_eq!(snark.transcript_type, snark2.transcript_type);
     }
+
+    #[test]
+    fn test_snark_encoding_roundtrip() {
+        let snark = Snark::<Fr, G1Affine> {
+            proof: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            instances: vec![vec![Fr::from(1)], vec![Fr::from(2)]],
+            transcript_type: TranscriptType::EVM,
+            protocol: None,
+            split: None,
+            version: PROOF_VERSION,
+            settings_hash: None,
+        };
+
+        for encoding in [
+            ProofEncoding::Bin,
+            ProofEncoding::Hex,
+            ProofEncoding::Base64,
+            ProofEncoding::Json,
+        ] {
+            let path: PathBuf = format!("test_snark_encoding_roundtrip.{}", encoding).into();
+            snark.save_with_encoding(&path, encoding).unwrap();
+            let snark2 = Snark::<Fr, G1Affine>::load_with_encoding::<KZGCommitmentScheme<Bn256>>(
+                &path, encoding,
+            )
+            .unwrap();
+            assert_eq!(snark.instances, snark2.instances);
+            assert_eq!(snark.proof, snark2.proof);
+            assert_eq!(snark.transcript_type, snark2.transcript_type);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_snark_version_compat() {
+        let legacy = bincode::serialize(&(
+            None::<PlonkProtocol<G1Affine>>,
+            vec![vec![Fr::from(1)]],
+            vec![1u8, 2, 3],
+            TranscriptType::EVM,
+            None::<ProofSplitCommit>,
+        ))
+        .unwrap();
+        let snark = Snark::<Fr, G1Affine>::from_bytes(&legacy).unwrap();
+        assert_eq!(snark.version, 0);
+        assert_eq!(snark.proof, vec![1, 2, 3]);
+
+        let mut newer = Snark::<Fr, G1Affine> {
+            version: PROOF_VERSION,
+            ..snark
+        }
+        .to_bytes()
+        .unwrap();
+        newer[PROOF_MAGIC.len()..PROOF_MAGIC.len() + 4]
+            .copy_from_slice(&(PROOF_VERSION + 1).to_le_bytes());
+        assert!(Snark::<Fr, G1Affine>::from_bytes(&newer).is_err());
+    }
 }
@@ -11,12 +11,12 @@ use crate::fieldutils::{felt_to_i128, i128_to_felt};
 use crate::graph::modules::POSEIDON_LEN_GRAPH;
 use crate::graph::TestDataSource;
 use crate::graph::{
-    quantize_float, scale_to_multiplier, GraphCircuit, GraphSettings, Model, Visibility,
+    quantize_float, scale_to_multiplier, GraphCircuit, GraphSettings, Model, RoundingMode,
+    Visibility,
 };
 use crate::pfsys::evm::aggregation::AggregationCircuit;
 use crate::pfsys::{
-    load_pk, load_vk, save_params, save_vk, srs::gen_srs as ezkl_gen_srs, srs::load_srs, ProofType,
-    Snark, TranscriptType,
+    load_pk, load_vk, save_vk, srs::load_srs, ProofEncoding, ProofType, Snark, TranscriptType,
 };
 use crate::RunArgs;
 use ethers::types::H160;
@@ -268,6 +268,14 @@ struct PyRunArgs {
     #[pyo3(get, set)]
     pub scale_rebase_multiplier: u32,
     #[pyo3(get, set)]
+    pub rebase_ops: Vec<String>,
+    #[pyo3(get, set)]
+    pub rounding: RoundingMode,
+    #[pyo3(get, set)]
+    pub commitment: crate::pfsys::Commitments,
+    #[pyo3(get, set)]
+    pub multiopen: crate::pfsys::MultiOpen,
+    #[pyo3(get, set)]
     pub lookup_range: (i128, i128),
     #[pyo3(get, set)]
     pub logrows: u32,
@@ -281,6 +289,10 @@ struct PyRunArgs {
     pub param_visibility: Visibility,
     #[pyo3(get, set)]
     pub variables: Vec<(String, usize)>,
+    #[pyo3(get, set)]
+    pub batch_size: Option<usize>,
+    #[pyo3(get, set)]
+    pub scale_overrides: Vec<(String, crate::Scale)>,
 }
 
 /// default instantiation of PyRunArgs
@@ -301,12 +313,18 @@ impl From<PyRunArgs> for RunArgs {
             param_scale: py_run_args.param_scale,
             num_inner_cols: py_run_args.num_inner_cols,
             scale_rebase_multiplier: py_run_args.scale_rebase_multiplier,
+            rebase_ops: py_run_args.rebase_ops,
+            rounding: py_run_args.rounding,
+            commitment: py_run_args.commitment,
+            multiopen: py_run_args.multiopen,
             lookup_range: py_run_args.lookup_range,
             logrows: py_run_args.logrows,
             input_visibility: py_run_args.input_visibility,
             output_visibility: py_run_args.output_visibility,
             param_visibility: py_run_args.param_visibility,
             variables: py_run_args.variables,
+            batch_size: py_run_args.batch_size,
+            scale_overrides: py_run_args.scale_overrides,
         }
     }
 }
@@ -319,12 +337,18 @@ impl Into<PyRunArgs> for RunArgs {
             param_scale: self.param_scale,
             num_inner_cols: self.num_inner_cols,
             scale_rebase_multiplier: self.scale_rebase_multiplier,
+            rebase_ops: self.rebase_ops,
+            rounding: self.rounding,
+            commitment: self.commitment,
+            multiopen: self.multiopen,
             lookup_range: self.lookup_range,
             logrows: self.logrows,
             input_visibility: self.input_visibility,
             output_visibility: self.output_visibility,
             param_visibility: self.param_visibility,
             variables: self.variables,
+            batch_size: self.batch_size,
+            scale_overrides: self.scale_overrides,
         }
     }
 }
@@ -617,7 +641,7 @@ fn table(model: PathBuf, py_run_args: Option<PyRunArgs>) -> PyResult<String> {
 
     match result {
         Ok(m) => Ok(m.table_nodes()),
-        Err(_) => Err(PyIOError::new_err("Failed to import model")),
+        Err(e) => Err(PyIOError::new_err(e.to_json().to_string())),
     }
 }
 
@@ -625,11 +649,19 @@ fn table(model: PathBuf, py_run_args: Option<PyRunArgs>) -> PyResult<String> {
 #[pyfunction(signature = (
     srs_path,
     logrows,
+    commitment = None,
 ))]
-fn gen_srs(srs_path: PathBuf, logrows: usize) -> PyResult<()> {
-    let params = ezkl_gen_srs::<KZGCommitmentScheme<Bn256>>(logrows as u32);
-    save_params::<KZGCommitmentScheme<Bn256>>(&srs_path, &params)?;
-    Ok(())
+fn gen_srs(
+    srs_path: PathBuf,
+    logrows: usize,
+    commitment: Option<crate::pfsys::Commitments>,
+) -> PyResult<()> {
+    crate::execute::gen_srs_cmd(srs_path, logrows as u32, commitment.unwrap_or_default()).map_err(
+        |e| {
+            let err_str = format!("Failed to generate srs: {}", e);
+            PyRuntimeError::new_err(err_str)
+        },
+    )
 }
 
 /// gets a public srs
@@ -663,18 +695,23 @@ fn get_srs(
     model=PathBuf::from(DEFAULT_MODEL),
     output=PathBuf::from(DEFAULT_SETTINGS),
     py_run_args = None,
+    input_bound = None,
+    target_logrows = None,
 ))]
 fn gen_settings(
     model: PathBuf,
     output: PathBuf,
     py_run_args: Option<PyRunArgs>,
+    input_bound: Option<f64>,
+    target_logrows: Option<u32>,
 ) -> Result<bool, PyErr> {
     let run_args: RunArgs = py_run_args.unwrap_or_else(PyRunArgs::new).into();
 
-    crate::execute::gen_circuit_settings(model, output, run_args).map_err(|e| {
-        let err_str = format!("Failed to generate settings: {}", e);
-        PyRuntimeError::new_err(err_str)
-    })?;
+    crate::execute::gen_circuit_settings(model, output, run_args, input_bound, target_logrows)
+        .map_err(|e| {
+            let err_str = format!("Failed to generate settings: {}", e);
+            PyRuntimeError::new_err(err_str)
+        })?;
 
     Ok(true)
 }
@@ -711,6 +748,7 @@ fn calibrate_settings(
     output=None,
     vk_path=None,
     srs_path=None,
+    key_path=None,
 ))]
 fn gen_witness(
     data: PathBuf,
@@ -718,11 +756,12 @@ fn gen_witness(
     output: Option<PathBuf>,
     vk_path: Option<PathBuf>,
     srs_path: Option<PathBuf>,
+    key_path: Option<PathBuf>,
 ) -> PyResult<PyObject> {
     let output = Runtime::new()
         .unwrap()
         .block_on(crate::execute::gen_witness(
-            model, data, output, vk_path, srs_path,
+            model, data, output, vk_path, srs_path, key_path, None,
         ))
         .map_err(|e| {
             let err_str = format!("Failed to run generate witness: {}", e);
@@ -769,7 +808,9 @@ fn mock_aggregate(
     vk_path=PathBuf::from(DEFAULT_VK),
     pk_path=PathBuf::from(DEFAULT_PK),
     srs_path=None,
-    witness_path = None
+    witness_path = None,
+    reuse_keys = false,
+    key_cache = false
 ))]
 fn setup(
     model: PathBuf,
@@ -777,8 +818,19 @@ fn setup(
     pk_path: PathBuf,
     srs_path: Option<PathBuf>,
     witness_path: Option<PathBuf>,
+    reuse_keys: bool,
+    key_cache: bool,
 ) -> Result<bool, PyErr> {
-    crate::execute::setup(model, srs_path, vk_path, pk_path, witness_path).map_err(|e| {
+    crate::execute::setup(
+        model,
+        srs_path,
+        vk_path,
+        pk_path,
+        witness_path,
+        reuse_keys,
+        key_cache,
+    )
+    .map_err(|e| {
         let err_str = format!("Failed to run setup: {}", e);
         PyRuntimeError::new_err(err_str)
     })?;
@@ -794,6 +846,7 @@ fn setup(
     proof_path=None,
     proof_type=ProofType::default(),
     srs_path=None,
+    proof_encoding=ProofEncoding::default(),
 ))]
 fn prove(
     witness: PathBuf,
@@ -802,6 +855,7 @@ fn prove(
     proof_path: Option<PathBuf>,
     proof_type: ProofType,
     srs_path: Option<PathBuf>,
+    proof_encoding: ProofEncoding,
 ) -> PyResult<PyObject> {
     let snark = crate::execute::prove(
         witness,
@@ -811,6 +865,7 @@ fn prove(
         srs_path,
         proof_type,
         CheckMode::UNSAFE,
+        proof_encoding,
     )
     .map_err(|e| {
         let err_str = format!("Failed to run prove: {}", e);
@@ -826,14 +881,29 @@ fn prove(
     settings_path=PathBuf::from(DEFAULT_SETTINGS),
     vk_path=PathBuf::from(DEFAULT_VK),
     srs_path=None,
+    proof_encoding=ProofEncoding::default(),
+    input_commitment=None,
+    params_commitment=None,
 ))]
 fn verify(
     proof_path: PathBuf,
     settings_path: PathBuf,
     vk_path: PathBuf,
     srs_path: Option<PathBuf>,
+    proof_encoding: ProofEncoding,
+    input_commitment: Option<PathBuf>,
+    params_commitment: Option<PathBuf>,
 ) -> Result<bool, PyErr> {
-    crate::execute::verify(proof_path, settings_path, vk_path, srs_path).map_err(|e| {
+    crate::execute::verify(
+        proof_path,
+        settings_path,
+        vk_path,
+        srs_path,
+        proof_encoding,
+        input_commitment,
+        params_commitment,
+    )
+    .map_err(|e| {
         let err_str = format!("Failed to run verify: {}", e);
         PyRuntimeError::new_err(err_str)
     })?;
@@ -959,6 +1029,8 @@ fn verify_aggr(
     sol_code_path=PathBuf::from(DEFAULT_SOL_CODE),
     abi_path=PathBuf::from(DEFAULT_VERIFIER_ABI),
     srs_path=None,
+    vk_sol_code_path=None,
+    yul=false,
 ))]
 fn create_evm_verifier(
     vk_path: PathBuf,
@@ -966,12 +1038,22 @@ fn create_evm_verifier(
     sol_code_path: PathBuf,
     abi_path: PathBuf,
     srs_path: Option<PathBuf>,
+    vk_sol_code_path: Option<PathBuf>,
+    yul: bool,
 ) -> Result<bool, PyErr> {
-    crate::execute::create_evm_verifier(vk_path, srs_path, settings_path, sol_code_path, abi_path)
-        .map_err(|e| {
-            let err_str = format!("Failed to run create_evm_verifier: {}", e);
-            PyRuntimeError::new_err(err_str)
-        })?;
+    crate::execute::create_evm_verifier(
+        vk_path,
+        srs_path,
+        settings_path,
+        sol_code_path,
+        abi_path,
+        vk_sol_code_path,
+        yul,
+    )
+    .map_err(|e| {
+        let err_str = format!("Failed to run create_evm_verifier: {}", e);
+        PyRuntimeError::new_err(err_str)
+    })?;
 
     Ok(true)
 }
@@ -1074,6 +1156,38 @@ fn deploy_evm(
     Ok(true)
 }
 
+/// deploys a verifying key contract rendered separately from its verifier
+#[pyfunction(signature = (
+    addr_path,
+    sol_code_path=PathBuf::from(DEFAULT_VK_SOL_CODE),
+    rpc_url=None,
+    optimizer_runs=DEFAULT_OPTIMIZER_RUNS.parse().unwrap(),
+    private_key=None
+))]
+fn deploy_vk_evm(
+    addr_path: PathBuf,
+    sol_code_path: PathBuf,
+    rpc_url: Option<String>,
+    optimizer_runs: usize,
+    private_key: Option<String>,
+) -> Result<bool, PyErr> {
+    Runtime::new()
+        .unwrap()
+        .block_on(crate::execute::deploy_evm_vk(
+            sol_code_path,
+            rpc_url,
+            addr_path,
+            optimizer_runs,
+            private_key,
+        ))
+        .map_err(|e| {
+            let err_str = format!("Failed to run deploy_vk_evm: {}", e);
+            PyRuntimeError::new_err(err_str)
+        })?;
+
+    Ok(true)
+}
+
 #[pyfunction(signature = (
     addr_path,
     input_data,
@@ -1116,12 +1230,16 @@ fn deploy_da_evm(
     proof_path=PathBuf::from(DEFAULT_PROOF),
     rpc_url=None,
     addr_da = None,
+    addr_vk = None,
+    yul = false,
 ))]
 fn verify_evm(
     addr_verifier: &str,
     proof_path: PathBuf,
     rpc_url: Option<String>,
     addr_da: Option<&str>,
+    addr_vk: Option<&str>,
+    yul: bool,
 ) -> Result<bool, PyErr> {
     let addr_verifier = H160::from_str(addr_verifier).map_err(|e| {
         let err_str = format!("address is invalid: {}", e);
@@ -1136,6 +1254,15 @@ fn verify_evm(
     } else {
         None
     };
+    let addr_vk = if let Some(addr_vk) = addr_vk {
+        let addr_vk = H160::from_str(addr_vk).map_err(|e| {
+            let err_str = format!("address is invalid: {}", e);
+            PyRuntimeError::new_err(err_str)
+        })?;
+        Some(addr_vk)
+    } else {
+        None
+    };
 
     Runtime::new()
         .unwrap()
@@ -1144,6 +1271,8 @@ fn verify_evm(
             addr_verifier,
             rpc_url,
             addr_da,
+            addr_vk,
+            yul,
         ))
         .map_err(|e| {
             let err_str = format!("Failed to run verify_evm: {}", e);
@@ -1153,6 +1282,87 @@ fn verify_evm(
     Ok(true)
 }
 
+/// verifies an evm compatible proof on-chain by submitting it to the verifier in a transaction
+#[pyfunction(signature = (
+    addr_verifier,
+    rpc_url,
+    private_key,
+    proof_path=PathBuf::from(DEFAULT_PROOF),
+    addr_vk=None,
+    yul=false,
+))]
+fn verify_evm_onchain(
+    addr_verifier: &str,
+    rpc_url: String,
+    private_key: String,
+    proof_path: PathBuf,
+    addr_vk: Option<&str>,
+    yul: bool,
+) -> Result<bool, PyErr> {
+    let addr_verifier = H160::from_str(addr_verifier).map_err(|e| {
+        let err_str = format!("address is invalid: {}", e);
+        PyRuntimeError::new_err(err_str)
+    })?;
+    let addr_vk = if let Some(addr_vk) = addr_vk {
+        let addr_vk = H160::from_str(addr_vk).map_err(|e| {
+            let err_str = format!("address is invalid: {}", e);
+            PyRuntimeError::new_err(err_str)
+        })?;
+        Some(addr_vk)
+    } else {
+        None
+    };
+
+    Runtime::new()
+        .unwrap()
+        .block_on(crate::execute::verify_evm_onchain(
+            proof_path,
+            addr_verifier,
+            rpc_url,
+            private_key,
+            addr_vk,
+            yul,
+        ))
+        .map_err(|e| {
+            let err_str = format!("Failed to run verify_evm_onchain: {}", e);
+            PyRuntimeError::new_err(err_str)
+        })?;
+
+    Ok(true)
+}
+
+/// deploys a verifier to a throwaway local chain and writes the gas it takes to deploy it and verify a proof with it
+#[pyfunction(signature = (
+    proof_path=PathBuf::from(DEFAULT_PROOF),
+    sol_code_path=PathBuf::from(DEFAULT_SOL_CODE),
+    vk_sol_code_path=None,
+    optimizer_runs=DEFAULT_OPTIMIZER_RUNS.parse().unwrap(),
+    output=PathBuf::from(DEFAULT_GAS_REPORT),
+))]
+fn estimate_evm_gas(
+    proof_path: PathBuf,
+    sol_code_path: PathBuf,
+    vk_sol_code_path: Option<PathBuf>,
+    optimizer_runs: usize,
+    output: PathBuf,
+) -> Result<bool, PyErr> {
+    Runtime::new()
+        .unwrap()
+        .block_on(crate::execute::estimate_evm_gas(
+            proof_path,
+            sol_code_path,
+            vk_sol_code_path,
+            optimizer_runs,
+            output,
+        ))
+        .map_err(|e| {
+            let err_str = format!("Failed to run estimate_evm_gas: {}", e);
+            PyRuntimeError::new_err(err_str)
+        })?;
+
+    Ok(true)
+}
+
 /// creates an evm compatible aggregate verifier, you will need solc installed in your environment to run this
 #[pyfunction(signature = (
     aggregation_settings=vec![PathBuf::from(DEFAULT_PROOF)],
@@ -1185,6 +1395,111 @@ fn create_evm_verifier_aggr(
     Ok(true)
 }
 
+/// verifies each proof and checks that the hashed outputs of each proof are the hashed inputs of
+/// the next
+#[pyfunction(signature = (
+    proof_paths,
+    settings_paths,
+    vk_paths,
+    srs_path=None,
+))]
+fn link_proofs(
+    proof_paths: Vec<PathBuf>,
+    settings_paths: Vec<PathBuf>,
+    vk_paths: Vec<PathBuf>,
+    srs_path: Option<PathBuf>,
+) -> Result<bool, PyErr> {
+    crate::execute::link_proofs(proof_paths, settings_paths, vk_paths, srs_path).map_err(|e| {
+        let err_str = format!("Failed to run link_proofs: {}", e);
+        PyRuntimeError::new_err(err_str)
+    })?;
+
+    Ok(true)
+}
+
+/// splits an onnx model into a chain of smaller models
+#[pyfunction(signature = (
+    model,
+    output_dir=PathBuf::from(DEFAULT_SPLIT_DIR),
+    cut_after=vec![],
+    num_pieces=None,
+))]
+fn split_model(
+    model: PathBuf,
+    output_dir: PathBuf,
+    cut_after: Vec<String>,
+    num_pieces: Option<usize>,
+) -> Result<bool, PyErr> {
+    crate::execute::split_model(model, cut_after, num_pieces, output_dir).map_err(|e| {
+        let err_str = format!("Failed to run split_model: {}", e);
+        PyRuntimeError::new_err(err_str)
+    })?;
+
+    Ok(true)
+}
+
+/// times each phase of the proving pipeline on a model and writes a json report
+#[pyfunction(signature = (
+    model=PathBuf::from(DEFAULT_MODEL),
+    data=PathBuf::from(DEFAULT_DATA),
+    output=PathBuf::from(DEFAULT_BENCH_REPORT),
+    settings_path=None,
+    srs_path=None,
+    py_run_args=None,
+))]
+fn bench(
+    model: PathBuf,
+    data: PathBuf,
+    output: PathBuf,
+    settings_path: Option<PathBuf>,
+    srs_path: Option<PathBuf>,
+    py_run_args: Option<PyRunArgs>,
+) -> Result<bool, PyErr> {
+    let run_args: RunArgs = py_run_args.unwrap_or_else(PyRunArgs::new).into();
+    Runtime::new()
+        .unwrap()
+        .block_on(crate::execute::bench(
+            model,
+            data,
+            settings_path,
+            srs_path,
+            output,
+            run_args,
+        ))
+        .map_err(|e| {
+            let err_str = format!("Failed to run bench: {}", e);
+            PyRuntimeError::new_err(err_str)
+        })?;
+
+    Ok(true)
+}
+
+/// configures the circuit for a model without proving and writes its size and projected proving cost
+#[pyfunction(signature = (
+    model=PathBuf::from(DEFAULT_MODEL),
+    output=PathBuf::from(DEFAULT_ESTIMATE_REPORT),
+    settings_path=None,
+    bench_report=None,
+    py_run_args=None,
+))]
+fn estimate(
+    model: PathBuf,
+    output: PathBuf,
+    settings_path: Option<PathBuf>,
+    bench_report: Option<PathBuf>,
+    py_run_args: Option<PyRunArgs>,
+) -> Result<bool, PyErr> {
+    let run_args: RunArgs = py_run_args.unwrap_or_else(PyRunArgs::new).into();
+    crate::execute::estimate(model, settings_path, bench_report, output, run_args).map_err(
+        |e| {
+            let err_str = format!("Failed to run estimate: {}", e);
+            PyRuntimeError::new_err(err_str)
+        },
+    )?;
+
+    Ok(true)
+}
+
 /// print hex representation of a proof
 #[pyfunction(signature = (proof_path))]
 fn print_proof_hex(proof_path: PathBuf) -> Result<String, PyErr> {
@@ -1199,6 +1514,8 @@ fn print_proof_hex(proof_path: PathBuf) -> Result<String, PyErr> {
 fn ezkl(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     // NOTE: DeployVerifierEVM and SendProofEVM will be implemented in python in pyezkl
     pyo3_log::init();
+    #[cfg(feature = "icicle")]
+    crate::gpu::init_gpu();
     m.add_class::<PyRunArgs>()?;
     m.add_class::<PyElGamalCipher>()?;
     m.add_class::<PyElGamalVariables>()?;
@@ -1233,7 +1550,14 @@ fn ezkl(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(setup_aggregate, m)?)?;
     m.add_function(wrap_pyfunction!(compile_circuit, m)?)?;
     m.add_function(wrap_pyfunction!(verify_aggr, m)?)?;
+    m.add_function(wrap_pyfunction!(link_proofs, m)?)?;
+    m.add_function(wrap_pyfunction!(split_model, m)?)?;
+    m.add_function(wrap_pyfunction!(bench, m)?)?;
+    m.add_function(wrap_pyfunction!(estimate, m)?)?;
     m.add_function(wrap_pyfunction!(create_evm_verifier, m)?)?;
+    m.add_function(wrap_pyfunction!(deploy_vk_evm, m)?)?;
+    m.add_function(wrap_pyfunction!(estimate_evm_gas, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_evm_onchain, m)?)?;
     m.add_function(wrap_pyfunction!(deploy_evm, m)?)?;
     m.add_function(wrap_pyfunction!(deploy_da_evm, m)?)?;
     m.add_function(wrap_pyfunction!(verify_evm, m)?)?;
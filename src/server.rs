@@ -0,0 +1,192 @@
+use crate::circuit::CheckMode;
+use crate::execute::CommitmentParams;
+use crate::graph::input::GraphData;
+use crate::graph::{GraphCircuit, GraphWitness};
+use crate::pfsys::{load_pk, load_vk, ProofType, Snark};
+use halo2_proofs::plonk::{ProvingKey, VerifyingKey};
+use halo2_proofs::poly::kzg::commitment::KZGCommitmentScheme;
+use halo2curves::bn256::{Bn256, Fr, G1Affine};
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use log::{error, info};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Everything needed to prove and verify, loaded once and shared across requests.
+#[derive(Debug)]
+pub struct ProverState {
+    circuit: GraphCircuit,
+    params: CommitmentParams,
+    pk: ProvingKey<G1Affine>,
+    vk: VerifyingKey<G1Affine>,
+    proof_type: ProofType,
+    check_mode: CheckMode,
+}
+
+impl ProverState {
+    /// Loads the compiled circuit, keys and srs
+    pub fn load(
+        compiled_circuit_path: PathBuf,
+        pk_path: PathBuf,
+        vk_path: PathBuf,
+        srs_path: Option<PathBuf>,
+        proof_type: ProofType,
+        check_mode: CheckMode,
+    ) -> Result<Self, Box<dyn Error>> {
+        let circuit = GraphCircuit::load(compiled_circuit_path)?;
+        let settings = circuit.settings().clone();
+        let params = CommitmentParams::load(srs_path, &settings)?;
+        let pk =
+            load_pk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(pk_path, settings.clone())?;
+        let vk = load_vk::<KZGCommitmentScheme<Bn256>, Fr, GraphCircuit>(vk_path, settings)?;
+        Ok(Self {
+            circuit,
+            params,
+            pk,
+            vk,
+            proof_type,
+            check_mode,
+        })
+    }
+
+    /// Generates a witness for file sourced input data and proves it
+    fn prove(&self, data: &GraphData) -> Result<Snark<Fr, G1Affine>, Box<dyn Error>> {
+        let mut circuit = self.circuit.clone();
+        let mut inputs = circuit.load_graph_from_file_exclusively(data)?;
+        let witness = circuit.forward(&mut inputs, Some(&self.vk), self.params.kzg())?;
+        self.prove_witness(witness)
+    }
+
+    /// Proves an already generated witness
+    pub(crate) fn prove_witness(
+        &self,
+        witness: GraphWitness,
+    ) -> Result<Snark<Fr, G1Affine>, Box<dyn Error>> {
+        self.params.prove(
+            witness,
+            self.circuit.clone(),
+            &self.pk,
+            self.proof_type,
+            self.check_mode,
+        )
+    }
+
+    /// Verifies a proof against the loaded verifying key, with the commitment and multiopen
+    /// scheme of the circuit's settings
+    pub(crate) fn verify(&self, snark: Snark<Fr, G1Affine>) -> Result<(), Box<dyn Error>> {
+        let multiopen = self.circuit.settings().run_args.multiopen;
+        self.params.verify(snark, &self.vk, multiopen)
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+#[derive(Serialize)]
+struct VerifyBody {
+    verified: bool,
+    error: Option<String>,
+}
+
+fn json_response<T: Serialize>(status: StatusCode, value: &T) -> Response<Body> {
+    match serde_json::to_vec(value) {
+        Ok(body) => Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+fn error_response(status: StatusCode, error: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&ErrorBody { error }).unwrap_or_default(),
+        ))
+        .unwrap()
+}
+
+/// Reads and deserializes a json request body
+async fn read_json<T: serde::de::DeserializeOwned>(req: Request<Body>) -> Result<T, String> {
+    let bytes = hyper::body::to_bytes(req.into_body())
+        .await
+        .map_err(|e| e.to_string())?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("invalid request body: {}", e))
+}
+
+async fn handle(state: Arc<ProverState>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    info!("{} {}", method, path);
+
+    let res = match (&method, path.as_str()) {
+        (&Method::GET, "/settings") => json_response(StatusCode::OK, state.circuit.settings()),
+        (&Method::POST, "/prove") => match read_json::<GraphData>(req).await {
+            Ok(data) => {
+                // proving is cpu bound so it is kept off the async executor
+                let res = tokio::task::spawn_blocking(move || {
+                    state.prove(&data).map_err(|e| e.to_string())
+                })
+                .await;
+                match res {
+                    Ok(Ok(snark)) => json_response(StatusCode::OK, &snark),
+                    Ok(Err(e)) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+                    Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+                }
+            }
+            Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+        },
+        (&Method::POST, "/verify") => match read_json::<Snark<Fr, G1Affine>>(req).await {
+            Ok(snark) => {
+                let res = tokio::task::spawn_blocking(move || {
+                    state.verify(snark).map_err(|e| e.to_string())
+                })
+                .await;
+                match res {
+                    Ok(res) => json_response(
+                        StatusCode::OK,
+                        &VerifyBody {
+                            verified: res.is_ok(),
+                            error: res.err(),
+                        },
+                    ),
+                    Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+                }
+            }
+            Err(e) => error_response(StatusCode::BAD_REQUEST, e),
+        },
+        _ => error_response(
+            StatusCode::NOT_FOUND,
+            format!("no route for {} {}", method, path),
+        ),
+    };
+
+    if !res.status().is_success() {
+        error!("{} {} -> {}", method, path, res.status());
+    }
+    Ok(res)
+}
+
+/// Serves `POST /prove` (input data -> proof), `POST /verify` (proof -> verified) and
+/// `GET /settings` on `addr` until the process is stopped.
+pub async fn serve(addr: SocketAddr, state: ProverState) -> Result<(), Box<dyn Error>> {
+    let state = Arc::new(state);
+    let make_svc = make_service_fn(move |_| {
+        let state = state.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(state.clone(), req))) }
+    });
+    let server = Server::try_bind(&addr)?.serve(make_svc);
+    info!("listening on http://{}", addr);
+    server.await?;
+    Ok(())
+}
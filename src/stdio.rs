@@ -0,0 +1,60 @@
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The path that stands in for stdin (when reading) or stdout (when writing).
+pub const STDIO_PATH: &str = "-";
+
+/// Stdin can only be drained once per process, so a second `-` input is an error rather than
+/// silently reading nothing.
+static STDIN_CONSUMED: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if the path is `-`, i.e. it refers to stdin or stdout.
+pub fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == STDIO_PATH
+}
+
+/// Opens a reader over the file at `path`, or over stdin if the path is `-`.
+pub fn reader(path: &Path) -> io::Result<Box<dyn Read>> {
+    if is_stdio(path) {
+        if STDIN_CONSUMED.swap(true, Ordering::SeqCst) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "stdin has already been read, only one argument can be `-`",
+            ));
+        }
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(std::fs::File::open(path)?))
+    }
+}
+
+/// Opens a buffered writer to the file at `path`, or to stdout if the path is `-`.
+pub fn writer(path: &Path) -> io::Result<Box<dyn Write>> {
+    if is_stdio(path) {
+        Ok(Box::new(BufWriter::new(io::stdout().lock())))
+    } else {
+        Ok(Box::new(BufWriter::new(std::fs::File::create(path)?)))
+    }
+}
+
+/// Reads the entire contents of `path` (or stdin) into a byte vector.
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let mut buf = vec![];
+    reader(path)?.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Reads the entire contents of `path` (or stdin) into a string.
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    let mut buf = String::new();
+    reader(path)?.read_to_string(&mut buf)?;
+    Ok(buf)
+}
+
+/// Writes `contents` to `path` (or stdout), replacing any existing file.
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut w = writer(path)?;
+    w.write_all(contents.as_ref())?;
+    w.flush()
+}
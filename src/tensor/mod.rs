@@ -11,7 +11,7 @@ use rayon::{
         IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator,
         ParallelIterator,
     },
-    slice::ParallelSliceMut,
+    slice::{ParallelSlice, ParallelSliceMut},
 };
 use serde::{Deserialize, Serialize};
 pub use val::*;
@@ -1046,10 +1046,12 @@ impl<T: Clone + TensorType> Tensor<T> {
             .enumerate()
             .map(|(i, e)| f(i, e.clone()))
             .collect();
-        let mut t: Tensor<G> = Tensor::from(vec?.iter().cloned());
-        // safe to unwrap as we know the dims are correct
-        t.reshape(self.dims()).unwrap();
-        Ok(t)
+        Ok(Tensor {
+            inner: vec?,
+            dims: self.dims.clone(),
+            scale: None,
+            visibility: None,
+        })
     }
 
     /// Maps a function to tensors and enumerates in parallel
@@ -1076,10 +1078,12 @@ impl<T: Clone + TensorType> Tensor<T> {
             .enumerate()
             .map(move |(i, e)| f(i, e.clone()))
             .collect();
-        let mut t: Tensor<G> = Tensor::from(vec?.iter().cloned());
-        // safe to unwrap as we know the dims are correct
-        t.reshape(self.dims()).unwrap();
-        Ok(t)
+        Ok(Tensor {
+            inner: vec?,
+            dims: self.dims.clone(),
+            scale: None,
+            visibility: None,
+        })
     }
 
     /// Maps a function to tensors and enumerates in parallel
@@ -1109,6 +1113,53 @@ impl<T: Clone + TensorType> Tensor<T> {
     }
 }
 
+/// Elements quantized by each parallel task, long enough for the scaling loop to be vectorized
+const QUANTIZE_CHUNK_LEN: usize = 1 << 12;
+
+impl Tensor<f32> {
+    /// Quantizes each element `x` to the field element `round(mult * x, i)`, where `i` is the
+    /// element's index (which seeds stochastic rounding), in parallel over chunks of the tensor.
+    /// Errors if an element is larger than `max_value`.
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use halo2curves::bn256::Fr as F;
+    /// let a = Tensor::<f32>::new(Some(&[0.5, -1.25]), &[2]).unwrap();
+    /// let b = a.par_quantize::<F>(4.0, 10.0, |x, _| x.round()).unwrap();
+    /// assert_eq!(b, Tensor::new(Some(&[F::from(2), -F::from(5)]), &[2]).unwrap());
+    /// assert!(a.par_quantize::<F>(4.0, 0.1, |x, _| x.round()).is_err());
+    /// ```
+    pub fn par_quantize<F: PrimeField + TensorType + PartialOrd>(
+        &self,
+        mult: f64,
+        max_value: f64,
+        round: impl Fn(f64, u64) -> f64 + Send + Sync,
+    ) -> Result<Tensor<F>, TensorError> {
+        let mut quantized = vec![F::ZERO; self.len()];
+        quantized
+            .par_chunks_mut(QUANTIZE_CHUNK_LEN)
+            .zip(self.inner.par_chunks(QUANTIZE_CHUNK_LEN))
+            .enumerate()
+            .try_for_each(|(c, (out, chunk))| {
+                if chunk.iter().any(|x| *x as f64 > max_value) {
+                    return Err(TensorError::SigBitTruncationError);
+                }
+                // scale the whole chunk before the (branchy) rounding and field conversion
+                let scaled = chunk.iter().map(|x| mult * *x as f64).collect::<Vec<_>>();
+                let offset = c * QUANTIZE_CHUNK_LEN;
+                for (i, (o, x)) in out.iter_mut().zip(scaled).enumerate() {
+                    *o = i128_to_felt(round(x, (offset + i) as u64) as i128);
+                }
+                Ok(())
+            })?;
+        Ok(Tensor {
+            inner: quantized,
+            dims: self.dims.clone(),
+            scale: None,
+            visibility: None,
+        })
+    }
+}
+
 impl<T: Clone + TensorType> Tensor<Tensor<T>> {
     /// Flattens a tensor of tensors
     /// ```
@@ -510,6 +510,125 @@ pub fn resize<T: TensorType + Send + Sync>(
     Ok(output)
 }
 
+/// The integer weights that linearly interpolate an axis of length `dim` up by `scale`, with half
+/// pixel centers (as in onnx and pytorch). Row `c` holds the weights of output `c` over the
+/// input, each row sums to `2 * scale` so that the weights stay integers.
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::linear_interpolation_weights;
+/// let result = linear_interpolation_weights(2, 2).unwrap();
+/// let expected = Tensor::<usize>::new(Some(&[4, 0, 3, 1, 1, 3, 0, 4]), &[4, 2]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn linear_interpolation_weights(
+    dim: usize,
+    scale: usize,
+) -> Result<Tensor<usize>, TensorError> {
+    let denom = 2 * scale as i64;
+    let mut weights = Tensor::new(Some(&vec![0; dim * scale * dim]), &[dim * scale, dim])?;
+    for c in 0..dim * scale {
+        // output c samples the input at (2c + 1 - scale) / (2 * scale), clamped to the edges
+        let num = 2 * c as i64 + 1 - scale as i64;
+        let lo = num.div_euclid(denom);
+        let frac = num.rem_euclid(denom);
+        if lo < 0 {
+            weights.set(&[c, 0], denom as usize);
+        } else if lo as usize >= dim - 1 {
+            weights.set(&[c, dim - 1], denom as usize);
+        } else {
+            weights.set(&[c, lo as usize], (denom - frac) as usize);
+            weights.set(&[c, lo as usize + 1], frac as usize);
+        }
+    }
+    Ok(weights)
+}
+
+/// The einsum equation contracting `axis` of a tensor of rank `rank` with the rows of a matrix of
+/// interpolation weights (see [linear_interpolation_weights]).
+pub fn interpolation_equation(rank: usize, axis: usize) -> String {
+    let input = (b'a'..b'y').take(rank).map(char::from).collect::<String>();
+    let output = input
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if i == axis { 'z' } else { c })
+        .collect::<String>();
+    let contracted = input.chars().nth(axis).unwrap_or('a');
+    format!("{},z{}->{}", input, contracted, output)
+}
+
+/// Resizes a tensor by integer scale factors using bilinear (multilinear) interpolation with half
+/// pixel centers. To stay in the integers the output is scaled up by `2 * scale` along every
+/// axis that is resized.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `scales` - the factor to resize each axis by
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::resize_bilinear;
+/// let a = Tensor::<i128>::new(Some(&[0, 4]), &[1, 2]).unwrap();
+/// let result = resize_bilinear(&a, &[1, 2]).unwrap();
+/// // [0, 1, 3, 4] scaled up by 4
+/// let expected = Tensor::<i128>::new(Some(&[0, 4, 12, 16]), &[1, 4]).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn resize_bilinear<
+    T: TensorType
+        + Mul<Output = T>
+        + Add<Output = T>
+        + From<u64>
+        + std::marker::Send
+        + std::marker::Sync,
+>(
+    a: &Tensor<T>,
+    scales: &[usize],
+) -> Result<Tensor<T>, TensorError> {
+    let mut output = a.clone();
+    let rank = a.dims().len();
+    for (axis, scale) in scales.iter().enumerate().take(rank) {
+        if *scale == 1 {
+            continue;
+        }
+        let weights =
+            linear_interpolation_weights(output.dims()[axis], *scale)?.map(|w| T::from(w as u64));
+        output = einsum(&interpolation_equation(rank, axis), &[output, weights])?;
+    }
+    Ok(output)
+}
+
+/// Maps each index of an einsum equation to its size. As in numpy (and tract), an index of size 1
+/// in one input broadcasts against the same index in the others, e.g when attention heads share
+/// a batch of size 1.
+/// # Examples
+/// ```
+/// use ezkl::tensor::ops::einsum_index_sizes;
+/// let sizes = einsum_index_sizes(&["bij", "bjk"], &[&[1, 2, 3], &[4, 3, 5]]).unwrap();
+/// assert_eq!(sizes[&'b'], 4);
+/// assert!(einsum_index_sizes(&["ij", "jk"], &[&[2, 3], &[4, 5]]).is_err());
+/// ```
+pub fn einsum_index_sizes(
+    inputs_eq: &[&str],
+    input_dims: &[&[usize]],
+) -> Result<HashMap<char, usize>, TensorError> {
+    let mut indices_to_size = HashMap::new();
+    for (eq, dims) in inputs_eq.iter().zip(input_dims) {
+        if eq.len() != dims.len() {
+            return Err(TensorError::DimMismatch("einsum".to_string()));
+        }
+        for (c, d) in eq.chars().zip(dims.iter()) {
+            let size = indices_to_size.entry(c).or_insert(*d);
+            if *size == 1 {
+                *size = *d;
+            } else if *d != 1 && *size != *d {
+                return Err(TensorError::DimMismatch("einsum".to_string()));
+            }
+        }
+    }
+    Ok(indices_to_size)
+}
+
 /// Computes the einstein sum of a set of tensors.
 /// # Arguments
 /// * `equation` - Einstein summation equation
@@ -699,17 +818,17 @@ pub fn einsum<
         return Err(TensorError::DimMismatch("einsum".to_string()));
     }
 
-    let mut indices_to_size = HashMap::new();
-    for (i, input) in inputs.iter().enumerate() {
-        for j in 0..inputs_eq[i].len() {
-            let c = inputs_eq[i].chars().nth(j).unwrap();
-            if let std::collections::hash_map::Entry::Vacant(e) = indices_to_size.entry(c) {
-                e.insert(input.dims()[j]);
-            } else if indices_to_size[&c] != input.dims()[j] {
-                return Err(TensorError::DimMismatch("einsum".to_string()));
-            }
-        }
-    }
+    let mut indices_to_size = einsum_index_sizes(
+        &inputs_eq,
+        &inputs.iter().map(|i| i.dims()).collect::<Vec<_>>(),
+    )?;
+
+    // broadcast the inputs along their indices of size 1
+    let inputs = inputs
+        .iter()
+        .zip(&inputs_eq)
+        .map(|(input, eq)| input.expand(&eq.chars().map(|c| indices_to_size[&c]).collect_vec()))
+        .collect::<Result<Vec<_>, _>>()?;
 
     // maps unrepresented indices in the output to a trivial 1
     for c in output_eq.chars() {
@@ -1165,6 +1284,15 @@ pub fn downsample<T: TensorType + Send + Sync>(
 /// let result = gather(&x, &index, 1).unwrap();
 /// let expected = Tensor::<i128>::new(Some(&[1, 2, 4, 5]), &[2, 2]).unwrap();
 /// assert_eq!(result, expected);
+///
+/// // looking up a batch of token ids in an embedding table
+/// let index = Tensor::<usize>::new(
+///   Some(&[1, 1, 0]),
+///  &[1, 3],
+/// ).unwrap();
+/// let result = gather(&x, &index, 0).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[4, 5, 6, 4, 5, 6, 1, 2, 3]), &[1, 3, 3]).unwrap();
+/// assert_eq!(result, expected);
 /// ```
 pub fn gather<T: TensorType + Send + Sync>(
     input: &Tensor<T>,
@@ -1201,11 +1329,8 @@ pub fn gather<T: TensorType + Send + Sync>(
         Ok(input.get(&new_coord))
     })?;
 
-    // Reshape the output tensor
-    if index.is_singleton() {
-        output_size.remove(dim);
-    }
-
+    // the gathered axis is replaced by the dims of the index
+    let output_size = [&input.dims()[..dim], index.dims(), &input.dims()[dim + 1..]].concat();
     output.reshape(&output_size)?;
 
     Ok(output)
@@ -1503,6 +1628,91 @@ pub fn topk_axes<T: TensorType + PartialOrd + Send + Sync>(
     Ok(res)
 }
 
+/// Returns the indices of the top K values, in the order of their values. Ties go to the lowest
+/// index first, as in [topk].
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `k` - Number of values
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::topk_indices;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 15, 2, 1, 1, 0]),
+///     &[6],
+/// ).unwrap();
+/// let result = topk_indices(&x, 3).unwrap();
+/// let expected = Tensor::<usize>::new(
+///     Some(&[1, 0, 2]),
+///     &[3],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn topk_indices<T: TensorType + PartialOrd>(
+    a: &Tensor<T>,
+    k: usize,
+) -> Result<Tensor<usize>, TensorError> {
+    let mut indices = (0..a.len()).collect::<Vec<_>>();
+    indices.sort_by(|i, j| a[*j].partial_cmp(&a[*i]).unwrap());
+    Tensor::new(Some(&indices[..k]), &[k])
+}
+
+/// Returns the indices of the top K values along a dim.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `k` - Number of values
+/// * `dim` - Dimension to take the top K values along
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::topk_indices_axes;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 15, 2, 1, 1, 0]),
+///     &[2,3],
+/// ).unwrap();
+/// let result = topk_indices_axes(&x, 2, 1).unwrap();
+/// let expected = Tensor::<usize>::new(
+///     Some(&[1, 0, 0, 1]),
+///     &[2,2],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn topk_indices_axes<T: TensorType + PartialOrd + Send + Sync>(
+    a: &Tensor<T>,
+    k: usize,
+    dim: usize,
+) -> Result<Tensor<usize>, TensorError> {
+    let mut new_dims = a.dims().to_vec();
+    new_dims[dim] = k;
+
+    let res = Tensor::new(None, &new_dims)?;
+
+    let cartesian_coord = new_dims
+        .iter()
+        .map(|x| 0..*x)
+        .multi_cartesian_product()
+        .collect::<Vec<_>>();
+
+    let res = res.par_enum_map(|i, _: usize| {
+        let coord = cartesian_coord[i].clone();
+        let mut slice = vec![];
+        for (i, c) in coord.iter().enumerate() {
+            if i == dim {
+                slice.push(0..a.dims()[i]);
+            } else {
+                slice.push(*c..*c + 1);
+            }
+        }
+        let sliced_value = a.get_slice(&slice)?;
+        let topk = topk_indices(&sliced_value, k)?;
+        Ok(topk[coord[dim]])
+    })?;
+
+    Ok(res)
+}
+
 /// Sums a tensor along specific axes.
 /// # Arguments
 ///
@@ -1531,6 +1741,56 @@ pub fn sum_axes<T: TensorType + Add<Output = T> + Send + Sync>(
     axes_op(a, axes, sum)
 }
 
+/// Cumulative sum of a tensor along an axis.
+/// # Arguments
+///
+/// * `a` - Tensor
+/// * `axis` - The axis to accumulate along
+/// # Examples
+/// ```
+/// use ezkl::tensor::Tensor;
+/// use ezkl::tensor::ops::cumsum;
+/// let x = Tensor::<i128>::new(
+///     Some(&[2, 15, 2, 1, 1, 0]),
+///     &[2, 3],
+/// ).unwrap();
+/// let result = cumsum(&x, 1).unwrap();
+/// let expected = Tensor::<i128>::new(
+///     Some(&[2, 17, 19, 1, 2, 2]),
+///     &[2, 3],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+///
+/// let result = cumsum(&x, 0).unwrap();
+/// let expected = Tensor::<i128>::new(
+///     Some(&[2, 15, 2, 3, 16, 2]),
+///     &[2, 3],
+/// ).unwrap();
+/// assert_eq!(result, expected);
+/// ```
+pub fn cumsum<T: TensorType + Add<Output = T> + Send + Sync>(
+    a: &Tensor<T>,
+    axis: usize,
+) -> Result<Tensor<T>, TensorError> {
+    let slice = |i: usize| {
+        let ranges = a
+            .dims()
+            .iter()
+            .enumerate()
+            .map(|(j, d)| if j == axis { i..i + 1 } else { 0..*d })
+            .collect::<Vec<_>>();
+        a.get_slice(&ranges)
+    };
+
+    let mut running = slice(0)?;
+    let mut slices = vec![running.clone()];
+    for i in 1..a.dims()[axis] {
+        running = (running + slice(i)?)?;
+        slices.push(running.clone());
+    }
+    concat(&slices.iter().collect::<Vec<_>>(), axis)
+}
+
 /// Mins a tensor along specific axes.
 /// # Arguments
 ///
@@ -2358,6 +2618,15 @@ pub fn sumpool(
 /// let expected = Tensor::<f32>::new(Some(&[0.3940,  1.7860, 1.5160, -0.0355]), &[1, 1, 2, 2]).unwrap();
 /// let expected = expected.map(|x| F32(x));
 /// assert_eq!(pooled, expected);
+///
+/// // non square images keep their height and width
+/// let x = Tensor::<i128>::new(Some(&[1, 2, 3, 4, 5, 6]), &[1, 1, 2, 3]).unwrap();
+/// let pooled = max_pool2d::<i128>(&x, &[(0, 0); 2], &(1, 1), &(1, 2)).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[2, 3, 5, 6]), &[1, 1, 2, 2]).unwrap();
+/// assert_eq!(pooled, expected);
+/// let pooled = max_pool2d::<i128>(&x, &[(0, 0); 2], &(1, 1), &(2, 1)).unwrap();
+/// let expected = Tensor::<i128>::new(Some(&[4, 5, 6]), &[1, 1, 1, 3]).unwrap();
+/// assert_eq!(pooled, expected);
 /// ```
 pub fn max_pool2d<T: TensorType + std::marker::Sync + std::marker::Send + std::cmp::Ord>(
     image: &Tensor<T>,
@@ -2379,7 +2648,7 @@ pub fn max_pool2d<T: TensorType + std::marker::Sync + std::marker::Send + std::c
     let horz_slides = (image_width + padding[0].1 + padding[1].1 - pool_dims.1) / stride.1 + 1;
 
     let mut output: Tensor<T> =
-        Tensor::new(None, &[batch, input_channels, horz_slides, vert_slides]).unwrap();
+        Tensor::new(None, &[batch, input_channels, vert_slides, horz_slides]).unwrap();
 
     let cartesian_coord = [
         (0..batch),
@@ -2769,6 +3038,31 @@ pub mod nonlinearities {
         .unwrap()
     }
 
+    /// Truncation (rounding towards zero) operator.
+    /// # Arguments
+    /// * `a` - Tensor
+    /// * `scale` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::trunc;
+    /// let x = Tensor::<i128>::new(
+    ///   Some(&[-3, -2, -1, 1, 2, 3]),
+    ///  &[3, 2],
+    /// ).unwrap();
+    /// let result = trunc(&x, 2.0);
+    /// let expected = Tensor::<i128>::new(Some(&[-1, -1, 0, 0, 1, 1]), &[3, 2]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn trunc(a: &Tensor<i128>, scale: f64) -> Tensor<i128> {
+        a.par_enum_map(|_, a_i| {
+            let kix = (a_i as f64) / scale;
+            let rounded = kix.trunc();
+            Ok::<_, TensorError>(rounded as i128)
+        })
+        .unwrap()
+    }
+
     /// Round operator.
     /// # Arguments
     /// * `a` - Tensor
@@ -2834,6 +3128,15 @@ pub mod nonlinearities {
     /// let result = pow(&x, 1.0, 2.0);
     /// let expected = Tensor::<i128>::new(Some(&[4, 225, 4, 1, 1, 0]), &[2, 3]).unwrap();
     /// assert_eq!(result, expected);
+    ///
+    /// // fractional powers are applied to the dequantized input
+    /// let x = Tensor::<i128>::new(
+    ///    Some(&[4, 16, 1]),
+    ///  &[3],
+    /// ).unwrap();
+    /// let result = pow(&x, 4.0, 1.5);
+    /// let expected = Tensor::<i128>::new(Some(&[4, 32, 1]), &[3]).unwrap();
+    /// assert_eq!(result, expected);
     /// ```
     pub fn pow(a: &Tensor<i128>, scale_input: f64, power: f64) -> Tensor<i128> {
         a.par_enum_map(|_, a_i| {
@@ -2918,6 +3221,121 @@ pub mod nonlinearities {
         .unwrap()
     }
 
+    /// Elementwise applies hard sigmoid, `max(0, min(1, alpha * x + beta))`, to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `alpha` - the slope
+    /// * `beta` - the offset
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::hard_sigmoid;
+    /// let x = Tensor::<i128>::new(
+    ///    Some(&[-8, -2, 0, 2, 8]),
+    ///   &[5],
+    /// ).unwrap();
+    /// let result = hard_sigmoid(&x, 2.0, 0.2, 0.5);
+    /// let expected = Tensor::<i128>::new(Some(&[0, 1, 1, 1, 2]), &[5]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn hard_sigmoid(a: &Tensor<i128>, scale_input: f64, alpha: f64, beta: f64) -> Tensor<i128> {
+        a.par_enum_map(|_, a_i| {
+            let kix = (a_i as f64) / scale_input;
+            let fout = scale_input * (alpha * kix + beta).clamp(0.0, 1.0);
+            let rounded = fout.round();
+            Ok::<_, TensorError>(rounded as i128)
+        })
+        .unwrap()
+    }
+
+    /// Elementwise applies hard swish, `x * relu6(x + 3) / 6`, to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::hard_swish;
+    /// let x = Tensor::<i128>::new(
+    ///    Some(&[-8, -2, 0, 2, 8]),
+    ///   &[5],
+    /// ).unwrap();
+    /// let result = hard_swish(&x, 2.0);
+    /// let expected = Tensor::<i128>::new(Some(&[0, -1, 0, 1, 8]), &[5]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn hard_swish(a: &Tensor<i128>, scale_input: f64) -> Tensor<i128> {
+        a.par_enum_map(|_, a_i| {
+            let kix = (a_i as f64) / scale_input;
+            let fout = scale_input * kix * (kix + 3.0).clamp(0.0, 6.0) / 6.0;
+            let rounded = fout.round();
+            Ok::<_, TensorError>(rounded as i128)
+        })
+        .unwrap()
+    }
+
+    /// Elementwise applies softplus, `ln(1 + exp(x))`, to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::softplus;
+    /// let x = Tensor::<i128>::new(
+    ///    Some(&[-8, -2, 0, 2, 8]),
+    ///   &[5],
+    /// ).unwrap();
+    /// let result = softplus(&x, 2.0);
+    /// let expected = Tensor::<i128>::new(Some(&[0, 1, 1, 3, 8]), &[5]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn softplus(a: &Tensor<i128>, scale_input: f64) -> Tensor<i128> {
+        a.par_enum_map(|_, a_i| {
+            let kix = (a_i as f64) / scale_input;
+            let fout = scale_input * softplus_f64(kix);
+            let rounded = fout.round();
+            Ok::<_, TensorError>(rounded as i128)
+        })
+        .unwrap()
+    }
+
+    /// Elementwise applies mish, `x * tanh(softplus(x))`, to a tensor of integers.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::mish;
+    /// let x = Tensor::<i128>::new(
+    ///    Some(&[-8, -2, 0, 2, 8]),
+    ///   &[5],
+    /// ).unwrap();
+    /// let result = mish(&x, 2.0);
+    /// let expected = Tensor::<i128>::new(Some(&[0, -1, 0, 2, 8]), &[5]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn mish(a: &Tensor<i128>, scale_input: f64) -> Tensor<i128> {
+        a.par_enum_map(|_, a_i| {
+            let kix = (a_i as f64) / scale_input;
+            let fout = scale_input * kix * softplus_f64(kix).tanh();
+            let rounded = fout.round();
+            Ok::<_, TensorError>(rounded as i128)
+        })
+        .unwrap()
+    }
+
+    /// `ln(1 + exp(x))` without overflowing for large x
+    fn softplus_f64(x: f64) -> f64 {
+        x.max(0.0) + (-x.abs()).exp().ln_1p()
+    }
+
     /// Elementwise applies exponential to a tensor of integers.
     /// # Arguments
     ///
@@ -2957,7 +3375,9 @@ pub mod nonlinearities {
         .unwrap()
     }
 
-    /// Elementwise applies exponential to a tensor of integers.
+    /// Elementwise applies the natural logarithm to a tensor of integers. Inputs at or below zero
+    /// are clamped to the smallest positive value at `scale_input`, so the lookup table stays
+    /// finite over the whole range.
     /// # Arguments
     ///
     /// * `a` - Tensor
@@ -2985,10 +3405,18 @@ pub mod nonlinearities {
     /// let expected = Tensor::<i128>::new(Some(&[-1345, -1922, -1293]), &[3]).unwrap();
     ///
     /// assert_eq!(result, expected);
+    ///
+    /// let x = Tensor::<i128>::new(
+    ///    Some(&[-3, 0, 1, 2]),
+    ///   &[4],
+    /// ).unwrap();
+    /// let result = ln(&x, 2.0);
+    /// let expected = Tensor::<i128>::new(Some(&[-1, -1, -1, 0]), &[4]).unwrap();
+    /// assert_eq!(result, expected);
     /// ```
     pub fn ln(a: &Tensor<i128>, scale_input: f64) -> Tensor<i128> {
         a.par_enum_map(|_, a_i| {
-            let kix = (a_i as f64) / scale_input;
+            let kix = (a_i.max(1) as f64) / scale_input;
             let fout = scale_input * kix.ln();
             let rounded = fout.round();
             Ok::<_, TensorError>(rounded as i128)
@@ -3702,6 +4130,34 @@ pub mod nonlinearities {
         .unwrap()
     }
 
+    /// Elementwise clips a tensor of integers to `[min, max]`.
+    /// # Arguments
+    /// * `a` - Tensor
+    /// * `scale_input` - Single value
+    /// * `min` - the lower bound
+    /// * `max` - the upper bound
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::clip;
+    /// let x = Tensor::<i128>::new(
+    ///    Some(&[2, 15, 2, 1, 1, -5]),
+    ///   &[2, 3],
+    /// ).unwrap();
+    /// let result = clip(&x, 2.0, 0.0, 6.0);
+    /// let expected = Tensor::<i128>::new(Some(&[2, 12, 2, 1, 1, 0]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn clip(a: &Tensor<i128>, scale_input: f64, min: f64, max: f64) -> Tensor<i128> {
+        // calculate value of output
+        a.par_enum_map(|_, a_i| {
+            let d_inv_x = (a_i as f64) / scale_input;
+            let clipped = d_inv_x.max(min).min(max);
+            Ok::<_, TensorError>((clipped * scale_input).round() as i128)
+        })
+        .unwrap()
+    }
+
     /// Elementwise divides a tensor with a const integer element.
     /// # Arguments
     ///
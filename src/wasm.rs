@@ -278,6 +278,47 @@ pub fn genWitness(
         .map_err(|e| JsError::new(&format!("Failed to serialize witness: {}", e)))
 }
 
+/// Generate a witness like [genWitness], for models whose inputs, outputs or params are
+/// committed to with kzg (`polycommit` visibility), which needs the verifying key and srs. The
+/// witness can then be proven in the browser with [prove].
+#[wasm_bindgen]
+#[allow(non_snake_case)]
+pub fn genWitnessWithKeys(
+    compiled_circuit: wasm_bindgen::Clamped<Vec<u8>>,
+    input: wasm_bindgen::Clamped<Vec<u8>>,
+    vk: wasm_bindgen::Clamped<Vec<u8>>,
+    srs: wasm_bindgen::Clamped<Vec<u8>>,
+) -> Result<Vec<u8>, JsError> {
+    let mut circuit: crate::graph::GraphCircuit = bincode::deserialize(&compiled_circuit[..])
+        .map_err(|e| JsError::new(&format!("Failed to deserialize compiled model: {}", e)))?;
+    let input: crate::graph::input::GraphData = serde_json::from_slice(&input[..])
+        .map_err(|e| JsError::new(&format!("Failed to deserialize input: {}", e)))?;
+
+    let mut reader = std::io::BufReader::new(&srs[..]);
+    let params: ParamsKZG<Bn256> =
+        halo2_proofs::poly::commitment::Params::<'_, G1Affine>::read(&mut reader)
+            .map_err(|e| JsError::new(&format!("Failed to deserialize srs: {}", e)))?;
+
+    let mut reader = std::io::BufReader::new(&vk[..]);
+    let vk = VerifyingKey::<G1Affine>::read::<_, GraphCircuit>(
+        &mut reader,
+        halo2_proofs::SerdeFormat::RawBytes,
+        circuit.settings().clone(),
+    )
+    .map_err(|e| JsError::new(&format!("Failed to deserialize vk: {}", e)))?;
+
+    let mut input = circuit
+        .load_graph_input(&input)
+        .map_err(|e| JsError::new(&format!("{}", e)))?;
+
+    let witness = circuit
+        .forward(&mut input, Some(&vk), Some(&params))
+        .map_err(|e| JsError::new(&format!("{}", e)))?;
+
+    serde_json::to_vec(&witness)
+        .map_err(|e| JsError::new(&format!("Failed to serialize witness: {}", e)))
+}
+
 /// Generate verifying key in browser
 #[wasm_bindgen]
 #[allow(non_snake_case)]
@@ -380,6 +421,10 @@ pub fn verify(
     }
 }
 
+/// The largest circuit (in logrows) that the browser prover accepts. Bigger circuits need more
+/// memory than a wasm instance can address and should be proven natively.
+pub const MAX_WASM_PROVE_LOGROWS: u32 = 20;
+
 /// Prove in browser using wasm
 #[wasm_bindgen]
 pub fn prove(
@@ -388,26 +433,69 @@ pub fn prove(
     compiled_circuit: wasm_bindgen::Clamped<Vec<u8>>,
     srs: wasm_bindgen::Clamped<Vec<u8>>,
 ) -> Result<Vec<u8>, JsError> {
-    #[cfg(feature = "det-prove")]
-    log::set_max_level(log::LevelFilter::Debug);
-    #[cfg(not(feature = "det-prove"))]
-    log::set_max_level(log::LevelFilter::Info);
-    // read in kzg params
-    let mut reader = std::io::BufReader::new(&srs[..]);
-    let params: ParamsKZG<Bn256> =
-        halo2_proofs::poly::commitment::Params::<'_, G1Affine>::read(&mut reader)
-            .map_err(|e| JsError::new(&format!("Failed to deserialize srs: {}", e)))?;
+    // read in model input
+    let data: crate::graph::GraphWitness = serde_json::from_slice(&witness[..])
+        .map_err(|e| JsError::new(&format!("Failed to deserialize witness: {}", e)))?;
+
+    let (circuit, pk, params) = load_prover(&pk, &compiled_circuit, &srs)?;
+
+    prove_witness(circuit, data, &pk, &params)
+}
 
+/// Generate a witness from input.json and prove it in one call, so that private inputs never
+/// leave the browser. Without the `web` feature (or before `initThreadPool` is called) proving
+/// runs on the calling thread.
+#[wasm_bindgen]
+#[allow(non_snake_case)]
+pub fn genWitnessAndProve(
+    input: wasm_bindgen::Clamped<Vec<u8>>,
+    pk: wasm_bindgen::Clamped<Vec<u8>>,
+    compiled_circuit: wasm_bindgen::Clamped<Vec<u8>>,
+    srs: wasm_bindgen::Clamped<Vec<u8>>,
+) -> Result<Vec<u8>, JsError> {
+    let input: crate::graph::input::GraphData = serde_json::from_slice(&input[..])
+        .map_err(|e| JsError::new(&format!("Failed to deserialize input: {}", e)))?;
+
+    let (mut circuit, pk, params) = load_prover(&pk, &compiled_circuit, &srs)?;
+
+    let mut input = circuit
+        .load_graph_input(&input)
+        .map_err(|e| JsError::new(&format!("{}", e)))?;
+    // the vk and srs are passed so that kzg committed inputs can be processed
+    let witness = circuit
+        .forward(&mut input, Some(pk.get_vk()), Some(&params))
+        .map_err(|e| JsError::new(&format!("{}", e)))?;
+
+    prove_witness(circuit, witness, &pk, &params)
+}
+
+/// Deserializes the compiled circuit, proving key and srs needed to prove, rejecting circuits
+/// larger than [MAX_WASM_PROVE_LOGROWS].
+fn load_prover(
+    pk: &[u8],
+    compiled_circuit: &[u8],
+    srs: &[u8],
+) -> Result<(GraphCircuit, ProvingKey<G1Affine>, ParamsKZG<Bn256>), JsError> {
     // read in circuit
-    let mut circuit: crate::graph::GraphCircuit = bincode::deserialize(&compiled_circuit[..])
+    let circuit: crate::graph::GraphCircuit = bincode::deserialize(compiled_circuit)
         .map_err(|e| JsError::new(&format!("Failed to deserialize circuit: {}", e)))?;
 
-    // read in model input
-    let data: crate::graph::GraphWitness = serde_json::from_slice(&witness[..])
-        .map_err(|e| JsError::new(&format!("Failed to deserialize witness: {}", e)))?;
+    let logrows = circuit.settings().run_args.logrows;
+    if logrows > MAX_WASM_PROVE_LOGROWS {
+        return Err(JsError::new(&format!(
+            "circuit has {} logrows but at most {} can be proven in wasm",
+            logrows, MAX_WASM_PROVE_LOGROWS
+        )));
+    }
+
+    // read in kzg params
+    let mut reader = std::io::BufReader::new(srs);
+    let params: ParamsKZG<Bn256> =
+        halo2_proofs::poly::commitment::Params::<'_, G1Affine>::read(&mut reader)
+            .map_err(|e| JsError::new(&format!("Failed to deserialize srs: {}", e)))?;
 
     // read in proving key
-    let mut reader = std::io::BufReader::new(&pk[..]);
+    let mut reader = std::io::BufReader::new(pk);
     let pk = ProvingKey::<G1Affine>::read::<_, GraphCircuit>(
         &mut reader,
         halo2_proofs::SerdeFormat::RawBytes,
@@ -415,6 +503,21 @@ pub fn prove(
     )
     .map_err(|e| JsError::new(&format!("Failed to deserialize proving key: {}", e)))?;
 
+    Ok((circuit, pk, params))
+}
+
+/// Proves a witness and returns the json serialized proof
+fn prove_witness(
+    mut circuit: GraphCircuit,
+    data: crate::graph::GraphWitness,
+    pk: &ProvingKey<G1Affine>,
+    params: &ParamsKZG<Bn256>,
+) -> Result<Vec<u8>, JsError> {
+    #[cfg(feature = "det-prove")]
+    log::set_max_level(log::LevelFilter::Debug);
+    #[cfg(not(feature = "det-prove"))]
+    log::set_max_level(log::LevelFilter::Info);
+
     // prep public inputs
     circuit
         .load_graph_witness(&data)
@@ -424,12 +527,12 @@ pub fn prove(
         .map_err(|e| JsError::new(&format!("{}", e)))?;
     let proof_split_commits: Option<crate::pfsys::ProofSplitCommit> = data.into();
 
-    let strategy = KZGSingleStrategy::new(&params);
+    let strategy = KZGSingleStrategy::new(params);
     let proof = create_proof_circuit_kzg(
         circuit,
-        &params,
+        params,
         Some(public_inputs),
-        &pk,
+        pk,
         crate::pfsys::TranscriptType::EVM,
         strategy,
         crate::circuit::CheckMode::UNSAFE,
@@ -656,7 +656,7 @@ mod native_tests {
                 crate::native_tests::init_binary();
                 let test_dir = TempDir::new(test).unwrap();
                 let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(path, test);
-                mock(path, test.to_string(), "private", "hashed", "public", 1, "resources", None);
+                mock(path, test.to_string(), "private", "encrypted", "public", 1, "resources", None);
                 test_dir.close().unwrap();
             }
 
@@ -1052,13 +1052,23 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(path, test);
                     let _anvil_child = crate::native_tests::start_anvil(false, Hardfork::Latest);
-                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "public");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "public", false);
                     #[cfg(not(feature = "icicle"))]
                     run_js_tests(path, test.to_string(), "testBrowserEvmVerify");
                     test_dir.close().unwrap();
 
                 }
 
+                #(#[test_case(TESTS_EVM[N])])*
+                fn kzg_evm_prove_and_verify_render_vk_separately_(test: &str) {
+                    crate::native_tests::init_binary();
+                    let test_dir = TempDir::new(test).unwrap();
+                    let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(path, test);
+                    let _anvil_child = crate::native_tests::start_anvil(false, Hardfork::Latest);
+                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "public", true);
+                    test_dir.close().unwrap();
+                }
+
                 #[test]
                 #[ignore]
                 fn kzg_evm_prove_and_verify_encrypted_input_() {
@@ -1067,7 +1077,7 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(path, test);
                     let _anvil_child = crate::native_tests::start_anvil(false, Hardfork::Latest);
-                    kzg_evm_prove_and_verify(path, test.to_string(), "encrypted", "private", "public");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "encrypted", "private", "public", false);
                     #[cfg(not(feature = "icicle"))]
                     run_js_tests(path, test.to_string(), "testBrowserEvmVerify");
                     test_dir.close().unwrap();
@@ -1079,7 +1089,7 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(path, test);
                     let mut _anvil_child = crate::native_tests::start_anvil(false, Hardfork::Latest);
-                    kzg_evm_prove_and_verify(path, test.to_string(), "hashed", "private", "private");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "hashed", "private", "private", false);
                     #[cfg(not(feature = "icicle"))]
                     run_js_tests(path, test.to_string(), "testBrowserEvmVerify");
                     test_dir.close().unwrap();
@@ -1096,7 +1106,7 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(path, test);
                     let mut _anvil_child = crate::native_tests::start_anvil(false, hardfork);
-                    kzg_evm_prove_and_verify(path, test.to_string(), "kzgcommit", "private", "public");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "kzgcommit", "private", "public", false);
                     #[cfg(not(feature = "icicle"))]
                     run_js_tests(path, test.to_string(), "testBrowserEvmVerify");
                     test_dir.close().unwrap();
@@ -1109,7 +1119,7 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(path, test);
                     let _anvil_child = crate::native_tests::start_anvil(false, Hardfork::Latest);
-                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "hashed", "public");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "hashed", "public", false);
                     #[cfg(not(feature = "icicle"))]
                     run_js_tests(path, test.to_string(), "testBrowserEvmVerify");
                     test_dir.close().unwrap();
@@ -1122,7 +1132,7 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(path, test);
                     let _anvil_child = crate::native_tests::start_anvil(false, Hardfork::Latest);
-                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "hashed");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "hashed", false);
                     #[cfg(not(feature = "icicle"))]
                     run_js_tests(path, test.to_string(), "testBrowserEvmVerify");
                     test_dir.close().unwrap();
@@ -1135,7 +1145,7 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(path, test);
                     let _anvil_child = crate::native_tests::start_anvil(false, Hardfork::Latest);
-                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "kzgcommit", "public");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "kzgcommit", "public", false);
                     #[cfg(not(feature = "icicle"))]
                     run_js_tests(path, test.to_string(), "testBrowserEvmVerify");
                     test_dir.close().unwrap();
@@ -1148,7 +1158,7 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(path, test);
                     let _anvil_child = crate::native_tests::start_anvil(false, Hardfork::Latest);
-                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "kzgcommit");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "private", "private", "kzgcommit", false);
                     #[cfg(not(feature = "icicle"))]
                     run_js_tests(path, test.to_string(), "testBrowserEvmVerify");
                     test_dir.close().unwrap();
@@ -1160,7 +1170,7 @@ mod native_tests {
                     let test_dir = TempDir::new(test).unwrap();
                     let path = test_dir.path().to_str().unwrap(); crate::native_tests::mv_test_(path, test);
                     let _anvil_child = crate::native_tests::start_anvil(false, Hardfork::Latest);
-                    kzg_evm_prove_and_verify(path, test.to_string(), "kzgcommit", "kzgcommit", "kzgcommit");
+                    kzg_evm_prove_and_verify(path, test.to_string(), "kzgcommit", "kzgcommit", "kzgcommit", false);
                     #[cfg(not(feature = "icicle"))]
                     run_js_tests(path, test.to_string(), "testBrowserEvmVerify");
                     test_dir.close().unwrap();
@@ -1436,16 +1446,47 @@ mod native_tests {
             .expect("failed to execute process");
         assert!(status.success());
 
+        let mut gen_witness_args = vec![
+            "gen-witness".to_string(),
+            "-D".to_string(),
+            format!("{}/{}/input.json", test_dir, example_name),
+            "-M".to_string(),
+            format!("{}/{}/network.compiled", test_dir, example_name),
+            "-O".to_string(),
+            format!("{}/{}/witness.json", test_dir, example_name),
+        ];
+
+        // encrypted inputs are encrypted under the data provider's key
+        if input_visibility.starts_with("encrypted") {
+            let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+                .args([
+                    "encrypt-input",
+                    "-D",
+                    &format!("{}/{}/input.json", test_dir, example_name),
+                    "-M",
+                    &format!("{}/{}/network.compiled", test_dir, example_name),
+                    "-K",
+                    &format!("{}/{}/elgamal_key.json", test_dir, example_name),
+                    "-O",
+                    &format!("{}/{}/encrypted_input.json", test_dir, example_name),
+                    &format!(
+                        "--secret-path={}/{}/encryption_secret.json",
+                        test_dir, example_name
+                    ),
+                ])
+                .stdout(std::process::Stdio::null())
+                .status()
+                .expect("failed to execute process");
+            assert!(status.success());
+            gen_witness_args.push("--key-path".to_string());
+            gen_witness_args.push(format!(
+                "{}/{}/encryption_secret.json",
+                test_dir, example_name
+            ));
+        }
+
         let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
-            .args([
-                "gen-witness",
-                "-D",
-                &format!("{}/{}/input.json", test_dir, example_name),
-                "-M",
-                &format!("{}/{}/network.compiled", test_dir, example_name),
-                "-O",
-                &format!("{}/{}/witness.json", test_dir, example_name),
-            ])
+            .args(gen_witness_args)
             .stdout(std::process::Stdio::null())
             .status()
             .expect("failed to execute process");
@@ -1831,6 +1872,7 @@ mod native_tests {
         input_visibility: &str,
         param_visibility: &str,
         output_visibility: &str,
+        render_vk_separately: bool,
     ) {
         let anvil_url = ANVIL_URL.as_str();
 
@@ -1864,12 +1906,40 @@ mod native_tests {
         args.push("--sol-code-path");
         args.push(sol_arg.as_str());
 
+        let vk_sol_arg = format!("--vk-sol-code-path={}/{}/vk.sol", test_dir, example_name);
+        if render_vk_separately {
+            args.push(vk_sol_arg.as_str());
+        }
+
         let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
             .args(&args)
             .status()
             .expect("failed to execute process");
         assert!(status.success());
 
+        // deploy the verifying key on its own
+        let vk_addr_path_arg = format!("--addr-path={}/{}/addr_vk.txt", test_dir, example_name);
+        let deployed_addr_vk_arg = if render_vk_separately {
+            let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+                .args([
+                    "deploy-evm-vk",
+                    rpc_arg.as_str(),
+                    vk_addr_path_arg.as_str(),
+                    "--sol-code-path",
+                    &format!("{}/{}/vk.sol", test_dir, example_name),
+                ])
+                .status()
+                .expect("failed to execute process");
+            assert!(status.success());
+
+            let addr_vk =
+                std::fs::read_to_string(format!("{}/{}/addr_vk.txt", test_dir, example_name))
+                    .expect("failed to read address file");
+            Some(format!("--addr-vk={}", addr_vk))
+        } else {
+            None
+        };
+
         // deploy the verifier
         let mut args = vec![
             "deploy-evm-verifier",
@@ -1901,12 +1971,47 @@ mod native_tests {
             rpc_arg.as_str(),
             deployed_addr_arg.as_str(),
         ];
+        if let Some(deployed_addr_vk_arg) = &deployed_addr_vk_arg {
+            args.push(deployed_addr_vk_arg.as_str());
+        }
 
         let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
             .args(&args)
             .status()
             .expect("failed to execute process");
         assert!(status.success());
+
+        // submit the proof in a transaction too
+        let mut onchain_args = args.clone();
+        onchain_args[0] = "verify-evm-onchain";
+        let private_key = format!("--private-key={}", *ANVIL_DEFAULT_PRIVATE_KEY);
+        onchain_args.push(private_key.as_str());
+        let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+            .args(&onchain_args)
+            .status()
+            .expect("failed to execute process");
+        assert!(status.success());
+
+        // estimate the gas of the same pipeline on a throwaway chain
+        let gas_report = format!("{}/{}/gas_report.json", test_dir, example_name);
+        let mut gas_args = vec![
+            "estimate-evm-gas",
+            "--proof-path",
+            pf_arg.as_str(),
+            "--sol-code-path",
+            sol_arg.as_str(),
+            "--output",
+            gas_report.as_str(),
+        ];
+        if render_vk_separately {
+            gas_args.push(vk_sol_arg.as_str());
+        }
+        let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
+            .args(&gas_args)
+            .status()
+            .expect("failed to execute process");
+        assert!(status.success());
+        assert!(std::path::Path::new(&gas_report).exists());
         // As sanity check, add example that should fail.
         args[2] = PF_FAILURE;
         let status = Command::new(format!("{}/release/ezkl", *CARGO_TARGET_DIR))
@@ -11,10 +11,10 @@ mod wasm32 {
     use ezkl::pfsys;
     use ezkl::wasm::{
         bufferToVecOfVecU64, compiledCircuitValidation, elgamalDecrypt, elgamalEncrypt,
-        elgamalGenRandom, encodeVerifierCalldata, genPk, genVk, genWitness, inputValidation,
-        pkValidation, poseidonHash, printProofHex, proofValidation, prove, settingsValidation,
-        srsValidation, u8_array_to_u128_le, vecU64ToFelt, vecU64ToFloat, vecU64ToInt, verify,
-        vkValidation, witnessValidation,
+        elgamalGenRandom, encodeVerifierCalldata, genPk, genVk, genWitness, genWitnessAndProve,
+        genWitnessWithKeys, inputValidation, pkValidation, poseidonHash, printProofHex,
+        proofValidation, prove, settingsValidation, srsValidation, u8_array_to_u128_le,
+        vecU64ToFelt, vecU64ToFloat, vecU64ToInt, verify, vkValidation, witnessValidation,
     };
     use halo2_solidity_verifier::encode_calldata;
     use halo2curves::bn256::{Fr, G1Affine};
@@ -242,6 +242,24 @@ mod wasm32 {
         assert_eq!(witness, reference_witness);
     }
 
+    #[wasm_bindgen_test]
+    async fn verify_gen_witness_with_keys() {
+        let witness = genWitnessWithKeys(
+            wasm_bindgen::Clamped(NETWORK_COMPILED.to_vec()),
+            wasm_bindgen::Clamped(INPUT.to_vec()),
+            wasm_bindgen::Clamped(VK.to_vec()),
+            wasm_bindgen::Clamped(SRS.to_vec()),
+        )
+        .map_err(|_| "failed")
+        .unwrap();
+
+        let witness: GraphWitness = serde_json::from_slice(&witness[..]).unwrap();
+
+        let reference_witness: GraphWitness = serde_json::from_slice(&WITNESS).unwrap();
+        // nothing is committed to with kzg so the keys don't change the witness
+        assert_eq!(witness, reference_witness);
+    }
+
     #[wasm_bindgen_test]
     async fn gen_pk_test() {
         let vk = genVk(
@@ -316,6 +334,45 @@ mod wasm32 {
         assert!(value);
     }
 
+    #[wasm_bindgen_test]
+    async fn gen_witness_and_prove_test() {
+        let vk = genVk(
+            wasm_bindgen::Clamped(NETWORK_COMPILED.to_vec()),
+            wasm_bindgen::Clamped(SRS.to_vec()),
+        )
+        .map_err(|_| "failed")
+        .unwrap();
+
+        let pk = genPk(
+            wasm_bindgen::Clamped(vk.clone()),
+            wasm_bindgen::Clamped(NETWORK_COMPILED.to_vec()),
+            wasm_bindgen::Clamped(SRS.to_vec()),
+        )
+        .map_err(|_| "failed")
+        .unwrap();
+
+        let proof = genWitnessAndProve(
+            wasm_bindgen::Clamped(INPUT.to_vec()),
+            wasm_bindgen::Clamped(pk),
+            wasm_bindgen::Clamped(NETWORK_COMPILED.to_vec()),
+            wasm_bindgen::Clamped(SRS.to_vec()),
+        )
+        .map_err(|_| "failed")
+        .unwrap();
+
+        let value = verify(
+            wasm_bindgen::Clamped(proof),
+            wasm_bindgen::Clamped(vk),
+            wasm_bindgen::Clamped(SETTINGS.to_vec()),
+            wasm_bindgen::Clamped(SRS.to_vec()),
+        )
+        .map_err(|_| "failed")
+        .unwrap();
+
+        // should not fail
+        assert!(value);
+    }
+
     #[wasm_bindgen_test]
     async fn print_proof_hex_test() {
         let proof = printProofHex(wasm_bindgen::Clamped(PROOF.to_vec()))